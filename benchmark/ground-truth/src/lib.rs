@@ -0,0 +1,51 @@
+//! The benchmark's ground truth, `ground-truth.json`, as Rust types, for
+//! harnesses that score an analyzer's findings against it.
+//!
+//! Every program under `programs/` has exactly one finding. A reported
+//! finding matches when it names the same program and either the same
+//! `class` or a location within the annotated instruction.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GroundTruth {
+    /// Bumped whenever a field changes meaning
+    pub version: u32,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Finding {
+    /// Package name, also the directory under `programs/`
+    pub program: String,
+    pub program_id: String,
+    /// Vulnerability class, e.g. `missing-signer-check`
+    pub class: String,
+    pub cwe: String,
+    /// `critical` if it loses funds outright, `high` otherwise
+    pub severity: String,
+    /// Source file, relative to the benchmark root
+    pub file: PathBuf,
+    /// 1-based line of `snippet`
+    pub line: usize,
+    /// Instruction the bug is exploited through
+    pub instruction: String,
+    /// Account the missing or wrong check is about
+    pub account: String,
+    /// The flawed line, trimmed
+    pub snippet: String,
+    pub description: String,
+    pub fix: String,
+}
+
+/// Root of the benchmark workspace
+pub fn benchmark_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+pub fn load() -> Result<GroundTruth, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(benchmark_root().join("ground-truth.json"))?;
+    Ok(serde_json::from_str(&json)?)
+}
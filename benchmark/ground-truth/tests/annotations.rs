@@ -0,0 +1,44 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use ground_truth::{benchmark_root, load};
+
+#[test]
+fn every_program_has_exactly_one_finding() {
+    let truth = load().unwrap();
+    let programs: BTreeSet<String> = fs::read_dir(benchmark_root().join("programs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    let annotated: Vec<&str> = truth.findings.iter().map(|finding| finding.program.as_str()).collect();
+
+    assert_eq!(annotated.len(), programs.len(), "one finding per program");
+    assert_eq!(annotated.iter().map(|program| program.to_string()).collect::<BTreeSet<_>>(), programs);
+}
+
+#[test]
+fn findings_point_at_their_snippet() {
+    for finding in load().unwrap().findings {
+        let source = fs::read_to_string(benchmark_root().join(&finding.file)).unwrap();
+        let line = source
+            .lines()
+            .nth(finding.line - 1)
+            .unwrap_or_else(|| panic!("{}: line {} is past the end", finding.program, finding.line));
+        assert_eq!(line.trim(), finding.snippet, "{} moved; update its line", finding.program);
+    }
+}
+
+#[test]
+fn program_ids_match_the_sources_and_anchor_toml() {
+    let anchor_toml = fs::read_to_string(benchmark_root().join("Anchor.toml")).unwrap();
+    for finding in load().unwrap().findings {
+        let source = fs::read_to_string(benchmark_root().join(&finding.file)).unwrap();
+        assert!(
+            source.contains(&format!("declare_id!(\"{}\")", finding.program_id)),
+            "{} declares another id",
+            finding.program
+        );
+        let entry = format!("{} = \"{}\"", finding.program.replace('-', "_"), finding.program_id);
+        assert!(anchor_toml.contains(&entry), "Anchor.toml has no `{entry}`");
+    }
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("2eYGYp1t152vgM8TPiEtXFULEPcXySUkuvrDkLgWrvdo");
+
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+pub const LEDGER_SEED: &[u8] = b"ledger";
+
+/// A pooled token vault that keeps a ledger entry per depositor
+#[program]
+pub mod arbitrary_cpi {
+    use super::*;
+
+    pub fn open_ledger(_ctx: Context<OpenLedger>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_tokens.to_account_info(),
+                    to: ctx.accounts.vault_tokens.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let ledger = &mut ctx.accounts.ledger;
+        ledger.balance = ledger.balance.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let ledger = &mut ctx.accounts.ledger;
+        ledger.balance = ledger.balance.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+
+        let bump = [ctx.bumps.vault_authority];
+        let signer_seeds: &[&[&[u8]]] = &[&[AUTHORITY_SEED, &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_tokens.to_account_info(),
+                    to: ctx.accounts.user_tokens.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Ledger {
+    pub balance: u64,
+}
+
+#[derive(Accounts)]
+pub struct OpenLedger<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Ledger::INIT_SPACE,
+        seeds = [LEDGER_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub ledger: Account<'info, Ledger>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [LEDGER_SEED, user.key().as_ref()], bump)]
+    pub ledger: Account<'info, Ledger>,
+    /// CHECK: signs for the vault, holds no data
+    #[account(seeds = [AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, token::mint = mint, token::authority = vault_authority)]
+    pub vault_tokens: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::authority = user)]
+    pub user_tokens: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [LEDGER_SEED, user.key().as_ref()], bump)]
+    pub ledger: Account<'info, Ledger>,
+    /// CHECK: signs for the vault, holds no data
+    #[account(seeds = [AUTHORITY_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, token::mint = mint, token::authority = vault_authority)]
+    pub vault_tokens: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint)]
+    pub user_tokens: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    /// CHECK: the token program to transfer with
+    pub token_program: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Ledger balance too low")]
+    InsufficientBalance,
+    #[msg("Ledger balance overflowed")]
+    Overflow,
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, CreateAccount, Transfer};
+
+declare_id!("BDL3weV49k1amjSdovoTGb4R6vFkLavBWn9g2TQrLTtr");
+
+pub const FAUCET_SEED: &[u8] = b"faucet";
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+/// Lamports every wallet can claim once
+pub const CLAIM_AMOUNT: u64 = 100_000_000;
+
+/// A faucet that pays each wallet once, recording the claim in a receipt
+/// account that cannot be created twice
+#[program]
+pub mod bump_seed_canonicalization {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn fund(ctx: Context<Fund>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.faucet.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    pub fn claim(ctx: Context<Claim>, bump: u8) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let receipt_address =
+            Pubkey::create_program_address(&[RECEIPT_SEED, user.as_ref(), &[bump]], &crate::ID)
+                .map_err(|_| ErrorCode::InvalidReceipt)?;
+        require_keys_eq!(receipt_address, ctx.accounts.receipt.key(), ErrorCode::InvalidReceipt);
+
+        // Fails if this receipt already exists
+        let rent = Rent::get()?.minimum_balance(0);
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.receipt.to_account_info(),
+                },
+                &[&[RECEIPT_SEED, user.as_ref(), &[bump]]],
+            ),
+            rent,
+            0,
+            &crate::ID,
+        )?;
+
+        ctx.accounts.faucet.sub_lamports(CLAIM_AMOUNT)?;
+        ctx.accounts.user.add_lamports(CLAIM_AMOUNT)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Faucet {}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Faucet::INIT_SPACE, seeds = [FAUCET_SEED], bump)]
+    pub faucet: Account<'info, Faucet>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Fund<'info> {
+    #[account(mut, seeds = [FAUCET_SEED], bump)]
+    pub faucet: Account<'info, Faucet>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut, seeds = [FAUCET_SEED], bump)]
+    pub faucet: Account<'info, Faucet>,
+    /// CHECK: address verified and created in the handler
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Receipt address does not match the claimant")]
+    InvalidReceipt,
+}
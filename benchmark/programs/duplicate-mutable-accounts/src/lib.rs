@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+declare_id!("8VZvP6FjwbXVPrnZgV3hfs4NLypL2A24rTfFYA8g1JyJ");
+
+pub const BALANCE_SEED: &[u8] = b"balance";
+/// Points every new balance starts with
+pub const STARTING_POINTS: u64 = 1_000;
+
+/// Point balances that owners can send to each other
+#[program]
+pub mod duplicate_mutable_accounts {
+    use super::*;
+
+    pub fn open(ctx: Context<Open>) -> Result<()> {
+        ctx.accounts.balance.owner = ctx.accounts.owner.key();
+        ctx.accounts.balance.points = STARTING_POINTS;
+        Ok(())
+    }
+
+    pub fn transfer(ctx: Context<TransferPoints>, points: u64) -> Result<()> {
+        let from = &mut ctx.accounts.from;
+        from.points = from.points.checked_sub(points).ok_or(ErrorCode::InsufficientPoints)?;
+        let to = &mut ctx.accounts.to;
+        to.points = to.points.checked_add(points).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Balance {
+    pub owner: Pubkey,
+    pub points: u64,
+}
+
+#[derive(Accounts)]
+pub struct Open<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Balance::INIT_SPACE,
+        seeds = [BALANCE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub balance: Account<'info, Balance>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPoints<'info> {
+    #[account(mut, has_one = owner)]
+    pub from: Account<'info, Balance>,
+    #[account(mut)]
+    pub to: Account<'info, Balance>,
+    pub owner: Signer<'info>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Not enough points")]
+    InsufficientPoints,
+    #[msg("Point balance overflowed")]
+    Overflow,
+}
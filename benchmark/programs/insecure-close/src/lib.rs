@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+declare_id!("Bp89dBMg9Yp3phXqN7wp6ur77fbqoCie26EzX1rPSmFV");
+
+pub const POOL_SEED: &[u8] = b"pool";
+pub const TICKET_SEED: &[u8] = b"ticket";
+/// Lamports a ticket costs and refunds
+pub const TICKET_PRICE: u64 = 50_000_000;
+
+/// Tickets bought from a pool, each refundable once by closing it
+#[program]
+pub mod insecure_close {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn buy(ctx: Context<Buy>) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.pool.to_account_info(),
+                },
+            ),
+            TICKET_PRICE,
+        )?;
+        ctx.accounts.ticket.owner = ctx.accounts.owner.key();
+        Ok(())
+    }
+
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        ctx.accounts.pool.sub_lamports(TICKET_PRICE)?;
+        ctx.accounts.owner.add_lamports(TICKET_PRICE)?;
+
+        let ticket = ctx.accounts.ticket.to_account_info();
+        let rent = ticket.lamports();
+        ticket.sub_lamports(rent)?;
+        ctx.accounts.owner.add_lamports(rent)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Ticket {
+    pub owner: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Pool::INIT_SPACE, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(mut, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Ticket::INIT_SPACE,
+        seeds = [TICKET_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut, seeds = [TICKET_SEED, owner.key().as_ref()], bump, has_one = owner)]
+    pub ticket: Account<'info, Ticket>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
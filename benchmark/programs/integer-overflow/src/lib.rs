@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+declare_id!("8gsgfyns13HEj6CycEuYhMvZTPYEYXBVaWGujDDugRq2");
+
+pub const POOL_SEED: &[u8] = b"pool";
+pub const LEDGER_SEED: &[u8] = b"ledger";
+
+/// A shared SOL pool that tracks each depositor's share in a ledger entry
+#[program]
+pub mod integer_overflow {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn open_ledger(_ctx: Context<OpenLedger>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        ctx.accounts.ledger.balance += amount;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.ledger.balance -= amount;
+        ctx.accounts.pool.sub_lamports(amount)?;
+        ctx.accounts.user.add_lamports(amount)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Ledger {
+    pub balance: u64,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Pool::INIT_SPACE, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenLedger<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Ledger::INIT_SPACE,
+        seeds = [LEDGER_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub ledger: Account<'info, Ledger>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut, seeds = [LEDGER_SEED, user.key().as_ref()], bump)]
+    pub ledger: Account<'info, Ledger>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [POOL_SEED], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut, seeds = [LEDGER_SEED, user.key().as_ref()], bump)]
+    pub ledger: Account<'info, Ledger>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
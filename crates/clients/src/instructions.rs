@@ -0,0 +1,246 @@
+//! Instruction builders for the flows an exploit or a scoreboard needs,
+//! assembled from the generated `client::accounts` and `client::args` with
+//! every PDA derived in [`crate::pdas`].
+//!
+//! Builders take only the keys a caller actually chooses. They return plain
+//! `Instruction`s, which a native client signs and sends itself and a browser
+//! hands to its wallet adapter.
+
+use anchor_lang::solana_program::{instruction::Instruction, sysvar};
+use anchor_lang::{prelude::*, system_program, InstructionData};
+
+fn build(program_id: Pubkey, accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub mod missing_signer {
+    use super::*;
+    use crate::pda::client::{accounts, args};
+    use crate::pda::ID;
+    use crate::pdas::{missing_signer::vault, registry};
+
+    /// Open vault `vault_id` for `owner`; `vault_token_account` is a fresh
+    /// keypair that must also sign
+    pub fn initialize_vault(
+        owner: &Pubkey,
+        vault_id: u64,
+        vault_token_account: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        build(
+            ID,
+            accounts::InitializeVault {
+                vault: vault(owner, vault_id),
+                vault_token_account: *vault_token_account,
+                token_mint: *mint,
+                owner: *owner,
+                token_program: *token_program,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            args::InitializeVault { vault_id },
+        )
+    }
+
+    pub fn deposit(
+        owner: &Pubkey,
+        vault_id: u64,
+        vault_token_account: &Pubkey,
+        mint: &Pubkey,
+        source: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        build(
+            ID,
+            accounts::Deposit {
+                vault: vault(owner, vault_id),
+                vault_token_account: *vault_token_account,
+                token_mint: *mint,
+                source: *source,
+                owner: *owner,
+                token_program: *token_program,
+            },
+            args::Deposit { amount },
+        )
+    }
+
+    /// The vulnerable withdraw. `owner` is never marked as a signer, so this
+    /// is the exploit when `owner` is someone else.
+    pub fn withdraw(
+        owner: &Pubkey,
+        vault_id: u64,
+        vault_token_account: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        build(
+            ID,
+            accounts::WithdrawCtx {
+                vault: vault(owner, vault_id),
+                vault_token_account: *vault_token_account,
+                token_mint: *mint,
+                destination: *destination,
+                token_program: *token_program,
+                owner: *owner,
+            },
+            args::Withdraw { amount },
+        )
+    }
+
+    /// Report `player`'s drain of `owner`'s vault into `loot` to the registry
+    pub fn verify_exploit(
+        player: &Pubkey,
+        owner: &Pubkey,
+        vault_id: u64,
+        vault_token_account: &Pubkey,
+        loot: &Pubkey,
+    ) -> Instruction {
+        let challenge = registry::challenge(crate::pdas::missing_signer::CHALLENGE_ID);
+        build(
+            ID,
+            accounts::VerifyExploit {
+                vault: vault(owner, vault_id),
+                vault_token_account: *vault_token_account,
+                loot: *loot,
+                challenge,
+                solve: registry::solve(&challenge, player),
+                flag_authority: registry::flag_authority(&ID),
+                player: *player,
+                registry_program: crate::ctf_registry::ID,
+                system_program: system_program::ID,
+            },
+            args::VerifyExploit {},
+        )
+    }
+}
+
+pub mod memory_safety {
+    use super::*;
+    use crate::memory_safety_vulns::client::{accounts, args};
+    use crate::memory_safety_vulns::ID;
+    use crate::pdas::{memory_safety::complex, registry};
+
+    pub fn initialize_complex(player: &Pubkey) -> Instruction {
+        build(
+            ID,
+            accounts::InitializeComplex {
+                target_account: complex(player),
+                user: *player,
+                system_program: system_program::ID,
+            },
+            args::InitializeComplex {},
+        )
+    }
+
+    /// Operation 1 copies `data` into the 32-byte buffer with a 40-byte
+    /// bound; bytes 33 to 40 land in `is_admin` and its padding
+    pub fn complex_vulnerability_demo(player: &Pubkey, operation: u8, data: Vec<u8>) -> Instruction {
+        build(
+            ID,
+            accounts::ComplexDemo {
+                target_account: complex(player),
+                user: *player,
+            },
+            args::ComplexVulnerabilityDemo { operation, data },
+        )
+    }
+
+    pub fn claim_admin_flag(player: &Pubkey) -> Instruction {
+        let challenge = registry::challenge(crate::pdas::memory_safety::CHALLENGE_ID);
+        build(
+            ID,
+            accounts::ClaimAdminFlag {
+                target_account: complex(player),
+                challenge,
+                instance: registry::instance(&challenge, player),
+                solve: registry::solve(&challenge, player),
+                flag_authority: registry::flag_authority(&ID),
+                player: *player,
+                registry_program: crate::ctf_registry::ID,
+                system_program: system_program::ID,
+            },
+            args::ClaimAdminFlag {},
+        )
+    }
+}
+
+pub mod program_close {
+    use super::*;
+    use crate::pdas::{
+        associated_token_address, program_close as pdas, ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    };
+    use crate::solana_program_close::client::{accounts, args};
+    use crate::solana_program_close::ID;
+
+    /// Open vault `vault_id` for `owner` and deposit `initial_deposit` of
+    /// `mint` from their associated token account
+    pub fn initialize_vault(
+        owner: &Pubkey,
+        vault_id: u64,
+        mint: &Pubkey,
+        initial_deposit: u64,
+    ) -> Instruction {
+        let vault = pdas::vault(owner, vault_id);
+        let share_mint = pdas::share_mint(&vault);
+        build(
+            ID,
+            accounts::InitializeVault {
+                vault,
+                vault_token_account: associated_token_address(&vault, mint),
+                user_token_account: associated_token_address(owner, mint),
+                mint: *mint,
+                share_mint,
+                user_share_account: associated_token_address(owner, &share_mint),
+                config: pdas::config(),
+                owner: *owner,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            args::InitializeVault {
+                vault_id,
+                initial_deposit,
+            },
+        )
+    }
+
+    /// Withdraw `amount` of `mint` back to `owner`, with the fee going to
+    /// `fee_collector`
+    pub fn withdraw(
+        owner: &Pubkey,
+        vault_id: u64,
+        mint: &Pubkey,
+        fee_collector: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let vault = pdas::vault(owner, vault_id);
+        let share_mint = pdas::share_mint(&vault);
+        build(
+            ID,
+            accounts::Withdraw {
+                vault,
+                vault_token_account: associated_token_address(&vault, mint),
+                mint: *mint,
+                user_token_account: associated_token_address(owner, mint),
+                share_mint,
+                user_share_account: associated_token_address(owner, &share_mint),
+                config: pdas::config(),
+                circuit_breaker: pdas::circuit_breaker(),
+                fee_authority: pdas::fee_authority(),
+                fee_collector: *fee_collector,
+                owner: *owner,
+                token_program: TOKEN_PROGRAM_ID,
+            },
+            args::Withdraw { amount },
+        )
+    }
+}
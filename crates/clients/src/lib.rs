@@ -0,0 +1,51 @@
+//! Typed clients for every Anchor program in the repository, generated with
+//! `declare_program!` from the checked-in IDLs in `idls/`.
+//!
+//! Each module mirrors one program without depending on its crate, so an
+//! on-chain program can CPI into a challenge (or the registry) without
+//! pulling in the challenge's dependencies or hand-encoding discriminators:
+//!
+//! ```ignore
+//! use clients::pda;
+//!
+//! pda::cpi::withdraw(
+//!     CpiContext::new(
+//!         ctx.accounts.pda_program.to_account_info(),
+//!         pda::cpi::accounts::WithdrawCtx { /* ... */ },
+//!     ),
+//!     amount,
+//! )?;
+//! ```
+//!
+//! Every module also has `client::accounts` and `client::args` for building
+//! instructions off-chain, plus the program's `accounts`, `types`, `events`,
+//! `constants`, and `ID`.
+//!
+//! The modules follow the IDL snapshots, so they change exactly when
+//! `cargo run -p idl-snapshots` rewrites one.
+//!
+//! On top of the generated code, [`pdas`] derives every PDA and
+//! [`instructions`] builds the instructions exploits and scoreboards use.
+//! Neither needs an RPC connection, and the crate builds for
+//! `wasm32-unknown-unknown` so a browser UI can share them; [`rpc`], the only
+//! part that uses `solana-client`, is left out on wasm32.
+
+use anchor_lang::prelude::*;
+
+pub mod instructions;
+pub mod pdas;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rpc;
+
+declare_program!(ctf_registry);
+declare_program!(deployments);
+declare_program!(memory_safety_vulns);
+declare_program!(mock_spl);
+declare_program!(pda);
+declare_program!(solana_program_close);
+
+/// Decode raw account data as one of the generated account types, checking
+/// its discriminator
+pub fn decode_account<T: AccountDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut data)
+}
@@ -0,0 +1,153 @@
+//! PDA derivations for each program, matching the seeds in its crate.
+//!
+//! These are plain functions of their inputs, so the same addresses come out
+//! of a test, the grader, or a browser UI built for wasm32.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+/// SPL Token, the token program every challenge is deployed with
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// `wallet`'s associated token account for `mint` under SPL Token
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+pub mod registry {
+    use anchor_lang::prelude::*;
+
+    use crate::ctf_registry::ID;
+
+    /// Seed of the PDA each challenge program signs `capture_flag` with
+    pub const FLAG_AUTHORITY_SEED: &[u8] = b"flag_authority";
+
+    pub fn registry() -> Pubkey {
+        Pubkey::find_program_address(&[b"registry"], &ID).0
+    }
+
+    pub fn challenge(challenge_id: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"challenge", challenge_id.as_bytes()], &ID).0
+    }
+
+    /// `player`'s solve of a challenge, whichever version it was against
+    pub fn solve(challenge: &Pubkey, player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"solve", challenge.as_ref(), player.as_ref()], &ID).0
+    }
+
+    /// `player`'s randomized instance of a challenge
+    pub fn instance(challenge: &Pubkey, player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"instance", challenge.as_ref(), player.as_ref()], &ID).0
+    }
+
+    /// Verifiable build record of one version of a challenge
+    pub fn build(challenge: &Pubkey, version: u16) -> Pubkey {
+        Pubkey::find_program_address(&[b"build", challenge.as_ref(), &version.to_le_bytes()], &ID).0
+    }
+
+    /// Flag authority of the challenge program `program_id`, derived under
+    /// that program rather than the registry
+    pub fn flag_authority(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[FLAG_AUTHORITY_SEED], program_id).0
+    }
+}
+
+pub mod deployments {
+    use anchor_lang::prelude::*;
+
+    use crate::deployments::ID;
+
+    pub fn manager() -> Pubkey {
+        Pubkey::find_program_address(&[b"manager"], &ID).0
+    }
+
+    /// Deployment record of `challenge_id` on `cluster`
+    pub fn deployment(cluster: &str, challenge_id: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"deployment", cluster.as_bytes(), challenge_id.as_bytes()], &ID).0
+    }
+}
+
+pub mod missing_signer {
+    use anchor_lang::prelude::*;
+
+    use crate::pda::ID;
+
+    pub const CHALLENGE_ID: &str = "missing-signer-check";
+
+    pub fn vault(creator: &Pubkey, vault_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault", creator.as_ref(), &vault_id.to_le_bytes()], &ID).0
+    }
+
+    pub fn instance(player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"instance", player.as_ref()], &ID).0
+    }
+}
+
+pub mod memory_safety {
+    use anchor_lang::prelude::*;
+
+    use crate::memory_safety_vulns::ID;
+
+    pub const CHALLENGE_ID: &str = "memory-safety-vulns";
+
+    pub fn buffer(player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"buffer", player.as_ref()], &ID).0
+    }
+
+    pub fn target(player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"target", player.as_ref()], &ID).0
+    }
+
+    pub fn complex(player: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"complex", player.as_ref()], &ID).0
+    }
+}
+
+pub mod program_close {
+    use anchor_lang::prelude::*;
+
+    use crate::solana_program_close::ID;
+
+    fn singleton(seed: &[u8]) -> Pubkey {
+        Pubkey::find_program_address(&[seed], &ID).0
+    }
+
+    pub fn vault(owner: &Pubkey, vault_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault", owner.as_ref(), &vault_id.to_le_bytes()], &ID).0
+    }
+
+    pub fn share_mint(vault: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"share_mint", vault.as_ref()], &ID).0
+    }
+
+    pub fn config() -> Pubkey {
+        singleton(b"config")
+    }
+
+    pub fn circuit_breaker() -> Pubkey {
+        singleton(b"circuit_breaker")
+    }
+
+    pub fn fee_authority() -> Pubkey {
+        singleton(b"fee_authority")
+    }
+
+    pub fn health() -> Pubkey {
+        singleton(b"health")
+    }
+
+    pub fn multisig() -> Pubkey {
+        singleton(b"multisig")
+    }
+
+    /// Admin proposal number `index`, the multisig's `proposal_count` when it
+    /// was created
+    pub fn proposal(index: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"proposal", &index.to_le_bytes()], &ID).0
+    }
+}
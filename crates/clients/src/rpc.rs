@@ -0,0 +1,54 @@
+//! Typed account reads over `solana-client`. Not built for wasm32, where a
+//! browser fetches account data with its own JSON-RPC client and decodes it
+//! with [`crate::decode_account`].
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use solana_client::rpc_client::RpcClient;
+
+use crate::ctf_registry::accounts::Challenge;
+use crate::deployments::accounts::Deployment;
+use crate::pdas::{deployments, registry};
+
+/// Fetch and decode an account of one of the generated types, or `None` if
+/// it does not exist
+pub fn fetch_account<T: AccountDeserialize>(
+    rpc: &RpcClient,
+    address: &Pubkey,
+) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let account = rpc.get_account_with_commitment(address, rpc.commitment())?.value;
+    account
+        .map(|account| crate::decode_account(&account.data))
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// The registry entry of `challenge_id`, whose `version` the solve PDA and
+/// the flag-capturing builders need
+pub fn challenge(rpc: &RpcClient, challenge_id: &str) -> Result<Option<Challenge>, Box<dyn std::error::Error>> {
+    fetch_account(rpc, &registry::challenge(challenge_id))
+}
+
+/// The deployment of `challenge_id` published for `cluster`, e.g. `devnet`,
+/// including retired ones
+pub fn deployment(
+    rpc: &RpcClient,
+    cluster: &str,
+    challenge_id: &str,
+) -> Result<Option<Deployment>, Box<dyn std::error::Error>> {
+    fetch_account(rpc, &deployments::deployment(cluster, challenge_id))
+}
+
+/// The program currently serving `challenge_id` on `cluster`, or `None` if
+/// it was never published there or has been retired. Clients fall back to
+/// the `declare_id!` of the program crate when this is `None` on a local
+/// validator.
+pub fn active_program_id(
+    rpc: &RpcClient,
+    cluster: &str,
+    challenge_id: &str,
+) -> Result<Option<Pubkey>, Box<dyn std::error::Error>> {
+    Ok(deployment(rpc, cluster, challenge_id)?
+        .filter(|deployment| deployment.active)
+        .map(|deployment| deployment.program_id))
+}
@@ -0,0 +1,65 @@
+//! The generated clients agree with the program crates they were built from.
+//!
+//! A mismatch means `idls/` is stale: regenerate it with
+//! `cargo run -p idl-snapshots`.
+
+use anchor_lang::Discriminator;
+
+#[test]
+fn program_ids_match() {
+    assert_eq!(clients::ctf_registry::ID, ctf_registry::ID);
+    assert_eq!(clients::deployments::ID, deployments::ID);
+    assert_eq!(clients::memory_safety_vulns::ID, memory_safety_vulns::ID);
+    assert_eq!(clients::mock_spl::ID, mock_spl::ID);
+    assert_eq!(clients::pda::ID, pda::ID);
+    assert_eq!(clients::solana_program_close::ID, solana_program_close::ID);
+}
+
+#[test]
+fn instruction_discriminators_match() {
+    assert_eq!(
+        clients::ctf_registry::client::args::CaptureFlag::DISCRIMINATOR,
+        ctf_registry::instruction::CaptureFlag::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::deployments::client::args::PublishDeployment::DISCRIMINATOR,
+        deployments::instruction::PublishDeployment::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::memory_safety_vulns::client::args::ClaimAdminFlag::DISCRIMINATOR,
+        memory_safety_vulns::instruction::ClaimAdminFlag::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::mock_spl::client::args::CreateFundedMint::DISCRIMINATOR,
+        mock_spl::instruction::CreateFundedMint::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::pda::client::args::Withdraw::DISCRIMINATOR,
+        pda::instruction::Withdraw::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::solana_program_close::client::args::Withdraw::DISCRIMINATOR,
+        solana_program_close::instruction::Withdraw::DISCRIMINATOR
+    );
+}
+
+#[test]
+fn account_discriminators_match() {
+    assert_eq!(
+        clients::ctf_registry::accounts::Challenge::DISCRIMINATOR,
+        ctf_registry::Challenge::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::deployments::accounts::Deployment::DISCRIMINATOR,
+        deployments::Deployment::DISCRIMINATOR
+    );
+    assert_eq!(
+        clients::memory_safety_vulns::accounts::ComplexAccount::DISCRIMINATOR,
+        memory_safety_vulns::ComplexAccount::DISCRIMINATOR
+    );
+    assert_eq!(clients::pda::accounts::Vault::DISCRIMINATOR, pda::Vault::DISCRIMINATOR);
+    assert_eq!(
+        clients::solana_program_close::accounts::Vault::DISCRIMINATOR,
+        solana_program_close::Vault::DISCRIMINATOR
+    );
+}
@@ -0,0 +1,77 @@
+//! The PDA helpers derive the same addresses as the program crates.
+
+use anchor_lang::prelude::Pubkey;
+use clients::pdas;
+
+#[test]
+fn registry_addresses() {
+    let program = Pubkey::new_unique();
+    assert_eq!(
+        pdas::registry::flag_authority(&program),
+        ctf_registry::flag_authority_address(&program)
+    );
+    assert_eq!(pdas::registry::FLAG_AUTHORITY_SEED, ctf_registry::FLAG_AUTHORITY_SEED);
+}
+
+#[test]
+fn deployments_addresses() {
+    assert_eq!(
+        pdas::deployments::deployment("devnet", "native-vault"),
+        deployments::deployment_address("devnet", "native-vault")
+    );
+    assert_eq!(
+        pdas::deployments::manager(),
+        Pubkey::find_program_address(&[deployments::MANAGER_SEED], &deployments::ID).0
+    );
+}
+
+#[test]
+fn missing_signer_addresses() {
+    let owner = Pubkey::new_unique();
+    assert_eq!(pdas::missing_signer::vault(&owner, 7), pda::vault_address(&owner, 7).0);
+    assert_eq!(
+        pdas::missing_signer::instance(&owner),
+        Pubkey::find_program_address(&[pda::INSTANCE_SEED, owner.as_ref()], &pda::ID).0
+    );
+    assert_eq!(pdas::missing_signer::CHALLENGE_ID, pda::CHALLENGE_ID);
+}
+
+#[test]
+fn memory_safety_addresses() {
+    use memory_safety_vulns::{player_account_address, BUFFER_SEED, COMPLEX_SEED, TARGET_SEED};
+
+    let player = Pubkey::new_unique();
+    assert_eq!(pdas::memory_safety::buffer(&player), player_account_address(BUFFER_SEED, &player));
+    assert_eq!(pdas::memory_safety::target(&player), player_account_address(TARGET_SEED, &player));
+    assert_eq!(pdas::memory_safety::complex(&player), player_account_address(COMPLEX_SEED, &player));
+}
+
+#[test]
+fn program_close_addresses() {
+    use solana_program_close as program;
+
+    let owner = Pubkey::new_unique();
+    let vault = program::vault_address(&owner, 3);
+    let singleton = |seed: &[u8]| Pubkey::find_program_address(&[seed], &program::ID).0;
+    assert_eq!(pdas::program_close::vault(&owner, 3), vault);
+    assert_eq!(pdas::program_close::share_mint(&vault), program::share_mint_address(&vault));
+    assert_eq!(pdas::program_close::health(), program::health_address());
+    assert_eq!(pdas::program_close::config(), singleton(program::CONFIG_SEED));
+    assert_eq!(pdas::program_close::circuit_breaker(), singleton(program::CIRCUIT_BREAKER_SEED));
+    assert_eq!(pdas::program_close::fee_authority(), singleton(program::FEE_AUTHORITY_SEED));
+    assert_eq!(pdas::program_close::multisig(), singleton(program::MULTISIG_SEED));
+    assert_eq!(
+        pdas::program_close::proposal(2),
+        Pubkey::find_program_address(&[program::PROPOSAL_SEED, &2u64.to_le_bytes()], &program::ID).0
+    );
+}
+
+#[test]
+fn associated_token_address_matches_spl() {
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    assert_eq!(
+        pdas::associated_token_address(&wallet, &mint),
+        spl_associated_token_account::get_associated_token_address(&wallet, &mint)
+    );
+}
@@ -0,0 +1,372 @@
+//! Catalog of every custom error emitted by the CTF programs.
+//!
+//! Each program declares its `ErrorCode` enum with a distinct
+//! `#[error_code(offset = ...)]`, so a raw `custom program error: 0x...` from
+//! any transaction maps back to exactly one challenge:
+//!
+//! | Challenge | Codes |
+//! |-----------|-------|
+//! | `ctf-registry` | 6000-6999 |
+//! | `missing-signer-check` | 7000-7999 |
+//! | `solana-program-close` | 8000-8999 |
+//! | `memory-safety-vulns` | 9000-9999 |
+//! | `mock-spl` | 10000-10999 |
+//! | `native-vault` | 11000-11999 |
+//! | `pinocchio-owner-check` | 12000-12999 |
+//! | `steel-vault` | 13000-13999 |
+//! | `sbpf-crackme` | 14000-14999 |
+//! | `deployments` | 15000-15999 |
+//!
+//! `native-vault` and `pinocchio-owner-check` have no Anchor `#[error_code]`;
+//! their error enums add the offset themselves when converting to
+//! `ProgramError::Custom`. Steel's `error!` uses the discriminants as codes,
+//! so `steel-vault` numbers its variants from 13000 explicitly, and so does
+//! the dependency-free `sbpf-crackme`.
+//!
+//! When adding a variant to one of the enums, add it to the matching list
+//! below as well.
+
+use std::fmt::Display;
+use std::ops::Range;
+
+pub use ctf_registry::ErrorCode as RegistryError;
+pub use deployments::ErrorCode as DeploymentsError;
+pub use memory_safety_vulns::ErrorCode as MemorySafetyError;
+pub use mock_spl::ErrorCode as MockSplError;
+pub use native_vault::error::VaultError as NativeVaultError;
+pub use pda::ErrorCode as MissingSignerError;
+pub use pinocchio_owner_check::error::OwnerCheckError as PinocchioOwnerCheckError;
+pub use sbpf_crackme::CrackmeError;
+pub use solana_program_close::ErrorCode as ProgramCloseError;
+pub use steel_vault_api::error::VaultError as SteelVaultError;
+
+const REGISTRY_ERRORS: &[RegistryError] = &[
+    RegistryError::InvalidMembers,
+    RegistryError::InvalidThreshold,
+    RegistryError::NotEnoughApprovals,
+    RegistryError::InvalidChallengeId,
+    RegistryError::InvalidStatusTransition,
+    RegistryError::InvalidProgramId,
+    RegistryError::ChallengePaused,
+    RegistryError::EventFinalized,
+    RegistryError::EventNotFinalized,
+    RegistryError::DeadlineNotReached,
+    RegistryError::DeadlinePassed,
+    RegistryError::InvalidWriteupUri,
+    RegistryError::WriteupAlreadyRevealed,
+    RegistryError::CooldownActive,
+    RegistryError::AttemptLimitReached,
+    RegistryError::InvalidFlagAuthority,
+    RegistryError::MissingEntropy,
+    RegistryError::NoProfitTarget,
+    RegistryError::InsufficientProfit,
+    RegistryError::InvalidRepositoryUri,
+    RegistryError::InvalidCommit,
+    RegistryError::BuildHashMismatch,
+    RegistryError::StaleVersion,
+    RegistryError::SnapshotExists,
+    RegistryError::Unauthorized,
+    RegistryError::ProfitAlreadyClaimed,
+];
+
+const MISSING_SIGNER_ERRORS: &[MissingSignerError] = &[
+    MissingSignerError::NotImplemented,
+    MissingSignerError::Unauthorized,
+    MissingSignerError::VaultNotDrained,
+    MissingSignerError::OwnVault,
+    MissingSignerError::InvalidLoot,
+    MissingSignerError::NoWithdrawDestination,
+    MissingSignerError::VaultNotEmpty,
+    MissingSignerError::AllowanceExpired,
+    MissingSignerError::AllowanceExceeded,
+    MissingSignerError::AllowanceNotExceeded,
+    MissingSignerError::InvalidPermit,
+    MissingSignerError::NotPendingOwner,
+    MissingSignerError::StaleNonce,
+    MissingSignerError::NonceRequired,
+    MissingSignerError::WrongMint,
+    MissingSignerError::NotOwner,
+    MissingSignerError::DestinationNotAllowed,
+    MissingSignerError::RateLimited,
+    MissingSignerError::NotSeededVault,
+    MissingSignerError::NotPayee,
+];
+
+const PROGRAM_CLOSE_ERRORS: &[ProgramCloseError] = &[
+    ProgramCloseError::VaultInactive,
+    ProgramCloseError::InsufficientFunds,
+    ProgramCloseError::Unauthorized,
+    ProgramCloseError::InvalidOwners,
+    ProgramCloseError::InvalidThreshold,
+    ProgramCloseError::NotEnoughApprovals,
+    ProgramCloseError::AlreadyApproved,
+    ProgramCloseError::AlreadyExecuted,
+    ProgramCloseError::StaleProposal,
+    ProgramCloseError::ActionMismatch,
+    ProgramCloseError::TimelockNotExpired,
+    ProgramCloseError::NoSuccessor,
+    ProgramCloseError::SuccessorNotActive,
+    ProgramCloseError::MigrationIncomplete,
+    ProgramCloseError::InvalidGuardians,
+    ProgramCloseError::DepositsPaused,
+    ProgramCloseError::WithdrawalsPaused,
+    ProgramCloseError::VaultNotEmpty,
+    ProgramCloseError::DepositCapExceeded,
+    ProgramCloseError::CircuitBreakerTripped,
+    ProgramCloseError::FeeTooHigh,
+    ProgramCloseError::NotNominated,
+    ProgramCloseError::AdminStillActive,
+    ProgramCloseError::InvalidBatchAccounts,
+    ProgramCloseError::AllowanceExceeded,
+    ProgramCloseError::AllowanceExpired,
+    ProgramCloseError::CoverExceedsShortfall,
+    ProgramCloseError::ProgramFrozen,
+    ProgramCloseError::SolVaultNotRentExempt,
+    ProgramCloseError::WithdrawalNeedsRequest,
+    ProgramCloseError::WithdrawalBelowThreshold,
+    ProgramCloseError::ProgramNotFrozen,
+    ProgramCloseError::NotFrozenByPlayer,
+    ProgramCloseError::NotSeededVault,
+    ProgramCloseError::MathOverflow,
+    ProgramCloseError::WithdrawalLimitExceeded,
+];
+
+const MEMORY_SAFETY_ERRORS: &[MemorySafetyError] = &[
+    MemorySafetyError::BufferOverflow,
+    MemorySafetyError::UseAfterFree,
+    MemorySafetyError::UninitializedMemory,
+    MemorySafetyError::DoubleFree,
+    MemorySafetyError::NullPointerDereference,
+    MemorySafetyError::InvalidOperation,
+    MemorySafetyError::InvalidBufferLength,
+    MemorySafetyError::AliasedAccounts,
+    MemorySafetyError::MalformedInput,
+    MemorySafetyError::NestingTooDeep,
+    MemorySafetyError::AllocationTooLarge,
+    MemorySafetyError::InvalidOffset,
+    MemorySafetyError::WriteInterrupted,
+    MemorySafetyError::SizeNotForged,
+    MemorySafetyError::Unauthorized,
+    MemorySafetyError::NotAdmin,
+    MemorySafetyError::RecordBookFull,
+    MemorySafetyError::MigrationNotSkewed,
+    MemorySafetyError::NotImplemented,
+];
+
+const MOCK_SPL_ERRORS: &[MockSplError] = &[
+    MockSplError::InvalidDecimals,
+    MockSplError::AmountTooLarge,
+];
+
+const NATIVE_VAULT_ERRORS: &[NativeVaultError] = &[
+    NativeVaultError::NotImplemented,
+    NativeVaultError::InvalidInstruction,
+    NativeVaultError::VaultAlreadyInitialized,
+    NativeVaultError::VaultNotInitialized,
+    NativeVaultError::InvalidVaultAddress,
+    NativeVaultError::WrongVaultTokenAccount,
+    NativeVaultError::NotOwner,
+    NativeVaultError::WrongTokenProgram,
+    NativeVaultError::WrongMint,
+];
+
+const PINOCCHIO_OWNER_CHECK_ERRORS: &[PinocchioOwnerCheckError] = &[
+    PinocchioOwnerCheckError::NotImplemented,
+    PinocchioOwnerCheckError::InvalidConfig,
+    PinocchioOwnerCheckError::InvalidAddress,
+    PinocchioOwnerCheckError::NotAuthority,
+    PinocchioOwnerCheckError::InsufficientFunds,
+];
+
+const STEEL_VAULT_ERRORS: &[SteelVaultError] = &[
+    SteelVaultError::NotImplemented,
+    SteelVaultError::NotOwner,
+    SteelVaultError::InsufficientFunds,
+];
+
+const CRACKME_ERRORS: &[CrackmeError] = &[
+    CrackmeError::MalformedInput,
+    CrackmeError::WrongPassword,
+    CrackmeError::AccountsNotAllowed,
+];
+
+const DEPLOYMENTS_ERRORS: &[DeploymentsError] = &[
+    DeploymentsError::InvalidCluster,
+    DeploymentsError::InvalidChallengeId,
+    DeploymentsError::TooManyParams,
+    DeploymentsError::InvalidParamName,
+    DeploymentsError::DuplicateParam,
+    DeploymentsError::DeploymentRetired,
+    DeploymentsError::Unauthorized,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Challenge {
+    Registry,
+    MissingSignerCheck,
+    ProgramClose,
+    MemorySafetyVulns,
+    MockSpl,
+    NativeVault,
+    PinocchioOwnerCheck,
+    SteelVault,
+    SbpfCrackme,
+    Deployments,
+}
+
+impl Challenge {
+    pub const ALL: [Challenge; 10] = [
+        Challenge::Registry,
+        Challenge::MissingSignerCheck,
+        Challenge::ProgramClose,
+        Challenge::MemorySafetyVulns,
+        Challenge::MockSpl,
+        Challenge::NativeVault,
+        Challenge::PinocchioOwnerCheck,
+        Challenge::SteelVault,
+        Challenge::SbpfCrackme,
+        Challenge::Deployments,
+    ];
+
+    /// Directory name of the challenge, also used as its registry id
+    pub fn id(self) -> &'static str {
+        match self {
+            Challenge::Registry => "ctf-registry",
+            Challenge::MissingSignerCheck => "missing-signer-check",
+            Challenge::ProgramClose => "solana-program-close",
+            Challenge::MemorySafetyVulns => "memory-safety-vulns",
+            Challenge::MockSpl => "mock-spl",
+            Challenge::NativeVault => "native-vault",
+            Challenge::PinocchioOwnerCheck => "pinocchio-owner-check",
+            Challenge::SteelVault => "steel-vault",
+            Challenge::SbpfCrackme => "sbpf-crackme",
+            Challenge::Deployments => "deployments",
+        }
+    }
+
+    /// Codes reserved for this challenge's `ErrorCode` enum
+    pub fn error_range(self) -> Range<u32> {
+        let start = match self {
+            Challenge::Registry => 6000,
+            Challenge::MissingSignerCheck => 7000,
+            Challenge::ProgramClose => 8000,
+            Challenge::MemorySafetyVulns => 9000,
+            Challenge::MockSpl => 10_000,
+            Challenge::NativeVault => 11_000,
+            Challenge::PinocchioOwnerCheck => 12_000,
+            Challenge::SteelVault => 13_000,
+            Challenge::SbpfCrackme => 14_000,
+            Challenge::Deployments => 15_000,
+        };
+        start..start + 1000
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|challenge| challenge.error_range().contains(&code))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub challenge: Challenge,
+    pub code: u32,
+    pub name: String,
+    pub message: String,
+}
+
+/// Resolve a custom program error code to its challenge and meaning
+pub fn lookup(code: u32) -> Option<ErrorInfo> {
+    let challenge = Challenge::from_code(code)?;
+    let (name, message) = match challenge {
+        Challenge::Registry => describe(REGISTRY_ERRORS, code, RegistryError::name),
+        Challenge::MissingSignerCheck => {
+            describe(MISSING_SIGNER_ERRORS, code, MissingSignerError::name)
+        }
+        Challenge::ProgramClose => describe(PROGRAM_CLOSE_ERRORS, code, ProgramCloseError::name),
+        Challenge::MemorySafetyVulns => {
+            describe(MEMORY_SAFETY_ERRORS, code, MemorySafetyError::name)
+        }
+        Challenge::MockSpl => describe(MOCK_SPL_ERRORS, code, MockSplError::name),
+        Challenge::NativeVault => describe(NATIVE_VAULT_ERRORS, code, NativeVaultError::name),
+        Challenge::PinocchioOwnerCheck => describe(
+            PINOCCHIO_OWNER_CHECK_ERRORS,
+            code,
+            PinocchioOwnerCheckError::name,
+        ),
+        Challenge::SteelVault => describe(STEEL_VAULT_ERRORS, code, SteelVaultError::name),
+        // no_std without alloc, so the crate has no `name()` of its own
+        Challenge::SbpfCrackme => describe(CRACKME_ERRORS, code, |error| format!("{error:?}")),
+        Challenge::Deployments => describe(DEPLOYMENTS_ERRORS, code, DeploymentsError::name),
+    }?;
+    Some(ErrorInfo {
+        challenge,
+        code,
+        name,
+        message,
+    })
+}
+
+fn describe<E>(errors: &[E], code: u32, name: fn(&E) -> String) -> Option<(String, String)>
+where
+    E: Copy + Display + Into<u32>,
+{
+    errors
+        .iter()
+        .find(|error| (**error).into() == code)
+        .map(|error| (name(error), error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes<E: Copy + Into<u32>>(errors: &[E]) -> Vec<u32> {
+        errors.iter().map(|error| (*error).into()).collect()
+    }
+
+    #[test]
+    fn every_code_is_in_its_challenge_range() {
+        let catalog = [
+            (Challenge::Registry, codes(REGISTRY_ERRORS)),
+            (Challenge::MissingSignerCheck, codes(MISSING_SIGNER_ERRORS)),
+            (Challenge::ProgramClose, codes(PROGRAM_CLOSE_ERRORS)),
+            (Challenge::MemorySafetyVulns, codes(MEMORY_SAFETY_ERRORS)),
+            (Challenge::MockSpl, codes(MOCK_SPL_ERRORS)),
+            (Challenge::NativeVault, codes(NATIVE_VAULT_ERRORS)),
+            (Challenge::PinocchioOwnerCheck, codes(PINOCCHIO_OWNER_CHECK_ERRORS)),
+            (Challenge::SteelVault, codes(STEEL_VAULT_ERRORS)),
+            (Challenge::SbpfCrackme, codes(CRACKME_ERRORS)),
+            (Challenge::Deployments, codes(DEPLOYMENTS_ERRORS)),
+        ];
+        for (challenge, codes) in catalog {
+            for code in codes {
+                assert_eq!(Challenge::from_code(code), Some(challenge), "code {code}");
+                assert!(lookup(code).is_some(), "code {code}");
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_codes_across_challenges() {
+        let info = lookup(7000).unwrap();
+        assert_eq!(info.challenge, Challenge::MissingSignerCheck);
+        assert_eq!(info.name, "NotImplemented");
+
+        let info = lookup(9000).unwrap();
+        assert_eq!(info.challenge, Challenge::MemorySafetyVulns);
+        assert_eq!(info.message, "Buffer overflow detected");
+
+        assert!(lookup(6999).is_none());
+        let info = lookup(11_006).unwrap();
+        assert_eq!(info.challenge, Challenge::NativeVault);
+        assert_eq!(info.name, "NotOwner");
+
+        let info = lookup(15_000).unwrap();
+        assert_eq!(info.challenge, Challenge::Deployments);
+        assert_eq!(info.name, "InvalidCluster");
+
+        assert!(lookup(16_000).is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Assertions shared by the exploit tests.
+//!
+//! Balances are read through the `BanksClient` before and after the exploit:
+//!
+//! ```ignore
+//! let before = token_balance(&mut env.context.banks_client, &attacker_ata).await;
+//! env.process(&[exploit_ix], &[&attacker]).await?;
+//! assert_token_delta(&mut env.context.banks_client, &attacker_ata, before, 1_000_000).await;
+//! ```
+
+use solana_program_test::{BanksClient, BanksClientError};
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError,
+};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+
+/// Token amount held by a legacy SPL or Token-2022 token account
+pub async fn token_balance(banks: &mut BanksClient, account: &Pubkey) -> u64 {
+    let data = banks
+        .get_account(*account)
+        .await
+        .expect("banks client")
+        .unwrap_or_else(|| panic!("token account {account} does not exist"))
+        .data;
+    StateWithExtensions::<TokenAccount>::unpack(&data)
+        .unwrap_or_else(|_| panic!("{account} is not a token account"))
+        .base
+        .amount
+}
+
+/// Lamports held by `address`, 0 if the account does not exist
+pub async fn lamports(banks: &mut BanksClient, address: &Pubkey) -> u64 {
+    banks.get_balance(*address).await.expect("banks client")
+}
+
+/// Assert that the token balance of `account` changed by exactly `delta`
+/// since `before` was read
+pub async fn assert_token_delta(
+    banks: &mut BanksClient,
+    account: &Pubkey,
+    before: u64,
+    delta: i128,
+) {
+    let after = token_balance(banks, account).await;
+    assert_eq!(
+        after as i128 - before as i128,
+        delta,
+        "token balance of {account} went from {before} to {after}"
+    );
+}
+
+/// Assert that `address` lost at least `min_drained` lamports since `before`
+/// was read
+pub async fn assert_lamport_drain(
+    banks: &mut BanksClient,
+    address: &Pubkey,
+    before: u64,
+    min_drained: u64,
+) {
+    let after = lamports(banks, address).await;
+    assert!(
+        before.saturating_sub(after) >= min_drained,
+        "expected {address} to lose at least {min_drained} lamports, went from {before} to {after}"
+    );
+}
+
+/// Assert that `address` was closed (deleted or left without lamports)
+pub async fn assert_account_closed(banks: &mut BanksClient, address: &Pubkey) {
+    let account = banks.get_account(*address).await.expect("banks client");
+    if let Some(account) = account {
+        assert_eq!(
+            account.lamports, 0,
+            "{address} still holds {} lamports",
+            account.lamports
+        );
+    }
+}
+
+/// Assert that a transaction failed with the given custom program error.
+///
+/// Accepts any Anchor error enum, including `anchor_lang::error::ErrorCode`
+/// for constraint failures.
+pub fn assert_instruction_fails_with<E: Into<u32>>(result: Result<(), BanksClientError>, error: E) {
+    let expected = error.into();
+    let actual = match result {
+        Ok(()) => panic!("expected custom error {expected}, but the transaction succeeded"),
+        Err(err) => custom_error_code(&err)
+            .unwrap_or_else(|| panic!("expected custom error {expected}, got {err:?}")),
+    };
+    assert_eq!(
+        actual,
+        expected,
+        "expected {}, got {}",
+        describe(expected),
+        describe(actual)
+    );
+}
+
+/// Custom program error code carried by a failed transaction, if any
+pub fn custom_error_code(err: &BanksClientError) -> Option<u32> {
+    let transaction_error = match err {
+        BanksClientError::TransactionError(err) => err,
+        BanksClientError::SimulationError { err, .. } => err,
+        _ => return None,
+    };
+    match transaction_error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(*code),
+        _ => None,
+    }
+}
+
+fn describe(code: u32) -> String {
+    match ctf_errors::lookup(code) {
+        Some(info) => format!("{} ({code}, {}: {})", info.name, info.challenge.id(), info.message),
+        None => format!("error {code}"),
+    }
+}
@@ -0,0 +1,106 @@
+use solana_sdk::{
+    hash::hashv,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, Keypair},
+};
+
+/// Derives the same keypairs and addresses on every run from a challenge id.
+///
+/// Writeups, tests, and the grader can all refer to identical victim
+/// addresses without checking private keys into the repo. The keys are only
+/// as secret as the challenge id, so never use them outside a local or devnet
+/// deployment.
+#[derive(Clone, Debug)]
+pub struct FixtureSeed {
+    challenge_id: String,
+}
+
+impl FixtureSeed {
+    pub fn new(challenge_id: impl Into<String>) -> Self {
+        Self {
+            challenge_id: challenge_id.into(),
+        }
+    }
+
+    pub fn challenge_id(&self) -> &str {
+        &self.challenge_id
+    }
+
+    /// Keypair for an arbitrary label, e.g. `"admin"` or `"victim-bot"`
+    pub fn keypair(&self, label: &str) -> Keypair {
+        let seed = hashv(&[
+            b"ctf-fixtures",
+            self.challenge_id.as_bytes(),
+            label.as_bytes(),
+        ]);
+        keypair_from_seed(seed.as_ref()).expect("32-byte seed")
+    }
+
+    pub fn player(&self, index: usize) -> Keypair {
+        self.keypair(&format!("player-{index}"))
+    }
+
+    pub fn victim(&self, index: usize) -> Keypair {
+        self.keypair(&format!("victim-{index}"))
+    }
+
+    /// Keypair used as the address of a mint created by the fixtures
+    pub fn mint(&self, label: &str) -> Keypair {
+        self.keypair(&format!("mint-{label}"))
+    }
+
+    pub fn mint_authority(&self) -> Keypair {
+        self.keypair("mint-authority")
+    }
+
+    /// Upgrade authority of every program deployed by the fixtures
+    pub fn upgrade_authority(&self) -> Keypair {
+        self.keypair("upgrade-authority")
+    }
+
+    /// PDA of `program_id` for `seeds`. Deterministic as long as the seeds are
+    /// built from keys produced by this generator.
+    pub fn pda(&self, seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, program_id)
+    }
+
+    /// `[VAULT_SEED, owner, vault_id]` PDA of the vault challenges. `pda` and
+    /// `solana-program-close` export the same seed.
+    pub fn vault(&self, owner: &Pubkey, vault_id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        self.pda(&[pda::VAULT_SEED, owner.as_ref(), &vault_id.to_le_bytes()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signer;
+
+    #[test]
+    fn same_challenge_same_keys() {
+        let a = FixtureSeed::new("missing-signer-check");
+        let b = FixtureSeed::new("missing-signer-check");
+        assert_eq!(a.victim(0).pubkey(), b.victim(0).pubkey());
+        assert_eq!(a.mint("usdc").pubkey(), b.mint("usdc").pubkey());
+    }
+
+    #[test]
+    fn labels_and_challenges_are_independent() {
+        let seed = FixtureSeed::new("missing-signer-check");
+        let other = FixtureSeed::new("solana-program-close");
+        assert_ne!(seed.victim(0).pubkey(), seed.victim(1).pubkey());
+        assert_ne!(seed.victim(0).pubkey(), seed.player(0).pubkey());
+        assert_ne!(seed.victim(0).pubkey(), other.victim(0).pubkey());
+    }
+
+    #[test]
+    fn vault_matches_the_programs() {
+        let seed = FixtureSeed::new("missing-signer-check");
+        let owner = seed.victim(0).pubkey();
+        assert_eq!(seed.vault(&owner, 3, &pda::ID), pda::vault_address(&owner, 3));
+        assert_eq!(
+            seed.vault(&owner, 3, &solana_program_close::ID).0,
+            solana_program_close::vault_address(&owner, 3)
+        );
+    }
+}
@@ -0,0 +1,691 @@
+use std::path::{Path, PathBuf};
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program_test::{read_file, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+use crate::FixtureSeed;
+
+const DEFAULT_LAMPORTS: u64 = 10_000_000_000;
+const DEFAULT_FEE_BASIS_POINTS: u16 = 100;
+const DEFAULT_MAX_FEE: u64 = 1_000_000_000;
+
+/// Program ids of every challenge deployed into the environment
+#[derive(Clone, Copy, Debug)]
+pub struct Programs {
+    pub registry: Pubkey,
+    pub missing_signer_check: Pubkey,
+    pub program_close: Pubkey,
+    pub memory_safety_vulns: Pubkey,
+    /// Helper for one-instruction mint and ATA setup in exploit transactions
+    pub mock_spl: Pubkey,
+    /// Non-Anchor port of the missing-signer vault
+    pub native_vault: Pubkey,
+    /// Owner-check challenge written with Pinocchio
+    pub pinocchio_owner_check: Pubkey,
+    /// Missing-signer vault written with Steel
+    pub steel_vault: Pubkey,
+    /// Upgrade-authority deployment manager
+    pub deployments: Pubkey,
+    /// Dependency-free reverse-engineering challenge, at the address
+    /// `sbpf-loader` uses
+    pub sbpf_crackme: Pubkey,
+}
+
+impl Programs {
+    fn all() -> Self {
+        Self {
+            registry: ctf_registry::ID,
+            missing_signer_check: pda::ID,
+            program_close: solana_program_close::ID,
+            memory_safety_vulns: memory_safety_vulns::ID,
+            mock_spl: mock_spl::ID,
+            native_vault: native_vault::ID,
+            pinocchio_owner_check: Pubkey::new_from_array(pinocchio_owner_check::ID),
+            steel_vault: steel_vault_api::ID,
+            deployments: deployments::ID,
+            sbpf_crackme: sbpf_loader::PROGRAM_ID,
+        }
+    }
+
+    /// `(program id, path to the built .so)` for each workspace program
+    fn artifacts(&self) -> [(Pubkey, PathBuf); 10] {
+        let root = repo_root();
+        [
+            (
+                self.registry,
+                root.join("ctf-registry/target/deploy/ctf_registry.so"),
+            ),
+            (
+                self.missing_signer_check,
+                root.join("missing-signer-check/target/deploy/pda.so"),
+            ),
+            (
+                self.program_close,
+                root.join("solana-program-close/target/deploy/solana_program_close.so"),
+            ),
+            (
+                self.memory_safety_vulns,
+                root.join("memory-safety-vulns/memory-safety-vulns/target/deploy/memory_safety_vulns.so"),
+            ),
+            (
+                self.mock_spl,
+                root.join("mock-spl/target/deploy/mock_spl.so"),
+            ),
+            (
+                self.native_vault,
+                root.join("native-vault/target/deploy/native_vault.so"),
+            ),
+            (
+                self.pinocchio_owner_check,
+                root.join("pinocchio-owner-check/target/deploy/pinocchio_owner_check.so"),
+            ),
+            (
+                self.steel_vault,
+                root.join("steel-vault/target/deploy/steel_vault.so"),
+            ),
+            (
+                self.deployments,
+                root.join("deployments/target/deploy/deployments.so"),
+            ),
+            (
+                self.sbpf_crackme,
+                root.join("sbpf-crackme/target/deploy/sbpf_crackme.so"),
+            ),
+        ]
+    }
+}
+
+/// A mint created by the environment, along with the token program that owns it
+#[derive(Clone, Copy, Debug)]
+pub struct TokenMint {
+    pub address: Pubkey,
+    pub decimals: u8,
+    pub token_program: Pubkey,
+}
+
+/// Canonical mints shared by all challenges
+#[derive(Clone, Copy, Debug)]
+pub struct Mints {
+    /// Legacy SPL mint with 6 decimals
+    pub usdc: TokenMint,
+    /// Legacy SPL mint with 9 decimals
+    pub wsol: TokenMint,
+    /// Token-2022 mint with a transfer-fee extension
+    pub fee_mint: TokenMint,
+}
+
+pub struct CtfEnvironmentBuilder {
+    seed: Option<FixtureSeed>,
+    players: usize,
+    victims: usize,
+    lamports: u64,
+    fee_basis_points: u16,
+    max_fee: u64,
+}
+
+impl Default for CtfEnvironmentBuilder {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            players: 1,
+            victims: 1,
+            lamports: DEFAULT_LAMPORTS,
+            fee_basis_points: DEFAULT_FEE_BASIS_POINTS,
+            max_fee: DEFAULT_MAX_FEE,
+        }
+    }
+}
+
+impl CtfEnvironmentBuilder {
+    /// Derive every keypair and mint address from `challenge_id` instead of
+    /// generating fresh ones, so addresses are identical on every run
+    pub fn seed(mut self, challenge_id: &str) -> Self {
+        self.seed = Some(FixtureSeed::new(challenge_id));
+        self
+    }
+
+    /// Number of attacker keypairs to fund
+    pub fn players(mut self, players: usize) -> Self {
+        self.players = players;
+        self
+    }
+
+    /// Number of victim keypairs to fund
+    pub fn victims(mut self, victims: usize) -> Self {
+        self.victims = victims;
+        self
+    }
+
+    /// Lamports given to every player and victim
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = lamports;
+        self
+    }
+
+    /// Transfer fee charged by the Token-2022 fee mint
+    pub fn transfer_fee(mut self, basis_points: u16, max_fee: u64) -> Self {
+        self.fee_basis_points = basis_points;
+        self.max_fee = max_fee;
+        self
+    }
+
+    /// Deploy every program, fund the keypairs, and create the canonical mints.
+    ///
+    /// Panics if a program has not been built with `anchor build` (or
+    /// `cargo build-sbf` for the non-Anchor programs).
+    pub async fn build(self) -> CtfEnvironment {
+        let programs = Programs::all();
+        let seed = self.seed.as_ref();
+        let players: Vec<Keypair> = (0..self.players)
+            .map(|i| seed.map_or_else(Keypair::new, |seed| seed.player(i)))
+            .collect();
+        let victims: Vec<Keypair> = (0..self.victims)
+            .map(|i| seed.map_or_else(Keypair::new, |seed| seed.victim(i)))
+            .collect();
+        let mint_authority = seed.map_or_else(Keypair::new, FixtureSeed::mint_authority);
+        let upgrade_authority = seed.map_or_else(Keypair::new, FixtureSeed::upgrade_authority);
+        let mint_keypair =
+            |label: &str| seed.map_or_else(Keypair::new, |seed| seed.mint(label));
+
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+
+        for (program_id, path) in programs.artifacts() {
+            add_program(&mut program_test, program_id, &path, &upgrade_authority.pubkey());
+        }
+        for keypair in players
+            .iter()
+            .chain(victims.iter())
+            .chain([&mint_authority, &upgrade_authority])
+        {
+            program_test.add_account(
+                keypair.pubkey(),
+                Account::new(self.lamports, 0, &system_program::id()),
+            );
+        }
+
+        let mut context = program_test.start_with_context().await;
+
+        let usdc = create_mint(&mut context, mint_keypair("usdc"), &mint_authority, 6, None)
+            .await
+            .expect("create 6 decimal mint");
+        let wsol = create_mint(&mut context, mint_keypair("wsol"), &mint_authority, 9, None)
+            .await
+            .expect("create 9 decimal mint");
+        let fee_mint = create_mint(
+            &mut context,
+            mint_keypair("fee"),
+            &mint_authority,
+            6,
+            Some((self.fee_basis_points, self.max_fee)),
+        )
+        .await
+        .expect("create Token-2022 fee mint");
+
+        CtfEnvironment {
+            context,
+            programs,
+            mints: Mints {
+                usdc,
+                wsol,
+                fee_mint,
+            },
+            mint_authority,
+            upgrade_authority,
+            players,
+            victims,
+        }
+    }
+}
+
+/// A local validator with every challenge deployed and funded keypairs ready
+/// to attack them
+pub struct CtfEnvironment {
+    pub context: ProgramTestContext,
+    pub programs: Programs,
+    pub mints: Mints,
+    pub mint_authority: Keypair,
+    /// Upgrade authority of every deployed program, needed by admin setup
+    /// instructions that check the ProgramData account
+    pub upgrade_authority: Keypair,
+    pub players: Vec<Keypair>,
+    pub victims: Vec<Keypair>,
+}
+
+impl CtfEnvironment {
+    pub fn builder() -> CtfEnvironmentBuilder {
+        CtfEnvironmentBuilder::default()
+    }
+
+    /// The payer funded by `solana-program-test`
+    pub fn payer(&self) -> &Keypair {
+        &self.context.payer
+    }
+
+    /// Sign and send a transaction paid for by the environment payer
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        process(&mut self.context, instructions, signers).await
+    }
+
+    /// Create (or reuse) the associated token account of `owner` for `mint`
+    pub async fn create_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &TokenMint,
+    ) -> Result<Pubkey, BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        let instruction = create_associated_token_account_idempotent(
+            &payer,
+            owner,
+            &mint.address,
+            &mint.token_program,
+        );
+        self.process(&[instruction], &[]).await?;
+        Ok(get_associated_token_address_with_program_id(
+            owner,
+            &mint.address,
+            &mint.token_program,
+        ))
+    }
+
+    /// Mint `amount` base units of `mint` into `destination`
+    pub async fn mint_to(
+        &mut self,
+        mint: &TokenMint,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = spl_token_2022::instruction::mint_to(
+            &mint.token_program,
+            &mint.address,
+            destination,
+            &self.mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .expect("valid token program");
+        let mint_authority = self.mint_authority.insecure_clone();
+        self.process(&[instruction], &[&mint_authority]).await
+    }
+
+    /// Create an associated token account for `owner` and fund it in one step
+    pub async fn fund_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &TokenMint,
+        amount: u64,
+    ) -> Result<Pubkey, BanksClientError> {
+        let account = self.create_token_account(owner, mint).await?;
+        self.mint_to(mint, &account, amount).await?;
+        Ok(account)
+    }
+
+    /// Create the registry with the environment payer as its only member, if
+    /// it doesn't exist yet, and register `challenge_id` for `program_id` with
+    /// the payer as checker. Returns the challenge PDA. The registry is created
+    /// by the upgrade authority, the only key allowed to.
+    pub async fn register_challenge(
+        &mut self,
+        challenge_id: &str,
+        program_id: Pubkey,
+    ) -> Result<Pubkey, BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        let (registry, _) = Pubkey::find_program_address(&[b"registry"], &ctf_registry::ID);
+        let (challenge, _) =
+            Pubkey::find_program_address(&[b"challenge", challenge_id.as_bytes()], &ctf_registry::ID);
+
+        if self.context.banks_client.get_account(registry).await?.is_none() {
+            let upgrade_authority = self.upgrade_authority.insecure_clone();
+            let initialize = Instruction {
+                program_id: ctf_registry::ID,
+                accounts: ctf_registry::accounts::InitializeRegistry {
+                    registry,
+                    program: ctf_registry::ID,
+                    program_data: bpf_loader_upgradeable::get_program_data_address(&ctf_registry::ID),
+                    payer: upgrade_authority.pubkey(),
+                    system_program: system_program::id(),
+                }
+                .to_account_metas(None),
+                data: ctf_registry::instruction::InitializeRegistry {
+                    members: vec![payer],
+                    threshold: 1,
+                }
+                .data(),
+            };
+            self.process(&[initialize], &[&upgrade_authority]).await?;
+        }
+
+        let mut register_accounts = ctf_registry::accounts::RegisterChallenge {
+            registry,
+            challenge,
+            payer,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None);
+        // The approving members follow as signers
+        register_accounts.push(AccountMeta::new_readonly(payer, true));
+        let register = Instruction {
+            program_id: ctf_registry::ID,
+            accounts: register_accounts,
+            data: ctf_registry::instruction::RegisterChallenge {
+                challenge_id: challenge_id.to_string(),
+                program_id,
+                checker: payer,
+            }
+            .data(),
+        };
+        self.process(&[register], &[]).await?;
+        Ok(challenge)
+    }
+
+    /// Open `owner`'s missing-signer-check vault `vault_id` in `mint` and
+    /// deposit `amount` from a freshly funded token account, signing as the
+    /// owner the way a victim bot would
+    pub async fn open_missing_signer_vault(
+        &mut self,
+        owner: &Keypair,
+        mint: &TokenMint,
+        vault_id: u64,
+        amount: u64,
+    ) -> Result<MissingSignerVault, BanksClientError> {
+        let source = self.fund_token_account(&owner.pubkey(), mint, amount).await?;
+        let (vault, _) = pda::vault_address(&owner.pubkey(), vault_id);
+        let vault_token_account = Keypair::new();
+        self.process(
+            &[
+                Instruction {
+                    program_id: pda::ID,
+                    accounts: pda::accounts::InitializeVault {
+                        vault,
+                        vault_token_account: vault_token_account.pubkey(),
+                        token_mint: mint.address,
+                        owner: owner.pubkey(),
+                        token_program: mint.token_program,
+                        system_program: system_program::id(),
+                        rent: sysvar::rent::id(),
+                    }
+                    .to_account_metas(None),
+                    data: pda::instruction::InitializeVault { vault_id }.data(),
+                },
+                Instruction {
+                    program_id: pda::ID,
+                    accounts: pda::accounts::Deposit {
+                        vault,
+                        vault_token_account: vault_token_account.pubkey(),
+                        token_mint: mint.address,
+                        source,
+                        owner: owner.pubkey(),
+                        token_program: mint.token_program,
+                    }
+                    .to_account_metas(None),
+                    data: pda::instruction::Deposit { amount }.data(),
+                },
+            ],
+            &[owner, &vault_token_account],
+        )
+        .await?;
+        Ok(MissingSignerVault {
+            vault,
+            vault_token_account: vault_token_account.pubkey(),
+            source,
+        })
+    }
+
+    /// Seed a missing-signer-check victim vault for `victim` with
+    /// `setup_victim`, funded by the upgrade authority, so it counts for
+    /// `verify_exploit`
+    pub async fn seed_missing_signer_victim(
+        &mut self,
+        victim: &Pubkey,
+        mint: &TokenMint,
+        vault_id: u64,
+        amount: u64,
+    ) -> Result<MissingSignerVault, BanksClientError> {
+        let admin = self.upgrade_authority.insecure_clone();
+        let source = self.fund_token_account(&admin.pubkey(), mint, amount).await?;
+        let (vault, _) = pda::vault_address(victim, vault_id);
+        let vault_token_account = Keypair::new();
+        self.process(
+            &[Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::SetupVictim {
+                    vault,
+                    vault_token_account: vault_token_account.pubkey(),
+                    token_mint: mint.address,
+                    authority_token_account: source,
+                    victim: *victim,
+                    program: pda::ID,
+                    program_data: bpf_loader_upgradeable::get_program_data_address(&pda::ID),
+                    authority: admin.pubkey(),
+                    token_program: mint.token_program,
+                    system_program: system_program::id(),
+                    rent: sysvar::rent::id(),
+                }
+                .to_account_metas(None),
+                data: pda::instruction::SetupVictim { vault_id, amount }.data(),
+            }],
+            &[&admin, &vault_token_account],
+        )
+        .await?;
+        Ok(MissingSignerVault {
+            vault,
+            vault_token_account: vault_token_account.pubkey(),
+            source,
+        })
+    }
+
+    /// Open and fund `owner`'s native-vault vault in `mint`, returning its
+    /// token account
+    pub async fn open_native_vault(
+        &mut self,
+        owner: &Keypair,
+        mint: &TokenMint,
+        amount: u64,
+    ) -> Result<Pubkey, BanksClientError> {
+        let source = self.fund_token_account(&owner.pubkey(), mint, amount).await?;
+        let vault_token_account = Keypair::new();
+        self.process(
+            &[
+                native_vault::instruction::initialize_vault(
+                    &owner.pubkey(),
+                    &vault_token_account.pubkey(),
+                    &mint.address,
+                    &mint.token_program,
+                ),
+                native_vault::instruction::deposit(
+                    &owner.pubkey(),
+                    &vault_token_account.pubkey(),
+                    &source,
+                    &mint.address,
+                    &mint.token_program,
+                    amount,
+                ),
+            ],
+            &[owner, &vault_token_account],
+        )
+        .await?;
+        Ok(vault_token_account.pubkey())
+    }
+
+    /// Open `owner`'s steel-vault vault and deposit `lamports`, returning the
+    /// vault PDA
+    pub async fn open_steel_vault(&mut self, owner: &Keypair, lamports: u64) -> Result<Pubkey, BanksClientError> {
+        self.process(
+            &[
+                steel_vault_api::sdk::initialize(owner.pubkey()),
+                steel_vault_api::sdk::deposit(owner.pubkey(), lamports),
+            ],
+            &[owner],
+        )
+        .await?;
+        Ok(steel_vault_api::state::vault_pda(owner.pubkey()).0)
+    }
+}
+
+/// A funded missing-signer-check vault
+#[derive(Clone, Copy, Debug)]
+pub struct MissingSignerVault {
+    pub vault: Pubkey,
+    pub vault_token_account: Pubkey,
+    /// Token account the deposit came from
+    pub source: Pubkey,
+}
+
+/// The same instruction with nobody signing for `signer`, the way an attacker
+/// sends it
+pub fn without_signature(mut instruction: Instruction, signer: &Pubkey) -> Instruction {
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == *signer {
+            meta.is_signer = false;
+        }
+    }
+    instruction
+}
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+/// Deploy the program at `path` through the upgradeable loader, the same way
+/// `anchor deploy` does, so instructions that read its ProgramData see
+/// `upgrade_authority`
+fn add_program(
+    program_test: &mut ProgramTest,
+    program_id: Pubkey,
+    path: &Path,
+    upgrade_authority: &Pubkey,
+) {
+    assert!(
+        path.exists(),
+        "{} not found, run `anchor build` (`cargo build-sbf` without an Anchor.toml) in its workspace first",
+        path.display()
+    );
+    let elf = read_file(path);
+    let programdata_address = bpf_loader_upgradeable::get_program_data_address(&program_id);
+
+    // UpgradeableLoaderState::Program { programdata_address }: u32 tag, then the address
+    let mut program = 2u32.to_le_bytes().to_vec();
+    program.extend_from_slice(programdata_address.as_ref());
+    // UpgradeableLoaderState::ProgramData: u32 tag, u64 slot, Option<Pubkey> authority
+    let mut programdata = 3u32.to_le_bytes().to_vec();
+    programdata.extend_from_slice(&0u64.to_le_bytes());
+    programdata.push(1);
+    programdata.extend_from_slice(upgrade_authority.as_ref());
+    programdata.extend_from_slice(&elf);
+
+    for (address, data, executable) in [
+        (programdata_address, programdata, false),
+        (program_id, program, true),
+    ] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()).max(1),
+                data,
+                owner: bpf_loader_upgradeable::id(),
+                executable,
+                rent_epoch: 0,
+            },
+        );
+    }
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await?;
+    let mut all_signers: Vec<&Keypair> = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Create a legacy SPL mint, or a Token-2022 mint with a transfer fee when
+/// `transfer_fee` is set
+async fn create_mint(
+    context: &mut ProgramTestContext,
+    mint: Keypair,
+    mint_authority: &Keypair,
+    decimals: u8,
+    transfer_fee: Option<(u16, u64)>,
+) -> Result<TokenMint, BanksClientError> {
+    let authority = mint_authority.pubkey();
+
+    let (token_program, space) = match transfer_fee {
+        Some(_) => (
+            spl_token_2022::id(),
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                ExtensionType::TransferFeeConfig,
+            ])
+            .expect("valid extension list"),
+        ),
+        None => (spl_token::id(), spl_token_2022::state::Mint::LEN),
+    };
+
+    let rent = context.banks_client.get_rent().await?;
+    let mut instructions = vec![system_instruction::create_account(
+        &context.payer.pubkey(),
+        &mint.pubkey(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program,
+    )];
+    if let Some((basis_points, max_fee)) = transfer_fee {
+        instructions.push(
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &token_program,
+                &mint.pubkey(),
+                Some(&authority),
+                Some(&authority),
+                basis_points,
+                max_fee,
+            )
+            .expect("valid token program"),
+        );
+    }
+    instructions.push(
+        spl_token_2022::instruction::initialize_mint2(
+            &token_program,
+            &mint.pubkey(),
+            &authority,
+            None,
+            decimals,
+        )
+        .expect("valid token program"),
+    );
+
+    process(context, &instructions, &[&mint]).await?;
+
+    Ok(TokenMint {
+        address: mint.pubkey(),
+        decimals,
+        token_program,
+    })
+}
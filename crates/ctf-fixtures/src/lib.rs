@@ -0,0 +1,33 @@
+//! Shared fixtures for exploit tests, graders, and writeups.
+//!
+//! Every exploit starts the same way: deploy the challenge programs, create a
+//! few mints, and fund an attacker and some victims. [`CtfEnvironment`] does
+//! all of that in one call:
+//!
+//! ```ignore
+//! let mut env = CtfEnvironment::builder().players(1).victims(2).build().await;
+//! let usdc = env.mints.usdc;
+//! let victim = env.victims[0].pubkey();
+//! let victim_ata = env.fund_token_account(&victim, &usdc, 1_000_000).await?;
+//! ```
+
+//!
+//! Call [`CtfEnvironmentBuilder::seed`] with the challenge id to make every
+//! keypair and mint address reproducible through [`FixtureSeed`], and use the
+//! [`assertions`] module to check the outcome of an exploit. The setup most
+//! exploits share, registering a challenge or opening a funded victim vault,
+//! lives on [`CtfEnvironment`] too.
+//!
+//! The environment deploys every challenge program, so every test that builds
+//! one needs the `.so` files: run `anchor build` in each Anchor workspace and
+//! `cargo build-sbf` in `native-vault`, `pinocchio-owner-check`,
+//! `steel-vault` and `sbpf-crackme` first.
+
+pub mod assertions;
+mod deterministic;
+mod environment;
+
+pub use deterministic::FixtureSeed;
+pub use environment::{
+    without_signature, CtfEnvironment, CtfEnvironmentBuilder, Mints, MissingSignerVault, Programs, TokenMint,
+};
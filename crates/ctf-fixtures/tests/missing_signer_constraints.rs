@@ -0,0 +1,284 @@
+//! Constraint-by-constraint tests for `WithdrawCtx` and `SecureWithdrawCtx`.
+//!
+//! Every case starts from a valid set of accounts, breaks exactly one
+//! constraint, and asserts the exact error Anchor or the program reports, so a
+//! failure points at the one constraint that changed. The same shape works for
+//! any context in the repository: a valid baseline, one mutation per case.
+//!
+//! Runs against the challenge build, where `owner` is never checked as a
+//! signer and `secure_withdraw` is still the player TODO.
+
+use anchor_lang::error::ErrorCode as AnchorError;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use ctf_fixtures::assertions::custom_error_code;
+use ctf_fixtures::{CtfEnvironment, MissingSignerVault};
+use solana_sdk::{
+    account::AccountSharedData,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+};
+
+const DEPOSIT: u64 = 5_000_000;
+const AMOUNT: u64 = 1_000;
+
+#[derive(Clone, Copy, Debug)]
+enum Withdrawal {
+    /// `withdraw` through `WithdrawCtx`
+    Unchecked,
+    /// `secure_withdraw` through `SecureWithdrawCtx`
+    Secure,
+}
+
+/// The accounts both contexts share, in a form each case can tweak
+#[derive(Clone, Copy)]
+struct WithdrawAccounts {
+    vault: Pubkey,
+    vault_token_account: Pubkey,
+    token_mint: Pubkey,
+    destination: Pubkey,
+    owner: Pubkey,
+}
+
+impl Withdrawal {
+    fn instruction(self, accounts: WithdrawAccounts) -> Instruction {
+        let WithdrawAccounts {
+            vault,
+            vault_token_account,
+            token_mint,
+            destination,
+            owner,
+        } = accounts;
+        match self {
+            Withdrawal::Unchecked => Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::WithdrawCtx {
+                    vault,
+                    vault_token_account,
+                    token_mint,
+                    destination,
+                    token_program: spl_token::id(),
+                    owner,
+                }
+                .to_account_metas(None),
+                data: pda::instruction::Withdraw { amount: AMOUNT }.data(),
+            },
+            Withdrawal::Secure => Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::SecureWithdrawCtx {
+                    vault,
+                    vault_token_account,
+                    token_mint,
+                    destination,
+                    token_program: spl_token::id(),
+                    owner,
+                }
+                .to_account_metas(None),
+                data: pda::instruction::SecureWithdraw { _amount: AMOUNT }.data(),
+            },
+        }
+    }
+}
+
+/// Everything the cases swap in for a valid account
+struct Fixture {
+    env: CtfEnvironment,
+    valid: WithdrawAccounts,
+    /// Byte-for-byte copy of the vault at an address that is not its PDA
+    vault_copy: Pubkey,
+    /// Token account of the vault's mint that is not the vault's
+    other_token_account: Pubkey,
+    /// A mint other than the vault's
+    other_mint: Pubkey,
+    /// Program-owned account of another type (the owner's permit nonce)
+    nonce: Pubkey,
+    attacker: Pubkey,
+}
+
+async fn fixture() -> Fixture {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let mint = env.mints.usdc;
+    let victim = env.victims[0].insecure_clone();
+    let attacker = env.players[0].pubkey();
+
+    let MissingSignerVault {
+        vault,
+        vault_token_account,
+        source,
+    } = env
+        .open_missing_signer_vault(&victim, &mint, 0, DEPOSIT)
+        .await
+        .expect("victim vault setup");
+    let (nonce, _) = Pubkey::find_program_address(&[pda::NONCE_SEED, victim.pubkey().as_ref()], &pda::ID);
+    env.process(
+        &[Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::InitializeNonce {
+                nonce,
+                owner: victim.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::InitializeNonce {}.data(),
+        }],
+        &[&victim],
+    )
+    .await
+    .expect("victim nonce setup");
+
+    let vault_account = env
+        .context
+        .banks_client
+        .get_account(vault)
+        .await
+        .expect("banks client")
+        .expect("vault exists");
+    let vault_copy = Pubkey::new_unique();
+    env.context
+        .set_account(&vault_copy, &AccountSharedData::from(vault_account));
+
+    let destination = env
+        .create_token_account(&attacker, &mint)
+        .await
+        .expect("attacker token account");
+    Fixture {
+        valid: WithdrawAccounts {
+            vault,
+            vault_token_account,
+            token_mint: mint.address,
+            destination,
+            owner: victim.pubkey(),
+        },
+        vault_copy,
+        other_token_account: source,
+        other_mint: env.mints.wsol.address,
+        nonce,
+        attacker,
+        env,
+    }
+}
+
+/// Send `withdrawal` with the accounts `mutate` leaves, signed only by the fee
+/// payer, and compare the custom error code (None for success)
+async fn check(
+    fixture: &mut Fixture,
+    withdrawal: Withdrawal,
+    case: &str,
+    mutate: impl FnOnce(&mut WithdrawAccounts),
+    expected: Option<u32>,
+) {
+    let mut accounts = fixture.valid;
+    mutate(&mut accounts);
+    let result = fixture
+        .env
+        .process(&[withdrawal.instruction(accounts)], &[])
+        .await;
+    let actual = match &result {
+        Ok(()) => None,
+        Err(err) => Some(
+            custom_error_code(err)
+                .unwrap_or_else(|| panic!("{withdrawal:?} / {case}: no custom error in {err:?}")),
+        ),
+    };
+    assert_eq!(actual, expected, "{withdrawal:?} / {case}");
+}
+
+/// The cases shared by both contexts: each breaks one constraint
+async fn check_constraints(fixture: &mut Fixture, withdrawal: Withdrawal) {
+    let vault_copy = fixture.vault_copy;
+    check(
+        fixture,
+        withdrawal,
+        "vault not at its PDA",
+        |accounts| accounts.vault = vault_copy,
+        Some(AnchorError::ConstraintSeeds.into()),
+    )
+    .await;
+
+    let other_token_account = fixture.other_token_account;
+    check(
+        fixture,
+        withdrawal,
+        "has_one = vault_token_account",
+        |accounts| accounts.vault_token_account = other_token_account,
+        Some(AnchorError::ConstraintHasOne.into()),
+    )
+    .await;
+
+    let other_mint = fixture.other_mint;
+    check(
+        fixture,
+        withdrawal,
+        "has_one = token_mint",
+        |accounts| accounts.token_mint = other_mint,
+        Some(pda::ErrorCode::WrongMint.into()),
+    )
+    .await;
+
+    let attacker = fixture.attacker;
+    check(
+        fixture,
+        withdrawal,
+        "has_one = owner",
+        |accounts| accounts.owner = attacker,
+        Some(pda::ErrorCode::NotOwner.into()),
+    )
+    .await;
+
+    check(
+        fixture,
+        withdrawal,
+        "vault owned by the token program",
+        |accounts| accounts.vault = other_token_account,
+        Some(AnchorError::AccountOwnedByWrongProgram.into()),
+    )
+    .await;
+
+    let nonce = fixture.nonce;
+    check(
+        fixture,
+        withdrawal,
+        "vault of another account type",
+        |accounts| accounts.vault = nonce,
+        Some(AnchorError::AccountDiscriminatorMismatch.into()),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn withdraw_ctx_constraints() {
+    let mut fixture = fixture().await;
+    check_constraints(&mut fixture, Withdrawal::Unchecked).await;
+
+    // The bug: every constraint holds, nobody signed for `owner`, and it works
+    check(
+        &mut fixture,
+        Withdrawal::Unchecked,
+        "owner does not sign",
+        |_| {},
+        None,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn secure_withdraw_ctx_constraints() {
+    let mut fixture = fixture().await;
+    check_constraints(&mut fixture, Withdrawal::Secure).await;
+
+    // Until the player fills it in, valid accounts reach the TODO body
+    check(
+        &mut fixture,
+        Withdrawal::Secure,
+        "owner does not sign",
+        |_| {},
+        Some(pda::ErrorCode::NotImplemented.into()),
+    )
+    .await;
+}
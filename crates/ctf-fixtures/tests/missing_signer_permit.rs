@@ -0,0 +1,193 @@
+//! Permit withdrawals on missing-signer-check.
+//!
+//! A relayer submits withdrawals the vault owner signed off-chain. With the
+//! owner's nonce account every permit can be used once; the challenge build
+//! still lets the relayer leave the nonce account out and replay a permit.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_token_delta, token_balance};
+use ctf_fixtures::CtfEnvironment;
+use solana_sdk::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+
+const DEPOSIT: u64 = 5_000_000;
+const PERMIT_AMOUNT: u64 = 1_000_000;
+
+/// Accounts of a funded vault and the token account permits pay out to
+struct PermitVault {
+    owner: Keypair,
+    mint: Pubkey,
+    vault: Pubkey,
+    vault_token_account: Pubkey,
+    nonce: Pubkey,
+    recipient: Pubkey,
+}
+
+/// Open and fund a vault for the first victim, create their nonce account,
+/// and a token account for the permit recipient (the first player)
+async fn permit_vault(env: &mut CtfEnvironment) -> PermitVault {
+    let mint = env.mints.usdc;
+    let owner = env.victims[0].insecure_clone();
+    let owner_vault = env
+        .open_missing_signer_vault(&owner, &mint, 0, DEPOSIT)
+        .await
+        .expect("permit vault setup");
+    let (nonce, _) = Pubkey::find_program_address(&[pda::NONCE_SEED, owner.pubkey().as_ref()], &pda::ID);
+    env.process(
+        &[Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::InitializeNonce {
+                nonce,
+                owner: owner.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::InitializeNonce {}.data(),
+        }],
+        &[&owner],
+    )
+    .await
+    .expect("permit nonce setup");
+
+    let recipient_owner = env.players[0].pubkey();
+    let recipient = env
+        .create_token_account(&recipient_owner, &mint)
+        .await
+        .expect("recipient token account");
+    PermitVault {
+        owner,
+        mint: mint.address,
+        vault: owner_vault.vault,
+        vault_token_account: owner_vault.vault_token_account,
+        nonce,
+        recipient,
+    }
+}
+
+/// ed25519 program instruction verifying `signer`'s signature over `message`,
+/// with every offset pointing into the instruction itself
+fn ed25519_permit(signer: &Keypair, message: &[u8]) -> Instruction {
+    const PUBLIC_KEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    let mut data = vec![1, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBLIC_KEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// The permit for `nonce` followed by the withdrawal that uses it, with or
+/// without the owner's nonce account
+fn permit_withdrawal(setup: &PermitVault, nonce: u64, with_nonce_account: bool) -> [Instruction; 2] {
+    let message = pda::permit_message(&setup.vault, &setup.recipient, PERMIT_AMOUNT, nonce);
+    [
+        ed25519_permit(&setup.owner, &message),
+        Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::WithdrawWithPermit {
+                vault: setup.vault,
+                vault_token_account: setup.vault_token_account,
+                token_mint: setup.mint,
+                destination: setup.recipient,
+                nonce: with_nonce_account.then_some(setup.nonce),
+                instructions: sysvar::instructions::id(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::WithdrawWithPermit {
+                amount: PERMIT_AMOUNT,
+                nonce,
+            }
+            .data(),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn replayed_permit_fails_once_its_nonce_is_used() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let setup = permit_vault(&mut env).await;
+    let before = token_balance(&mut env.context.banks_client, &setup.recipient).await;
+
+    // Only the relayer (the environment payer) signs the transaction
+    env.process(&permit_withdrawal(&setup, 0, true), &[])
+        .await
+        .expect("first use of the permit");
+    assert_instruction_fails_with(
+        env.process(&permit_withdrawal(&setup, 0, true), &[]).await,
+        pda::ErrorCode::StaleNonce,
+    );
+    // Skipping ahead is no better than going back
+    assert_instruction_fails_with(
+        env.process(&permit_withdrawal(&setup, 2, true), &[]).await,
+        pda::ErrorCode::StaleNonce,
+    );
+    env.process(&permit_withdrawal(&setup, 1, true), &[])
+        .await
+        .expect("next permit");
+
+    assert_token_delta(
+        &mut env.context.banks_client,
+        &setup.recipient,
+        before,
+        2 * PERMIT_AMOUNT as i128,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn permit_without_the_nonce_account_can_be_replayed() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let setup = permit_vault(&mut env).await;
+    let before = token_balance(&mut env.context.banks_client, &setup.recipient).await;
+
+    env.process(&permit_withdrawal(&setup, 0, true), &[])
+        .await
+        .expect("first use of the permit");
+    // The challenge build treats a missing nonce account as "no nonce"
+    for _ in 0..3 {
+        env.process(&permit_withdrawal(&setup, 0, false), &[])
+            .await
+            .expect("replay without the nonce account");
+    }
+
+    assert_token_delta(
+        &mut env.context.banks_client,
+        &setup.recipient,
+        before,
+        4 * PERMIT_AMOUNT as i128,
+    )
+    .await;
+}
@@ -0,0 +1,179 @@
+//! Per-window withdrawal rate limit on missing-signer-check vaults.
+//!
+//! Windows are aligned to multiples of the window length, so the limit must
+//! hold up to the last slot of a window and reset on the first slot of the
+//! next one.
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_token_delta, token_balance};
+use ctf_fixtures::CtfEnvironment;
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DEPOSIT: u64 = 5_000_000;
+const LIMIT: u64 = 1_000;
+const WINDOW_SLOTS: u64 = 10;
+
+/// A funded, rate-limited vault of the first victim and the owner's own
+/// token account to withdraw into
+struct LimitedVault {
+    owner: Keypair,
+    mint: Pubkey,
+    vault: Pubkey,
+    vault_token_account: Pubkey,
+    destination: Pubkey,
+}
+
+impl LimitedVault {
+    async fn new(env: &mut CtfEnvironment) -> Self {
+        let mint = env.mints.usdc;
+        let owner = env.victims[0].insecure_clone();
+        let owner_vault = env
+            .open_missing_signer_vault(&owner, &mint, 0, DEPOSIT)
+            .await
+            .expect("vault setup");
+        env.process(
+            &[Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::SetRateLimit {
+                    vault: owner_vault.vault,
+                    owner: owner.pubkey(),
+                }
+                .to_account_metas(None),
+                data: pda::instruction::SetRateLimit {
+                    max_amount: LIMIT,
+                    window_slots: WINDOW_SLOTS,
+                }
+                .data(),
+            }],
+            &[&owner],
+        )
+        .await
+        .expect("rate limit setup");
+
+        LimitedVault {
+            owner,
+            mint: mint.address,
+            vault: owner_vault.vault,
+            vault_token_account: owner_vault.vault_token_account,
+            destination: owner_vault.source,
+        }
+    }
+
+    fn withdraw(&self, amount: u64) -> Instruction {
+        Instruction {
+            program_id: pda::ID,
+            accounts: pda::accounts::WithdrawCtx {
+                vault: self.vault,
+                vault_token_account: self.vault_token_account,
+                token_mint: self.mint,
+                destination: self.destination,
+                token_program: spl_token::id(),
+                owner: self.owner.pubkey(),
+            }
+            .to_account_metas(None),
+            data: pda::instruction::Withdraw { amount }.data(),
+        }
+    }
+
+    async fn state(&self, env: &mut CtfEnvironment) -> pda::Vault {
+        let account = env
+            .context
+            .banks_client
+            .get_account(self.vault)
+            .await
+            .expect("banks client")
+            .expect("vault exists");
+        pda::Vault::try_deserialize(&mut account.data.as_slice()).expect("decode Vault")
+    }
+}
+
+/// First slot of a window that has not started yet
+async fn next_window_start(env: &mut CtfEnvironment) -> u64 {
+    let slot = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .expect("clock sysvar")
+        .slot;
+    (slot / WINDOW_SLOTS + 2) * WINDOW_SLOTS
+}
+
+#[tokio::test]
+async fn limit_holds_until_the_last_slot_of_the_window() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .victims(1)
+        .build()
+        .await;
+    let setup = LimitedVault::new(&mut env).await;
+    let before = token_balance(&mut env.context.banks_client, &setup.destination).await;
+
+    let start = next_window_start(&mut env).await;
+    env.context.warp_to_slot(start).expect("warp to a window start");
+    env.process(&[setup.withdraw(LIMIT / 2)], &[])
+        .await
+        .expect("first half of the limit");
+    env.process(&[setup.withdraw(LIMIT / 2)], &[])
+        .await
+        .expect("second half of the limit");
+    assert_instruction_fails_with(
+        env.process(&[setup.withdraw(1)], &[]).await,
+        pda::ErrorCode::RateLimited,
+    );
+
+    env.context
+        .warp_to_slot(start + WINDOW_SLOTS - 1)
+        .expect("warp to the last slot of the window");
+    assert_instruction_fails_with(
+        env.process(&[setup.withdraw(1)], &[]).await,
+        pda::ErrorCode::RateLimited,
+    );
+
+    let state = setup.state(&mut env).await;
+    assert_eq!(state.window_start, start);
+    assert_eq!(state.window_withdrawn, LIMIT);
+    assert_token_delta(&mut env.context.banks_client, &setup.destination, before, LIMIT as i128).await;
+}
+
+#[tokio::test]
+async fn limit_rolls_over_on_the_first_slot_of_the_next_window() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pda::CHALLENGE_ID)
+        .victims(1)
+        .build()
+        .await;
+    let setup = LimitedVault::new(&mut env).await;
+    let before = token_balance(&mut env.context.banks_client, &setup.destination).await;
+
+    let start = next_window_start(&mut env).await;
+    env.context
+        .warp_to_slot(start + WINDOW_SLOTS - 1)
+        .expect("warp to the last slot of a window");
+    env.process(&[setup.withdraw(LIMIT)], &[])
+        .await
+        .expect("whole limit at the end of the window");
+
+    // A full limit again one slot later: the window, not the last payout, resets it
+    env.context
+        .warp_to_slot(start + WINDOW_SLOTS)
+        .expect("warp to the next window");
+    env.process(&[setup.withdraw(LIMIT)], &[])
+        .await
+        .expect("whole limit in the next window");
+    assert_instruction_fails_with(
+        env.process(&[setup.withdraw(1)], &[]).await,
+        pda::ErrorCode::RateLimited,
+    );
+
+    let state = setup.state(&mut env).await;
+    assert_eq!(state.window_start, start + WINDOW_SLOTS);
+    assert_eq!(state.window_withdrawn, LIMIT);
+    assert_eq!(state.total_withdrawn, 2 * LIMIT);
+    assert_token_delta(&mut env.context.banks_client, &setup.destination, before, 2 * LIMIT as i128).await;
+}
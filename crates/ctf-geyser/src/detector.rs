@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+/// Offset of `amount` in an SPL Token (or Token-2022) account
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultKind {
+    /// A token account; its balance is the token amount
+    Token,
+    /// An account holding SOL; its balance is what it holds above rent
+    /// exemption
+    Lamports,
+}
+
+/// The parts of an account update the detector looks at
+pub struct AccountUpdate<'a> {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: &'a [u8],
+    pub slot: u64,
+    /// Fee payer of the transaction that wrote the update, if any
+    pub fee_payer: Option<Pubkey>,
+}
+
+/// A victim vault that just went from holding funds to holding nothing
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Drain {
+    pub challenge_id: String,
+    pub account: String,
+    /// Fee payer of the draining transaction, credited as the attacker
+    pub player: Option<String>,
+    /// Balance before the drain, in tokens or lamports
+    pub drained: u64,
+    pub slot: u64,
+}
+
+struct Watch {
+    challenge_id: String,
+    kind: VaultKind,
+}
+
+struct ProgramWatch {
+    challenge_id: String,
+    /// Where the vault's owner sits in the account data, if the layout has one
+    owner_offset: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct Detector {
+    accounts: HashMap<Pubkey, Watch>,
+    /// Accounts owned by these programs are watched as lamport vaults
+    programs: HashMap<Pubkey, ProgramWatch>,
+    balances: HashMap<Pubkey, u64>,
+    /// Keys whose drain of a program-owned vault is not an exploit: its
+    /// recorded owner and the fee payers of deposits into it
+    funders: HashMap<Pubkey, HashSet<Pubkey>>,
+    rent: Rent,
+}
+
+impl Detector {
+    pub fn watch_account(&mut self, address: Pubkey, challenge_id: String, kind: VaultKind) {
+        self.accounts.insert(address, Watch { challenge_id, kind });
+    }
+
+    pub fn watch_program(&mut self, program_id: Pubkey, challenge_id: String, owner_offset: Option<usize>) {
+        self.programs.insert(program_id, ProgramWatch { challenge_id, owner_offset });
+    }
+
+    /// Whether updates to an account with this owner can matter, checked
+    /// before copying anything out of the update
+    pub fn is_watched(&self, pubkey: &Pubkey, owner: &Pubkey) -> bool {
+        self.accounts.contains_key(pubkey) || self.programs.contains_key(owner)
+    }
+
+    /// Record the account's new balance, returning a drain if a vault that
+    /// held funds is now empty. Startup updates (the snapshot being loaded)
+    /// only seed balances. A program-owned vault emptied by its own owner or
+    /// depositor is not a drain.
+    pub fn observe(&mut self, update: &AccountUpdate, is_startup: bool) -> Option<Drain> {
+        let (challenge_id, kind, owner_offset) = match self.accounts.get(&update.pubkey) {
+            Some(watch) => (&watch.challenge_id, watch.kind, None),
+            None => {
+                let watch = self.programs.get(&update.owner)?;
+                (&watch.challenge_id, VaultKind::Lamports, Some(watch.owner_offset))
+            }
+        };
+        let balance = match kind {
+            VaultKind::Token => token_amount(update.data)?,
+            VaultKind::Lamports => update
+                .lamports
+                .saturating_sub(self.rent.minimum_balance(update.data.len())),
+        };
+
+        let previous = self.balances.insert(update.pubkey, balance);
+        if let Some(owner_offset) = owner_offset {
+            let funders = self.funders.entry(update.pubkey).or_default();
+            if let Some(owner) = owner_offset.and_then(|offset| pubkey_at(update.data, offset)) {
+                funders.insert(owner);
+            }
+            if let Some(fee_payer) = update.fee_payer.filter(|_| balance > previous.unwrap_or(0)) {
+                funders.insert(fee_payer);
+            }
+        }
+        if is_startup || balance != 0 {
+            return None;
+        }
+        let own_vault = update
+            .fee_payer
+            .zip(self.funders.get(&update.pubkey))
+            .is_some_and(|(fee_payer, funders)| funders.contains(&fee_payer));
+        match previous {
+            Some(drained) if drained > 0 && !own_vault => Some(Drain {
+                challenge_id: challenge_id.clone(),
+                account: update.pubkey.to_string(),
+                player: update.fee_payer.map(|payer| payer.to_string()),
+                drained,
+                slot: update.slot,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn pubkey_at(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes: [u8; 32] = data.get(offset..offset.checked_add(32)?)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(bytes))
+}
+
+fn token_amount(data: &[u8]) -> Option<u64> {
+    let bytes = data.get(TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account(amount: u64) -> Vec<u8> {
+        let mut data = vec![0; 165];
+        data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    fn update<'a>(pubkey: Pubkey, owner: Pubkey, lamports: u64, data: &'a [u8], slot: u64) -> AccountUpdate<'a> {
+        AccountUpdate {
+            pubkey,
+            owner,
+            lamports,
+            data,
+            slot,
+            fee_payer: Some(Pubkey::new_from_array([7; 32])),
+        }
+    }
+
+    #[test]
+    fn reports_a_token_vault_emptied_after_holding_funds() {
+        let vault = Pubkey::new_unique();
+        let mut detector = Detector::default();
+        detector.watch_account(vault, "missing-signer-check".to_string(), VaultKind::Token);
+
+        let full = token_account(1_000);
+        let empty = token_account(0);
+        assert!(detector.observe(&update(vault, spl_token(), 0, &full, 1), true).is_none());
+        let drain = detector.observe(&update(vault, spl_token(), 0, &empty, 5), false).unwrap();
+        assert_eq!(drain.challenge_id, "missing-signer-check");
+        assert_eq!(drain.drained, 1_000);
+        assert_eq!(drain.slot, 5);
+        assert_eq!(drain.player, Some(Pubkey::new_from_array([7; 32]).to_string()));
+
+        // Staying empty is not a second drain
+        assert!(detector.observe(&update(vault, spl_token(), 0, &empty, 6), false).is_none());
+    }
+
+    #[test]
+    fn lamport_vaults_count_only_funds_above_rent() {
+        let program = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let mut detector = Detector::default();
+        detector.watch_program(program, "native-vault".to_string(), None);
+
+        let data = [0u8; 48];
+        let rent = Rent::default().minimum_balance(data.len());
+        // Created rent-exempt and empty: nothing to drain yet. A deposit from
+        // someone other than the attacker funds it.
+        assert!(detector.observe(&update(vault, program, rent, &data, 1), false).is_none());
+        assert!(detector
+            .observe(&from(depositor(), update(vault, program, rent + 500, &data, 2)), false)
+            .is_none());
+        assert_eq!(
+            detector.observe(&update(vault, program, rent, &data, 3), false).unwrap().drained,
+            500
+        );
+
+        // Closing a funded vault drains it too
+        detector.observe(&from(depositor(), update(vault, program, rent + 500, &data, 4)), false);
+        assert!(detector.observe(&update(vault, program, 0, &[], 5), false).is_some());
+    }
+
+    #[test]
+    fn emptying_your_own_vault_is_not_a_drain() {
+        let program = Pubkey::new_unique();
+        let mut detector = Detector::default();
+        detector.watch_program(program, "steel-vault".to_string(), Some(8));
+        let attacker = Pubkey::new_from_array([7; 32]);
+
+        // The attacker deposits into a vault and withdraws it again
+        let vault = Pubkey::new_unique();
+        let data = [0u8; 48];
+        let rent = Rent::default().minimum_balance(data.len());
+        detector.observe(&update(vault, program, rent + 500, &data, 1), false);
+        assert!(detector.observe(&update(vault, program, rent, &data, 2), false).is_none());
+
+        // Someone else funds a vault the attacker owns, then the attacker
+        // closes it
+        let owned = Pubkey::new_unique();
+        let mut data = [0u8; 48];
+        data[8..40].copy_from_slice(attacker.as_ref());
+        detector.observe(&from(depositor(), update(owned, program, rent + 500, &data, 3)), false);
+        assert!(detector.observe(&update(owned, program, 0, &[], 4), false).is_none());
+
+        // Another player's vault still counts
+        let victim = Pubkey::new_unique();
+        let mut data = [0u8; 48];
+        data[8..40].copy_from_slice(depositor().as_ref());
+        detector.observe(&from(depositor(), update(victim, program, rent + 500, &data, 5)), false);
+        assert!(detector.observe(&update(victim, program, rent, &data, 6), false).is_some());
+    }
+
+    #[test]
+    fn ignores_unwatched_accounts_and_startup_snapshots() {
+        let vault = Pubkey::new_unique();
+        let mut detector = Detector::default();
+        detector.watch_account(vault, "steel-vault".to_string(), VaultKind::Lamports);
+        assert!(!detector.is_watched(&Pubkey::new_unique(), &Pubkey::new_unique()));
+        assert!(detector.is_watched(&vault, &Pubkey::new_unique()));
+
+        let owner = Pubkey::new_unique();
+        detector.observe(&update(vault, owner, 5_000_000_000, &[], 1), true);
+        assert!(detector.observe(&update(vault, owner, 0, &[], 2), true).is_none());
+    }
+
+    fn from(fee_payer: Pubkey, update: AccountUpdate) -> AccountUpdate {
+        AccountUpdate {
+            fee_payer: Some(fee_payer),
+            ..update
+        }
+    }
+
+    fn depositor() -> Pubkey {
+        Pubkey::new_from_array([9; 32])
+    }
+
+    fn spl_token() -> Pubkey {
+        Pubkey::new_from_array([6; 32])
+    }
+}
@@ -0,0 +1,210 @@
+//! Geyser plugin that watches the challenges' victim vaults and reports every
+//! vault that gets emptied, so the scoreboard can credit exploits against
+//! challenges with no on-chain checker.
+//!
+//! Load it into a local validator with a config file like
+//! `config.example.json`:
+//!
+//! ```text
+//! cargo build -p ctf-geyser --release
+//! solana-test-validator --geyser-plugin-config crates/ctf-geyser/config.example.json
+//! ```
+//!
+//! - `accounts` lists vaults by address, each a `token` account (balance is
+//!   the token amount) or a `lamports` account (balance is what it holds
+//!   above rent exemption)
+//! - `programs` watches every account a challenge program owns as a lamport
+//!   vault, for vaults created per player. `owner_offset`, if set, is where
+//!   the vault's owner sits in the account data.
+//! - `notify_url`, if set, receives each drain as a JSON `POST`; point it at
+//!   the scoreboard's `/drains`, with `notify_token` set to the scoreboard's
+//!   `CTF_DRAIN_TOKEN` so it accepts the reports
+//!
+//! A drain is a watched balance going from positive to zero. The fee payer of
+//! the transaction that did it is reported as the player. A player emptying
+//! their own vault is not an exploit, so drains of program-owned accounts are
+//! dropped when the fee payer is the vault's recorded owner or paid for a
+//! deposit into it. Watching seeded victims by address under `accounts` is
+//! still the safer choice: a player can fund a vault from a second key. Drains
+//! are always logged through the validator's logger.
+//!
+//! The plugin is loaded with `dlopen`, so it must be built with the same Rust
+//! toolchain and agave version as the validator that loads it.
+
+mod detector;
+
+use std::{
+    fs,
+    sync::{mpsc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result,
+};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+pub use detector::{AccountUpdate, Detector, Drain, VaultKind};
+
+#[derive(Deserialize)]
+pub struct Config {
+    pub notify_url: Option<String>,
+    pub notify_token: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<WatchedAccount>,
+    #[serde(default)]
+    pub programs: Vec<WatchedProgram>,
+}
+
+#[derive(Deserialize)]
+pub struct WatchedAccount {
+    pub challenge_id: String,
+    pub address: String,
+    pub kind: VaultKind,
+}
+
+#[derive(Deserialize)]
+pub struct WatchedProgram {
+    pub challenge_id: String,
+    pub program_id: String,
+    #[serde(default)]
+    pub owner_offset: Option<usize>,
+}
+
+impl Config {
+    pub fn detector(&self) -> Result<Detector> {
+        let mut detector = Detector::default();
+        for account in &self.accounts {
+            detector.watch_account(parse_pubkey(&account.address)?, account.challenge_id.clone(), account.kind);
+        }
+        for program in &self.programs {
+            detector.watch_program(
+                parse_pubkey(&program.program_id)?,
+                program.challenge_id.clone(),
+                program.owner_offset,
+            );
+        }
+        Ok(detector)
+    }
+}
+
+fn parse_pubkey(address: &str) -> Result<Pubkey> {
+    address.parse().map_err(|_| GeyserPluginError::ConfigFileReadError {
+        msg: format!("invalid address {address}"),
+    })
+}
+
+#[derive(Default)]
+pub struct DrainDetector {
+    detector: Mutex<Detector>,
+    /// Drains are handed to a worker thread so a slow or unreachable
+    /// scoreboard never holds up account updates
+    notifier: Option<(mpsc::Sender<Drain>, JoinHandle<()>)>,
+}
+
+impl std::fmt::Debug for DrainDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DrainDetector").finish_non_exhaustive()
+    }
+}
+
+impl GeyserPlugin for DrainDetector {
+    fn name(&self) -> &'static str {
+        "ctf-geyser"
+    }
+
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let config = fs::read_to_string(config_file)?;
+        let config: Config = serde_json::from_str(&config)
+            .map_err(|err| GeyserPluginError::ConfigFileReadError { msg: err.to_string() })?;
+        self.detector = Mutex::new(config.detector()?);
+
+        let (sender, drains) = mpsc::channel();
+        let (notify_url, notify_token) = (config.notify_url, config.notify_token);
+        let worker = thread::spawn(move || notify(drains, notify_url, notify_token));
+        self.notifier = Some((sender, worker));
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        if let Some((sender, worker)) = self.notifier.take() {
+            drop(sender);
+            let _ = worker.join();
+        }
+    }
+
+    fn update_account(&self, account: ReplicaAccountInfoVersions, slot: u64, is_startup: bool) -> Result<()> {
+        let ReplicaAccountInfoVersions::V0_0_3(account) = account else {
+            return Ok(());
+        };
+        let (Ok(pubkey), Ok(owner)) = (Pubkey::try_from(account.pubkey), Pubkey::try_from(account.owner)) else {
+            return Ok(());
+        };
+
+        let mut detector = self.detector.lock().unwrap();
+        if !detector.is_watched(&pubkey, &owner) {
+            return Ok(());
+        }
+        let update = AccountUpdate {
+            pubkey,
+            owner,
+            lamports: account.lamports,
+            data: account.data,
+            slot,
+            fee_payer: account.txn.map(|txn| *txn.message().fee_payer()),
+        };
+        if let Some(drain) = detector.observe(&update, is_startup) {
+            if let Some((sender, _)) = &self.notifier {
+                // The worker only stops on unload
+                let _ = sender.send(drain);
+            }
+        }
+        Ok(())
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+}
+
+fn notify(drains: mpsc::Receiver<Drain>, notify_url: Option<String>, notify_token: Option<String>) {
+    for drain in drains {
+        log::info!(
+            "{} drained from {} ({}) at slot {} by {}",
+            drain.drained,
+            drain.account,
+            drain.challenge_id,
+            drain.slot,
+            drain.player.as_deref().unwrap_or("an unknown player"),
+        );
+        let Some(url) = &notify_url else { continue };
+        // A drain nobody can be credited for is only logged
+        if drain.player.is_none() {
+            continue;
+        }
+        let mut request = ureq::post(url);
+        if let Some(token) = &notify_token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        if let Err(err) = request.send_json(&drain) {
+            log::warn!("reporting the {} drain to {url} failed: {err}", drain.challenge_id);
+        }
+    }
+}
+
+/// Entry point the validator looks up after loading the library
+///
+/// # Safety
+///
+/// Called once by the validator, which takes ownership of the returned
+/// plugin.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    Box::into_raw(Box::new(DrainDetector::default()))
+}
@@ -0,0 +1,89 @@
+//! Local indexer for registry telemetry.
+//!
+//! Subscribes to the registry program's logs, decodes the `AttemptEvent` and
+//! `SolveEvent` telemetry it emits, and aggregates it into per-challenge
+//! difficulty statistics. A summary table is printed periodically and the
+//! full report is written as JSON on Ctrl-C.
+//!
+//! ```text
+//! cargo run -p ctf-indexer -- [ws_url] [report.json]
+//! ```
+
+mod stats;
+
+use std::time::Duration;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ctf_registry::{AttemptEvent, SolveEvent};
+use futures_util::StreamExt;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+
+use stats::{Stats, Telemetry};
+
+const DEFAULT_WS_URL: &str = "ws://127.0.0.1:8900";
+const DEFAULT_REPORT_PATH: &str = "challenge-stats.json";
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let ws_url = args.next().unwrap_or_else(|| DEFAULT_WS_URL.to_string());
+    let report_path = args.next().unwrap_or_else(|| DEFAULT_REPORT_PATH.to_string());
+
+    let client = PubsubClient::new(&ws_url).await?;
+    let (mut logs, unsubscribe) = client
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![ctf_registry::ID.to_string()]),
+            RpcTransactionLogsConfig { commitment: None },
+        )
+        .await?;
+    println!("Indexing registry {} via {ws_url}", ctf_registry::ID);
+
+    let mut stats = Stats::default();
+    let mut summary_interval = tokio::time::interval(SUMMARY_INTERVAL);
+    loop {
+        tokio::select! {
+            Some(response) = logs.next() => {
+                // Events logged by a failed transaction were rolled back
+                if response.value.err.is_some() {
+                    continue;
+                }
+                for event in response.value.logs.iter().filter_map(|line| parse_event(line)) {
+                    stats.record(event);
+                }
+            }
+            _ = summary_interval.tick() => stats.print_summary(),
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    unsubscribe().await;
+    std::fs::write(&report_path, serde_json::to_string_pretty(&stats.summary())?)?;
+    println!("Wrote {report_path}");
+    Ok(())
+}
+
+/// Decode an Anchor `emit!` log line (`Program data: <base64>`)
+fn parse_event(line: &str) -> Option<Telemetry> {
+    let data = STANDARD
+        .decode(line.strip_prefix("Program data: ")?)
+        .ok()?;
+    if data.len() < 8 {
+        return None;
+    }
+    let (discriminator, payload) = data.split_at(8);
+
+    if discriminator == AttemptEvent::DISCRIMINATOR {
+        AttemptEvent::try_from_slice(payload)
+            .ok()
+            .map(Telemetry::Attempt)
+    } else if discriminator == SolveEvent::DISCRIMINATOR {
+        SolveEvent::try_from_slice(payload).ok().map(Telemetry::Solve)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ctf_registry::{AttemptEvent, SolveEvent};
+use serde::Serialize;
+
+pub enum Telemetry {
+    Attempt(AttemptEvent),
+    Solve(SolveEvent),
+}
+
+#[derive(Default)]
+struct ChallengeStats {
+    attempts: u64,
+    failed_attempts: u64,
+    deprecated_solves: u64,
+    errors: BTreeMap<u32, u64>,
+    attempts_by_player: HashMap<[u8; 32], u32>,
+    solvers: HashSet<[u8; 32]>,
+    attempts_to_solve: Vec<u32>,
+}
+
+/// Difficulty statistics for one challenge, as written to the JSON report
+#[derive(Debug, Serialize)]
+pub struct ChallengeSummary {
+    pub challenge_id: String,
+    pub attempts: u64,
+    pub failed_attempts: u64,
+    pub players: usize,
+    pub solvers: usize,
+    pub deprecated_solves: u64,
+    /// Share of players who attempted the challenge and solved it
+    pub solve_rate: f64,
+    /// Mean number of checker attempts before a solve, for solves that went
+    /// through the checker
+    pub mean_attempts_to_solve: Option<f64>,
+    /// Most frequent error codes, most common first
+    pub top_errors: Vec<(u32, u64)>,
+}
+
+#[derive(Default)]
+pub struct Stats {
+    challenges: BTreeMap<String, ChallengeStats>,
+}
+
+impl Stats {
+    pub fn record(&mut self, event: Telemetry) {
+        match event {
+            Telemetry::Attempt(event) => {
+                let stats = self.challenges.entry(event.challenge_id).or_default();
+                stats.attempts += 1;
+                stats
+                    .attempts_by_player
+                    .insert(event.player_hash, event.attempt);
+                if event.error_code != 0 {
+                    stats.failed_attempts += 1;
+                    *stats.errors.entry(event.error_code).or_default() += 1;
+                }
+            }
+            Telemetry::Solve(event) => {
+                let stats = self.challenges.entry(event.challenge_id).or_default();
+                if event.deprecated {
+                    stats.deprecated_solves += 1;
+                    return;
+                }
+                if stats.solvers.insert(event.player_hash) {
+                    if let Some(attempts) = stats.attempts_by_player.get(&event.player_hash) {
+                        stats.attempts_to_solve.push(*attempts);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn summary(&self) -> Vec<ChallengeSummary> {
+        self.challenges
+            .iter()
+            .map(|(challenge_id, stats)| {
+                let players = stats
+                    .attempts_by_player
+                    .keys()
+                    .chain(stats.solvers.iter())
+                    .collect::<HashSet<_>>()
+                    .len();
+                let mut top_errors: Vec<(u32, u64)> =
+                    stats.errors.iter().map(|(code, count)| (*code, *count)).collect();
+                top_errors.sort_by(|a, b| b.1.cmp(&a.1));
+                top_errors.truncate(5);
+
+                ChallengeSummary {
+                    challenge_id: challenge_id.clone(),
+                    attempts: stats.attempts,
+                    failed_attempts: stats.failed_attempts,
+                    players,
+                    solvers: stats.solvers.len(),
+                    deprecated_solves: stats.deprecated_solves,
+                    solve_rate: if players == 0 {
+                        0.0
+                    } else {
+                        stats.solvers.len() as f64 / players as f64
+                    },
+                    mean_attempts_to_solve: (!stats.attempts_to_solve.is_empty()).then(|| {
+                        stats.attempts_to_solve.iter().map(|a| *a as f64).sum::<f64>()
+                            / stats.attempts_to_solve.len() as f64
+                    }),
+                    top_errors,
+                }
+            })
+            .collect()
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "{:<24} {:>8} {:>8} {:>8} {:>10} {:>12}",
+            "challenge", "attempts", "players", "solvers", "solve rate", "tries/solve"
+        );
+        for summary in self.summary() {
+            println!(
+                "{:<24} {:>8} {:>8} {:>8} {:>9.0}% {:>12}",
+                summary.challenge_id,
+                summary.attempts,
+                summary.players,
+                summary.solvers,
+                summary.solve_rate * 100.0,
+                summary
+                    .mean_attempts_to_solve
+                    .map_or_else(|| "-".to_string(), |mean| format!("{mean:.1}")),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(player: u8, attempt: u32, error_code: u32) -> Telemetry {
+        Telemetry::Attempt(AttemptEvent {
+            challenge_id: "missing-signer-check".to_string(),
+            player_hash: [player; 32],
+            attempt,
+            error_code,
+            slot: 0,
+        })
+    }
+
+    fn solve(player: u8) -> Telemetry {
+        Telemetry::Solve(SolveEvent {
+            challenge_id: "missing-signer-check".to_string(),
+            player_hash: [player; 32],
+            version: 1,
+            deprecated: false,
+            slot: 0,
+        })
+    }
+
+    #[test]
+    fn aggregates_attempts_and_solves() {
+        let mut stats = Stats::default();
+        stats.record(attempt(1, 1, 7000));
+        stats.record(attempt(1, 2, 7000));
+        stats.record(attempt(1, 3, 0));
+        stats.record(solve(1));
+        stats.record(attempt(2, 1, 2006));
+
+        let summary = &stats.summary()[0];
+        assert_eq!(summary.attempts, 4);
+        assert_eq!(summary.failed_attempts, 3);
+        assert_eq!(summary.players, 2);
+        assert_eq!(summary.solvers, 1);
+        assert_eq!(summary.solve_rate, 0.5);
+        assert_eq!(summary.mean_attempts_to_solve, Some(3.0));
+        assert_eq!(summary.top_errors[0], (7000, 2));
+    }
+}
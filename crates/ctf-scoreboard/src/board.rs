@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ctf_registry::ChallengeStatus;
+use serde::Serialize;
+
+/// A solve, from either a `Solve` account or a live `SolveEvent`
+#[derive(Clone, Debug, Serialize)]
+pub struct SolveRecord {
+    pub challenge_id: String,
+    /// Hex `player_hash`, the pseudonymous id the registry's telemetry uses
+    pub player: String,
+    pub version: u16,
+    pub deprecated: bool,
+    pub slot: u64,
+}
+
+/// Registry state of one challenge, refreshed from its `Challenge` account
+pub struct ChallengeInfo {
+    pub challenge_id: String,
+    pub program_id: String,
+    pub version: u16,
+    pub status: ChallengeStatus,
+    pub solve_count: u64,
+    pub deprecated_solve_count: u64,
+}
+
+/// One row of the leaderboard
+#[derive(Debug, Serialize)]
+pub struct Standing {
+    pub rank: usize,
+    pub player: String,
+    /// Distinct challenges solved against a current version
+    pub solves: usize,
+    /// Solves against a retired version, shown but not scored
+    pub deprecated_solves: usize,
+    /// Slot of the solve that set the current score, the tie-breaker
+    pub last_solve_slot: u64,
+    pub challenges: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeView {
+    pub challenge_id: String,
+    pub program_id: Option<String>,
+    pub version: Option<u16>,
+    /// `active`, `paused` or `deprecated`, or `unregistered` for a solve the
+    /// service saw before the challenge's account
+    pub status: &'static str,
+    pub solvers: usize,
+    pub solve_count: u64,
+    pub deprecated_solve_count: u64,
+    pub first_blood: Option<SolveRecord>,
+}
+
+#[derive(Default)]
+pub struct Scoreboard {
+    challenges: BTreeMap<String, ChallengeInfo>,
+    /// Keyed by challenge, player and version, so a solve seen both as an
+    /// event and as an account counts once
+    solves: BTreeMap<(String, String, u16), SolveRecord>,
+}
+
+fn status_name(status: ChallengeStatus) -> &'static str {
+    match status {
+        ChallengeStatus::Active => "active",
+        ChallengeStatus::Paused => "paused",
+        ChallengeStatus::Deprecated => "deprecated",
+    }
+}
+
+impl Scoreboard {
+    pub fn set_challenge(&mut self, info: ChallengeInfo) {
+        self.challenges.insert(info.challenge_id.clone(), info);
+    }
+
+    /// Version a solve seen off-chain is credited against, 0 if the
+    /// challenge isn't registered
+    pub fn current_version(&self, challenge_id: &str) -> u16 {
+        self.challenges.get(challenge_id).map_or(0, |info| info.version)
+    }
+
+    /// Add a solve, returning false if it was already counted
+    pub fn record(&mut self, solve: SolveRecord) -> bool {
+        let key = (solve.challenge_id.clone(), solve.player.clone(), solve.version);
+        if self.solves.contains_key(&key) {
+            return false;
+        }
+        self.solves.insert(key, solve);
+        true
+    }
+
+    /// Players by distinct scored challenges, most first; ties go to whoever
+    /// reached the score first
+    pub fn leaderboard(&self) -> Vec<Standing> {
+        #[derive(Default)]
+        struct Tally {
+            // Earliest scored solve of each challenge
+            solved: BTreeMap<String, u64>,
+            deprecated: BTreeSet<String>,
+        }
+
+        let mut players: BTreeMap<&str, Tally> = BTreeMap::new();
+        for solve in self.solves.values() {
+            let tally = players.entry(solve.player.as_str()).or_default();
+            if solve.deprecated {
+                tally.deprecated.insert(solve.challenge_id.clone());
+            } else {
+                let slot = tally.solved.entry(solve.challenge_id.clone()).or_insert(solve.slot);
+                *slot = (*slot).min(solve.slot);
+            }
+        }
+
+        let mut standings: Vec<Standing> = players
+            .into_iter()
+            .map(|(player, tally)| Standing {
+                rank: 0,
+                player: player.to_string(),
+                solves: tally.solved.len(),
+                deprecated_solves: tally.deprecated.len(),
+                last_solve_slot: tally.solved.values().copied().max().unwrap_or(0),
+                challenges: tally.solved.into_keys().collect(),
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.solves
+                .cmp(&a.solves)
+                .then(a.last_solve_slot.cmp(&b.last_solve_slot))
+                .then(a.player.cmp(&b.player))
+        });
+        for (index, standing) in standings.iter_mut().enumerate() {
+            standing.rank = index + 1;
+        }
+        standings
+    }
+
+    /// Every challenge that is registered or has been solved
+    pub fn challenges(&self) -> Vec<ChallengeView> {
+        let ids: BTreeSet<&str> = self
+            .challenges
+            .keys()
+            .map(String::as_str)
+            .chain(self.solves.keys().map(|(challenge_id, _, _)| challenge_id.as_str()))
+            .collect();
+        ids.into_iter()
+            .filter_map(|challenge_id| self.challenge(challenge_id))
+            .collect()
+    }
+
+    pub fn challenge(&self, challenge_id: &str) -> Option<ChallengeView> {
+        let info = self.challenges.get(challenge_id);
+        let solves: Vec<&SolveRecord> = self
+            .solves
+            .values()
+            .filter(|solve| solve.challenge_id == challenge_id)
+            .collect();
+        if info.is_none() && solves.is_empty() {
+            return None;
+        }
+
+        let scored = || solves.iter().filter(|solve| !solve.deprecated);
+        Some(ChallengeView {
+            challenge_id: challenge_id.to_string(),
+            program_id: info.map(|info| info.program_id.clone()),
+            version: info.map(|info| info.version),
+            status: info.map_or("unregistered", |info| status_name(info.status)),
+            solvers: scored().map(|solve| &solve.player).collect::<BTreeSet<_>>().len(),
+            solve_count: info.map_or(0, |info| info.solve_count),
+            deprecated_solve_count: info.map_or(0, |info| info.deprecated_solve_count),
+            first_blood: scored().min_by_key(|solve| solve.slot).map(|solve| (*solve).clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(challenge_id: &str, player: &str, slot: u64) -> SolveRecord {
+        SolveRecord {
+            challenge_id: challenge_id.to_string(),
+            player: player.to_string(),
+            version: 1,
+            deprecated: false,
+            slot,
+        }
+    }
+
+    #[test]
+    fn ranks_by_solves_then_by_who_got_there_first() {
+        let mut board = Scoreboard::default();
+        board.record(solve("missing-signer-check", "bob", 10));
+        board.record(solve("solana-program-close", "bob", 40));
+        board.record(solve("missing-signer-check", "alice", 20));
+        board.record(solve("solana-program-close", "alice", 30));
+        board.record(solve("missing-signer-check", "carol", 5));
+
+        let leaderboard = board.leaderboard();
+        let order: Vec<&str> = leaderboard.iter().map(|standing| standing.player.as_str()).collect();
+        assert_eq!(order, ["alice", "bob", "carol"]);
+        assert_eq!(leaderboard[0].rank, 1);
+        assert_eq!(leaderboard[0].solves, 2);
+        assert_eq!(leaderboard[0].last_solve_slot, 30);
+    }
+
+    #[test]
+    fn duplicate_and_deprecated_solves_do_not_score() {
+        let mut board = Scoreboard::default();
+        assert!(board.record(solve("missing-signer-check", "alice", 10)));
+        assert!(!board.record(solve("missing-signer-check", "alice", 10)));
+        board.record(SolveRecord {
+            deprecated: true,
+            version: 0,
+            ..solve("solana-program-close", "alice", 12)
+        });
+
+        let standing = &board.leaderboard()[0];
+        assert_eq!(standing.solves, 1);
+        assert_eq!(standing.deprecated_solves, 1);
+
+        let challenge = board.challenge("solana-program-close").unwrap();
+        assert_eq!(challenge.status, "unregistered");
+        assert_eq!(challenge.solvers, 0);
+        assert!(challenge.first_blood.is_none());
+    }
+
+    #[test]
+    fn challenge_view_combines_registry_state_and_solves() {
+        let mut board = Scoreboard::default();
+        board.set_challenge(ChallengeInfo {
+            challenge_id: "missing-signer-check".to_string(),
+            program_id: "GWrYoNZrnR7hst1dKXYAV1YSxjLZx8ij5cdYtodJePAS".to_string(),
+            version: 1,
+            status: ChallengeStatus::Paused,
+            solve_count: 2,
+            deprecated_solve_count: 0,
+        });
+        board.record(solve("missing-signer-check", "bob", 25));
+        board.record(solve("missing-signer-check", "alice", 15));
+
+        let challenge = board.challenge("missing-signer-check").unwrap();
+        assert_eq!(challenge.status, "paused");
+        assert_eq!(challenge.solvers, 2);
+        assert_eq!(challenge.first_blood.unwrap().player, "alice");
+        assert!(board.challenge("memory-safety-vulns").is_none());
+    }
+}
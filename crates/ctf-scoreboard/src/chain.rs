@@ -0,0 +1,74 @@
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ctf_registry::{player_hash, Challenge, Solve, SolveEvent};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::board::{ChallengeInfo, SolveRecord};
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Every registered challenge and every recorded solve, read from the
+/// registry's accounts. Solves reference their challenge by address, so both
+/// come from the same scan.
+pub async fn load_registry(rpc: &RpcClient) -> Result<(Vec<ChallengeInfo>, Vec<SolveRecord>), Box<dyn std::error::Error>> {
+    let accounts = rpc.get_program_accounts(&ctf_registry::ID).await?;
+
+    let mut challenges: Vec<(Pubkey, Challenge)> = Vec::new();
+    let mut solves: Vec<Solve> = Vec::new();
+    for (address, account) in accounts {
+        let data = account.data.as_slice();
+        if data.starts_with(Challenge::DISCRIMINATOR) {
+            if let Ok(challenge) = Challenge::try_deserialize(&mut &data[..]) {
+                challenges.push((address, challenge));
+            }
+        } else if data.starts_with(Solve::DISCRIMINATOR) {
+            if let Ok(solve) = Solve::try_deserialize(&mut &data[..]) {
+                solves.push(solve);
+            }
+        }
+    }
+
+    let solves = solves
+        .into_iter()
+        .filter_map(|solve| {
+            let (_, challenge) = challenges.iter().find(|(address, _)| *address == solve.challenge)?;
+            Some(SolveRecord {
+                challenge_id: challenge.challenge_id.clone(),
+                player: hex(&player_hash(&solve.player)),
+                version: solve.version,
+                deprecated: solve.deprecated,
+                slot: solve.slot,
+            })
+        })
+        .collect();
+    let challenges = challenges
+        .into_iter()
+        .map(|(_, challenge)| ChallengeInfo {
+            challenge_id: challenge.challenge_id,
+            program_id: challenge.program_id.to_string(),
+            version: challenge.version,
+            status: challenge.status,
+            solve_count: challenge.solve_count,
+            deprecated_solve_count: challenge.deprecated_solve_count,
+        })
+        .collect();
+    Ok((challenges, solves))
+}
+
+/// Decode a `SolveEvent` from an Anchor `emit!` log line
+/// (`Program data: <base64>`)
+pub fn parse_solve(line: &str) -> Option<SolveRecord> {
+    let data = STANDARD.decode(line.strip_prefix("Program data: ")?).ok()?;
+    let payload = data.strip_prefix(SolveEvent::DISCRIMINATOR)?;
+    let event = SolveEvent::try_from_slice(payload).ok()?;
+    Some(SolveRecord {
+        challenge_id: event.challenge_id,
+        player: hex(&event.player_hash),
+        version: event.version,
+        deprecated: event.deprecated,
+        slot: event.slot,
+    })
+}
@@ -0,0 +1,132 @@
+//! Live scoring backend for a local event.
+//!
+//! Reads every registered challenge and recorded solve from the registry's
+//! accounts, then follows the registry's `SolveEvent`s over a WebSocket log
+//! subscription. Challenge status (paused, deprecated, hot-swapped) is
+//! re-read periodically. The standings are served as JSON:
+//!
+//! - `GET /leaderboard` - players ranked by distinct challenges solved, ties
+//!   going to whoever got there first
+//! - `GET /challenges` and `GET /challenges/{challenge_id}` - registry status,
+//!   solvers, and first blood
+//! - `POST /drains` - drained victim vaults reported by the `ctf-geyser`
+//!   plugin, credited like solves so challenges without an on-chain checker
+//!   still score. Reports must carry `Authorization: Bearer <token>` with the
+//!   token from the `CTF_DRAIN_TOKEN` environment variable, which only the
+//!   plugin's `notify_token` should know; without it every report is refused.
+//! - `GET /ws` - WebSocket that sends the leaderboard on connect and then
+//!   every new solve with the updated leaderboard
+//!
+//! ```text
+//! cargo run -p ctf-scoreboard -- [rpc_url] [ws_url] [listen_addr]
+//! ```
+//!
+//! Players appear under their `player_hash`, the same pseudonymous id the
+//! registry's telemetry uses.
+
+mod board;
+mod chain;
+mod server;
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use tokio::sync::broadcast;
+
+use board::Scoreboard;
+use server::AppState;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+const DEFAULT_WS_URL: &str = "ws://127.0.0.1:8900";
+const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:3000";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let rpc_url = args.next().unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+    let ws_url = args.next().unwrap_or_else(|| DEFAULT_WS_URL.to_string());
+    let listen_addr = args.next().unwrap_or_else(|| DEFAULT_LISTEN_ADDR.to_string());
+
+    let state = AppState {
+        board: Arc::new(RwLock::new(Scoreboard::default())),
+        solves: broadcast::channel(64).0,
+        drain_token: std::env::var("CTF_DRAIN_TOKEN").ok().filter(|token| !token.is_empty()),
+    };
+    if state.drain_token.is_none() {
+        println!("CTF_DRAIN_TOKEN is not set, drains reported to /drains will be refused");
+    }
+    let rpc = RpcClient::new(rpc_url);
+    refresh(&rpc, &state).await?;
+
+    let pubsub = PubsubClient::new(&ws_url).await?;
+    let (mut logs, unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![ctf_registry::ID.to_string()]),
+            RpcTransactionLogsConfig { commitment: None },
+        )
+        .await?;
+
+    let listener = tokio::net::TcpListener::bind(&listen_addr).await?;
+    println!("Serving the scoreboard for registry {} on http://{listen_addr}", ctf_registry::ID);
+    let server = axum::serve(listener, server::router(state.clone()));
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            eprintln!("server stopped: {err}");
+        }
+    });
+
+    let mut refresh_interval = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        tokio::select! {
+            Some(response) = logs.next() => {
+                // Events logged by a failed transaction were rolled back
+                if response.value.err.is_some() {
+                    continue;
+                }
+                for solve in response.value.logs.iter().filter_map(|line| chain::parse_solve(line)) {
+                    publish(&state, solve);
+                }
+            }
+            _ = refresh_interval.tick() => {
+                if let Err(err) = refresh(&rpc, &state).await {
+                    eprintln!("refreshing registry accounts failed: {err}");
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    unsubscribe().await;
+    Ok(())
+}
+
+/// Re-read challenge status and pick up any solve whose event was missed,
+/// e.g. while the service was down
+async fn refresh(rpc: &RpcClient, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let (challenges, solves) = chain::load_registry(rpc).await?;
+    {
+        let mut board = state.board.write().unwrap();
+        for challenge in challenges {
+            board.set_challenge(challenge);
+        }
+    }
+    for solve in solves {
+        publish(state, solve);
+    }
+    Ok(())
+}
+
+fn publish(state: &AppState, solve: board::SolveRecord) {
+    let new = state.board.write().unwrap().record(solve.clone());
+    if new {
+        println!("{} solved {} (version {})", solve.player, solve.challenge_id, solve.version);
+        // No subscribers is fine
+        let _ = state.solves.send(solve);
+    }
+}
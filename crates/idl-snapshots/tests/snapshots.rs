@@ -0,0 +1,56 @@
+//! Rebuilds each program's IDL and compares it with `idls/<program>.json`.
+//!
+//! Needs the toolchain `anchor idl build` uses; each test compiles one program
+//! with its `idl-build` feature.
+
+use idl_snapshots::{changed_sections, PROGRAMS};
+
+fn check(name: &str) {
+    let program = PROGRAMS
+        .iter()
+        .find(|program| program.name == name)
+        .unwrap_or_else(|| panic!("{name} is not in idl_snapshots::PROGRAMS"));
+    let built = serde_json::to_value(program.build().unwrap()).unwrap();
+    let snapshot = program.snapshot().unwrap().unwrap_or_else(|| {
+        panic!(
+            "{} has no snapshot, run `cargo run -p idl-snapshots` and commit it",
+            program.snapshot_path().display()
+        )
+    });
+    let changed = changed_sections(&built, &snapshot);
+    assert!(
+        changed.is_empty(),
+        "{name} IDL no longer matches {} in {changed:?}; if the change is intended, run `cargo run -p idl-snapshots` and commit the result",
+        program.snapshot_path().display()
+    );
+}
+
+#[test]
+fn ctf_registry() {
+    check("ctf_registry");
+}
+
+#[test]
+fn deployments() {
+    check("deployments");
+}
+
+#[test]
+fn memory_safety_vulns() {
+    check("memory_safety_vulns");
+}
+
+#[test]
+fn mock_spl() {
+    check("mock_spl");
+}
+
+#[test]
+fn pda() {
+    check("pda");
+}
+
+#[test]
+fn solana_program_close() {
+    check("solana_program_close");
+}
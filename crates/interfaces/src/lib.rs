@@ -0,0 +1,33 @@
+//! Stable CPI interfaces shared by the challenge programs.
+//!
+//! A multi-program challenge (a flash loan lending into an AMM that reads an
+//! oracle, say) needs its programs to call each other without knowing which
+//! implementation sits on the other side. Each module here pins down one
+//! interface: the instruction discriminator, its arguments, the accounts
+//! every implementation takes first, and what it returns. Callers use the
+//! typed `cpi` functions instead of hand-building an `Instruction` for
+//! `invoke`:
+//!
+//! ```ignore
+//! interfaces::vault::cpi::deposit(
+//!     CpiContext::new(
+//!         ctx.accounts.vault_program.to_account_info(),
+//!         interfaces::vault::cpi::accounts::Deposit { /* ... */ },
+//!     )
+//!     .with_remaining_accounts(implementation_accounts),
+//!     amount,
+//! )?;
+//! ```
+//!
+//! Discriminators are the ones Anchor derives from the instruction name, so
+//! an Anchor program implements an interface by naming its handler after it
+//! and listing the interface accounts first. A native program matches the
+//! discriminator bytes by hand.
+//!
+//! | Interface | Instructions | Implemented by |
+//! |-----------|--------------|----------------|
+//! | [`vault`] | `deposit`, `withdraw` | `missing-signer-check`, `solana-program-close` |
+//! | [`price`] | `get_price` | `mock-spl` |
+
+pub mod price;
+pub mod vault;
@@ -0,0 +1,115 @@
+//! Price sources: `get_price()` returns the current [`Price`] of one asset
+//! as return data, so lending and AMM challenges can value collateral
+//! without caring whether the feed is a mock, an AMM's spot price, or an
+//! oracle wrapper.
+//!
+//! The only interface account is the price source itself:
+//!
+//! 0. `[]` price feed
+//!
+//! Implementations that need more accounts take them as remaining accounts.
+//! An Anchor implementation declares its handler as
+//! `pub fn get_price(ctx: Context<GetPrice>) -> Result<Price>`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    program::{get_return_data, invoke_signed},
+};
+use anchor_lang::{Discriminator, InstructionData};
+
+/// The price of one whole unit of an asset
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    /// Quote amount, scaled by `10^decimals`
+    pub price: u64,
+    pub decimals: u8,
+    /// Slot the price was last updated in, for staleness checks
+    pub publish_slot: u64,
+}
+
+pub mod instruction {
+    use super::*;
+
+    /// `sha256("global:get_price")[..8]`
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct GetPrice {}
+
+    impl Discriminator for GetPrice {
+        const DISCRIMINATOR: &'static [u8] = &[238, 38, 193, 106, 228, 32, 210, 33];
+    }
+
+    impl InstructionData for GetPrice {}
+}
+
+/// Interface accounts for building instructions off-chain
+pub mod accounts {
+    use super::*;
+
+    pub struct GetPrice {
+        pub price_feed: Pubkey,
+    }
+
+    impl ToAccountMetas for GetPrice {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![AccountMeta::new_readonly(self.price_feed, false)]
+        }
+    }
+}
+
+pub mod cpi {
+    use super::*;
+
+    pub mod accounts {
+        use super::*;
+
+        pub struct GetPrice<'info> {
+            pub price_feed: AccountInfo<'info>,
+        }
+
+        impl<'info> ToAccountMetas for GetPrice<'info> {
+            fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+                crate::price::accounts::GetPrice {
+                    price_feed: *self.price_feed.key,
+                }
+                .to_account_metas(is_signer)
+            }
+        }
+
+        impl<'info> ToAccountInfos<'info> for GetPrice<'info> {
+            fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+                vec![self.price_feed.clone()]
+            }
+        }
+    }
+
+    /// Read the price, rejecting return data that did not come from the
+    /// called program
+    pub fn get_price<'info>(ctx: CpiContext<'_, '_, '_, 'info, accounts::GetPrice<'info>>) -> Result<Price> {
+        let mut metas = ctx.accounts.to_account_metas(None);
+        metas.extend(ctx.remaining_accounts.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }));
+        let instruction = Instruction {
+            program_id: *ctx.program.key,
+            accounts: metas,
+            data: instruction::GetPrice {}.data(),
+        };
+
+        let mut infos = ctx.accounts.to_account_infos();
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+        infos.push(ctx.program.clone());
+        invoke_signed(&instruction, &infos, ctx.signer_seeds)?;
+
+        let price = match get_return_data() {
+            Some((program_id, data)) if program_id == *ctx.program.key => Price::try_from_slice(&data).ok(),
+            _ => None,
+        };
+        price.ok_or_else(|| {
+            msg!("{} returned no price", ctx.program.key);
+            ProgramError::InvalidAccountData.into()
+        })
+    }
+}
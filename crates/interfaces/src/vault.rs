@@ -0,0 +1,144 @@
+//! Token vaults: `deposit(amount)` moves tokens from the user into the vault,
+//! `withdraw(amount)` moves them back out.
+//!
+//! Both instructions start with the same four accounts:
+//!
+//! 0. `[writable]` vault state
+//! 1. `[writable]` vault token account
+//! 2. `[]` mint
+//! 3. `[writable]` user token account, the source of a deposit or the
+//!    destination of a withdrawal
+//!
+//! Everything after them belongs to the implementation (the authority that
+//! signs, the token program, share mints, configuration) and is passed
+//! through as remaining accounts, in the order the implementation lists
+//! them. Their signer and writable flags are taken from the account infos,
+//! so a PDA signing through `signer_seeds` must be passed with `is_signer`
+//! set.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_lang::{Discriminator, InstructionData};
+
+pub mod instruction {
+    use super::*;
+
+    /// `sha256("global:deposit")[..8]`
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct Deposit {
+        pub amount: u64,
+    }
+
+    impl Discriminator for Deposit {
+        const DISCRIMINATOR: &'static [u8] = &[242, 35, 198, 137, 82, 225, 242, 182];
+    }
+
+    impl InstructionData for Deposit {}
+
+    /// `sha256("global:withdraw")[..8]`
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct Withdraw {
+        pub amount: u64,
+    }
+
+    impl Discriminator for Withdraw {
+        const DISCRIMINATOR: &'static [u8] = &[183, 18, 70, 156, 148, 109, 161, 34];
+    }
+
+    impl InstructionData for Withdraw {}
+}
+
+/// Interface accounts for building instructions off-chain. Append the
+/// implementation's accounts to the metas.
+pub mod accounts {
+    use super::*;
+
+    pub struct VaultAccounts {
+        pub vault: Pubkey,
+        pub vault_token_account: Pubkey,
+        pub mint: Pubkey,
+        pub user_token_account: Pubkey,
+    }
+
+    impl ToAccountMetas for VaultAccounts {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(self.vault_token_account, false),
+                AccountMeta::new_readonly(self.mint, false),
+                AccountMeta::new(self.user_token_account, false),
+            ]
+        }
+    }
+
+    pub type Deposit = VaultAccounts;
+    pub type Withdraw = VaultAccounts;
+}
+
+pub mod cpi {
+    use super::*;
+
+    pub mod accounts {
+        use super::*;
+
+        pub struct VaultAccounts<'info> {
+            pub vault: AccountInfo<'info>,
+            pub vault_token_account: AccountInfo<'info>,
+            pub mint: AccountInfo<'info>,
+            pub user_token_account: AccountInfo<'info>,
+        }
+
+        impl<'info> ToAccountMetas for VaultAccounts<'info> {
+            fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+                crate::vault::accounts::VaultAccounts {
+                    vault: *self.vault.key,
+                    vault_token_account: *self.vault_token_account.key,
+                    mint: *self.mint.key,
+                    user_token_account: *self.user_token_account.key,
+                }
+                .to_account_metas(is_signer)
+            }
+        }
+
+        impl<'info> ToAccountInfos<'info> for VaultAccounts<'info> {
+            fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+                vec![
+                    self.vault.clone(),
+                    self.vault_token_account.clone(),
+                    self.mint.clone(),
+                    self.user_token_account.clone(),
+                ]
+            }
+        }
+
+        pub type Deposit<'info> = VaultAccounts<'info>;
+        pub type Withdraw<'info> = VaultAccounts<'info>;
+    }
+
+    pub fn deposit<'info>(ctx: CpiContext<'_, '_, '_, 'info, accounts::Deposit<'info>>, amount: u64) -> Result<()> {
+        invoke(ctx, instruction::Deposit { amount }.data())
+    }
+
+    pub fn withdraw<'info>(ctx: CpiContext<'_, '_, '_, 'info, accounts::Withdraw<'info>>, amount: u64) -> Result<()> {
+        invoke(ctx, instruction::Withdraw { amount }.data())
+    }
+
+    fn invoke<'info>(ctx: CpiContext<'_, '_, '_, 'info, accounts::VaultAccounts<'info>>, data: Vec<u8>) -> Result<()> {
+        let mut metas = ctx.accounts.to_account_metas(None);
+        metas.extend(ctx.remaining_accounts.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }));
+        let instruction = Instruction {
+            program_id: *ctx.program.key,
+            accounts: metas,
+            data,
+        };
+
+        let mut infos = ctx.accounts.to_account_infos();
+        infos.extend(ctx.remaining_accounts.iter().cloned());
+        infos.push(ctx.program.clone());
+        invoke_signed(&instruction, &infos, ctx.signer_seeds).map_err(Into::into)
+    }
+}
@@ -0,0 +1,95 @@
+//! The programs listed as implementations match each interface's
+//! discriminators and list its accounts first.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use interfaces::{price, vault};
+
+fn vault_accounts() -> vault::accounts::VaultAccounts {
+    vault::accounts::VaultAccounts {
+        vault: Pubkey::new_unique(),
+        vault_token_account: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        user_token_account: Pubkey::new_unique(),
+    }
+}
+
+/// `implementation` starts with exactly the interface's metas
+fn assert_prefix(interface: Vec<AccountMeta>, implementation: Vec<AccountMeta>) {
+    assert_eq!(interface[..], implementation[..interface.len()]);
+}
+
+#[test]
+fn vault_discriminators() {
+    assert_eq!(vault::instruction::Deposit::DISCRIMINATOR, pda::instruction::Deposit::DISCRIMINATOR);
+    assert_eq!(vault::instruction::Withdraw::DISCRIMINATOR, pda::instruction::Withdraw::DISCRIMINATOR);
+    assert_eq!(
+        vault::instruction::Deposit::DISCRIMINATOR,
+        solana_program_close::instruction::Deposit::DISCRIMINATOR
+    );
+    assert_eq!(
+        vault::instruction::Withdraw::DISCRIMINATOR,
+        solana_program_close::instruction::Withdraw::DISCRIMINATOR
+    );
+}
+
+#[test]
+fn missing_signer_vault_accounts() {
+    let interface = vault_accounts();
+    let (owner, token_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_prefix(
+        interface.to_account_metas(None),
+        pda::accounts::Deposit {
+            vault: interface.vault,
+            vault_token_account: interface.vault_token_account,
+            token_mint: interface.mint,
+            source: interface.user_token_account,
+            owner,
+            token_program,
+        }
+        .to_account_metas(None),
+    );
+    assert_prefix(
+        interface.to_account_metas(None),
+        pda::accounts::WithdrawCtx {
+            vault: interface.vault,
+            vault_token_account: interface.vault_token_account,
+            token_mint: interface.mint,
+            destination: interface.user_token_account,
+            token_program,
+            owner,
+        }
+        .to_account_metas(None),
+    );
+}
+
+#[test]
+fn program_close_vault_accounts() {
+    let interface = vault_accounts();
+    assert_prefix(
+        interface.to_account_metas(None),
+        solana_program_close::accounts::Deposit {
+            vault: interface.vault,
+            vault_token_account: interface.vault_token_account,
+            mint: interface.mint,
+            user_token_account: interface.user_token_account,
+            share_mint: Pubkey::new_unique(),
+            user_share_account: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+        .to_account_metas(None),
+    );
+}
+
+#[test]
+fn mock_spl_price_feed() {
+    assert_eq!(price::instruction::GetPrice::DISCRIMINATOR, mock_spl::instruction::GetPrice::DISCRIMINATOR);
+
+    let price_feed = Pubkey::new_unique();
+    assert_prefix(
+        price::accounts::GetPrice { price_feed }.to_account_metas(None),
+        mock_spl::accounts::GetPrice { price_feed }.to_account_metas(None),
+    );
+}
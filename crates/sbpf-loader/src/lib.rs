@@ -0,0 +1,82 @@
+//! Loader and test harness for standalone sBPF programs such as
+//! `sbpf-crackme`.
+//!
+//! The ELF is added to a fresh `solana-program-test` bank as a `BPFLoader2`
+//! program at [`PROGRAM_ID`], and a single instruction with no accounts is
+//! simulated against it. Nothing else is deployed, so the outcome depends only
+//! on the bytes under test and the instruction data.
+
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    instruction::{Instruction, InstructionError},
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Address the program under test is loaded at
+pub const PROGRAM_ID: Pubkey = pubkey!("EonkSMpiVoykYy38UTmbUj19mS9wuGDK5Luh7SW5cK9o");
+
+/// What one simulated instruction did
+#[derive(Debug)]
+pub struct Outcome {
+    pub result: Result<(), TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: u64,
+    pub return_data: Option<Vec<u8>>,
+}
+
+impl Outcome {
+    /// Custom program error code, if the instruction failed with one
+    pub fn custom_error_code(&self) -> Option<u32> {
+        match &self.result {
+            Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+/// Load `elf` and simulate one instruction carrying `data` and no accounts
+pub async fn run(elf: &[u8], data: &[u8]) -> Outcome {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_account(
+        PROGRAM_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(elf.len()),
+            data: elf.to_vec(),
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let instruction = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![],
+        data: data.to_vec(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .expect("banks client");
+    let details = simulation.simulation_details.expect("simulation details");
+    Outcome {
+        result: simulation.result.expect("simulation result"),
+        logs: details.logs,
+        units_consumed: details.units_consumed,
+        return_data: details.return_data.map(|return_data| return_data.data),
+    }
+}
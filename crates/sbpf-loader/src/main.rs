@@ -0,0 +1,51 @@
+//! Run a standalone sBPF program on hand-crafted instruction data.
+//!
+//! ```text
+//! cargo run -p sbpf-loader -- <program.so> [hex instruction data]
+//! ```
+//!
+//! Prints the result, compute units, program logs, and return data (as hex
+//! and as text), which is all a reverse-engineering challenge needs to test a
+//! guess without a validator.
+
+use std::process::ExitCode;
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 {
+        return Err(format!("odd number of hex digits in {hex:?}"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|err| format!("{hex:?}: {err}")))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(path) = args.first() else {
+        eprintln!("usage: sbpf-loader <program.so> [hex instruction data]");
+        return Ok(ExitCode::FAILURE);
+    };
+    let elf = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    let data = parse_hex(args.get(1).map_or("", String::as_str))?;
+
+    let outcome = sbpf_loader::run(&elf, &data).await;
+    println!("result: {:?}", outcome.result);
+    println!("compute units: {}", outcome.units_consumed);
+    for line in &outcome.logs {
+        println!("  {line}");
+    }
+    if let Some(return_data) = &outcome.return_data {
+        let hex: String = return_data.iter().map(|byte| format!("{byte:02x}")).collect();
+        println!("return data: {hex}");
+        println!("return data (text): {}", String::from_utf8_lossy(return_data));
+    }
+
+    Ok(if outcome.result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
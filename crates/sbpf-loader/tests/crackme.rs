@@ -0,0 +1,37 @@
+//! Runs the built `sbpf-crackme` through the loader.
+//!
+//! Needs `cargo build-sbf` in `sbpf-crackme` first.
+
+use sbpf_crackme::CrackmeError;
+
+fn crackme() -> Vec<u8> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../sbpf-crackme/target/deploy/sbpf_crackme.so"
+    );
+    std::fs::read(path)
+        .unwrap_or_else(|_| panic!("{path} not found, run `cargo build-sbf` in sbpf-crackme first"))
+}
+
+#[tokio::test]
+async fn wrong_password_is_rejected() {
+    let outcome = sbpf_loader::run(&crackme(), &[4, b'f', b'l', b'a', b'g']).await;
+    assert_eq!(outcome.custom_error_code(), Some(CrackmeError::WrongPassword.into()));
+    assert!(outcome.logs.iter().any(|line| line.contains("Wrong password")));
+    assert_eq!(outcome.return_data, None);
+}
+
+#[tokio::test]
+async fn length_byte_longer_than_the_data_is_malformed() {
+    let outcome = sbpf_loader::run(&crackme(), &[5, b's']).await;
+    assert_eq!(outcome.custom_error_code(), Some(CrackmeError::MalformedInput.into()));
+}
+
+#[tokio::test]
+async fn zero_length_claim_skips_the_comparison() {
+    let outcome = sbpf_loader::run(&crackme(), &[0]).await;
+    assert_eq!(outcome.result, Ok(()));
+    assert!(outcome.logs.iter().any(|line| line.contains("Correct!")));
+    let flag = outcome.return_data.expect("flag as return data");
+    assert!(flag.starts_with(b"ctf{"), "{}", String::from_utf8_lossy(&flag));
+}
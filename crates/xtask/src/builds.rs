@@ -0,0 +1,117 @@
+use std::process::Command;
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+use crate::programs::challenges;
+use crate::seed::{exists, send};
+use crate::{repo_root, XtaskResult};
+
+/// Hashing a whole program executable on-chain takes a few hundred thousand
+/// compute units, past the default limit
+const RECORD_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Record the build hash of every registered challenge's program in the
+/// registry, with the payer as the sole governance member. The hash is taken
+/// from the local `target/deploy` artifact and the registry rejects it unless
+/// it matches the deployed program, so run this from the commit the programs
+/// were built and deployed from. Challenges already recorded with the same
+/// hash and commit are skipped.
+pub fn record_builds(rpc_url: &str, payer: &Keypair) -> XtaskResult<()> {
+    let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    if !git(&["status", "--porcelain"])?.is_empty() {
+        return Err("the working tree has uncommitted changes, commit them before recording builds".into());
+    }
+    let commit = git(&["rev-parse", "HEAD"])?;
+    let repository = git(&["remote", "get-url", "origin"])?;
+    let (registry, _) = Pubkey::find_program_address(&[b"registry"], &ctf_registry::ID);
+
+    for (challenge_id, program_id, program) in challenges() {
+        let (challenge, _) =
+            Pubkey::find_program_address(&[b"challenge", challenge_id.as_bytes()], &ctf_registry::ID);
+        if !exists(&rpc, &challenge)? {
+            return Err(format!("{challenge_id} is not registered, run `cargo xtask seed` first").into());
+        }
+        let state = ctf_registry::Challenge::try_deserialize(&mut rpc.get_account_data(&challenge)?.as_slice())?;
+        if state.program_id != program_id {
+            println!("{challenge_id} is served by {}, not a program built here; skipped", state.program_id);
+            continue;
+        }
+
+        let (_, artifact) = program.deploy.unwrap();
+        let so = repo_root().join(program.workspace).join("target/deploy").join(format!("{artifact}.so"));
+        let executable = std::fs::read(&so).map_err(|err| format!("failed to read {}: {err}", so.display()))?;
+        let executable_hash = ctf_registry::build_hash(&executable);
+
+        let (build, _) = Pubkey::find_program_address(
+            &[b"build", challenge.as_ref(), &state.version.to_le_bytes()],
+            &ctf_registry::ID,
+        );
+        if exists(&rpc, &build)? {
+            let recorded = ctf_registry::VerifiedBuild::try_deserialize(&mut rpc.get_account_data(&build)?.as_slice())?;
+            if recorded.executable_hash == executable_hash && recorded.commit == commit {
+                println!("{challenge_id} version {} already recorded", state.version);
+                continue;
+            }
+        }
+
+        let mut accounts = ctf_registry::accounts::RecordBuild {
+            registry,
+            challenge,
+            program_data: bpf_loader_upgradeable::get_program_data_address(&program_id),
+            build,
+            payer: payer.pubkey(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None);
+        // The payer's approval as the sole governance member
+        accounts.push(AccountMeta::new_readonly(payer.pubkey(), true));
+        send(
+            &rpc,
+            payer,
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(RECORD_COMPUTE_UNITS),
+                Instruction {
+                    program_id: ctf_registry::ID,
+                    accounts,
+                    data: ctf_registry::instruction::RecordBuild {
+                        executable_hash,
+                        repository: repository.clone(),
+                        commit: commit.clone(),
+                    }
+                    .data(),
+                },
+            ],
+        )
+        .map_err(|err| {
+            format!("recording the {challenge_id} build failed, does {} match the deployed program? {err}", so.display())
+        })?;
+        println!(
+            "recorded {challenge_id} version {}: {}",
+            state.version,
+            hex(&executable_hash)
+        );
+    }
+    Ok(())
+}
+
+fn git(args: &[&str]) -> XtaskResult<String> {
+    let output = Command::new("git").args(args).current_dir(repo_root()).output()?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr)).into());
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
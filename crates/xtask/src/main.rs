@@ -0,0 +1,220 @@
+//! Repository automation, run through the `cargo xtask` alias:
+//!
+//! ```text
+//! cargo xtask build-all [--solutions]
+//! cargo xtask deploy-local [payer_keypair]
+//! cargo xtask seed [rpc_url] [payer_keypair]
+//! cargo xtask record-builds [rpc_url] [payer_keypair]
+//! cargo xtask run-exploits [rpc_url] [payer_keypair]
+//! cargo xtask verify-solutions [payer_keypair]
+//! ```
+//!
+//! - `build-all` builds every program workspace, with `anchor build` or
+//!   `cargo build-sbf` as the workspace needs. `--solutions` builds the
+//!   reference fixes instead, where a program has them.
+//! - `deploy-local` starts `solana-test-validator` on a fresh ledger with
+//!   every program loaded at its declared id, the payer as upgrade authority,
+//!   and runs until interrupted.
+//! - `seed` creates the registry with the payer as its only governance member,
+//!   registers every challenge, and publishes each challenge's program id in
+//!   the `deployments` program under the cluster named in the RPC URL
+//!   (`localnet` if none is).
+//! - `record-builds` records the hash of each challenge's `target/deploy`
+//!   artifact in the registry, with the repository and commit it was built
+//!   from. The registry refuses hashes that differ from the deployed program,
+//!   and the command refuses to run with uncommitted changes.
+//! - `run-exploits` runs each reference exploit in the `exploits` crate and
+//!   fails if any of them does. The last one closes the program-close
+//!   program, so start a new validator before running them again.
+//! - `verify-solutions` builds the reference fixes, grades them with the
+//!   grader on a validator of its own, then rebuilds the challenge versions.
+//!
+//! Arguments default to `http://127.0.0.1:8899` and
+//! `~/.config/solana/id.json`, as for the grader.
+
+mod builds;
+mod programs;
+mod seed;
+mod validator;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+
+use programs::{Build, Program, EXPLOITS, PROGRAMS, REFERENCE_PATCHES};
+
+pub type XtaskResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+const USAGE: &str = "usage: cargo xtask <build-all [--solutions] | deploy-local [payer_keypair] | \
+                     seed [rpc_url] [payer_keypair] | record-builds [rpc_url] [payer_keypair] | \
+                     run-exploits [rpc_url] [payer_keypair] | verify-solutions [payer_keypair]>";
+
+fn main() {
+    if let Err(err) = run_command() {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_command() -> XtaskResult<()> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().ok_or(USAGE)?;
+    let (flags, args): (Vec<String>, Vec<String>) = args.partition(|arg| arg.starts_with("--"));
+    let solutions = flags.iter().any(|flag| flag == "--solutions");
+    if let Some(flag) = flags.iter().find(|flag| *flag != "--solutions") {
+        return Err(format!("unknown flag {flag}\n{USAGE}").into());
+    }
+
+    match command.as_str() {
+        "build-all" => build(PROGRAMS.iter(), solutions),
+        "deploy-local" => deploy_local(&payer_path(args.first())),
+        "seed" => {
+            let rpc_url = rpc_url(args.first());
+            seed::seed(&rpc_url, &read_payer(&payer_path(args.get(1)))?)
+        }
+        "record-builds" => {
+            let rpc_url = rpc_url(args.first());
+            builds::record_builds(&rpc_url, &read_payer(&payer_path(args.get(1)))?)
+        }
+        "run-exploits" => {
+            let rpc_url = rpc_url(args.first());
+            run_exploits(&rpc_url, &payer_path(args.get(1)))
+        }
+        "verify-solutions" => verify_solutions(&payer_path(args.first())),
+        _ => Err(format!("unknown command {command}\n{USAGE}").into()),
+    }
+}
+
+/// Root of the repository, two levels above this crate
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..").canonicalize().unwrap()
+}
+
+fn rpc_url(arg: Option<&String>) -> String {
+    arg.cloned().unwrap_or_else(|| validator::RPC_URL.to_string())
+}
+
+fn payer_path(arg: Option<&String>) -> String {
+    arg.cloned().unwrap_or_else(|| {
+        format!(
+            "{}/.config/solana/id.json",
+            std::env::var("HOME").unwrap_or_default()
+        )
+    })
+}
+
+fn read_payer(path: &str) -> XtaskResult<Keypair> {
+    read_keypair_file(path).map_err(|err| format!("failed to read payer keypair {path}: {err}").into())
+}
+
+/// Run a command to completion, echoing it first
+fn run(command: &mut Command) -> XtaskResult<()> {
+    println!("$ {command:?}");
+    let status = command
+        .status()
+        .map_err(|err| format!("failed to run {:?}: {err}", command.get_program()))?;
+    if !status.success() {
+        return Err(format!("{:?} failed with {status}", command.get_program()).into());
+    }
+    Ok(())
+}
+
+fn cargo() -> Command {
+    Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+}
+
+fn build<'a>(programs: impl Iterator<Item = &'a Program>, solutions: bool) -> XtaskResult<()> {
+    let root = repo_root();
+    for program in programs {
+        let feature = program.solutions_feature.filter(|_| solutions);
+        println!("building {}{}", program.name, if feature.is_some() { " (solutions)" } else { "" });
+        match program.build {
+            Build::Anchor => {
+                let mut command = Command::new("anchor");
+                command.arg("build").current_dir(root.join(program.workspace));
+                if let Some(feature) = feature {
+                    command.args(["--", "--features", feature]);
+                }
+                run(&mut command)?;
+            }
+            Build::Sbf { manifest } => {
+                let mut command = cargo();
+                command
+                    .arg("build-sbf")
+                    .arg("--manifest-path")
+                    .arg(root.join(manifest))
+                    .current_dir(&root);
+                if let Some(feature) = feature {
+                    command.args(["--features", feature]);
+                }
+                run(&mut command)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn deploy_local(payer_path: &str) -> XtaskResult<()> {
+    let authority = read_payer(payer_path)?.pubkey();
+    let ledger = repo_root().join("target/xtask/ledger");
+    let mut validator = validator::start(&authority, &ledger, false)?;
+    println!(
+        "local validator ready at {} with upgrade authority {authority}; stop it with Ctrl-C",
+        validator::RPC_URL
+    );
+    validator.wait()?;
+    Ok(())
+}
+
+fn run_exploits(rpc_url: &str, payer_path: &str) -> XtaskResult<()> {
+    let mut failed = Vec::new();
+    for exploit in EXPLOITS {
+        let result = run(cargo()
+            .args(["run", "--quiet", "--package", "exploits", "--bin", exploit, "--"])
+            .args([rpc_url, payer_path])
+            .current_dir(repo_root()));
+        if let Err(err) = result {
+            eprintln!("{exploit}: {err}");
+            failed.push(*exploit);
+        }
+    }
+
+    println!();
+    for exploit in EXPLOITS {
+        let status = if failed.contains(exploit) { "FAIL" } else { "PASS" };
+        println!("{exploit:<28} {status}");
+    }
+    if !failed.is_empty() {
+        return Err(format!("{} of {} exploits failed", failed.len(), EXPLOITS.len()).into());
+    }
+    Ok(())
+}
+
+/// Build the reference fixes, load them into a throwaway validator, and grade
+/// the patches that have one. The challenge builds are restored afterwards,
+/// whatever the outcome, so `target/deploy` never keeps the fixed programs.
+/// Programs without reference fixes must already be built.
+fn verify_solutions(payer_path: &str) -> XtaskResult<()> {
+    let payer = read_payer(payer_path)?;
+    let with_solutions = || PROGRAMS.iter().filter(|program| program.solutions_feature.is_some());
+    let result = build(with_solutions(), true).and_then(|()| grade_solutions(&payer, payer_path));
+    println!("restoring the challenge builds");
+    build(with_solutions(), false)?;
+    result
+}
+
+fn grade_solutions(payer: &Keypair, payer_path: &str) -> XtaskResult<()> {
+    let ledger = repo_root().join("target/xtask/solutions-ledger");
+    let mut validator = validator::start(&payer.pubkey(), &ledger, true)?;
+    let result = seed::seed(validator::RPC_URL, payer).and_then(|()| {
+        run(cargo()
+            .args(["run", "--quiet", "--package", "grader", "--"])
+            .args([validator::RPC_URL, payer_path])
+            .args(REFERENCE_PATCHES.iter().map(|patch| format!("--patch={patch}")))
+            .current_dir(repo_root()))
+    });
+    validator.kill()?;
+    validator.wait()?;
+    result
+}
@@ -0,0 +1,146 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// How a workspace is built
+pub enum Build {
+    /// `anchor build` in the workspace directory
+    Anchor,
+    /// `cargo build-sbf` on one program's manifest
+    Sbf { manifest: &'static str },
+}
+
+/// One buildable workspace, and the program it deploys if any
+pub struct Program {
+    /// Directory name, also the registry id of challenges
+    pub name: &'static str,
+    /// Workspace directory, relative to the repository root
+    pub workspace: &'static str,
+    pub build: Build,
+    /// Cargo feature that builds the reference fixes
+    pub solutions_feature: Option<&'static str>,
+    /// Program id and the `target/deploy` artifact loaded at it
+    pub deploy: Option<(Pubkey, &'static str)>,
+    /// Registered by `seed`, with the organizer as checker
+    pub challenge: bool,
+}
+
+pub const PROGRAMS: &[Program] = &[
+    Program {
+        name: "ctf-registry",
+        workspace: "ctf-registry",
+        build: Build::Anchor,
+        solutions_feature: None,
+        deploy: Some((ctf_registry::ID, "ctf_registry")),
+        challenge: false,
+    },
+    Program {
+        name: "deployments",
+        workspace: "deployments",
+        build: Build::Anchor,
+        solutions_feature: None,
+        deploy: Some((deployments::ID, "deployments")),
+        challenge: false,
+    },
+    Program {
+        name: "mock-spl",
+        workspace: "mock-spl",
+        build: Build::Anchor,
+        solutions_feature: None,
+        deploy: Some((mock_spl::ID, "mock_spl")),
+        challenge: false,
+    },
+    Program {
+        name: "missing-signer-check",
+        workspace: "missing-signer-check",
+        build: Build::Anchor,
+        solutions_feature: Some("solutions"),
+        deploy: Some((pda::ID, "pda")),
+        challenge: true,
+    },
+    Program {
+        name: "solana-program-close",
+        workspace: "solana-program-close",
+        build: Build::Anchor,
+        solutions_feature: None,
+        deploy: Some((solana_program_close::ID, "solana_program_close")),
+        challenge: true,
+    },
+    Program {
+        name: "memory-safety-vulns",
+        workspace: "memory-safety-vulns/memory-safety-vulns",
+        build: Build::Anchor,
+        solutions_feature: Some("secure-init"),
+        deploy: Some((memory_safety_vulns::ID, "memory_safety_vulns")),
+        challenge: true,
+    },
+    Program {
+        name: "native-vault",
+        workspace: "native-vault",
+        build: Build::Sbf {
+            manifest: "native-vault/programs/native-vault/Cargo.toml",
+        },
+        solutions_feature: Some("solutions"),
+        deploy: Some((native_vault::ID, "native_vault")),
+        challenge: true,
+    },
+    Program {
+        name: "pinocchio-owner-check",
+        workspace: "pinocchio-owner-check",
+        build: Build::Sbf {
+            manifest: "pinocchio-owner-check/programs/pinocchio-owner-check/Cargo.toml",
+        },
+        solutions_feature: Some("solutions"),
+        deploy: Some((
+            Pubkey::new_from_array(pinocchio_owner_check::ID),
+            "pinocchio_owner_check",
+        )),
+        challenge: true,
+    },
+    Program {
+        name: "steel-vault",
+        workspace: "steel-vault",
+        build: Build::Sbf {
+            manifest: "steel-vault/program/Cargo.toml",
+        },
+        solutions_feature: Some("solutions"),
+        deploy: Some((steel_vault_api::ID, "steel_vault")),
+        challenge: true,
+    },
+    // Runs in `sbpf-loader` rather than on a validator
+    Program {
+        name: "sbpf-crackme",
+        workspace: "sbpf-crackme",
+        build: Build::Sbf {
+            manifest: "sbpf-crackme/programs/sbpf-crackme/Cargo.toml",
+        },
+        solutions_feature: Some("solutions"),
+        deploy: None,
+        challenge: false,
+    },
+    Program {
+        name: "benchmark",
+        workspace: "benchmark",
+        build: Build::Anchor,
+        solutions_feature: None,
+        deploy: None,
+        challenge: false,
+    },
+];
+
+/// Challenges registered on top of their program's own entry
+pub const EXTRA_CHALLENGES: &[(&str, Pubkey)] = &[(pda::BONUS_CHALLENGE_ID, pda::ID)];
+
+/// Every challenge `seed` registers, with the id and entry of the program
+/// serving it
+pub fn challenges() -> impl Iterator<Item = (&'static str, Pubkey, &'static Program)> {
+    let deployed = PROGRAMS
+        .iter()
+        .filter_map(|program| Some((program.deploy?.0, program)));
+    deployed
+        .clone()
+        .filter(|(_, program)| program.challenge)
+        .map(|(program_id, program)| (program.name, program_id, program))
+        .chain(EXTRA_CHALLENGES.iter().filter_map(move |(challenge_id, program_id)| {
+            let (_, program) = deployed.clone().find(|(id, _)| id == program_id)?;
+            Some((*challenge_id, *program_id, program))
+        }))
+}
@@ -0,0 +1,173 @@
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+
+use crate::programs::challenges;
+use crate::XtaskResult;
+
+/// Create the registry with the payer as its only governance member, then
+/// register every challenge with the payer as checker, and publish where each
+/// one is deployed on the cluster behind `rpc_url`. Anything that already
+/// exists is left alone, so seeding twice is harmless.
+pub fn seed(rpc_url: &str, payer: &Keypair) -> XtaskResult<()> {
+    let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let (registry, _) = Pubkey::find_program_address(&[b"registry"], &ctf_registry::ID);
+
+    if exists(&rpc, &registry)? {
+        println!("registry {registry} already exists");
+    } else {
+        send(
+            &rpc,
+            payer,
+            &[Instruction {
+                program_id: ctf_registry::ID,
+                accounts: ctf_registry::accounts::InitializeRegistry {
+                    registry,
+                    program: ctf_registry::ID,
+                    program_data: bpf_loader_upgradeable::get_program_data_address(&ctf_registry::ID),
+                    payer: payer.pubkey(),
+                    system_program: system_program::id(),
+                }
+                .to_account_metas(None),
+                data: ctf_registry::instruction::InitializeRegistry {
+                    members: vec![payer.pubkey()],
+                    threshold: 1,
+                }
+                .data(),
+            }],
+        )?;
+        println!("created registry {registry} governed by {}", payer.pubkey());
+    }
+
+    for (challenge_id, program_id, _) in challenges() {
+        let (challenge, _) =
+            Pubkey::find_program_address(&[b"challenge", challenge_id.as_bytes()], &ctf_registry::ID);
+        if exists(&rpc, &challenge)? {
+            println!("{challenge_id} already registered");
+            continue;
+        }
+
+        let mut accounts = ctf_registry::accounts::RegisterChallenge {
+            registry,
+            challenge,
+            payer: payer.pubkey(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None);
+        // The payer's approval as the sole governance member
+        accounts.push(AccountMeta::new_readonly(payer.pubkey(), true));
+        send(
+            &rpc,
+            payer,
+            &[Instruction {
+                program_id: ctf_registry::ID,
+                accounts,
+                data: ctf_registry::instruction::RegisterChallenge {
+                    challenge_id: challenge_id.to_string(),
+                    program_id,
+                    checker: payer.pubkey(),
+                }
+                .data(),
+            }],
+        )?;
+        println!("registered {challenge_id} -> {program_id}");
+    }
+
+    publish_deployments(&rpc, payer, cluster_name(rpc_url))
+}
+
+/// Cluster name the deployment records are published under
+fn cluster_name(rpc_url: &str) -> &'static str {
+    ["devnet", "testnet", "mainnet-beta"]
+        .into_iter()
+        .find(|cluster| rpc_url.contains(cluster.trim_end_matches("-beta")))
+        .unwrap_or("localnet")
+}
+
+/// Create the deployment manager with the payer as its authority, then
+/// publish every challenge's program id for `cluster`. Deployments already
+/// published with the same program id are left alone.
+fn publish_deployments(rpc: &RpcClient, payer: &Keypair, cluster: &str) -> XtaskResult<()> {
+    let manager = Pubkey::find_program_address(&[deployments::MANAGER_SEED], &deployments::ID).0;
+    if !exists(rpc, &manager)? {
+        send(
+            rpc,
+            payer,
+            &[Instruction {
+                program_id: deployments::ID,
+                accounts: deployments::accounts::InitializeManager {
+                    manager,
+                    program: deployments::ID,
+                    program_data: bpf_loader_upgradeable::get_program_data_address(&deployments::ID),
+                    authority: payer.pubkey(),
+                    system_program: system_program::id(),
+                }
+                .to_account_metas(None),
+                data: deployments::instruction::InitializeManager {}.data(),
+            }],
+        )?;
+        println!("created deployment manager {manager}");
+    }
+
+    for (challenge_id, program_id, _) in challenges() {
+        let deployment = deployments::deployment_address(cluster, challenge_id);
+        if exists(rpc, &deployment)? {
+            let published =
+                deployments::Deployment::try_deserialize(&mut rpc.get_account_data(&deployment)?.as_slice())?;
+            if published.active && published.program_id == program_id {
+                println!("{challenge_id} already published for {cluster}");
+                continue;
+            }
+        }
+
+        send(
+            rpc,
+            payer,
+            &[Instruction {
+                program_id: deployments::ID,
+                accounts: deployments::accounts::PublishDeployment {
+                    manager,
+                    deployment,
+                    authority: payer.pubkey(),
+                    system_program: system_program::id(),
+                }
+                .to_account_metas(None),
+                data: deployments::instruction::PublishDeployment {
+                    cluster: cluster.to_string(),
+                    challenge_id: challenge_id.to_string(),
+                    program_id,
+                    params: Vec::new(),
+                }
+                .data(),
+            }],
+        )?;
+        println!("published {challenge_id} -> {program_id} for {cluster}");
+    }
+    Ok(())
+}
+
+pub fn exists(rpc: &RpcClient, address: &Pubkey) -> XtaskResult<bool> {
+    Ok(rpc
+        .get_account_with_commitment(address, CommitmentConfig::confirmed())?
+        .value
+        .is_some())
+}
+
+pub fn send(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction]) -> XtaskResult<()> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash()?,
+    );
+    rpc.send_and_confirm_transaction(&transaction)?;
+    Ok(())
+}
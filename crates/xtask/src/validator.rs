@@ -0,0 +1,64 @@
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::programs::PROGRAMS;
+use crate::{repo_root, XtaskResult};
+
+pub const RPC_URL: &str = "http://127.0.0.1:8899";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Start `solana-test-validator` on a fresh ledger with every built program
+/// loaded at its declared id through the upgradeable loader. `authority` is
+/// the upgrade authority, which the setup instructions and the
+/// program-close exploit check.
+pub fn start(authority: &Pubkey, ledger: &Path, quiet: bool) -> XtaskResult<Child> {
+    let root = repo_root();
+    let mut command = Command::new("solana-test-validator");
+    command.arg("--reset").arg("--ledger").arg(ledger);
+    if quiet {
+        command.arg("--quiet").stdout(Stdio::null());
+    }
+    for program in PROGRAMS {
+        let Some((program_id, artifact)) = program.deploy else {
+            continue;
+        };
+        let so = root.join(program.workspace).join("target/deploy").join(format!("{artifact}.so"));
+        if !so.exists() {
+            return Err(format!("{} is missing, run `cargo xtask build-all` first", so.display()).into());
+        }
+        command
+            .arg("--upgradeable-program")
+            .arg(program_id.to_string())
+            .arg(so)
+            .arg(authority.to_string());
+    }
+
+    let mut validator = command
+        .spawn()
+        .map_err(|err| format!("failed to start solana-test-validator: {err}"))?;
+    if let Err(err) = wait_until_healthy(&mut validator) {
+        let _ = validator.kill();
+        return Err(err);
+    }
+    Ok(validator)
+}
+
+fn wait_until_healthy(validator: &mut Child) -> XtaskResult<()> {
+    let rpc = RpcClient::new(RPC_URL.to_string());
+    let started = Instant::now();
+    while rpc.get_health().is_err() {
+        if let Some(status) = validator.try_wait()? {
+            return Err(format!("solana-test-validator exited with {status}").into());
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(format!("validator not healthy after {}s", STARTUP_TIMEOUT.as_secs()).into());
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    Ok(())
+}
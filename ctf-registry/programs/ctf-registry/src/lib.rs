@@ -0,0 +1,1204 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_spl::token_interface::TokenAccount;
+
+declare_id!("CH4SZAsJAwTf4nwjvErPZt7KyXCHysCxGKptVPReQpCc");
+
+pub const MAX_MEMBERS: usize = 10;
+pub const MAX_CHALLENGE_ID_LEN: usize = 32;
+pub const MAX_WRITEUP_URI_LEN: usize = 200;
+pub const MAX_BUFFER_OFFSET: u16 = 32;
+pub const MAX_REPOSITORY_URI_LEN: usize = 200;
+pub const GIT_COMMIT_LEN: usize = 40;
+
+#[program]
+pub mod ctf_registry {
+    use super::*;
+
+    /// Create the registry and the governance multisig that administers it.
+    /// Only the program's upgrade authority can, so nobody else can take
+    /// over governance by creating the singleton first.
+    pub fn initialize_registry(
+        ctx: Context<InitializeRegistry>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= MAX_MEMBERS,
+            ErrorCode::InvalidMembers
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[..i].contains(member), ErrorCode::InvalidMembers);
+        }
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            ErrorCode::InvalidThreshold
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        registry.members = members;
+        registry.threshold = threshold;
+        registry.challenge_count = 0;
+        registry.submission_deadline = i64::MAX;
+        registry.finalized = false;
+        registry.writeup_count = 0;
+        registry.bump = ctx.bumps.registry;
+
+        msg!(
+            "Registry initialized with a {}-of-{} governance multisig",
+            threshold,
+            registry.members.len()
+        );
+        Ok(())
+    }
+
+    /// Register a challenge program. Requires governance approval.
+    pub fn register_challenge(
+        ctx: Context<RegisterChallenge>,
+        challenge_id: String,
+        program_id: Pubkey,
+        checker: Pubkey,
+    ) -> Result<()> {
+        require!(
+            !challenge_id.is_empty() && challenge_id.len() <= MAX_CHALLENGE_ID_LEN,
+            ErrorCode::InvalidChallengeId
+        );
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.challenge_id = challenge_id;
+        challenge.program_id = program_id;
+        challenge.previous_program_id = Pubkey::default();
+        challenge.version = 1;
+        challenge.checker = checker;
+        challenge.status = ChallengeStatus::Active;
+        challenge.solve_count = 0;
+        challenge.deprecated_solve_count = 0;
+        challenge.cooldown_slots = 0;
+        challenge.max_attempts = 0;
+        challenge.profit_vault = Pubkey::default();
+        challenge.profit_threshold = 0;
+        challenge.profit_claimed_slot = 0;
+        challenge.bump = ctx.bumps.challenge;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.challenge_count = registry.challenge_count.checked_add(1).unwrap();
+
+        msg!(
+            "Registered challenge {} -> {}",
+            challenge.challenge_id,
+            challenge.program_id
+        );
+        Ok(())
+    }
+
+    /// Temporarily stop accepting solves for a challenge
+    pub fn pause_challenge(ctx: Context<UpdateChallenge>) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        require!(
+            challenge.status == ChallengeStatus::Active,
+            ErrorCode::InvalidStatusTransition
+        );
+        challenge.status = ChallengeStatus::Paused;
+
+        msg!("Challenge {} paused", challenge.challenge_id);
+        Ok(())
+    }
+
+    /// Resume a paused challenge
+    pub fn resume_challenge(ctx: Context<UpdateChallenge>) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        require!(
+            challenge.status == ChallengeStatus::Paused,
+            ErrorCode::InvalidStatusTransition
+        );
+        challenge.status = ChallengeStatus::Active;
+
+        msg!("Challenge {} resumed", challenge.challenge_id);
+        Ok(())
+    }
+
+    /// Retire a challenge. Solves are still accepted but flagged as deprecated.
+    pub fn deprecate_challenge(ctx: Context<UpdateChallenge>) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        require!(
+            challenge.status != ChallengeStatus::Deprecated,
+            ErrorCode::InvalidStatusTransition
+        );
+        challenge.status = ChallengeStatus::Deprecated;
+
+        msg!("Challenge {} deprecated", challenge.challenge_id);
+        Ok(())
+    }
+
+    /// Hot-swap the program backing a challenge, e.g. to ship a fix mid-event.
+    /// Solves against the replaced program are rejected from then on.
+    pub fn swap_challenge_program(
+        ctx: Context<UpdateChallenge>,
+        new_program_id: Pubkey,
+    ) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        require!(
+            challenge.status != ChallengeStatus::Deprecated,
+            ErrorCode::InvalidStatusTransition
+        );
+        require!(
+            new_program_id != challenge.program_id,
+            ErrorCode::InvalidProgramId
+        );
+
+        challenge.previous_program_id = challenge.program_id;
+        challenge.program_id = new_program_id;
+        challenge.version = challenge.version.checked_add(1).unwrap();
+
+        msg!(
+            "Challenge {} now served by {} (version {})",
+            challenge.challenge_id,
+            challenge.program_id,
+            challenge.version
+        );
+        Ok(())
+    }
+
+    /// Publish the verifiable build of the program currently serving a
+    /// challenge: the hash of its executable, as `solana-verify` computes it,
+    /// and the repository and commit it was built from. The hash is checked
+    /// against the deployed program, so a record always matches the bytecode
+    /// at `deploy_slot`. Requires governance approval. Recording again for the
+    /// same version replaces the record, e.g. after a redeploy.
+    pub fn record_build(
+        ctx: Context<RecordBuild>,
+        executable_hash: [u8; 32],
+        repository: String,
+        commit: String,
+    ) -> Result<()> {
+        require!(
+            !repository.is_empty() && repository.len() <= MAX_REPOSITORY_URI_LEN,
+            ErrorCode::InvalidRepositoryUri
+        );
+        require!(
+            commit.len() == GIT_COMMIT_LEN && commit.bytes().all(|b| b.is_ascii_hexdigit()),
+            ErrorCode::InvalidCommit
+        );
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let program_data = ctx.accounts.program_data.to_account_info();
+        let data = program_data.try_borrow_data()?;
+        let executable = &data[UpgradeableLoaderState::size_of_programdata_metadata()..];
+        require!(
+            build_hash(executable) == executable_hash,
+            ErrorCode::BuildHashMismatch
+        );
+
+        let challenge = &ctx.accounts.challenge;
+        let build = &mut ctx.accounts.build;
+        build.challenge = challenge.key();
+        build.program_id = challenge.program_id;
+        build.version = challenge.version;
+        build.executable_hash = executable_hash;
+        build.deploy_slot = ctx.accounts.program_data.slot;
+        build.repository = repository;
+        build.commit = commit;
+        build.recorded_at = Clock::get()?.unix_timestamp;
+        build.bump = ctx.bumps.build;
+
+        msg!(
+            "Build of {} version {} recorded from {}@{}",
+            challenge.challenge_id,
+            build.version,
+            build.repository,
+            build.commit
+        );
+        Ok(())
+    }
+
+    /// Configure how often a player may submit an attempt to the checker.
+    /// A `max_attempts` of 0 means unlimited attempts.
+    pub fn set_attempt_policy(
+        ctx: Context<UpdateChallenge>,
+        cooldown_slots: u64,
+        max_attempts: u32,
+    ) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.cooldown_slots = cooldown_slots;
+        challenge.max_attempts = max_attempts;
+
+        msg!(
+            "Challenge {} attempt policy: cooldown {} slots, max {} attempts",
+            challenge.challenge_id,
+            cooldown_slots,
+            max_attempts
+        );
+        Ok(())
+    }
+
+    /// Make an economic challenge solvable by profit: any exploit that drains
+    /// at least `profit_threshold` tokens out of `profit_vault` counts as a
+    /// solve. The vault must be a token account only the challenge program
+    /// can pay out of, such as a seeded victim's, so the amount can't be
+    /// faked from the player's side. A threshold of 0 disables profit-based
+    /// solves.
+    pub fn set_profit_target(
+        ctx: Context<UpdateChallenge>,
+        profit_vault: Pubkey,
+        profit_threshold: u64,
+    ) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.profit_vault = profit_vault;
+        challenge.profit_threshold = profit_threshold;
+
+        msg!(
+            "Challenge {} profit target: {} out of vault {}",
+            challenge.challenge_id,
+            profit_threshold,
+            profit_vault
+        );
+        Ok(())
+    }
+
+    /// Count an attempt by a player. Called by the checker with the outcome of
+    /// the evaluated submission (`error_code` 0 for success); fails while the
+    /// player is cooling down or out of attempts.
+    pub fn record_attempt(ctx: Context<RecordAttempt>, error_code: u32) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+        require!(
+            challenge.status != ChallengeStatus::Paused,
+            ErrorCode::ChallengePaused
+        );
+
+        let attempts = &mut ctx.accounts.attempts;
+        let slot = Clock::get()?.slot;
+
+        if attempts.count > 0 {
+            require!(
+                slot >= attempts.last_attempt_slot.saturating_add(challenge.cooldown_slots),
+                ErrorCode::CooldownActive
+            );
+        }
+        if challenge.max_attempts > 0 {
+            require!(
+                attempts.count < challenge.max_attempts,
+                ErrorCode::AttemptLimitReached
+            );
+        }
+
+        attempts.challenge = challenge.key();
+        attempts.player = ctx.accounts.player.key();
+        attempts.count = attempts.count.checked_add(1).unwrap();
+        attempts.last_attempt_slot = slot;
+        attempts.bump = ctx.bumps.attempts;
+
+        emit!(AttemptEvent {
+            challenge_id: challenge.challenge_id.clone(),
+            player_hash: player_hash(&attempts.player),
+            attempt: attempts.count,
+            error_code,
+            slot,
+        });
+
+        msg!(
+            "Attempt {} recorded for {} at slot {}",
+            attempts.count,
+            challenge.challenge_id,
+            slot
+        );
+        Ok(())
+    }
+
+    /// Create a player's instance of a challenge with randomized parameters,
+    /// so a solution transaction copied from another player does not replay
+    /// verbatim. Instances cannot be re-rolled.
+    pub fn create_instance(ctx: Context<CreateInstance>) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot_hashes = ctx.accounts.slot_hashes.try_borrow_data()?;
+        // SlotHashes layout: u64 length, then (u64 slot, [u8; 32] hash) newest first
+        let recent_hash = slot_hashes
+            .get(16..48)
+            .ok_or(ErrorCode::MissingEntropy)?;
+
+        let challenge = ctx.accounts.challenge.key();
+        let player = ctx.accounts.player.key();
+        let entropy = hashv(&[
+            INSTANCE_SEED,
+            challenge.as_ref(),
+            player.as_ref(),
+            recent_hash,
+            &clock.slot.to_le_bytes(),
+        ])
+        .to_bytes();
+
+        let instance = &mut ctx.accounts.instance;
+        instance.challenge = challenge;
+        instance.player = player;
+        instance.entropy = entropy;
+        instance.created_slot = clock.slot;
+        instance.bump = ctx.bumps.instance;
+
+        let admin = instance.derive(b"admin");
+        let offset = instance.derive(b"buffer_offset");
+        let seed = instance.derive(b"seed");
+        instance.admin = Pubkey::new_from_array(admin);
+        instance.buffer_offset = u16::from_le_bytes([offset[0], offset[1]]) % MAX_BUFFER_OFFSET;
+        instance.seed.copy_from_slice(&seed[..16]);
+
+        msg!(
+            "Instance of {} created for {}",
+            ctx.accounts.challenge.challenge_id,
+            player
+        );
+        Ok(())
+    }
+
+    /// Set the deadline after which writeup commitments are no longer accepted
+    pub fn set_submission_deadline(
+        ctx: Context<UpdateRegistry>,
+        submission_deadline: i64,
+    ) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let registry = &mut ctx.accounts.registry;
+        require!(!registry.finalized, ErrorCode::EventFinalized);
+        registry.submission_deadline = submission_deadline;
+
+        msg!("Submission deadline set to {}", submission_deadline);
+        Ok(())
+    }
+
+    /// Close the event once the deadline has passed. Unlocks writeup reveals.
+    pub fn finalize_event(ctx: Context<UpdateRegistry>) -> Result<()> {
+        verify_governance(&ctx.accounts.registry, ctx.remaining_accounts)?;
+
+        let registry = &mut ctx.accounts.registry;
+        require!(!registry.finalized, ErrorCode::EventFinalized);
+        require!(
+            Clock::get()?.unix_timestamp >= registry.submission_deadline,
+            ErrorCode::DeadlineNotReached
+        );
+        registry.finalized = true;
+
+        msg!("Event finalized with {} writeup commitments", registry.writeup_count);
+        Ok(())
+    }
+
+    /// Commit to a writeup by its hash before the deadline. The commit index
+    /// records the order in which solvers committed.
+    pub fn commit_writeup(ctx: Context<CommitWriteup>, writeup_hash: [u8; 32]) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < registry.submission_deadline, ErrorCode::DeadlinePassed);
+
+        let writeup = &mut ctx.accounts.writeup;
+        writeup.challenge = ctx.accounts.challenge.key();
+        writeup.player = ctx.accounts.player.key();
+        writeup.writeup_hash = writeup_hash;
+        writeup.commit_index = registry.writeup_count;
+        writeup.committed_at = now;
+        writeup.uri = String::new();
+        writeup.revealed_at = 0;
+        writeup.bump = ctx.bumps.writeup;
+
+        registry.writeup_count = registry.writeup_count.checked_add(1).unwrap();
+
+        msg!(
+            "Writeup #{} committed for {}",
+            writeup.commit_index,
+            ctx.accounts.challenge.challenge_id
+        );
+        Ok(())
+    }
+
+    /// Publish the writeup location once the event has been finalized
+    pub fn reveal_writeup(ctx: Context<RevealWriteup>, uri: String) -> Result<()> {
+        require!(ctx.accounts.registry.finalized, ErrorCode::EventNotFinalized);
+        require!(
+            !uri.is_empty() && uri.len() <= MAX_WRITEUP_URI_LEN,
+            ErrorCode::InvalidWriteupUri
+        );
+
+        let writeup = &mut ctx.accounts.writeup;
+        require!(writeup.revealed_at == 0, ErrorCode::WriteupAlreadyRevealed);
+        writeup.uri = uri;
+        writeup.revealed_at = Clock::get()?.unix_timestamp;
+
+        msg!("Writeup #{} revealed: {}", writeup.commit_index, writeup.uri);
+        Ok(())
+    }
+
+    /// Record a solve reported by the challenge's checker
+    pub fn record_solve(ctx: Context<RecordSolve>, program_id: Pubkey) -> Result<()> {
+        let player = ctx.accounts.player.key();
+        save_solve(
+            &mut ctx.accounts.challenge,
+            &mut ctx.accounts.solve,
+            player,
+            program_id,
+            ctx.bumps.solve,
+        )
+    }
+
+    /// Record a solve reported by the challenge program itself. Challenge
+    /// programs CPI into this from their vulnerable handlers once the exploit
+    /// condition holds, signing with their `[FLAG_AUTHORITY_SEED]` PDA. Only
+    /// the program serving the active version can capture.
+    pub fn capture_flag(ctx: Context<CaptureFlag>) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+        let flag_authority = ctx.accounts.flag_authority.key();
+
+        let program_id = if flag_authority == flag_authority_address(&challenge.program_id) {
+            challenge.program_id
+        } else if challenge.previous_program_id != Pubkey::default()
+            && flag_authority == flag_authority_address(&challenge.previous_program_id)
+        {
+            return Err(ErrorCode::StaleVersion.into());
+        } else {
+            return Err(ErrorCode::InvalidFlagAuthority.into());
+        };
+
+        let player = ctx.accounts.player.key();
+        save_solve(
+            &mut ctx.accounts.challenge,
+            &mut ctx.accounts.solve,
+            player,
+            program_id,
+            ctx.bumps.solve,
+        )
+    }
+
+    /// Record the profit vault's balance before the player starts exploiting
+    /// an economic challenge. A snapshot left over from a version that has
+    /// since been hot-swapped, or taken before someone's claim, can be taken
+    /// again.
+    pub fn snapshot_balance(ctx: Context<SnapshotBalance>) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+        require!(challenge.profit_threshold > 0, ErrorCode::NoProfitTarget);
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        // Versions start at 1, so a new snapshot reads as version 0
+        require!(
+            snapshot.version != challenge.version || snapshot.slot <= challenge.profit_claimed_slot,
+            ErrorCode::SnapshotExists
+        );
+        snapshot.challenge = challenge.key();
+        snapshot.player = ctx.accounts.player.key();
+        snapshot.vault = ctx.accounts.vault.key();
+        snapshot.balance = ctx.accounts.vault.amount;
+        snapshot.version = challenge.version;
+        snapshot.slot = Clock::get()?.slot;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        msg!(
+            "Snapshot of {} for {}: {}",
+            snapshot.vault,
+            challenge.challenge_id,
+            snapshot.balance
+        );
+        Ok(())
+    }
+
+    /// Record a solve for an economic challenge once the profit vault has
+    /// lost at least the challenge's threshold since the player's snapshot.
+    /// Only the outcome is checked, not how it was reached, so unintended
+    /// solutions count too. Each claim outdates every earlier snapshot, so
+    /// one drain earns one solve; claim in the exploit's transaction.
+    pub fn claim_profit(ctx: Context<ClaimProfit>) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+        let snapshot = &ctx.accounts.snapshot;
+        require!(challenge.profit_threshold > 0, ErrorCode::NoProfitTarget);
+        require!(
+            snapshot.slot > challenge.profit_claimed_slot,
+            ErrorCode::ProfitAlreadyClaimed
+        );
+
+        let profit = snapshot
+            .balance
+            .saturating_sub(ctx.accounts.vault.amount);
+        require!(
+            profit >= challenge.profit_threshold,
+            ErrorCode::InsufficientProfit
+        );
+
+        // The exploit must have run against the active version, start to end
+        require!(
+            snapshot.version == challenge.version,
+            ErrorCode::StaleVersion
+        );
+
+        let program_id = challenge.program_id;
+        let player = ctx.accounts.player.key();
+        save_solve(
+            &mut ctx.accounts.challenge,
+            &mut ctx.accounts.solve,
+            player,
+            program_id,
+            ctx.bumps.solve,
+        )?;
+        ctx.accounts.solve.profit = profit;
+        ctx.accounts.challenge.profit_claimed_slot = Clock::get()?.slot;
+
+        msg!("Drained from the profit vault: {}", profit);
+        Ok(())
+    }
+}
+
+/// Seed of the PDA a challenge program signs with when calling `capture_flag`
+pub const FLAG_AUTHORITY_SEED: &[u8] = b"flag_authority";
+
+/// Address of the flag authority PDA for a challenge program
+pub fn flag_authority_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FLAG_AUTHORITY_SEED], program_id).0
+}
+
+/// Seed of a player's `Instance` of a challenge: `[INSTANCE_SEED, challenge, player]`
+pub const INSTANCE_SEED: &[u8] = b"instance";
+
+/// Address of `player`'s instance of `challenge`
+pub fn instance_address(challenge: &Pubkey, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[INSTANCE_SEED, challenge.as_ref(), player.as_ref()], &ID).0
+}
+
+/// Record `player`'s one solve of `challenge`, which must be against the
+/// active version. Solves are keyed by challenge and player, so a redeploy
+/// neither lets a player score twice nor leaves an old solve in the way.
+fn save_solve(
+    challenge: &mut Account<Challenge>,
+    solve: &mut Account<Solve>,
+    player: Pubkey,
+    program_id: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    require!(
+        challenge.status != ChallengeStatus::Paused,
+        ErrorCode::ChallengePaused
+    );
+
+    if program_id != challenge.program_id {
+        return Err(if challenge.previous_program_id != Pubkey::default()
+            && program_id == challenge.previous_program_id
+        {
+            ErrorCode::StaleVersion.into()
+        } else {
+            ErrorCode::InvalidProgramId.into()
+        });
+    }
+    let deprecated = challenge.status == ChallengeStatus::Deprecated;
+
+    solve.challenge = challenge.key();
+    solve.player = player;
+    solve.program_id = program_id;
+    solve.version = challenge.version;
+    solve.slot = Clock::get()?.slot;
+    solve.deprecated = deprecated;
+    solve.bump = bump;
+
+    emit!(SolveEvent {
+        challenge_id: challenge.challenge_id.clone(),
+        player_hash: player_hash(&player),
+        version: solve.version,
+        deprecated,
+        slot: solve.slot,
+    });
+
+    if deprecated {
+        challenge.deprecated_solve_count = challenge.deprecated_solve_count.checked_add(1).unwrap();
+        msg!(
+            "Solve recorded for {} after it was deprecated",
+            challenge.challenge_id
+        );
+    } else {
+        challenge.solve_count = challenge.solve_count.checked_add(1).unwrap();
+        msg!("Solve recorded for {}", challenge.challenge_id);
+    }
+    Ok(())
+}
+
+/// Hash of a program executable the way `solana-verify` reports it: SHA-256
+/// of the ELF with trailing zero bytes removed, so the padding of a
+/// ProgramData account does not change the result
+pub fn build_hash(executable: &[u8]) -> [u8; 32] {
+    let len = executable.iter().rposition(|b| *b != 0).map_or(0, |last| last + 1);
+    hash(&executable[..len]).to_bytes()
+}
+
+/// Pseudonymous player id used in telemetry so analytics never store raw
+/// player keys
+pub fn player_hash(player: &Pubkey) -> [u8; 32] {
+    hashv(&[b"ctf-player", player.as_ref()]).to_bytes()
+}
+
+/// Counts distinct governance members that signed the transaction. Member
+/// signers are passed as remaining accounts.
+fn verify_governance(registry: &Registry, signers: &[AccountInfo]) -> Result<()> {
+    let mut approvals: Vec<Pubkey> = Vec::with_capacity(signers.len());
+    for account in signers {
+        if account.is_signer
+            && registry.members.contains(account.key)
+            && !approvals.contains(account.key)
+        {
+            approvals.push(*account.key);
+        }
+    }
+    require!(
+        approvals.len() >= registry.threshold as usize,
+        ErrorCode::NotEnoughApprovals
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::CtfRegistry>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(challenge_id: String)]
+pub struct RegisterChallenge<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Challenge::INIT_SPACE,
+        seeds = [b"challenge", challenge_id.as_bytes()],
+        bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateChallenge<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+}
+
+#[derive(Accounts)]
+pub struct RecordBuild<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    /// Executable of the challenge's current program, hashed in the handler
+    #[account(address = bpf_loader_upgradeable::get_program_data_address(&challenge.program_id))]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VerifiedBuild::INIT_SPACE,
+        seeds = [b"build", challenge.key().as_ref(), &challenge.version.to_le_bytes()],
+        bump
+    )]
+    pub build: Account<'info, VerifiedBuild>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSolve<'info> {
+    #[account(
+        mut,
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+        has_one = checker,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        init,
+        payer = checker,
+        space = 8 + Solve::INIT_SPACE,
+        seeds = [b"solve", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub solve: Account<'info, Solve>,
+
+    /// CHECK: Only used as a seed and recorded as the solver
+    pub player: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub checker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CaptureFlag<'info> {
+    #[account(
+        mut,
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Solve::INIT_SPACE,
+        seeds = [b"solve", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub solve: Account<'info, Solve>,
+
+    /// CHECK: Only used as a seed and recorded as the solver
+    pub player: UncheckedAccount<'info>,
+
+    /// PDA of the calling challenge program, checked in the handler
+    pub flag_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotBalance<'info> {
+    #[account(
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + ProfitSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, ProfitSnapshot>,
+
+    #[account(address = challenge.profit_vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimProfit<'info> {
+    #[account(
+        mut,
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"snapshot", challenge.key().as_ref(), player.key().as_ref()],
+        bump = snapshot.bump,
+        has_one = vault,
+    )]
+    pub snapshot: Account<'info, ProfitSnapshot>,
+
+    #[account(address = challenge.profit_vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Solve::INIT_SPACE,
+        seeds = [b"solve", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub solve: Account<'info, Solve>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAttempt<'info> {
+    #[account(
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+        has_one = checker,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        init_if_needed,
+        payer = checker,
+        space = 8 + Attempts::INIT_SPACE,
+        seeds = [b"attempts", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub attempts: Account<'info, Attempts>,
+
+    /// CHECK: Only used as a seed and recorded as the attempting player
+    pub player: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub checker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInstance<'info> {
+    #[account(
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Instance::INIT_SPACE,
+        seeds = [INSTANCE_SEED, challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub instance: Account<'info, Instance>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: Read raw for entropy, address is checked
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitWriteup<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        seeds = [b"challenge", challenge.challenge_id.as_bytes()],
+        bump = challenge.bump,
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    // Only solvers may commit a writeup
+    #[account(
+        has_one = challenge,
+        has_one = player,
+    )]
+    pub solve: Account<'info, Solve>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Writeup::INIT_SPACE,
+        seeds = [b"writeup", challenge.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub writeup: Account<'info, Writeup>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealWriteup<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"writeup", writeup.challenge.as_ref(), player.key().as_ref()],
+        bump = writeup.bump,
+        has_one = player,
+    )]
+    pub writeup: Account<'info, Writeup>,
+
+    pub player: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    #[max_len(MAX_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub challenge_count: u64,
+    pub submission_deadline: i64, // i64::MAX until governance sets a deadline
+    pub finalized: bool,
+    pub writeup_count: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ChallengeStatus {
+    Active,
+    Paused,
+    Deprecated,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Challenge {
+    #[max_len(MAX_CHALLENGE_ID_LEN)]
+    pub challenge_id: String,
+    pub program_id: Pubkey,
+    pub previous_program_id: Pubkey, // Default pubkey until the first hot-swap
+    pub version: u16,
+    pub checker: Pubkey,
+    pub status: ChallengeStatus,
+    pub solve_count: u64,
+    pub deprecated_solve_count: u64,
+    pub cooldown_slots: u64,
+    pub max_attempts: u32, // 0 means unlimited
+    pub profit_vault: Pubkey,     // Token account profit is drained from
+    pub profit_threshold: u64,    // 0 unless the challenge is solved by profit
+    pub profit_claimed_slot: u64, // Slot of the latest claim_profit
+    pub bump: u8,
+}
+
+/// Verifiable build of one version of a challenge. Players compare
+/// `executable_hash` with `solana-verify get-program-hash` of `program_id`
+/// and with their own verifiable build of `repository` at `commit`.
+#[account]
+#[derive(InitSpace)]
+pub struct VerifiedBuild {
+    pub challenge: Pubkey,
+    pub program_id: Pubkey,
+    pub version: u16,
+    pub executable_hash: [u8; 32],
+    pub deploy_slot: u64, // ProgramData slot when recorded; a later upgrade changes it
+    #[max_len(MAX_REPOSITORY_URI_LEN)]
+    pub repository: String,
+    #[max_len(GIT_COMMIT_LEN)]
+    pub commit: String,
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Solve {
+    pub challenge: Pubkey,
+    pub player: Pubkey,
+    pub program_id: Pubkey,
+    pub version: u16, // Version solved; not a seed, a player solves each challenge once
+    pub slot: u64,
+    pub deprecated: bool,
+    pub profit: u64, // Amount drained from the profit vault, for solves claimed through claim_profit
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Attempts {
+    pub challenge: Pubkey,
+    pub player: Pubkey,
+    pub count: u32,
+    pub last_attempt_slot: u64,
+    pub bump: u8,
+}
+
+/// Profit vault's balance before the player started exploiting, closed when
+/// the profit is claimed
+#[account]
+#[derive(InitSpace)]
+pub struct ProfitSnapshot {
+    pub challenge: Pubkey,
+    pub player: Pubkey,
+    pub vault: Pubkey,
+    pub balance: u64,
+    pub version: u16,
+    pub slot: u64,
+    pub bump: u8,
+}
+
+/// Per-player challenge parameters. Challenge programs read this account to
+/// pick the admin key, buffer offset, or seed string for the player's
+/// instance; `derive` produces further parameters from the same entropy.
+/// `memory-safety-vulns` takes its admin sentinel from `admin`, and
+/// `missing-signer-check` makes `admin` the owner of the player's victim.
+#[account]
+#[derive(InitSpace)]
+pub struct Instance {
+    pub challenge: Pubkey,
+    pub player: Pubkey,
+    pub entropy: [u8; 32],
+    pub admin: Pubkey,
+    pub buffer_offset: u16, // Always below MAX_BUFFER_OFFSET
+    pub seed: [u8; 16],
+    pub created_slot: u64,
+    pub bump: u8,
+}
+
+impl Instance {
+    pub fn derive(&self, label: &[u8]) -> [u8; 32] {
+        hashv(&[&self.entropy, label]).to_bytes()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Writeup {
+    pub challenge: Pubkey,
+    pub player: Pubkey,
+    pub writeup_hash: [u8; 32],
+    pub commit_index: u64,
+    pub committed_at: i64,
+    #[max_len(MAX_WRITEUP_URI_LEN)]
+    pub uri: String,
+    pub revealed_at: i64, // 0 until revealed
+    pub bump: u8,
+}
+
+#[event]
+pub struct AttemptEvent {
+    pub challenge_id: String,
+    pub player_hash: [u8; 32],
+    pub attempt: u32,
+    pub error_code: u32, // 0 when the submission succeeded
+    pub slot: u64,
+}
+
+#[event]
+pub struct SolveEvent {
+    pub challenge_id: String,
+    pub player_hash: [u8; 32],
+    pub version: u16,
+    pub deprecated: bool,
+    pub slot: u64,
+}
+
+// Codes 6000-6999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 6000)]
+pub enum ErrorCode {
+    #[msg("Governance members must be unique and between 1 and 10")]
+    InvalidMembers,
+    #[msg("Threshold must be between 1 and the number of members")]
+    InvalidThreshold,
+    #[msg("Not enough governance approvals")]
+    NotEnoughApprovals,
+    #[msg("Challenge id must be between 1 and 32 bytes")]
+    InvalidChallengeId,
+    #[msg("Challenge cannot move to the requested status")]
+    InvalidStatusTransition,
+    #[msg("Program id does not match any version of this challenge")]
+    InvalidProgramId,
+    #[msg("Challenge is paused")]
+    ChallengePaused,
+    #[msg("Event has already been finalized")]
+    EventFinalized,
+    #[msg("Event has not been finalized yet")]
+    EventNotFinalized,
+    #[msg("Submission deadline has not been reached")]
+    DeadlineNotReached,
+    #[msg("Submission deadline has passed")]
+    DeadlinePassed,
+    #[msg("Writeup URI must be between 1 and 200 bytes")]
+    InvalidWriteupUri,
+    #[msg("Writeup has already been revealed")]
+    WriteupAlreadyRevealed,
+    #[msg("Player is still cooling down from the previous attempt")]
+    CooldownActive,
+    #[msg("Player has used all attempts for this challenge")]
+    AttemptLimitReached,
+    #[msg("Flag authority is not the PDA of this challenge's program")]
+    InvalidFlagAuthority,
+    #[msg("SlotHashes sysvar has no entries to derive instance parameters from")]
+    MissingEntropy,
+    #[msg("Challenge is not solved by profit")]
+    NoProfitTarget,
+    #[msg("Amount drained is below the challenge's threshold")]
+    InsufficientProfit,
+    #[msg("Repository URI must be between 1 and 200 bytes")]
+    InvalidRepositoryUri,
+    #[msg("Commit must be a 40-character hex git hash")]
+    InvalidCommit,
+    #[msg("Build hash does not match the deployed program")]
+    BuildHashMismatch,
+    #[msg("Solves are only accepted against the challenge's active version")]
+    StaleVersion,
+    #[msg("Player already has a snapshot for the active version")]
+    SnapshotExists,
+    #[msg("Only the program's upgrade authority can do this")]
+    Unauthorized,
+    #[msg("The vault was drained and claimed since this snapshot")]
+    ProfitAlreadyClaimed,
+}
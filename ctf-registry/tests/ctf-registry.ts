@@ -0,0 +1,517 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import { CtfRegistry } from "../target/types/ctf_registry";
+import { expect } from "chai";
+import { createHash } from "crypto";
+import { readFileSync } from "fs";
+import { createMint, getOrCreateAssociatedTokenAccount, mintTo, transfer } from "@solana/spl-token";
+
+describe("ctf-registry", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.CtfRegistry as Program<CtfRegistry>;
+  const provider = anchor.getProvider();
+
+  // 2-of-3 governance multisig
+  const members = [web3.Keypair.generate(), web3.Keypair.generate(), web3.Keypair.generate()];
+  const checker = web3.Keypair.generate();
+  const player = web3.Keypair.generate();
+
+  const challengeId = "missing-signer-check";
+  const originalProgram = web3.Keypair.generate().publicKey;
+  const patchedProgram = web3.Keypair.generate().publicKey;
+
+  const [registry] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("registry")],
+    program.programId
+  );
+  const [challenge] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("challenge"), Buffer.from(challengeId)],
+    program.programId
+  );
+
+  const approvals = (signers: web3.Keypair[]) =>
+    signers.map((signer) => ({ pubkey: signer.publicKey, isSigner: true, isWritable: false }));
+
+  // Anchor.toml deploys through the upgradeable loader, with the provider
+  // wallet as upgrade authority
+  const [programData] = web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  const [solvePda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("solve"), challenge.toBuffer(), player.publicKey.toBuffer()],
+    program.programId
+  );
+
+  before(async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(checker.publicKey, 2 * web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+  });
+
+  it("Only lets the upgrade authority create the registry", async () => {
+    // The checker is just some funded key that does not hold the upgrade authority
+    try {
+      await program.methods
+        .initializeRegistry([checker.publicKey], 1)
+        .accountsPartial({ programData, payer: checker.publicKey })
+        .signers([checker])
+        .rpc();
+
+      expect.fail("Expected a stranger to be refused the registry");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Only the program's upgrade authority can do this");
+      console.log("✅ A stranger cannot take over registry governance");
+    }
+
+    await program.methods
+      .initializeRegistry(members.map((member) => member.publicKey), 2)
+      .accountsPartial({ programData, payer: provider.publicKey })
+      .rpc();
+  });
+
+  it("Rejects registration without enough approvals", async () => {
+    try {
+      await program.methods
+        .registerChallenge(challengeId, originalProgram, checker.publicKey)
+        .accounts({ payer: provider.publicKey })
+        .remainingAccounts(approvals([members[0]]))
+        .signers([members[0]])
+        .rpc();
+
+      expect.fail("Expected governance check to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Not enough governance approvals");
+      console.log("✅ Single member cannot register a challenge");
+    }
+  });
+
+  it("Registers a challenge with 2-of-3 approvals", async () => {
+    await program.methods
+      .registerChallenge(challengeId, originalProgram, checker.publicKey)
+      .accounts({ payer: provider.publicKey })
+      .remainingAccounts(approvals([members[0], members[2]]))
+      .signers([members[0], members[2]])
+      .rpc();
+
+    const account = await program.account.challenge.fetch(challenge);
+    expect(account.programId.toString()).to.equal(originalProgram.toString());
+    expect(account.version).to.equal(1);
+    expect(account.status).to.deep.equal({ active: {} });
+  });
+
+  it("Rejects solves while the challenge is paused", async () => {
+    await program.methods
+      .pauseChallenge()
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    try {
+      await program.methods
+        .recordSolve(originalProgram)
+        .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+        .signers([checker])
+        .rpc();
+
+      expect.fail("Expected paused challenge to reject solves");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Challenge is paused");
+    }
+
+    await program.methods
+      .resumeChallenge()
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[1], members[2]]))
+      .signers([members[1], members[2]])
+      .rpc();
+  });
+
+  it("Only accepts solves against the hot-swapped program", async () => {
+    await program.methods
+      .swapChallengeProgram(patchedProgram)
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    try {
+      await program.methods
+        .recordSolve(originalProgram)
+        .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+        .signers([checker])
+        .rpc();
+
+      expect.fail("Expected a solve against the replaced program to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("only accepted against the challenge's active version");
+    }
+
+    await program.methods
+      .recordSolve(patchedProgram)
+      .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+      .signers([checker])
+      .rpc();
+
+    const solve = await program.account.solve.fetch(solvePda);
+    expect(solve.version).to.equal(2);
+    expect(solve.deprecated).to.be.false;
+
+    const account = await program.account.challenge.fetch(challenge);
+    expect(account.version).to.equal(2);
+    expect(account.solveCount.toNumber()).to.equal(1);
+    expect(account.deprecatedSolveCount.toNumber()).to.equal(0);
+    console.log("✅ Solve against the replaced program rejected");
+  });
+
+  it("Records one solve per player across versions", async () => {
+    try {
+      await program.methods
+        .recordSolve(patchedProgram)
+        .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+        .signers([checker])
+        .rpc();
+
+      expect.fail("Expected a second solve by the same player to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("already in use");
+    }
+
+    const account = await program.account.challenge.fetch(challenge);
+    expect(account.solveCount.toNumber()).to.equal(1);
+  });
+
+  it("Rejects capture_flag from anything but the challenge program's PDA", async () => {
+    const impostor = web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .captureFlag()
+        .accounts({
+          challenge,
+          player: player.publicKey,
+          flagAuthority: impostor.publicKey,
+          payer: provider.publicKey,
+        })
+        .signers([impostor])
+        .rpc();
+
+      expect.fail("Expected capture_flag with a keypair signer to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Flag authority is not the PDA");
+      console.log("✅ Flags can only be captured via CPI from the challenge program");
+    }
+  });
+
+  it("Gives each player an instance with different randomized parameters", async () => {
+    const players = [web3.Keypair.generate(), web3.Keypair.generate()];
+    const instances = [];
+
+    for (const instancePlayer of players) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(instancePlayer.publicKey, web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+      const [instance] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("instance"), challenge.toBuffer(), instancePlayer.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createInstance()
+        .accounts({
+          challenge,
+          player: instancePlayer.publicKey,
+          slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([instancePlayer])
+        .rpc();
+
+      instances.push(await program.account.instance.fetch(instance));
+    }
+
+    expect(instances[0].admin.toString()).to.not.equal(instances[1].admin.toString());
+    expect(instances[0].seed).to.not.deep.equal(instances[1].seed);
+    for (const instance of instances) {
+      expect(instance.bufferOffset).to.be.lessThan(32);
+    }
+    console.log("✅ Solutions cannot be copied verbatim between instances");
+  });
+
+  it("Enforces attempt cooldowns and limits", async () => {
+    const [attempts] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("attempts"), challenge.toBuffer(), player.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .setAttemptPolicy(new anchor.BN(1000), 2)
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    await program.methods
+      .recordAttempt(7000)
+      .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+      .signers([checker])
+      .rpc();
+
+    try {
+      await program.methods
+        .recordAttempt(7000)
+        .accounts({ challenge, player: player.publicKey, checker: checker.publicKey })
+        .signers([checker])
+        .rpc();
+
+      expect.fail("Expected second attempt inside the cooldown to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("still cooling down");
+    }
+
+    const account = await program.account.attempts.fetch(attempts);
+    expect(account.count).to.equal(1);
+    console.log("✅ Brute-force attempt blocked by cooldown");
+
+    await program.methods
+      .setAttemptPolicy(new anchor.BN(0), 0)
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+  });
+
+  it("Commits a writeup before the deadline and reveals it after finalization", async () => {
+    const [writeup] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("writeup"), challenge.toBuffer(), player.publicKey.toBuffer()],
+      program.programId
+    );
+    const uri = "https://example.com/writeups/missing-signer-check";
+    const writeupHash = Array.from(createHash("sha256").update("writeup body").digest());
+
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(player.publicKey, web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    const deadline = Math.floor(Date.now() / 1000) + 5;
+    await program.methods
+      .setSubmissionDeadline(new anchor.BN(deadline))
+      .accounts({})
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    await program.methods
+      .commitWriteup(writeupHash)
+      .accounts({ challenge, solve: solvePda, player: player.publicKey })
+      .signers([player])
+      .rpc();
+
+    try {
+      await program.methods
+        .revealWriteup(uri)
+        .accounts({ writeup, player: player.publicKey })
+        .signers([player])
+        .rpc();
+
+      expect.fail("Expected reveal before finalization to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Event has not been finalized yet");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+    await program.methods
+      .finalizeEvent()
+      .accounts({})
+      .remainingAccounts(approvals([members[1], members[2]]))
+      .signers([members[1], members[2]])
+      .rpc();
+
+    await program.methods
+      .revealWriteup(uri)
+      .accounts({ writeup, player: player.publicKey })
+      .signers([player])
+      .rpc();
+
+    const account = await program.account.writeup.fetch(writeup);
+    expect(account.commitIndex.toNumber()).to.equal(0);
+    expect(account.writeupHash).to.deep.equal(writeupHash);
+    expect(account.uri).to.equal(uri);
+    console.log("✅ Writeup provenance recorded on-chain");
+  });
+
+  it("Accepts any exploit that drains the profit vault past the threshold", async () => {
+    const economicId = "solana-program-close";
+    const [economic] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), Buffer.from(economicId)],
+      program.programId
+    );
+    const snapshotAddress = (player: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("snapshot"), economic.toBuffer(), player.toBuffer()],
+        program.programId
+      )[0];
+    const payer = (provider as anchor.AnchorProvider).wallet.payer;
+    const rival = web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(rival.publicKey, web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    await program.methods
+      .registerChallenge(economicId, originalProgram, checker.publicKey)
+      .accounts({ payer: provider.publicKey })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    // The victim keypair stands in for the challenge program, the only one
+    // that can pay out of the vault; its transfers stand in for the exploit
+    const victim = web3.Keypair.generate();
+    const mint = await createMint(provider.connection, payer, provider.publicKey, null, 6);
+    const vault = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, victim.publicKey))
+      .address;
+    const tokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, player.publicKey)
+    ).address;
+    await mintTo(provider.connection, payer, mint, vault, payer, 2_000_000);
+    const drain = (amount: number) => transfer(provider.connection, payer, vault, tokenAccount, victim, amount);
+
+    await program.methods
+      .setProfitTarget(vault, new anchor.BN(1_000_000))
+      .accounts({ challenge: economic })
+      .remainingAccounts(approvals([members[0], members[2]]))
+      .signers([members[0], members[2]])
+      .rpc();
+
+    for (const snapshotter of [player, rival]) {
+      await program.methods
+        .snapshotBalance()
+        .accounts({ challenge: economic, vault, player: snapshotter.publicKey })
+        .signers([snapshotter])
+        .rpc();
+    }
+    const claim = (claimer: web3.Keypair) =>
+      program.methods
+        .claimProfit()
+        .accounts({ challenge: economic, vault, player: claimer.publicKey })
+        .signers([claimer])
+        .rpc();
+
+    // Tokens that did not leave the vault are not profit
+    await mintTo(provider.connection, payer, mint, tokenAccount, payer, 5_000_000);
+    await drain(999_999);
+    try {
+      await claim(player);
+      expect.fail("Expected a claim below the threshold to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("below the challenge's threshold");
+    }
+
+    await drain(1);
+    await claim(player);
+
+    const [solve] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("solve"), economic.toBuffer(), player.publicKey.toBuffer()],
+      program.programId
+    );
+    const account = await program.account.solve.fetch(solve);
+    expect(account.profit.toNumber()).to.equal(1_000_000);
+    expect(account.deprecated).to.be.false;
+    expect(await provider.connection.getAccountInfo(snapshotAddress(player.publicKey))).to.be.null;
+
+    // The rival snapshotted before the same drain, which is already claimed
+    try {
+      await claim(rival);
+      expect.fail("Expected a second claim of the same drain to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("drained and claimed since this snapshot");
+    }
+    console.log("✅ Solve recorded from the vault's loss alone, once per drain");
+  });
+
+  it("Records a build hash only if it matches the deployed program", async () => {
+    // The registry itself stands in for a challenge program; Anchor.toml
+    // deploys it through the upgradeable loader so it has ProgramData
+    const buildId = "verified-build";
+    const [buildChallenge] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), Buffer.from(buildId)],
+      program.programId
+    );
+    const [programData] = web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const versionBytes = Buffer.alloc(2);
+    versionBytes.writeUInt16LE(1);
+    const [build] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("build"), buildChallenge.toBuffer(), versionBytes],
+      program.programId
+    );
+
+    await program.methods
+      .registerChallenge(buildId, program.programId, checker.publicKey)
+      .accounts({ payer: provider.publicKey })
+      .remainingAccounts(approvals([members[0], members[1]]))
+      .signers([members[0], members[1]])
+      .rpc();
+
+    // solana-verify hashes the executable without its trailing zero padding
+    const executable = readFileSync("target/deploy/ctf_registry.so");
+    let end = executable.length;
+    while (end > 0 && executable[end - 1] === 0) end--;
+    const executableHash = createHash("sha256").update(executable.subarray(0, end)).digest();
+
+    const recordBuild = (hash: Buffer) =>
+      program.methods
+        .recordBuild([...hash], "https://github.com/BlockChomper/solana-ctf", "a".repeat(40))
+        .accountsPartial({ challenge: buildChallenge, programData, build, payer: provider.publicKey })
+        .remainingAccounts(approvals([members[0], members[2]]))
+        .preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+        .signers([members[0], members[2]])
+        .rpc();
+
+    try {
+      await recordBuild(Buffer.alloc(32, 1));
+      expect.fail("Expected a hash of some other executable to be rejected");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("does not match the deployed program");
+    }
+
+    await recordBuild(executableHash);
+    const account = await program.account.verifiedBuild.fetch(build);
+    expect(Buffer.from(account.executableHash)).to.deep.equal(executableHash);
+    expect(account.programId.toString()).to.equal(program.programId.toString());
+    expect(account.version).to.equal(1);
+    expect(account.commit).to.equal("a".repeat(40));
+    console.log("✅ Build hash recorded against the deployed bytecode");
+  });
+
+  it("Deprecates a challenge", async () => {
+    await program.methods
+      .deprecateChallenge()
+      .accounts({ challenge })
+      .remainingAccounts(approvals([members[0], members[2]]))
+      .signers([members[0], members[2]])
+      .rpc();
+
+    const account = await program.account.challenge.fetch(challenge);
+    expect(account.status).to.deep.equal({ deprecated: {} });
+  });
+});
@@ -0,0 +1,277 @@
+use anchor_lang::prelude::*;
+
+declare_id!("5oJjpi5Udn1x3ZVyFJYdKkoXCQVUJ8UrQeQBN5ou52gJ");
+
+pub const MAX_CLUSTER_LEN: usize = 16;
+pub const MAX_CHALLENGE_ID_LEN: usize = 32;
+pub const MAX_PARAMS: usize = 8;
+pub const MAX_PARAM_NAME_LEN: usize = 32;
+
+/// Seed of the singleton `Manager` PDA
+pub const MANAGER_SEED: &[u8] = b"manager";
+/// Seed of a `Deployment` PDA, followed by the cluster and the challenge id
+pub const DEPLOYMENT_SEED: &[u8] = b"deployment";
+
+#[program]
+pub mod deployments {
+    use super::*;
+
+    /// Create the manager with the caller as the authority that publishes
+    /// deployments. Only the program's upgrade authority may call it, so the
+    /// manager cannot be claimed by whoever gets there first after deploying.
+    pub fn initialize_manager(ctx: Context<InitializeManager>) -> Result<()> {
+        let manager = &mut ctx.accounts.manager;
+        manager.authority = ctx.accounts.authority.key();
+        manager.deployment_count = 0;
+        manager.bump = ctx.bumps.manager;
+
+        msg!("Deployment manager initialized for {}", manager.authority);
+        Ok(())
+    }
+
+    /// Hand the manager over, e.g. from the deploying key to the organizers'
+    /// multisig
+    pub fn set_authority(ctx: Context<UpdateManager>, new_authority: Pubkey) -> Result<()> {
+        let manager = &mut ctx.accounts.manager;
+        manager.authority = new_authority;
+
+        msg!("Deployment manager authority set to {}", new_authority);
+        Ok(())
+    }
+
+    /// Publish the active deployment of a challenge on `cluster`, creating
+    /// its record on first use. Publishing a new program id bumps the version
+    /// and keeps the old id in `previous_program_id`; publishing the same id
+    /// only replaces the parameters. A retired deployment becomes active again.
+    pub fn publish_deployment(
+        ctx: Context<PublishDeployment>,
+        cluster: String,
+        challenge_id: String,
+        program_id: Pubkey,
+        params: Vec<Param>,
+    ) -> Result<()> {
+        require!(
+            !cluster.is_empty() && cluster.len() <= MAX_CLUSTER_LEN,
+            ErrorCode::InvalidCluster
+        );
+        require!(
+            !challenge_id.is_empty() && challenge_id.len() <= MAX_CHALLENGE_ID_LEN,
+            ErrorCode::InvalidChallengeId
+        );
+        require!(params.len() <= MAX_PARAMS, ErrorCode::TooManyParams);
+        for (i, param) in params.iter().enumerate() {
+            require!(
+                !param.name.is_empty() && param.name.len() <= MAX_PARAM_NAME_LEN,
+                ErrorCode::InvalidParamName
+            );
+            require!(
+                !params[..i].iter().any(|other| other.name == param.name),
+                ErrorCode::DuplicateParam
+            );
+        }
+
+        let deployment = &mut ctx.accounts.deployment;
+        if deployment.version == 0 {
+            deployment.cluster = cluster;
+            deployment.challenge_id = challenge_id;
+            deployment.program_id = program_id;
+            deployment.previous_program_id = Pubkey::default();
+            deployment.version = 1;
+            deployment.bump = ctx.bumps.deployment;
+
+            let manager = &mut ctx.accounts.manager;
+            manager.deployment_count = manager.deployment_count.checked_add(1).unwrap();
+        } else if deployment.program_id != program_id {
+            deployment.previous_program_id = deployment.program_id;
+            deployment.program_id = program_id;
+            deployment.version = deployment.version.checked_add(1).unwrap();
+        }
+        deployment.params = params;
+        deployment.active = true;
+        deployment.published_slot = Clock::get()?.slot;
+
+        msg!(
+            "{} on {} served by {} (version {})",
+            deployment.challenge_id,
+            deployment.cluster,
+            deployment.program_id,
+            deployment.version
+        );
+        Ok(())
+    }
+
+    /// Take a deployment down. The record stays so clients can tell a
+    /// retired challenge from one that was never deployed.
+    pub fn retire_deployment(ctx: Context<RetireDeployment>) -> Result<()> {
+        let deployment = &mut ctx.accounts.deployment;
+        require!(deployment.active, ErrorCode::DeploymentRetired);
+        deployment.active = false;
+
+        msg!("{} on {} retired", deployment.challenge_id, deployment.cluster);
+        Ok(())
+    }
+}
+
+/// Address of the deployment record of `challenge_id` on `cluster`
+pub fn deployment_address(cluster: &str, challenge_id: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[DEPLOYMENT_SEED, cluster.as_bytes(), challenge_id.as_bytes()],
+        &ID,
+    )
+    .0
+}
+
+#[derive(Accounts)]
+pub struct InitializeManager<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Manager::INIT_SPACE,
+        seeds = [MANAGER_SEED],
+        bump
+    )]
+    pub manager: Account<'info, Manager>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Deployments>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateManager<'info> {
+    #[account(
+        mut,
+        seeds = [MANAGER_SEED],
+        bump = manager.bump,
+        has_one = authority,
+    )]
+    pub manager: Account<'info, Manager>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cluster: String, challenge_id: String)]
+pub struct PublishDeployment<'info> {
+    #[account(
+        mut,
+        seeds = [MANAGER_SEED],
+        bump = manager.bump,
+        has_one = authority,
+    )]
+    pub manager: Account<'info, Manager>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Deployment::INIT_SPACE,
+        seeds = [DEPLOYMENT_SEED, cluster.as_bytes(), challenge_id.as_bytes()],
+        bump
+    )]
+    pub deployment: Account<'info, Deployment>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RetireDeployment<'info> {
+    #[account(
+        seeds = [MANAGER_SEED],
+        bump = manager.bump,
+        has_one = authority,
+    )]
+    pub manager: Account<'info, Manager>,
+
+    #[account(
+        mut,
+        seeds = [
+            DEPLOYMENT_SEED,
+            deployment.cluster.as_bytes(),
+            deployment.challenge_id.as_bytes(),
+        ],
+        bump = deployment.bump,
+    )]
+    pub deployment: Account<'info, Deployment>,
+
+    pub authority: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Manager {
+    pub authority: Pubkey,
+    pub deployment_count: u64,
+    pub bump: u8,
+}
+
+/// Where a challenge is served on one cluster, and the parameters clients
+/// need to play it there
+#[account]
+#[derive(InitSpace)]
+pub struct Deployment {
+    #[max_len(MAX_CLUSTER_LEN)]
+    pub cluster: String,
+    #[max_len(MAX_CHALLENGE_ID_LEN)]
+    pub challenge_id: String,
+    pub program_id: Pubkey,
+    pub previous_program_id: Pubkey, // Default pubkey until the program changes
+    pub version: u16,
+    pub active: bool,
+    #[max_len(MAX_PARAMS)]
+    pub params: Vec<Param>,
+    pub published_slot: u64,
+    pub bump: u8,
+}
+
+impl Deployment {
+    pub fn param(&self, name: &str) -> Option<ParamValue> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.value)
+    }
+}
+
+/// A named instance parameter, such as the mint or victim vault of a
+/// challenge instance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Param {
+    #[max_len(MAX_PARAM_NAME_LEN)]
+    pub name: String,
+    pub value: ParamValue,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ParamValue {
+    Pubkey(Pubkey),
+    U64(u64),
+}
+
+// Codes 15000-15999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 15000)]
+pub enum ErrorCode {
+    #[msg("Cluster name must be between 1 and 16 bytes")]
+    InvalidCluster,
+    #[msg("Challenge id must be between 1 and 32 bytes")]
+    InvalidChallengeId,
+    #[msg("A deployment has at most 8 parameters")]
+    TooManyParams,
+    #[msg("Parameter name must be between 1 and 32 bytes")]
+    InvalidParamName,
+    #[msg("Parameter names must be unique")]
+    DuplicateParam,
+    #[msg("Deployment is already retired")]
+    DeploymentRetired,
+    #[msg("Only the program's upgrade authority can do this")]
+    Unauthorized,
+}
@@ -0,0 +1,141 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import { Deployments } from "../target/types/deployments";
+import { expect } from "chai";
+
+describe("deployments", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.Deployments as Program<Deployments>;
+  const provider = anchor.getProvider();
+
+  const cluster = "devnet";
+  const challengeId = "missing-signer-check";
+  const originalProgram = web3.Keypair.generate().publicKey;
+  const patchedProgram = web3.Keypair.generate().publicKey;
+  const mint = web3.Keypair.generate().publicKey;
+
+  const [manager] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("manager")],
+    program.programId
+  );
+  const [deployment] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("deployment"), Buffer.from(cluster), Buffer.from(challengeId)],
+    program.programId
+  );
+  // Anchor.toml deploys through the upgradeable loader, with the provider
+  // wallet as upgrade authority
+  const [programData] = web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  const publish = (programId: web3.PublicKey, params: any[]) =>
+    program.methods
+      .publishDeployment(cluster, challengeId, programId, params)
+      .accounts({ authority: provider.publicKey })
+      .rpc();
+
+  const fundedKeypair = async () => {
+    const keypair = web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(keypair.publicKey, web3.LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+    return keypair;
+  };
+
+  it("Only lets the upgrade authority create the manager", async () => {
+    const stranger = await fundedKeypair();
+
+    try {
+      await program.methods
+        .initializeManager()
+        .accountsPartial({ programData, authority: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+
+      expect.fail("Expected a stranger to be refused the manager");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Only the program's upgrade authority can do this");
+    }
+
+    await program.methods
+      .initializeManager()
+      .accountsPartial({ programData, authority: provider.publicKey })
+      .rpc();
+    const account = await program.account.manager.fetch(manager);
+    expect(account.authority.toString()).to.equal(provider.publicKey.toString());
+  });
+
+  it("Publishes a deployment with its instance parameters", async () => {
+    await publish(originalProgram, [
+      { name: "mint", value: { pubkey: { 0: mint } } },
+      { name: "vault_id", value: { u64: { 0: new anchor.BN(7) } } },
+    ]);
+
+    const account = await program.account.deployment.fetch(deployment);
+    expect(account.programId.toString()).to.equal(originalProgram.toString());
+    expect(account.version).to.equal(1);
+    expect(account.active).to.be.true;
+    expect(account.params.map((param) => param.name)).to.deep.equal(["mint", "vault_id"]);
+    expect((await program.account.manager.fetch(manager)).deploymentCount.toNumber()).to.equal(1);
+    console.log("✅ Deployment discoverable at", deployment.toString());
+  });
+
+  it("Bumps the version only when the program changes", async () => {
+    await publish(originalProgram, []);
+    let account = await program.account.deployment.fetch(deployment);
+    expect(account.version).to.equal(1);
+    expect(account.params).to.be.empty;
+
+    await publish(patchedProgram, []);
+    account = await program.account.deployment.fetch(deployment);
+    expect(account.version).to.equal(2);
+    expect(account.programId.toString()).to.equal(patchedProgram.toString());
+    expect(account.previousProgramId.toString()).to.equal(originalProgram.toString());
+  });
+
+  it("Rejects duplicate parameter names", async () => {
+    try {
+      await publish(patchedProgram, [
+        { name: "mint", value: { pubkey: { 0: mint } } },
+        { name: "mint", value: { pubkey: { 0: mint } } },
+      ]);
+
+      expect.fail("Expected duplicate parameters to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Parameter names must be unique");
+    }
+  });
+
+  it("Only lets the authority publish", async () => {
+    const stranger = await fundedKeypair();
+
+    try {
+      await program.methods
+        .publishDeployment(cluster, challengeId, stranger.publicKey, [])
+        .accounts({ authority: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+
+      expect.fail("Expected a stranger's deployment to be rejected");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("has one constraint was violated");
+    }
+  });
+
+  it("Retires a deployment", async () => {
+    await program.methods
+      .retireDeployment()
+      .accounts({ deployment, authority: provider.publicKey })
+      .rpc();
+
+    const account = await program.account.deployment.fetch(deployment);
+    expect(account.active).to.be.false;
+    expect(account.version).to.equal(2);
+  });
+});
@@ -1,8 +1,42 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::program::{set_return_data, MAX_RETURN_DATA};
+use anchor_lang::system_program;
+use ctf_registry::program::CtfRegistry;
+use ctf_registry::Instance;
+use std::mem::MaybeUninit;
 use std::ptr;
 
+pub mod secure;
+
 declare_id!("HdQsMWTESthxYtyZJVuwXAG4KuJH2LakDersvBYRooc8");
 
+/// Start of `BufferAccount::data` in the raw account (discriminator + size)
+const BUFFER_DATA_OFFSET: usize = 8 + 8;
+/// First byte after the fixed 64-byte buffer, where grown space begins
+const BUFFER_EXTENSION_OFFSET: usize = 8 + std::mem::size_of::<BufferAccount>();
+/// Deepest nesting accepted by secure_recursive_parse
+pub const MAX_NESTING_DEPTH: usize = 16;
+/// Slots in RecordBook::records
+pub const RECORD_CAPACITY: usize = 4;
+/// Stale limit from an older RecordBook layout, mistakenly used by
+/// append_record
+pub const MAX_RECORDS: usize = 6;
+/// Seeds of the per-player demo accounts: `[seed, player]`
+pub const BUFFER_SEED: &[u8] = b"buffer";
+pub const TARGET_SEED: &[u8] = b"target";
+pub const COMPLEX_SEED: &[u8] = b"complex";
+pub const PREFIXED_SEED: &[u8] = b"prefixed";
+pub const RECORD_BOOK_SEED: &[u8] = b"record_book";
+pub const PACKED_SEED: &[u8] = b"packed";
+/// Usable bytes in a PrefixedBuffer
+pub const PREFIXED_CAPACITY: usize = 32;
+/// Largest scratch allocation accepted by secure_allocate_scratch, well under
+/// the default 32KB heap
+pub const MAX_SCRATCH_LEN: u64 = 4096;
+
 #[program]
 pub mod memory_safety_vulns {
     use super::*;
@@ -10,6 +44,8 @@ pub mod memory_safety_vulns {
     /// Demonstrates buffer overflow vulnerability
     /// Rust normally prevents this, but we can create scenarios that panic or misbehave
     pub fn buffer_overflow_demo(ctx: Context<BufferOverflowDemo>, data: Vec<u8>) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let before = data_checksum(&info)?;
         let mut buffer = ctx.accounts.buffer_account.load_mut()?;
         
         // This demonstrates a logical buffer overflow - trying to write more data
@@ -31,6 +67,8 @@ pub mod memory_safety_vulns {
         
         buffer.size = data.len() as u64;
         msg!("Wrote {} bytes to buffer", data.len());
+        drop(buffer);
+        emit_checksum(&info, "buffer_overflow_demo", before)?;
         Ok(())
     }
 
@@ -72,6 +110,8 @@ pub mod memory_safety_vulns {
     /// Demonstrates double-free conceptual vulnerability
     /// While Rust prevents literal double-free, we can show double-close scenarios
     pub fn double_free_demo(ctx: Context<DoubleFreeDemo>) -> Result<()> {
+        let info = ctx.accounts.target_account.to_account_info();
+        let before = data_checksum(&info)?;
         let mut account = ctx.accounts.target_account.load_mut()?;
         
         // First "free" - close the account
@@ -84,7 +124,9 @@ pub mod memory_safety_vulns {
             msg!("ERROR: Attempting to close already closed account!");
             return Err(ErrorCode::DoubleFree.into());
         }
-        
+
+        drop(account);
+        emit_checksum(&info, "double_free_demo", before)?;
         Ok(())
     }
 
@@ -114,198 +156,1764 @@ pub mod memory_safety_vulns {
         Ok(())
     }
 
-    /// Initialize account for demonstrations
-    pub fn initialize_buffer(ctx: Context<InitializeBuffer>) -> Result<()> {
-        let mut account = ctx.accounts.buffer_account.load_init()?;
-        account.size = 0;
-        account.data = [0; 64]; // Initialize with zeros
-        msg!("Buffer account initialized");
+    /// Demonstrates integer truncation in size handling
+    /// VULNERABLE: fills the buffer with `len` bytes of a repeated pattern,
+    /// but bounds-checks `len as u16`. A length like 65600 truncates to 64 and
+    /// passes, then the fill writes all 65600 bytes past the end of the buffer.
+    pub fn fill_buffer_demo(ctx: Context<BufferOverflowDemo>, pattern: Vec<u8>, len: u64) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        let checked_len = len as u16;
+        if checked_len as usize > buffer.data.len() {
+            return Err(ErrorCode::BufferOverflow.into());
+        }
+
+        unsafe {
+            let dest = buffer.data.as_mut_ptr();
+            for (i, byte) in pattern.iter().cycle().take(len as usize).enumerate() {
+                *dest.add(i) = *byte;
+            }
+        }
+
+        buffer.size = checked_len as u64;
+        msg!("Filled {} bytes", checked_len);
+        drop(buffer);
+        emit_checksum(&info, "fill_buffer_demo", before)?;
         Ok(())
     }
 
-    pub fn initialize_target(ctx: Context<InitializeTarget>) -> Result<()> {
-        let mut account = ctx.accounts.target_account.load_init()?;
-        account.is_active = 1; // Use 1 for true
-        account.is_initialized = 1; // Use 1 for true  
-        account.data = 42;
-        account.sensitive_data = 12345;
-        msg!("Target account initialized");
+    /// Secure version of fill_buffer_demo
+    /// Checks the full-width length before anything is narrowed or written
+    pub fn secure_fill_buffer(ctx: Context<BufferOverflowDemo>, pattern: Vec<u8>, len: u64) -> Result<()> {
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        let len = usize::try_from(len).map_err(|_| ErrorCode::BufferOverflow)?;
+        require!(len <= buffer.data.len(), ErrorCode::BufferOverflow);
+
+        for (slot, byte) in buffer.data[..len].iter_mut().zip(pattern.iter().cycle()) {
+            *slot = *byte;
+        }
+
+        buffer.size = len as u64;
+        msg!("Filled {} bytes", len);
         Ok(())
     }
 
-    pub fn initialize_complex(ctx: Context<InitializeComplex>) -> Result<()> {
-        let mut account = ctx.accounts.target_account.load_init()?;
-        account.is_active = 1; // Use 1 for true
-        account.is_initialized = 1; // Use 1 for true  
-        account.data = 42;
-        account.sensitive_data = 12345;
-        account.buffer = [0; 32]; // Initialize buffer with zeros
-        msg!("Complex account initialized");
+    /// Secure version of buffer_overflow_demo
+    /// Bounds-checked safe copy that also clears stale bytes past the new data
+    pub fn secure_buffer_write(ctx: Context<BufferOverflowDemo>, data: Vec<u8>) -> Result<()> {
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        require!(data.len() <= buffer.data.len(), ErrorCode::BufferOverflow);
+        buffer.data[..data.len()].copy_from_slice(&data);
+        buffer.data[data.len()..].fill(0);
+        buffer.size = data.len() as u64;
+
+        msg!("Safely wrote {} bytes to buffer", data.len());
         Ok(())
     }
 
-    /// Demonstrates a complex vulnerability combining multiple issues
-    pub fn complex_vulnerability_demo(
+    /// Secure counterpart to use_after_free_demo and double_free_demo
+    /// Zeroes the data and really closes the account, so later uses fail
+    /// Anchor's owner and discriminator checks instead of reading stale state,
+    /// and a second close has nothing to close
+    pub fn secure_close(ctx: Context<SecureClose>) -> Result<()> {
+        let mut account = ctx.accounts.target_account.load_mut()?;
+        require!(account.is_active == 1, ErrorCode::DoubleFree);
+
+        account.is_active = 0;
+        account.data = 0;
+        account.sensitive_data = 0;
+
+        msg!("Account zeroed and closed");
+        Ok(())
+    }
+
+    /// Secure version of uninitialized_memory_demo
+    /// AccountLoader::load rejects accounts without this program's
+    /// discriminator, so never-initialized data cannot be read; the
+    /// is_initialized flag is checked before any field is used
+    pub fn secure_read(ctx: Context<UninitializedDemo>) -> Result<()> {
+        let account = ctx.accounts.target_account.load()?;
+        require!(account.is_initialized == 1, ErrorCode::UninitializedMemory);
+        require!(account.is_active == 1, ErrorCode::UseAfterFree);
+
+        msg!("Safe access to initialized data: {}", account.sensitive_data);
+        Ok(())
+    }
+
+    /// Secure version of null_pointer_demo
+    /// Models the missing value as an Option instead of a raw pointer
+    pub fn secure_deref(ctx: Context<NullPointerDemo>, use_null: bool) -> Result<()> {
+        let account = ctx.accounts.target_account.load()?;
+
+        let value: Option<&u64> = if use_null { None } else { Some(&account.data) };
+        let value = value.ok_or(ErrorCode::NullPointerDereference)?;
+
+        msg!("Safe operation with valid data: {}", value);
+        Ok(())
+    }
+
+    /// Capture the flag once is_admin and the padding after it hold the
+    /// player's admin sentinel. The only way to set them is overflowing the
+    /// buffer through complex_vulnerability_demo. The sentinel comes from the
+    /// player's registry instance, so a payload copied from another player
+    /// does not unlock the flag. Reports the solve to the CTF registry.
+    pub fn claim_admin_flag(ctx: Context<ClaimAdminFlag>) -> Result<()> {
+        require!(
+            ctx.accounts.target_account.load()?.admin_bytes() == admin_sentinel(&ctx.accounts.instance),
+            ErrorCode::NotAdmin
+        );
+
+        capture_flag(
+            &ctx.accounts.registry_program,
+            &ctx.accounts.challenge,
+            &ctx.accounts.solve,
+            &ctx.accounts.player,
+            &ctx.accounts.flag_authority,
+            &ctx.accounts.system_program,
+            ctx.bumps.flag_authority,
+        )?;
+
+        msg!("FLAG CAPTURED: is_admin overwritten");
+        Ok(())
+    }
+
+    /// Secure version of complex_vulnerability_demo
+    /// Checks state before acting and bounds before copying
+    pub fn secure_complex_operation(
         ctx: Context<ComplexDemo>,
         operation: u8,
-        data: Vec<u8>
+        data: Vec<u8>,
     ) -> Result<()> {
         let mut account = ctx.accounts.target_account.load_mut()?;
-        
+        require!(account.is_initialized == 1, ErrorCode::UninitializedMemory);
+
         match operation {
             1 => {
-                // Buffer overflow + uninitialized read
-                if account.is_initialized == 0 {
-                    // Reading uninitialized sensitive_data
-                    msg!("Using uninitialized data: {}", account.sensitive_data);
-                }
-                
-                // Potential buffer overflow
-                if data.len() > 32 {
-                    return Err(ErrorCode::BufferOverflow.into());
-                }
-                
-                unsafe {
-                    // Copy data without proper bounds checking in unsafe context
-                    let dest = account.buffer.as_mut_ptr();
-                    ptr::copy_nonoverlapping(data.as_ptr(), dest, data.len());
-                }
-            },
+                require!(account.is_active == 1, ErrorCode::UseAfterFree);
+                require!(data.len() <= account.buffer.len(), ErrorCode::BufferOverflow);
+                account.buffer[..data.len()].copy_from_slice(&data);
+            }
             2 => {
-                // Use after free scenario
-                account.is_active = 0; // "Free" the account
-                
-                // Then try to use it
-                if account.is_active == 0 {
-                    msg!("Accessing freed account data: {}", account.data);
-                    return Err(ErrorCode::UseAfterFree.into());
-                }
-            },
+                require!(account.is_active == 1, ErrorCode::UseAfterFree);
+                msg!("Reading live account data: {}", account.data);
+            }
             3 => {
-                // Double free scenario
-                if account.is_active == 0 {
-                    msg!("Attempting to free already freed account");
-                    return Err(ErrorCode::DoubleFree.into());
-                }
+                require!(account.is_active == 1, ErrorCode::DoubleFree);
                 account.is_active = 0;
-            },
-            _ => {
-                return Err(ErrorCode::InvalidOperation.into());
+                account.data = 0;
+                account.sensitive_data = 0;
             }
+            _ => return Err(ErrorCode::InvalidOperation.into()),
         }
-        
+
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct BufferOverflowDemo<'info> {
-    #[account(mut)]
-    pub buffer_account: AccountLoader<'info, BufferAccount>,
-    pub user: Signer<'info>,
-}
+    /// Demonstrates realloc-related overflows
+    /// VULNERABLE: the new length is attacker-supplied and the old contents are
+    /// copied into the grown region without checking that they fit
+    pub fn grow_buffer(ctx: Context<GrowBuffer>, new_len: u64) -> Result<()> {
+        let size = ctx.accounts.buffer_account.load()?.size as usize;
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let before = data_checksum(&info)?;
 
-#[derive(Accounts)]
-pub struct UseAfterFreeDemo<'info> {
-    #[account(mut)]
-    pub target_account: AccountLoader<'info, TargetAccount>,
-    pub user: Signer<'info>,
-}
+        // No check that new_len leaves room for the copy below, or that it
+        // doesn't shrink the account under its fixed layout. zero_init = false
+        // also resurrects stale bytes when growing after a shrink; resize()
+        // always zero-fills, so the deprecated realloc is kept on purpose.
+        #[allow(deprecated)]
+        info.realloc(new_len as usize, false)?;
+        fund_rent(&info, &ctx.accounts.user, &ctx.accounts.system_program)?;
 
-#[derive(Accounts)]
-pub struct UninitializedDemo<'info> {
-    pub target_account: AccountLoader<'info, TargetAccount>,
-    pub user: Signer<'info>,
-}
+        let mut data = info.try_borrow_mut_data()?;
+        unsafe {
+            // Mirror the old contents into the space after the fixed buffer.
+            // If new_len is too small this writes past the end of the account
+            // data into the runtime's realloc padding, where it is silently lost
+            let base = data.as_mut_ptr();
+            ptr::copy(
+                base.add(BUFFER_DATA_OFFSET),
+                base.add(BUFFER_EXTENSION_OFFSET),
+                size,
+            );
+        }
 
-#[derive(Accounts)]
-pub struct DoubleFreeDemo<'info> {
-    #[account(mut)]
-    pub target_account: AccountLoader<'info, TargetAccount>,
-    pub user: Signer<'info>,
-}
+        msg!("Buffer reallocated to {} bytes", new_len);
+        drop(data);
+        emit_checksum(&info, "grow_buffer", before)?;
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct NullPointerDemo<'info> {
-    pub target_account: AccountLoader<'info, TargetAccount>,
-    pub user: Signer<'info>,
-}
+    /// Secure version of grow_buffer
+    /// Only grows, stays within the runtime's per-instruction limit, and checks
+    /// the copy fits before touching the data
+    pub fn secure_grow_buffer(ctx: Context<GrowBuffer>, new_len: u64) -> Result<()> {
+        let size = ctx.accounts.buffer_account.load()?.size as usize;
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let current_len = info.data_len();
 
-#[derive(Accounts)]
-pub struct InitializeBuffer<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + std::mem::size_of::<BufferAccount>(),
-    )]
-    pub buffer_account: AccountLoader<'info, BufferAccount>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let new_len = usize::try_from(new_len).map_err(|_| ErrorCode::InvalidBufferLength)?;
+        require!(
+            new_len >= current_len && new_len >= BUFFER_EXTENSION_OFFSET + size,
+            ErrorCode::InvalidBufferLength
+        );
+        require!(
+            new_len <= current_len + MAX_PERMITTED_DATA_INCREASE,
+            ErrorCode::InvalidBufferLength
+        );
 
-#[derive(Accounts)]
-pub struct InitializeTarget<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + std::mem::size_of::<TargetAccount>(),
-    )]
-    pub target_account: AccountLoader<'info, TargetAccount>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        info.resize(new_len)?;
+        fund_rent(&info, &ctx.accounts.user, &ctx.accounts.system_program)?;
 
-#[derive(Accounts)]
-pub struct InitializeComplex<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + std::mem::size_of::<ComplexAccount>(),
-    )]
-    pub target_account: AccountLoader<'info, ComplexAccount>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let mut data = info.try_borrow_mut_data()?;
+        data.copy_within(
+            BUFFER_DATA_OFFSET..BUFFER_DATA_OFFSET + size,
+            BUFFER_EXTENSION_OFFSET,
+        );
 
-#[derive(Accounts)]
-pub struct ComplexDemo<'info> {
-    #[account(mut)]
-    pub target_account: AccountLoader<'info, ComplexAccount>,
-    pub user: Signer<'info>,
-}
+        msg!("Buffer safely grown to {} bytes", new_len);
+        Ok(())
+    }
 
-#[account(zero_copy)]
-#[repr(C)]
-pub struct BufferAccount {
-    pub size: u64,
-    pub data: [u8; 64],
-}
+    /// Demonstrates aliasing through duplicate accounts
+    /// VULNERABLE: nothing stops the caller passing the same account as source
+    /// and destination, so the second borrow of its data RefCell panics
+    pub fn copy_buffer(ctx: Context<CopyBuffer>) -> Result<()> {
+        let source_info = ctx.accounts.source.to_account_info();
+        let destination_info = ctx.accounts.destination.to_account_info();
+        let before = data_checksum(&destination_info)?;
 
-#[account(zero_copy)]
-#[repr(C)]
-pub struct TargetAccount {
-    pub is_active: u8,      // Use u8 instead of bool for Pod compatibility
-    pub is_initialized: u8, // Use u8 instead of bool for Pod compatibility
-    pub _padding1: [u8; 6], // Explicit padding for alignment
-    pub data: u64,
-    pub sensitive_data: u64,
-}
+        // Both AccountInfos share one RefCell when the keys are equal.
+        // borrow_mut() panics with "already borrowed" instead of returning an error
+        let source = source_info.data.borrow();
+        let mut destination = destination_info.data.borrow_mut();
+        destination[8..BUFFER_EXTENSION_OFFSET].copy_from_slice(&source[8..BUFFER_EXTENSION_OFFSET]);
 
-#[account(zero_copy)]
-#[repr(C)]
-pub struct ComplexAccount {
-    pub is_active: u8,      // Use u8 instead of bool for Pod compatibility
-    pub is_initialized: u8, // Use u8 instead of bool for Pod compatibility
-    pub _padding1: [u8; 6], // Explicit padding for alignment
-    pub data: u64,
-    pub sensitive_data: u64,
-    pub buffer: [u8; 32],
+        msg!("Copied buffer {} into {}", source_info.key, destination_info.key);
+        drop(destination);
+        emit_checksum(&destination_info, "copy_buffer", before)?;
+        Ok(())
+    }
+
+    /// Secure version of copy_buffer
+    /// Rejects aliased accounts up front and uses try_borrow so any remaining
+    /// borrow conflict surfaces as an error rather than a panic
+    pub fn secure_copy_buffer(ctx: Context<CopyBuffer>) -> Result<()> {
+        require_keys_neq!(
+            ctx.accounts.source.key(),
+            ctx.accounts.destination.key(),
+            ErrorCode::AliasedAccounts
+        );
+
+        let source_info = ctx.accounts.source.to_account_info();
+        let destination_info = ctx.accounts.destination.to_account_info();
+        let source = source_info.try_borrow_data()?;
+        let mut destination = destination_info.try_borrow_mut_data()?;
+        destination[8..BUFFER_EXTENSION_OFFSET].copy_from_slice(&source[8..BUFFER_EXTENSION_OFFSET]);
+
+        msg!("Safely copied buffer {} into {}", source_info.key, destination_info.key);
+        Ok(())
+    }
+
+    /// Demonstrates stack exhaustion
+    /// VULNERABLE: recursion depth is driven by attacker-controlled length
+    /// prefixes, and each frame copies its node onto the stack. Deep enough
+    /// nesting exceeds the SBF call depth and 4KB-per-frame stack limits.
+    pub fn recursive_parse(_ctx: Context<RecursiveParse>, data: Vec<u8>) -> Result<()> {
+        let nodes = parse_nested(&data)?;
+        msg!("Parsed {} nested nodes", nodes);
+        Ok(())
+    }
+
+    /// Secure version of recursive_parse
+    /// Walks the input iteratively with an explicit work list on the heap and
+    /// caps the nesting depth
+    pub fn secure_recursive_parse(_ctx: Context<RecursiveParse>, data: Vec<u8>) -> Result<()> {
+        let nodes = parse_nested_iterative(&data)?;
+        msg!("Safely parsed {} nested nodes", nodes);
+        Ok(())
+    }
+
+    /// Demonstrates heap exhaustion
+    /// VULNERABLE: the allocation size comes straight from instruction data.
+    /// The SBF bump allocator has a 32KB heap by default and never frees, so a
+    /// large length aborts the transaction with an out-of-memory error.
+    pub fn allocate_scratch(_ctx: Context<AllocateScratch>, len: u64) -> Result<()> {
+        let mut scratch: Vec<u8> = Vec::with_capacity(len as usize);
+        scratch.push(1);
+        msg!("Allocated {} bytes of scratch space", scratch.capacity());
+        Ok(())
+    }
+
+    /// Secure version of allocate_scratch
+    /// Validates the length before allocating
+    pub fn secure_allocate_scratch(_ctx: Context<AllocateScratch>, len: u64) -> Result<()> {
+        require!(len <= MAX_SCRATCH_LEN, ErrorCode::AllocationTooLarge);
+
+        let mut scratch: Vec<u8> = Vec::with_capacity(len as usize);
+        scratch.push(1);
+        msg!("Safely allocated {} bytes of scratch space", scratch.capacity());
+        Ok(())
+    }
+
+    /// Measure what the unsafe copy actually buys: copies `len` bytes once with
+    /// a safe iterator loop and once with ptr::copy_nonoverlapping, logs the
+    /// compute units each took, and returns both plus the difference. Both
+    /// figures include the cost of one remaining-CU syscall.
+    pub fn copy_benchmark(_ctx: Context<AllocateScratch>, len: u64) -> Result<()> {
+        require!(len <= MAX_SCRATCH_LEN, ErrorCode::AllocationTooLarge);
+        let source: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut destination = vec![0u8; len as usize];
+
+        let start = sol_remaining_compute_units();
+        for (dst, src) in destination.iter_mut().zip(source.iter()) {
+            *dst = *src;
+        }
+        let loop_units = start - sol_remaining_compute_units();
+        std::hint::black_box(&destination);
+
+        destination.fill(0);
+        let start = sol_remaining_compute_units();
+        unsafe {
+            ptr::copy_nonoverlapping(source.as_ptr(), destination.as_mut_ptr(), source.len());
+        }
+        let copy_units = start - sol_remaining_compute_units();
+        std::hint::black_box(&destination);
+
+        let result = CopyBenchmarkResult {
+            len,
+            loop_units,
+            copy_units,
+            delta: loop_units as i64 - copy_units as i64,
+        };
+        msg!(
+            "Copied {} bytes: loop {} CU, copy_nonoverlapping {} CU, delta {}",
+            len,
+            loop_units,
+            copy_units,
+            result.delta
+        );
+        set_return_data(&result.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Demonstrates misaligned and out-of-bounds pointer casts
+    /// VULNERABLE: reinterprets a caller-chosen offset of the buffer as a &u64.
+    /// Offsets that are not a multiple of 8 are misaligned (a panic in debug
+    /// builds, undefined behavior otherwise), and offsets past 56 read beyond
+    /// the buffer.
+    pub fn read_u64_demo(ctx: Context<ReadU64>, offset: u16) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+
+        let value = unsafe {
+            let ptr = buffer.data.as_ptr().add(offset as usize) as *const u64;
+            *ptr
+        };
+
+        msg!("Value at offset {}: {}", offset, value);
+        Ok(())
+    }
+
+    /// Secure version of read_u64_demo
+    /// Bounds-checks the slice and decodes it with from_le_bytes, which has
+    /// no alignment requirement
+    pub fn secure_read_u64(ctx: Context<ReadU64>, offset: u16) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+
+        let offset = offset as usize;
+        let bytes = buffer
+            .data
+            .get(offset..offset + 8)
+            .ok_or(ErrorCode::InvalidOffset)?;
+        let value = u64::from_le_bytes(bytes.try_into().unwrap());
+
+        msg!("Value at offset {}: {}", offset, value);
+        Ok(())
+    }
+
+    /// Demonstrates creating a reference from a misaligned pointer
+    /// VULNERABLE: the window is bounds-checked, but `&*ptr` at an odd offset
+    /// produces a misaligned &u64, which is undefined behavior even if every
+    /// read through it happens to return the right bytes. Debug builds panic
+    /// on the dereference; release builds log whether it matched.
+    pub fn unaligned_ref_demo(ctx: Context<ReadU64>, offset: u8) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+        require!(offset as usize + 8 <= buffer.data.len(), ErrorCode::InvalidOffset);
+
+        let ptr = unsafe { buffer.data.as_ptr().add(offset as usize) } as *const u64;
+        let misalignment = ptr as usize % std::mem::align_of::<u64>();
+        msg!("Pointer at offset {} is {} bytes off alignment", offset, misalignment);
+
+        let value: &u64 = unsafe { &*ptr };
+        // Same bytes read without assuming alignment, for comparison
+        let expected = unsafe { ptr.read_unaligned() };
+        msg!("Reference read {}, read_unaligned {}, match {}", *value, expected, *value == expected);
+        Ok(())
+    }
+
+    /// Secure version of unaligned_ref_demo
+    /// Checks alignment first and falls back to read_unaligned, so no
+    /// misaligned reference is ever created
+    pub fn secure_unaligned_read(ctx: Context<ReadU64>, offset: u8) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+        require!(offset as usize + 8 <= buffer.data.len(), ErrorCode::InvalidOffset);
+
+        let ptr = unsafe { buffer.data.as_ptr().add(offset as usize) } as *const u64;
+        let value = if ptr.is_aligned() {
+            msg!("Pointer is aligned, dereferencing");
+            unsafe { *ptr }
+        } else {
+            msg!("Pointer is misaligned, using read_unaligned");
+            unsafe { ptr.read_unaligned() }
+        };
+
+        msg!("Value at offset {}: {}", offset, value);
+        Ok(())
+    }
+
+    /// Demonstrates slice indexing panics on raw account data
+    /// VULNERABLE: indexes the account data with unchecked user offsets. An
+    /// out-of-range slice panics, so any caller can make the instruction fail.
+    pub fn read_slice_demo(ctx: Context<ReadSlice>, offset: u32, len: u32) -> Result<()> {
+        let data = ctx.accounts.account.try_borrow_data()?;
+
+        let start = offset as usize;
+        let slice = &data[start..start + len as usize];
+
+        let checksum: u64 = slice.iter().map(|byte| *byte as u64).sum();
+        msg!("Read {} bytes at offset {}, checksum {}", slice.len(), offset, checksum);
+        Ok(())
+    }
+
+    /// Secure version of read_slice_demo
+    /// Uses get() with a checked end so bad offsets return a clean error
+    pub fn secure_read_slice(ctx: Context<ReadSlice>, offset: u32, len: u32) -> Result<()> {
+        let data = ctx.accounts.account.try_borrow_data()?;
+
+        let start = offset as usize;
+        let end = start
+            .checked_add(len as usize)
+            .ok_or(ErrorCode::InvalidOffset)?;
+        let slice = data.get(start..end).ok_or(ErrorCode::InvalidOffset)?;
+
+        let checksum: u64 = slice.iter().map(|byte| *byte as u64).sum();
+        msg!("Read {} bytes at offset {}, checksum {}", slice.len(), offset, checksum);
+        Ok(())
+    }
+
+    /// Demonstrates reads that walk out of one account and into the next
+    /// VULNERABLE: adds a caller-chosen index to the buffer pointer with no
+    /// bounds check. Accounts are serialized back to back in the program's
+    /// input, so large indexes read whatever account comes after this one.
+    pub fn peek_demo(ctx: Context<Peek>, index: u32, len: u32) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+        require!(len as usize <= MAX_RETURN_DATA, ErrorCode::InvalidOffset);
+
+        let bytes = unsafe {
+            let ptr = buffer.data.as_ptr().add(index as usize);
+            std::slice::from_raw_parts(ptr, len as usize)
+        };
+
+        set_return_data(bytes);
+        msg!("Peeked {} bytes at index {}", len, index);
+        Ok(())
+    }
+
+    /// Secure version of peek_demo
+    /// The caller names the account to read (0 = buffer_account,
+    /// 1 = neighbour) and the window is bounds-checked against that account's
+    /// own data
+    pub fn secure_peek(ctx: Context<Peek>, account_index: u8, offset: u32, len: u32) -> Result<()> {
+        let account = match account_index {
+            0 => ctx.accounts.buffer_account.to_account_info(),
+            1 => ctx.accounts.neighbour.to_account_info(),
+            _ => return Err(ErrorCode::InvalidOperation.into()),
+        };
+        let data = account.try_borrow_data()?;
+
+        let start = offset as usize;
+        let end = start
+            .checked_add(len as usize)
+            .ok_or(ErrorCode::InvalidOffset)?;
+        require!(len as usize <= MAX_RETURN_DATA, ErrorCode::InvalidOffset);
+        let window = data.get(start..end).ok_or(ErrorCode::InvalidOffset)?;
+
+        set_return_data(window);
+        msg!("Peeked bytes {}..{} of {}", start, end, account.key());
+        Ok(())
+    }
+
+    /// Demonstrates torn writes
+    /// VULNERABLE: updates the account field by field and swallows errors as a
+    /// "best effort" write. A failure midway persists a half-applied update,
+    /// e.g. size says 40 but only 20 bytes were written.
+    pub fn torn_write_demo(
+        ctx: Context<TornWrite>,
+        data: Vec<u8>,
+        fail_midway: bool,
+    ) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        // The error never reaches the runtime, so nothing is rolled back
+        if let Err(err) = write_in_halves(&mut buffer, &data, fail_midway) {
+            msg!("Write failed, continuing: {}", err);
+        }
+        drop(buffer);
+        emit_checksum(&info, "torn_write_demo", before)?;
+        Ok(())
+    }
+
+    /// Secure version of torn_write_demo
+    /// Stages the update in a copy and commits it last, so a failure leaves
+    /// the account untouched even when the error is swallowed
+    pub fn secure_staged_write(
+        ctx: Context<TornWrite>,
+        data: Vec<u8>,
+        fail_midway: bool,
+    ) -> Result<()> {
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        let mut staged = *buffer;
+        match write_in_halves(&mut staged, &data, fail_midway) {
+            Ok(()) => {
+                *buffer = staged;
+                msg!("Committed {} bytes", data.len());
+            }
+            Err(err) => msg!("Write failed, buffer unchanged: {}", err),
+        }
+        Ok(())
+    }
+
+    /// Off-by-one challenge: write into a length-prefixed buffer
+    /// VULNERABLE: the bound is inclusive, so one byte past capacity gets
+    /// through and lands in the low byte of the adjacent size field
+    pub fn write_prefixed(ctx: Context<WritePrefixed>, data: Vec<u8>) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        if data.len() <= PREFIXED_CAPACITY + 1 {
+            buffer.size = data.len() as u64;
+            unsafe {
+                // The 33rd byte overwrites size, which was just set above
+                ptr::copy_nonoverlapping(data.as_ptr(), buffer.data.as_mut_ptr(), data.len());
+            }
+        } else {
+            return Err(ErrorCode::BufferOverflow.into());
+        }
+
+        msg!("Prefixed buffer size is now {}", buffer.size);
+        drop(buffer);
+        emit_checksum(&info, "write_prefixed", before)?;
+        Ok(())
+    }
+
+    /// Secure version of write_prefixed
+    pub fn secure_write_prefixed(ctx: Context<WritePrefixed>, data: Vec<u8>) -> Result<()> {
+        let mut buffer = ctx.accounts.buffer_account.load_mut()?;
+
+        require!(data.len() <= PREFIXED_CAPACITY, ErrorCode::BufferOverflow);
+        buffer.data[..data.len()].copy_from_slice(&data);
+        buffer.size = data.len() as u64;
+
+        msg!("Prefixed buffer size is now {}", buffer.size);
+        Ok(())
+    }
+
+    /// Capture the flag once the buffer's size has been forged to its target
+    /// value. Reports the solve to the CTF registry.
+    pub fn capture_size_flag(ctx: Context<CaptureSizeFlag>) -> Result<()> {
+        {
+            let buffer = ctx.accounts.buffer_account.load()?;
+            require!(
+                buffer.size == buffer.target_size,
+                ErrorCode::SizeNotForged
+            );
+        }
+
+        capture_flag(
+            &ctx.accounts.registry_program,
+            &ctx.accounts.challenge,
+            &ctx.accounts.solve,
+            &ctx.accounts.player,
+            &ctx.accounts.flag_authority,
+            &ctx.accounts.system_program,
+            ctx.bumps.flag_authority,
+        )?;
+
+        msg!("FLAG CAPTURED: size forged to {}", ctx.accounts.buffer_account.load()?.size);
+        Ok(())
+    }
+
+    pub fn initialize_prefixed_buffer(ctx: Context<InitializePrefixedBuffer>) -> Result<()> {
+        let key = ctx.accounts.buffer_account.key();
+        let mut account = ctx.accounts.buffer_account.load_init()?;
+        account.data = [0; PREFIXED_CAPACITY];
+        account.size = 0;
+        // Per-account target between 64 and 191: unreachable by honest writes,
+        // reachable with a single overflowing byte
+        account.target_size = 64 + (key.to_bytes()[0] % 128) as u64;
+        account.owner = ctx.accounts.user.key();
+        msg!("Prefixed buffer initialized, target size {}", account.target_size);
+        Ok(())
+    }
+
+    /// Migrate the player's buffer to the BufferAccountV2 layout
+    /// VULNERABLE: only swaps the discriminator. V2 moved size after data, so
+    /// the old size ends up in data[0..8] and the last 8 bytes of the old data
+    /// become the new size.
+    pub fn migrate_buffer(ctx: Context<MigrateBuffer>) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        require!(
+            data.starts_with(BufferAccount::DISCRIMINATOR),
+            ErrorCode::InvalidOperation
+        );
+
+        data[..8].copy_from_slice(BufferAccountV2::DISCRIMINATOR);
+
+        msg!("Buffer migrated to V2");
+        Ok(())
+    }
+
+    /// Secure version of migrate_buffer
+    /// Decodes the V1 layout and writes each field to its V2 position
+    pub fn secure_migrate_buffer(ctx: Context<MigrateBuffer>) -> Result<()> {
+        let info = ctx.accounts.buffer_account.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        require!(
+            data.starts_with(BufferAccount::DISCRIMINATOR),
+            ErrorCode::InvalidOperation
+        );
+
+        let layout = 8..8 + std::mem::size_of::<BufferAccount>();
+        let v1: BufferAccount = bytemuck::pod_read_unaligned(&data[layout.clone()]);
+        let v2 = BufferAccountV2 {
+            data: v1.data,
+            size: v1.size,
+        };
+        data[..8].copy_from_slice(BufferAccountV2::DISCRIMINATOR);
+        data[layout].copy_from_slice(bytemuck::bytes_of(&v2));
+
+        msg!("Buffer migrated to V2, size {}", v2.size);
+        Ok(())
+    }
+
+    /// Migration challenge: capture the flag with a V2 buffer whose size is
+    /// larger than its capacity, which no correct migration can produce.
+    /// Reports the solve to the CTF registry.
+    pub fn capture_migration_flag(ctx: Context<CaptureMigrationFlag>) -> Result<()> {
+        let size = {
+            let buffer = ctx.accounts.buffer_account.load()?;
+            require!(
+                buffer.size > buffer.data.len() as u64,
+                ErrorCode::MigrationNotSkewed
+            );
+            buffer.size
+        };
+
+        capture_flag(
+            &ctx.accounts.registry_program,
+            &ctx.accounts.challenge,
+            &ctx.accounts.solve,
+            &ctx.accounts.player,
+            &ctx.accounts.flag_authority,
+            &ctx.accounts.system_program,
+            ctx.bumps.flag_authority,
+        )?;
+
+        msg!("FLAG CAPTURED: migrated size is {}", size);
+        Ok(())
+    }
+
+    /// Demonstrates an overflow in a record array
+    /// VULNERABLE: the count check uses MAX_RECORDS instead of the array's real
+    /// capacity, so the 5th and 6th appends are written over sensitive_key
+    pub fn append_record(ctx: Context<AppendRecord>, id: u64, value: u64) -> Result<()> {
+        let info = ctx.accounts.record_book.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut book = ctx.accounts.record_book.load_mut()?;
+
+        let count = book.count as usize;
+        if count >= MAX_RECORDS {
+            return Err(ErrorCode::RecordBookFull.into());
+        }
+
+        unsafe {
+            // Raw write skips the array's own bounds check
+            ptr::write(book.records.as_mut_ptr().add(count), Record { id, value });
+        }
+        book.count = (count + 1) as u64;
+
+        msg!("Appended record {} of {}", book.count, MAX_RECORDS);
+        drop(book);
+        emit_checksum(&info, "append_record", before)?;
+        Ok(())
+    }
+
+    /// Secure version of append_record
+    /// Checks against the array's own length and writes through safe indexing
+    pub fn secure_append_record(ctx: Context<AppendRecord>, id: u64, value: u64) -> Result<()> {
+        let mut book = ctx.accounts.record_book.load_mut()?;
+
+        let count = book.count as usize;
+        require!(count < book.records.len(), ErrorCode::RecordBookFull);
+        book.records[count] = Record { id, value };
+        book.count = (count + 1) as u64;
+
+        msg!("Appended record {} of {}", book.count, book.records.len());
+        Ok(())
+    }
+
+    pub fn initialize_record_book(ctx: Context<InitializeRecordBook>) -> Result<()> {
+        let key = ctx.accounts.record_book.key();
+        let mut book = ctx.accounts.record_book.load_init()?;
+        book.count = 0;
+        book.records = [Record { id: 0, value: 0 }; RECORD_CAPACITY];
+        book.sensitive_key = hashv(&[b"sensitive_key", key.as_ref()]).to_bytes();
+        msg!("Record book initialized");
+        Ok(())
+    }
+
+    /// Replace sensitive_data, keeping the low 6 bytes of the old value in
+    /// _padding1 as an undo slot. Nothing ever reads the padding back out
+    /// as a field, so it looks like a harmless place to stash them.
+    pub fn rotate_sensitive_data(ctx: Context<RotateSensitiveData>, new_value: u64) -> Result<()> {
+        let info = ctx.accounts.target_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut account = ctx.accounts.target_account.load_mut()?;
+
+        let old = account.sensitive_data.to_le_bytes();
+        account._padding1.copy_from_slice(&old[..6]);
+        account.sensitive_data = new_value;
+
+        msg!("Sensitive data rotated");
+        drop(account);
+        emit_checksum(&info, "rotate_sensitive_data", before)?;
+        Ok(())
+    }
+
+    /// Demonstrates Vec::set_len misuse
+    /// VULNERABLE: sizes the output Vec for the buffer's contents, then calls
+    /// set_len with the caller's `len` before writing. Everything past the
+    /// copied bytes is uninitialized, and past the capacity it is other heap
+    /// allocations made earlier in the transaction; all of it is returned.
+    #[allow(clippy::uninit_vec)]
+    pub fn export_buffer_demo(ctx: Context<ExportBuffer>, len: u32) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+        require!(len as usize <= MAX_RETURN_DATA, ErrorCode::InvalidBufferLength);
+
+        let size = (buffer.size as usize).min(buffer.data.len());
+        let mut out: Vec<u8> = Vec::with_capacity(size);
+        unsafe {
+            out.set_len(len as usize);
+        }
+        let copied = size.min(len as usize);
+        out[..copied].copy_from_slice(&buffer.data[..copied]);
+
+        set_return_data(&out);
+        msg!("Exported {} bytes", out.len());
+        Ok(())
+    }
+
+    /// Secure version of export_buffer_demo
+    /// Builds the output with extend_from_slice and pads with resize, so every
+    /// returned byte was written first
+    pub fn secure_export_buffer(ctx: Context<ExportBuffer>, len: u32) -> Result<()> {
+        let buffer = ctx.accounts.buffer_account.load()?;
+        require!(len as usize <= MAX_RETURN_DATA, ErrorCode::InvalidBufferLength);
+
+        let size = (buffer.size as usize).min(buffer.data.len());
+        let copied = size.min(len as usize);
+        let mut out = Vec::with_capacity(len as usize);
+        out.extend_from_slice(&buffer.data[..copied]);
+        out.resize(len as usize, 0);
+
+        set_return_data(&out);
+        msg!("Exported {} bytes", out.len());
+        Ok(())
+    }
+
+    /// Demonstrates MaybeUninit misuse
+    /// VULNERABLE (default build): overwrites the account with a struct built
+    /// by fresh_target, which only fills in some fields. sensitive_data and
+    /// the padding are whatever was left on the stack. Build with
+    /// `--features secure-init` for the fixed version.
+    pub fn rebuild_target(ctx: Context<RebuildTarget>, data: u64) -> Result<()> {
+        let info = ctx.accounts.target_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut account = ctx.accounts.target_account.load_mut()?;
+
+        *account = fresh_target(data, ctx.accounts.user.key());
+
+        msg!("Target rebuilt, sensitive_data is now {}", account.sensitive_data);
+        drop(account);
+        emit_checksum(&info, "rebuild_target", before)?;
+        Ok(())
+    }
+
+    /// Demonstrates references to unaligned fields of a packed struct
+    /// VULNERABLE: `&mut account.amount` is rejected by the compiler (E0793)
+    /// because amount sits at offset 1, so this goes through addr_of_mut! and
+    /// then turns the raw pointer back into a &mut u64 anyway. That reference
+    /// is misaligned: a panic in debug builds, undefined behavior otherwise.
+    pub fn credit_packed_demo(ctx: Context<CreditPacked>, delta: u64) -> Result<()> {
+        let info = ctx.accounts.packed_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut account = ctx.accounts.packed_account.load_mut()?;
+
+        let amount: &mut u64 = unsafe { &mut *ptr::addr_of_mut!(account.amount) };
+        *amount += delta;
+
+        msg!("Credited {}", delta);
+        drop(account);
+        emit_checksum(&info, "credit_packed_demo", before)?;
+        Ok(())
+    }
+
+    /// Secure version of credit_packed_demo
+    /// Copies the field out by value, updates the copy, and writes it back;
+    /// no reference to the unaligned field is ever created
+    pub fn secure_credit_packed(ctx: Context<CreditPacked>, delta: u64) -> Result<()> {
+        let mut account = ctx.accounts.packed_account.load_mut()?;
+
+        let amount = account.amount;
+        account.amount = amount
+            .checked_add(delta)
+            .ok_or(ErrorCode::InvalidOperation)?;
+
+        let amount = account.amount;
+        msg!("Credited {}, balance {}", delta, amount);
+        Ok(())
+    }
+
+    pub fn initialize_packed(ctx: Context<InitializePacked>) -> Result<()> {
+        let mut account = ctx.accounts.packed_account.load_init()?;
+        account.flag = 1;
+        account.amount = 0;
+        account.owner = ctx.accounts.user.key();
+        msg!("Packed account initialized");
+        Ok(())
+    }
+
+    /// Demonstrates an information leak through padding bytes
+    /// VULNERABLE: copies the public header (everything before sensitive_data)
+    /// straight out of account memory, _padding1 included, so whatever was
+    /// left in the padding goes out with it
+    pub fn export_target(ctx: Context<ExportTarget>) -> Result<()> {
+        let account = ctx.accounts.target_account.load()?;
+
+        let header_len = std::mem::offset_of!(TargetAccount, sensitive_data);
+        set_return_data(&bytemuck::bytes_of(&*account)[..header_len]);
+
+        msg!("Exported {} header bytes", header_len);
+        Ok(())
+    }
+
+    /// Secure version of export_target
+    /// Serializes the public fields one by one, so padding never leaves the
+    /// account
+    pub fn secure_export_target(ctx: Context<ExportTarget>) -> Result<()> {
+        let account = ctx.accounts.target_account.load()?;
+
+        let view = TargetView {
+            is_active: account.is_active,
+            is_initialized: account.is_initialized,
+            data: account.data,
+        };
+        set_return_data(&view.try_to_vec()?);
+
+        msg!("Exported target fields");
+        Ok(())
+    }
+
+    /// Debugging aid for players: copy a window of any account's raw bytes
+    /// into return data, so exploit clients can inspect memory layout without
+    /// writing their own deserializers. At most 1024 bytes per call.
+    pub fn dump_account(ctx: Context<DumpAccount>, offset: u32, len: u32) -> Result<()> {
+        let data = ctx.accounts.account.try_borrow_data()?;
+
+        let start = offset as usize;
+        let end = start
+            .checked_add(len as usize)
+            .ok_or(ErrorCode::InvalidOffset)?;
+        require!(len as usize <= MAX_RETURN_DATA, ErrorCode::InvalidOffset);
+        let window = data.get(start..end).ok_or(ErrorCode::InvalidOffset)?;
+
+        set_return_data(window);
+        msg!("Dumped bytes {}..{} of {}", start, end, ctx.accounts.account.key());
+        Ok(())
+    }
+
+    /// Initialize account for demonstrations
+    pub fn initialize_buffer(ctx: Context<InitializeBuffer>) -> Result<()> {
+        let mut account = ctx.accounts.buffer_account.load_init()?;
+        account.size = 0;
+        account.data = [0; 64]; // Initialize with zeros
+        msg!("Buffer account initialized");
+        Ok(())
+    }
+
+    pub fn initialize_target(ctx: Context<InitializeTarget>) -> Result<()> {
+        let mut account = ctx.accounts.target_account.load_init()?;
+        account.is_active = 1; // Use 1 for true
+        account.is_initialized = 1; // Use 1 for true  
+        account.data = 42;
+        account.sensitive_data = 12345;
+        account.owner = ctx.accounts.user.key();
+        msg!("Target account initialized");
+        Ok(())
+    }
+
+    pub fn initialize_complex(ctx: Context<InitializeComplex>) -> Result<()> {
+        let mut account = ctx.accounts.target_account.load_init()?;
+        account.is_active = 1; // Use 1 for true
+        account.is_initialized = 1; // Use 1 for true  
+        account.data = 42;
+        account.sensitive_data = 12345;
+        account.buffer = [0; 32]; // Initialize buffer with zeros
+        account.is_admin = 0;
+        account.owner = ctx.accounts.user.key();
+        msg!("Complex account initialized");
+        Ok(())
+    }
+
+    /// Restore a buffer to its freshly initialized state, so exploit payloads
+    /// can be retried without creating a new account
+    pub fn reset_buffer(ctx: Context<ResetBuffer>) -> Result<()> {
+        let mut account = ctx.accounts.buffer_account.load_mut()?;
+        account.size = 0;
+        account.data = [0; 64];
+        msg!("Buffer account reset");
+        Ok(())
+    }
+
+    /// Restore a target account to its initialized values, padding included.
+    /// The owner is kept.
+    pub fn reset_target(ctx: Context<ResetTarget>) -> Result<()> {
+        let mut account = ctx.accounts.target_account.load_mut()?;
+        account.is_active = 1;
+        account.is_initialized = 1;
+        account._padding1 = [0; 6];
+        account.data = 42;
+        account.sensitive_data = 12345;
+        msg!("Target account reset");
+        Ok(())
+    }
+
+    /// Restore a complex account to its initialized values, clearing is_admin.
+    /// The owner is kept.
+    pub fn reset_complex(ctx: Context<ResetComplex>) -> Result<()> {
+        let mut account = ctx.accounts.target_account.load_mut()?;
+        account.is_active = 1;
+        account.is_initialized = 1;
+        account._padding1 = [0; 6];
+        account.data = 42;
+        account.sensitive_data = 12345;
+        account.buffer = [0; 32];
+        account.is_admin = 0;
+        account._padding2 = [0; 7];
+        msg!("Complex account reset");
+        Ok(())
+    }
+
+    /// Patch-it challenge: player-implemented fix for buffer_overflow_demo
+    pub fn patch_buffer_write(ctx: Context<BufferOverflowDemo>, data: Vec<u8>) -> Result<()> {
+        secure::patch_buffer_write(ctx, data)
+    }
+
+    /// Patch-it challenge: player-implemented fix for fill_buffer_demo
+    pub fn patch_fill_buffer(ctx: Context<BufferOverflowDemo>, pattern: Vec<u8>, len: u64) -> Result<()> {
+        secure::patch_fill_buffer(ctx, pattern, len)
+    }
+
+    /// Patch-it challenge: player-implemented fix for write_prefixed
+    pub fn patch_write_prefixed(ctx: Context<WritePrefixed>, data: Vec<u8>) -> Result<()> {
+        secure::patch_write_prefixed(ctx, data)
+    }
+
+    /// Patch-it challenge: player-implemented fix for append_record
+    pub fn patch_append_record(ctx: Context<AppendRecord>, id: u64, value: u64) -> Result<()> {
+        secure::patch_append_record(ctx, id, value)
+    }
+
+    /// Demonstrates a complex vulnerability combining multiple issues
+    pub fn complex_vulnerability_demo(
+        ctx: Context<ComplexDemo>,
+        operation: u8,
+        data: Vec<u8>
+    ) -> Result<()> {
+        let info = ctx.accounts.target_account.to_account_info();
+        let before = data_checksum(&info)?;
+        let mut account = ctx.accounts.target_account.load_mut()?;
+        
+        match operation {
+            1 => {
+                // Buffer overflow + uninitialized read
+                if account.is_initialized == 0 {
+                    // Reading uninitialized sensitive_data
+                    msg!("Using uninitialized data: {}", account.sensitive_data);
+                }
+                
+                // VULNERABLE: bounds against everything between the buffer and
+                // the owner field rather than the 32-byte buffer itself, so up
+                // to 8 extra bytes spill into is_admin and the padding after it
+                let room = std::mem::size_of::<ComplexAccount>()
+                    - std::mem::offset_of!(ComplexAccount, buffer)
+                    - std::mem::size_of::<Pubkey>();
+                if data.len() > room {
+                    return Err(ErrorCode::BufferOverflow.into());
+                }
+                
+                unsafe {
+                    // Copy data without proper bounds checking in unsafe context
+                    let dest = account.buffer.as_mut_ptr();
+                    ptr::copy_nonoverlapping(data.as_ptr(), dest, data.len());
+                }
+            },
+            2 => {
+                // Use after free scenario
+                account.is_active = 0; // "Free" the account
+                
+                // Then try to use it
+                if account.is_active == 0 {
+                    msg!("Accessing freed account data: {}", account.data);
+                    return Err(ErrorCode::UseAfterFree.into());
+                }
+            },
+            3 => {
+                // Double free scenario
+                if account.is_active == 0 {
+                    msg!("Attempting to free already freed account");
+                    return Err(ErrorCode::DoubleFree.into());
+                }
+                account.is_active = 0;
+            },
+            _ => {
+                return Err(ErrorCode::InvalidOperation.into());
+            }
+        }
+        
+        drop(account);
+        emit_checksum(&info, "complex_vulnerability_demo", before)?;
+        Ok(())
+    }
+}
+
+/// Count the nodes in a sequence of `[len: u8][body: len bytes]` nodes, where
+/// each body is itself a sequence of nodes. Recurses once per nesting level
+/// and once per sibling.
+fn parse_nested(input: &[u8]) -> Result<u64> {
+    let Some((&len, rest)) = input.split_first() else {
+        return Ok(0);
+    };
+    require!(rest.len() >= len as usize, ErrorCode::MalformedInput);
+    let (body, siblings) = rest.split_at(len as usize);
+
+    // Per-frame scratch copy, so every level of recursion costs real stack
+    let mut scratch = [0u8; 256];
+    scratch[..body.len()].copy_from_slice(body);
+
+    let children = parse_nested(&scratch[..body.len()])?;
+    let following = parse_nested(siblings)?;
+    Ok(1 + children + following)
+}
+
+/// Same node count as `parse_nested`, without recursion and with bounded depth
+fn parse_nested_iterative(input: &[u8]) -> Result<u64> {
+    let mut nodes = 0u64;
+    let mut pending: Vec<(&[u8], usize)> = vec![(input, 0)];
+
+    while let Some((mut cursor, depth)) = pending.pop() {
+        while let Some((&len, rest)) = cursor.split_first() {
+            require!(rest.len() >= len as usize, ErrorCode::MalformedInput);
+            let (body, siblings) = rest.split_at(len as usize);
+            nodes = nodes.checked_add(1).unwrap();
+
+            if !body.is_empty() {
+                require!(depth < MAX_NESTING_DEPTH, ErrorCode::NestingTooDeep);
+                pending.push((body, depth + 1));
+            }
+            cursor = siblings;
+        }
+    }
+    Ok(nodes)
+}
+
+/// Set the new size, then copy the payload in two halves. Fails between the
+/// halves when `fail_midway` is set, standing in for any error raised partway
+/// through a multi-field update.
+fn write_in_halves(buffer: &mut BufferAccount, data: &[u8], fail_midway: bool) -> Result<()> {
+    require!(data.len() <= buffer.data.len(), ErrorCode::BufferOverflow);
+
+    buffer.size = data.len() as u64;
+    let half = data.len() / 2;
+    buffer.data[..half].copy_from_slice(&data[..half]);
+
+    require!(!fail_midway, ErrorCode::WriteInterrupted);
+    buffer.data[half..data.len()].copy_from_slice(&data[half..]);
+    Ok(())
+}
+
+/// Bytes claim_admin_flag expects over `is_admin` and the padding after it:
+/// the first 8 bytes of the admin key in the player's registry instance
+pub fn admin_sentinel(instance: &Instance) -> [u8; 8] {
+    let mut sentinel = [0; 8];
+    sentinel.copy_from_slice(&instance.admin.as_ref()[..8]);
+    sentinel
+}
+
+/// Report the player's solve to the CTF registry, signing as this program's
+/// flag authority
+fn capture_flag<'info>(
+    registry_program: &Program<'info, CtfRegistry>,
+    challenge: &UncheckedAccount<'info>,
+    solve: &UncheckedAccount<'info>,
+    player: &Signer<'info>,
+    flag_authority: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    bump: u8,
+) -> Result<()> {
+    ctf_registry::cpi::capture_flag(CpiContext::new_with_signer(
+        registry_program.to_account_info(),
+        ctf_registry::cpi::accounts::CaptureFlag {
+            challenge: challenge.to_account_info(),
+            solve: solve.to_account_info(),
+            player: player.to_account_info(),
+            flag_authority: flag_authority.to_account_info(),
+            payer: player.to_account_info(),
+            system_program: system_program.to_account_info(),
+        },
+        &[&[ctf_registry::FLAG_AUTHORITY_SEED, &[bump]]],
+    ))
+}
+
+/// Top up `account` so it stays rent exempt at its current size
+fn fund_rent<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(account.data_len());
+    let missing = required.saturating_sub(account.lamports());
+    if missing > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            missing,
+        )?;
+    }
+    Ok(())
+}
+
+/// Build a replacement TargetAccount. Only the fields the author cared about
+/// are written before assume_init, which is undefined behavior
+#[cfg(not(feature = "secure-init"))]
+#[allow(invalid_value, clippy::uninit_assumed_init)]
+fn fresh_target(data: u64, owner: Pubkey) -> TargetAccount {
+    let mut target: TargetAccount = unsafe { MaybeUninit::uninit().assume_init() };
+    target.is_active = 1;
+    target.is_initialized = 1;
+    target.data = data;
+    target.owner = owner;
+    target
+}
+
+/// Secure version of fresh_target: start from zeroed memory, which is a
+/// valid TargetAccount, and then set every field explicitly
+#[cfg(feature = "secure-init")]
+fn fresh_target(data: u64, owner: Pubkey) -> TargetAccount {
+    // Safety: TargetAccount is Pod, so all-zero bytes are a valid value
+    let mut target: TargetAccount = unsafe { MaybeUninit::zeroed().assume_init() };
+    target.is_active = 1;
+    target.is_initialized = 1;
+    target._padding1 = [0; 6];
+    target.data = data;
+    target.sensitive_data = 0;
+    target.owner = owner;
+    target
+}
+
+/// 64-bit FNV-1a
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Checksum of an account's full data region
+fn data_checksum(account: &AccountInfo) -> Result<u64> {
+    Ok(fnv1a(&account.try_borrow_data()?))
+}
+
+/// Emit a MemoryChecksumEvent comparing the account's data now with the
+/// checksum taken before the instruction touched it. The account's data
+/// must not be borrowed.
+fn emit_checksum(account: &AccountInfo, instruction: &str, before: u64) -> Result<()> {
+    emit!(MemoryChecksumEvent {
+        account: account.key(),
+        instruction: instruction.to_string(),
+        before,
+        after: data_checksum(account)?,
+        data_len: account.data_len() as u64,
+    });
+    Ok(())
+}
+
+/// Address of `player`'s demo account for one of the `*_SEED` constants
+pub fn player_account_address(seed: &[u8], player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[seed, player.as_ref()], &ID).0
+}
+
+#[derive(Accounts)]
+pub struct BufferOverflowDemo<'info> {
+    #[account(
+        mut,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrowBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CopyBuffer<'info> {
+    pub source: AccountLoader<'info, BufferAccount>,
+    #[account(
+        mut,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub destination: AccountLoader<'info, BufferAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecursiveParse<'info> {
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AllocateScratch<'info> {
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadU64<'info> {
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadSlice<'info> {
+    /// CHECK: Any account; only its raw data is read
+    pub account: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Peek<'info> {
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    /// CHECK: Any account; serialized right after buffer_account
+    pub neighbour: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TornWrite<'info> {
+    #[account(
+        mut,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WritePrefixed<'info> {
+    #[account(
+        mut,
+        seeds = [PREFIXED_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, PrefixedBuffer>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CaptureSizeFlag<'info> {
+    #[account(constraint = buffer_account.load()?.owner == player.key())]
+    pub buffer_account: AccountLoader<'info, PrefixedBuffer>,
+
+    /// CHECK: Validated by the registry
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateBuffer<'info> {
+    /// CHECK: Holds a V1 buffer on entry and a V2 buffer on exit, so the
+    /// discriminator is checked by hand instead of through AccountLoader
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CaptureMigrationFlag<'info> {
+    #[account(seeds = [BUFFER_SEED, player.key().as_ref()], bump)]
+    pub buffer_account: AccountLoader<'info, BufferAccountV2>,
+
+    /// CHECK: Validated by the registry
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAdminFlag<'info> {
+    #[account(constraint = target_account.load()?.owner == player.key())]
+    pub target_account: AccountLoader<'info, ComplexAccount>,
+
+    /// CHECK: Validated by the registry
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// The player's instance of the challenge, which picks the admin sentinel
+    #[account(
+        seeds = [ctf_registry::INSTANCE_SEED, challenge.key().as_ref(), player.key().as_ref()],
+        bump = instance.bump,
+        seeds::program = registry_program.key(),
+    )]
+    pub instance: Account<'info, Instance>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DumpAccount<'info> {
+    /// CHECK: Any account; only its raw data is read
+    pub account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreditPacked<'info> {
+    #[account(
+        mut,
+        seeds = [PACKED_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub packed_account: AccountLoader<'info, PackedAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RebuildTarget<'info> {
+    #[account(
+        mut,
+        constraint = target_account.load()?.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RotateSensitiveData<'info> {
+    #[account(
+        mut,
+        constraint = target_account.load()?.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExportBuffer<'info> {
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ExportTarget<'info> {
+    pub target_account: AccountLoader<'info, TargetAccount>,
+}
+
+#[derive(Accounts)]
+pub struct AppendRecord<'info> {
+    #[account(
+        mut,
+        seeds = [RECORD_BOOK_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub record_book: AccountLoader<'info, RecordBook>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UseAfterFreeDemo<'info> {
+    #[account(
+        mut,
+        seeds = [TARGET_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SecureClose<'info> {
+    #[account(
+        mut,
+        close = user,
+        constraint = target_account.load()?.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UninitializedDemo<'info> {
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DoubleFreeDemo<'info> {
+    #[account(
+        mut,
+        seeds = [TARGET_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct NullPointerDemo<'info> {
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBuffer<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<BufferAccount>(),
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePrefixedBuffer<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<PrefixedBuffer>(),
+        seeds = [PREFIXED_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, PrefixedBuffer>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRecordBook<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<RecordBook>(),
+        seeds = [RECORD_BOOK_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub record_book: AccountLoader<'info, RecordBook>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePacked<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<PackedAccount>(),
+        seeds = [PACKED_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub packed_account: AccountLoader<'info, PackedAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTarget<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<TargetAccount>(),
+        seeds = [TARGET_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeComplex<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<ComplexAccount>(),
+        seeds = [COMPLEX_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub target_account: AccountLoader<'info, ComplexAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ComplexDemo<'info> {
+    #[account(
+        mut,
+        seeds = [COMPLEX_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub target_account: AccountLoader<'info, ComplexAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [BUFFER_SEED, user.key().as_ref()],
+        bump,
+    )]
+    pub buffer_account: AccountLoader<'info, BufferAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetTarget<'info> {
+    #[account(
+        mut,
+        constraint = target_account.load()?.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub target_account: AccountLoader<'info, TargetAccount>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetComplex<'info> {
+    #[account(
+        mut,
+        constraint = target_account.load()?.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub target_account: AccountLoader<'info, ComplexAccount>,
+    pub user: Signer<'info>,
+}
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct BufferAccount {
+    pub size: u64,
+    pub data: [u8; 64],
+}
+
+/// Second layout of BufferAccount: size moved after data
+#[account(zero_copy)]
+#[repr(C)]
+pub struct BufferAccountV2 {
+    pub data: [u8; 64],
+    pub size: u64,
+}
+
+// Buffers are migrated in place, so both layouts must be the same size
+const _: () = assert!(
+    std::mem::size_of::<BufferAccount>() == std::mem::size_of::<BufferAccountV2>()
+);
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct PrefixedBuffer {
+    pub data: [u8; 32],
+    pub size: u64,        // Directly after data: a one-byte overflow hits its low byte
+    pub target_size: u64, // Value the player must forge into size
+    pub owner: Pubkey,
+}
+
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct PackedAccount {
+    pub flag: u8,
+    pub amount: u64, // Offset 1: never aligned
+    pub owner: Pubkey,
+}
+
+#[zero_copy]
+#[repr(C)]
+pub struct Record {
+    pub id: u64,
+    pub value: u64,
+}
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct RecordBook {
+    pub count: u64,
+    pub records: [Record; RECORD_CAPACITY],
+    pub sensitive_key: [u8; 32], // Room for exactly two stray records
+}
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct TargetAccount {
+    pub is_active: u8,      // Use u8 instead of bool for Pod compatibility
+    pub is_initialized: u8, // Use u8 instead of bool for Pod compatibility
+    pub _padding1: [u8; 6], // Explicit padding for alignment
+    pub data: u64,
+    pub sensitive_data: u64,
+    pub owner: Pubkey,      // Only the owner may close the account
+}
+
+/// Return data of copy_benchmark
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CopyBenchmarkResult {
+    pub len: u64,
+    pub loop_units: u64,
+    pub copy_units: u64,
+    /// loop_units - copy_units
+    pub delta: i64,
+}
+
+/// Public fields of a TargetAccount, as returned by secure_export_target
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TargetView {
+    pub is_active: u8,
+    pub is_initialized: u8,
+    pub data: u64,
+}
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct ComplexAccount {
+    pub is_active: u8,      // Use u8 instead of bool for Pod compatibility
+    pub is_initialized: u8, // Use u8 instead of bool for Pod compatibility
+    pub _padding1: [u8; 6], // Explicit padding for alignment
+    pub data: u64,
+    pub sensitive_data: u64,
+    pub buffer: [u8; 32],
+    pub is_admin: u8,       // Directly after buffer: the overflow target
+    pub _padding2: [u8; 7], // Explicit padding for alignment
+    pub owner: Pubkey,
+}
+
+impl ComplexAccount {
+    /// `is_admin` and the padding after it, the 8 bytes the overflow reaches
+    pub fn admin_bytes(&self) -> [u8; 8] {
+        let mut bytes = [self.is_admin; 8];
+        bytes[1..].copy_from_slice(&self._padding2);
+        bytes
+    }
+}
+
+/// Emitted after each demo instruction that writes to an account, so tests
+/// and the grader can detect corruption without parsing logs
+#[event]
+pub struct MemoryChecksumEvent {
+    pub account: Pubkey,
+    pub instruction: String,
+    /// FNV-1a of the full account data before the instruction
+    pub before: u64,
+    /// FNV-1a of the full account data after the instruction
+    pub after: u64,
+    pub data_len: u64,
 }
 
-#[error_code]
+// Codes 9000-9999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 9000)]
 pub enum ErrorCode {
     #[msg("Buffer overflow detected")]
     BufferOverflow,
@@ -319,4 +1927,30 @@ pub enum ErrorCode {
     NullPointerDereference,
     #[msg("Invalid operation")]
     InvalidOperation,
+    #[msg("Requested buffer length is out of bounds")]
+    InvalidBufferLength,
+    #[msg("Source and destination must be different accounts")]
+    AliasedAccounts,
+    #[msg("Length prefix runs past the end of the input")]
+    MalformedInput,
+    #[msg("Input is nested too deeply")]
+    NestingTooDeep,
+    #[msg("Requested allocation is too large")]
+    AllocationTooLarge,
+    #[msg("Offset is outside the buffer")]
+    InvalidOffset,
+    #[msg("Write was interrupted midway")]
+    WriteInterrupted,
+    #[msg("Buffer size has not been forged to the target value")]
+    SizeNotForged,
+    #[msg("Signer does not own this account")]
+    Unauthorized,
+    #[msg("is_admin has not been overwritten with the instance's admin sentinel")]
+    NotAdmin,
+    #[msg("Record book is full")]
+    RecordBookFull,
+    #[msg("Buffer size is within capacity")]
+    MigrationNotSkewed,
+    #[msg("This function has not been implemented yet")]
+    NotImplemented,
 }
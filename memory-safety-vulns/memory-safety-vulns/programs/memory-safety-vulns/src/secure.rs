@@ -0,0 +1,39 @@
+//! Patch-it challenge: write the fixes yourself
+//!
+//! Each stub below is wired to an instruction in the program (`patch_*`)
+//! and takes the same accounts as the vulnerable demo it replaces. The
+//! grader (`cargo run -p grader -- --patches`) replays the reference exploit
+//! payloads against every stub and checks that legitimate writes still land.
+//! Don't peek at the `secure_*` instructions until you're done.
+
+use anchor_lang::prelude::*;
+
+use crate::{AppendRecord, BufferOverflowDemo, ErrorCode, WritePrefixed};
+
+// CHALLENGE: Implement this function securely
+pub fn patch_buffer_write(_ctx: Context<BufferOverflowDemo>, _data: Vec<u8>) -> Result<()> {
+    // TODO: Copy data into the buffer and update size
+    // Hint: Writes of up to 64 bytes must still succeed
+    Err(error!(ErrorCode::NotImplemented))
+}
+
+// CHALLENGE: Implement this function securely
+pub fn patch_fill_buffer(_ctx: Context<BufferOverflowDemo>, _pattern: Vec<u8>, _len: u64) -> Result<()> {
+    // TODO: Fill the first len bytes of the buffer with the repeated pattern
+    // Hint: Look at what fill_buffer_demo narrows before it checks
+    Err(error!(ErrorCode::NotImplemented))
+}
+
+// CHALLENGE: Implement this function securely
+pub fn patch_write_prefixed(_ctx: Context<WritePrefixed>, _data: Vec<u8>) -> Result<()> {
+    // TODO: Copy data into the prefixed buffer and set size to its length
+    // Hint: Count how many bytes write_prefixed really lets through
+    Err(error!(ErrorCode::NotImplemented))
+}
+
+// CHALLENGE: Implement this function securely
+pub fn patch_append_record(_ctx: Context<AppendRecord>, _id: u64, _value: u64) -> Result<()> {
+    // TODO: Append a record and bump count
+    // Hint: sensitive_key must be untouched no matter how many appends are sent
+    Err(error!(ErrorCode::NotImplemented))
+}
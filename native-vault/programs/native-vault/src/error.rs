@@ -0,0 +1,56 @@
+use std::fmt;
+
+use solana_program::program_error::ProgramError;
+
+/// First code of this program's range, see `crates/ctf-errors`
+pub const ERROR_OFFSET: u32 = 11_000;
+
+/// Custom errors, returned as `ProgramError::Custom(ERROR_OFFSET + n)` so they
+/// line up with the Anchor programs' `#[error_code(offset = ...)]` ranges
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VaultError {
+    NotImplemented,
+    InvalidInstruction,
+    VaultAlreadyInitialized,
+    VaultNotInitialized,
+    InvalidVaultAddress,
+    WrongVaultTokenAccount,
+    NotOwner,
+    WrongTokenProgram,
+    WrongMint,
+}
+
+impl VaultError {
+    pub fn name(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VaultError::NotImplemented => "This function is not implemented yet",
+            VaultError::InvalidInstruction => "Instruction data does not decode",
+            VaultError::VaultAlreadyInitialized => "Vault is already initialized",
+            VaultError::VaultNotInitialized => "Vault is not initialized",
+            VaultError::InvalidVaultAddress => "Vault is not at the PDA of its owner",
+            VaultError::WrongVaultTokenAccount => "Token account does not belong to the vault",
+            VaultError::NotOwner => "Account is not the vault owner",
+            VaultError::WrongTokenProgram => "Token program is neither SPL Token nor Token-2022",
+            VaultError::WrongMint => "Mint is not the vault's mint",
+        })
+    }
+}
+
+impl From<VaultError> for u32 {
+    fn from(error: VaultError) -> u32 {
+        ERROR_OFFSET + error as u32
+    }
+}
+
+impl From<VaultError> for ProgramError {
+    fn from(error: VaultError) -> ProgramError {
+        ProgramError::Custom(error.into())
+    }
+}
@@ -0,0 +1,167 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_sdk_ids::system_program;
+
+use crate::error::VaultError;
+
+/// Instruction data, borsh-encoded with a one-byte variant tag. Anchor uses an
+/// 8-byte sighash instead; the tag is all a single program needs.
+///
+/// The token program may be SPL Token or Token-2022, as long as it owns the
+/// mint. Tokens move with `transfer_checked`, which both programs support.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum VaultInstruction {
+    /// Create the owner's vault PDA and a token account it controls, sized
+    /// for whatever extensions the mint requires.
+    ///
+    /// 0. `[writable]` vault PDA, `[b"vault", owner]`
+    /// 1. `[writable, signer]` new vault token account
+    /// 2. `[]` mint
+    /// 3. `[writable, signer]` owner, pays for both accounts
+    /// 4. `[]` token program
+    /// 5. `[]` system program
+    /// 6. `[]` rent sysvar
+    InitializeVault,
+    /// Move `amount` from the owner's token account into the vault.
+    ///
+    /// 0. `[]` vault
+    /// 1. `[writable]` vault token account
+    /// 2. `[writable]` source token account
+    /// 3. `[]` mint
+    /// 4. `[signer]` owner of the source
+    /// 5. `[]` token program
+    Deposit { amount: u64 },
+    /// Pay `amount` out of the vault. VULNERABLE: `owner` is compared with the
+    /// vault but never checked as a signer.
+    ///
+    /// 0. `[]` vault
+    /// 1. `[writable]` vault token account
+    /// 2. `[writable]` destination token account
+    /// 3. `[]` mint
+    /// 4. `[]` owner
+    /// 5. `[]` token program
+    Withdraw { amount: u64 },
+    /// The player's fix of `Withdraw`, same accounts.
+    SecureWithdraw { amount: u64 },
+}
+
+impl VaultInstruction {
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(data).map_err(|_| VaultError::InvalidInstruction.into())
+    }
+
+    fn pack(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("instruction data serializes")
+    }
+}
+
+pub fn initialize_vault(
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let (vault, _) = crate::vault_address(owner);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*vault_token_account, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: VaultInstruction::InitializeVault.pack(),
+    }
+}
+
+pub fn deposit(
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (vault, _) = crate::vault_address(owner);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new(*vault_token_account, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: VaultInstruction::Deposit { amount }.pack(),
+    }
+}
+
+/// `Withdraw` with `owner` marked as a signer, as an honest client sends it.
+/// Clear `is_signer` on the owner's meta to get the exploit.
+pub fn withdraw(
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    withdraw_instruction(
+        owner,
+        vault_token_account,
+        destination,
+        mint,
+        token_program,
+        VaultInstruction::Withdraw { amount },
+    )
+}
+
+pub fn secure_withdraw(
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    withdraw_instruction(
+        owner,
+        vault_token_account,
+        destination,
+        mint,
+        token_program,
+        VaultInstruction::SecureWithdraw { amount },
+    )
+}
+
+fn withdraw_instruction(
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    instruction: VaultInstruction,
+) -> Instruction {
+    let (vault, _) = crate::vault_address(owner);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new(*vault_token_account, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: instruction.pack(),
+    }
+}
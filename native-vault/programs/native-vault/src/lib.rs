@@ -0,0 +1,32 @@
+//! The missing-signer vault from `missing-signer-check`, written against plain
+//! `solana-program` instead of Anchor.
+//!
+//! Everything Anchor's `#[derive(Accounts)]` generates is spelled out by hand
+//! in [`processor`]: pulling accounts off the slice, checking which program
+//! owns the vault, decoding it, re-deriving its PDA, and comparing the stored
+//! keys. `withdraw` does all of that and still forgets one line, the
+//! `is_signer` check on `owner`.
+
+use solana_program::{declare_id, pubkey::Pubkey};
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+use processor::process_instruction;
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+declare_id!("FSU3mLB6srBpPWctERvo17MiQxPBEE95XgUgbgJvp9QB");
+
+pub const CHALLENGE_ID: &str = "native-vault";
+
+/// Seed prefix of every vault PDA; the owner's key follows it
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Address and bump of the vault PDA belonging to `owner`
+pub fn vault_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, owner.as_ref()], &ID)
+}
@@ -0,0 +1,286 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use solana_system_interface::instruction as system_instruction;
+
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::error::VaultError;
+use crate::instruction::VaultInstruction;
+use crate::state::Vault;
+use crate::VAULT_SEED;
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match VaultInstruction::unpack(data)? {
+        VaultInstruction::InitializeVault => initialize_vault(program_id, accounts),
+        VaultInstruction::Deposit { amount } => deposit(program_id, accounts, amount),
+        VaultInstruction::Withdraw { amount } => withdraw(program_id, accounts, amount),
+        VaultInstruction::SecureWithdraw { amount } => secure_withdraw(program_id, accounts, amount),
+    }
+}
+
+fn initialize_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let vault = next_account_info(accounts)?;
+    let vault_token_account = next_account_info(accounts)?;
+    let mint = next_account_info(accounts)?;
+    let owner = next_account_info(accounts)?;
+    let token_program = next_account_info(accounts)?;
+    let system_program = next_account_info(accounts)?;
+    let rent = Rent::from_account_info(next_account_info(accounts)?)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_token_program(token_program)?;
+    if mint.owner != token_program.key {
+        return Err(VaultError::WrongTokenProgram.into());
+    }
+    let (expected, bump) = Pubkey::find_program_address(&[VAULT_SEED, owner.key.as_ref()], program_id);
+    if *vault.key != expected {
+        return Err(VaultError::InvalidVaultAddress.into());
+    }
+    if vault.owner == program_id {
+        return Err(VaultError::VaultAlreadyInitialized.into());
+    }
+
+    // Anchor's `init` with `seeds`: the program signs for its own PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            vault.key,
+            rent.minimum_balance(Vault::LEN),
+            Vault::LEN as u64,
+            program_id,
+        ),
+        &[owner.clone(), vault.clone(), system_program.clone()],
+        &[&[VAULT_SEED, owner.key.as_ref(), &[bump]]],
+    )?;
+    // Token-2022 mints can require extensions on every account that holds
+    // them, e.g. a transfer-fee mint; a legacy mint requires none
+    let token_account_len = {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        let extensions = ExtensionType::get_required_init_account_extensions(&mint_state.get_extension_types()?);
+        ExtensionType::try_calculate_account_len::<TokenAccount>(&extensions)?
+    };
+    invoke(
+        &system_instruction::create_account(
+            owner.key,
+            vault_token_account.key,
+            rent.minimum_balance(token_account_len),
+            token_account_len as u64,
+            token_program.key,
+        ),
+        &[owner.clone(), vault_token_account.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token_2022::instruction::initialize_account3(
+            token_program.key,
+            vault_token_account.key,
+            mint.key,
+            vault.key,
+        )?,
+        &[vault_token_account.clone(), mint.clone(), token_program.clone()],
+    )?;
+
+    Vault {
+        is_initialized: true,
+        owner: *owner.key,
+        vault_token_account: *vault_token_account.key,
+        mint: *mint.key,
+        bump,
+    }
+    .store(vault)?;
+    msg!("Vault {} initialized for {}", vault.key, owner.key);
+    Ok(())
+}
+
+fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let vault = next_account_info(accounts)?;
+    let vault_token_account = next_account_info(accounts)?;
+    let source = next_account_info(accounts)?;
+    let mint = next_account_info(accounts)?;
+    let owner = next_account_info(accounts)?;
+    let token_program = next_account_info(accounts)?;
+
+    load_vault(program_id, vault, vault_token_account, mint, owner)?;
+    check_token_program(token_program)?;
+
+    // The token program rejects the transfer unless `owner` signed for `source`
+    invoke(
+        &spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            source.key,
+            mint.key,
+            vault_token_account.key,
+            owner.key,
+            &[],
+            amount,
+            mint_decimals(mint)?,
+        )?,
+        &[
+            source.clone(),
+            mint.clone(),
+            vault_token_account.clone(),
+            owner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    msg!("Deposited {} into vault {}", amount, vault.key);
+    Ok(())
+}
+
+/// Accounts of `Withdraw` and `SecureWithdraw`, in instruction order. This is
+/// the part of `#[derive(Accounts)]` that only collects accounts; the checks
+/// are in [`load_vault`] and the handlers.
+struct WithdrawAccounts<'a, 'info> {
+    vault: &'a AccountInfo<'info>,
+    vault_token_account: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    mint: &'a AccountInfo<'info>,
+    owner: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> WithdrawAccounts<'a, 'info> {
+    fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+        Ok(Self {
+            vault: next_account_info(accounts)?,
+            vault_token_account: next_account_info(accounts)?,
+            destination: next_account_info(accounts)?,
+            mint: next_account_info(accounts)?,
+            owner: next_account_info(accounts)?,
+            token_program: next_account_info(accounts)?,
+        })
+    }
+}
+
+fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts = WithdrawAccounts::parse(accounts)?;
+    let vault = load_vault(
+        program_id,
+        accounts.vault,
+        accounts.vault_token_account,
+        accounts.mint,
+        accounts.owner,
+    )?;
+    check_token_program(accounts.token_program)?;
+
+    // VULNERABLE: `owner` matches the vault, but nothing checks
+    // `accounts.owner.is_signer`, so anyone can pass the owner's key
+    pay_out(&accounts, &vault, amount)
+}
+
+#[cfg(not(feature = "solutions"))]
+fn secure_withdraw(_program_id: &Pubkey, _accounts: &[AccountInfo], _amount: u64) -> ProgramResult {
+    // TODO: Implement secure withdrawal with proper signer checks
+    // Hint: start from `withdraw` and add the check it is missing
+    Err(VaultError::NotImplemented.into())
+}
+
+// SOLUTION: Built only with `--features solutions`
+#[cfg(feature = "solutions")]
+fn secure_withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts = WithdrawAccounts::parse(accounts)?;
+    let vault = load_vault(
+        program_id,
+        accounts.vault,
+        accounts.vault_token_account,
+        accounts.mint,
+        accounts.owner,
+    )?;
+    check_token_program(accounts.token_program)?;
+    // What Anchor's `Signer<'info>` does for you
+    if !accounts.owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    pay_out(&accounts, &vault, amount)
+}
+
+/// Everything `Account<'info, Vault>` with `seeds`, `bump`,
+/// `has_one = vault_token_account`, `has_one = token_mint` and
+/// `has_one = owner` checks in the Anchor version. Leaving out any one of
+/// these is its own bug.
+fn load_vault(
+    program_id: &Pubkey,
+    vault: &AccountInfo,
+    vault_token_account: &AccountInfo,
+    mint: &AccountInfo,
+    owner: &AccountInfo,
+) -> Result<Vault, ProgramError> {
+    // Only this program can have written the data
+    if vault.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let state = Vault::load(vault)?;
+    // The stored bump makes this cheaper than `find_program_address`
+    let expected = Pubkey::create_program_address(&[VAULT_SEED, state.owner.as_ref(), &[state.bump]], program_id)
+        .map_err(|_| VaultError::InvalidVaultAddress)?;
+    if *vault.key != expected {
+        return Err(VaultError::InvalidVaultAddress.into());
+    }
+    if *vault_token_account.key != state.vault_token_account {
+        return Err(VaultError::WrongVaultTokenAccount.into());
+    }
+    if *mint.key != state.mint {
+        return Err(VaultError::WrongMint.into());
+    }
+    if *owner.key != state.owner {
+        return Err(VaultError::NotOwner.into());
+    }
+    Ok(state)
+}
+
+/// A CPI into an attacker-supplied "token program" would run their code with
+/// the vault's signature. Anchor's `Interface<'info, TokenInterface>` accepts
+/// the same two programs.
+fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    spl_token_2022::check_spl_token_program_account(token_program.key)
+        .map_err(|_| VaultError::WrongTokenProgram.into())
+}
+
+/// `transfer_checked` takes the decimals and the token program compares them
+/// with the mint, so reading them from the passed mint is enough
+fn mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow_data()?;
+    Ok(StateWithExtensions::<Mint>::unpack(&data)?.base.decimals)
+}
+
+fn pay_out(accounts: &WithdrawAccounts, vault: &Vault, amount: u64) -> ProgramResult {
+    invoke_signed(
+        &spl_token_2022::instruction::transfer_checked(
+            accounts.token_program.key,
+            accounts.vault_token_account.key,
+            accounts.mint.key,
+            accounts.destination.key,
+            accounts.vault.key,
+            &[],
+            amount,
+            mint_decimals(accounts.mint)?,
+        )?,
+        &[
+            accounts.vault_token_account.clone(),
+            accounts.mint.clone(),
+            accounts.destination.clone(),
+            accounts.vault.clone(),
+            accounts.token_program.clone(),
+        ],
+        &[&[VAULT_SEED, vault.owner.as_ref(), &[vault.bump]]],
+    )?;
+    msg!("Withdrew {} from vault {} to {}", amount, accounts.vault.key, accounts.destination.key);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::error::VaultError;
+
+/// The vault PDA's data. The first byte stands in for Anchor's discriminator:
+/// zero until `initialize_vault` writes the account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Vault {
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 1;
+
+    /// Decode an initialized vault. Does not check who owns the account or
+    /// where it lives; callers do that first.
+    pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.try_borrow_data()?;
+        let vault = Self::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)?;
+        if !vault.is_initialized {
+            return Err(VaultError::VaultNotInitialized.into());
+        }
+        Ok(vault)
+    }
+
+    pub fn store(&self, account: &AccountInfo) -> Result<(), ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        borsh::to_writer(&mut data[..], self).map_err(|_| ProgramError::AccountDataTooSmall)
+    }
+}
@@ -0,0 +1,47 @@
+use core::fmt;
+
+use pinocchio::program_error::ProgramError;
+
+/// First code of this program's range, see `crates/ctf-errors`
+pub const ERROR_OFFSET: u32 = 12_000;
+
+/// Custom errors, returned as `ProgramError::Custom(ERROR_OFFSET + n)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum OwnerCheckError {
+    NotImplemented,
+    InvalidConfig,
+    InvalidAddress,
+    NotAuthority,
+    InsufficientFunds,
+}
+
+impl OwnerCheckError {
+    pub fn name(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl fmt::Display for OwnerCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OwnerCheckError::NotImplemented => "This function is not implemented yet",
+            OwnerCheckError::InvalidConfig => "Config data is malformed",
+            OwnerCheckError::InvalidAddress => "Account is not at the expected PDA",
+            OwnerCheckError::NotAuthority => "Signer is not the config authority",
+            OwnerCheckError::InsufficientFunds => "Treasury would drop below rent exemption",
+        })
+    }
+}
+
+impl From<OwnerCheckError> for u32 {
+    fn from(error: OwnerCheckError) -> u32 {
+        ERROR_OFFSET + error as u32
+    }
+}
+
+impl From<OwnerCheckError> for ProgramError {
+    fn from(error: OwnerCheckError) -> ProgramError {
+        ProgramError::Custom(error.into())
+    }
+}
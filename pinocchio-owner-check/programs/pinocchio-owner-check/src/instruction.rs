@@ -0,0 +1,58 @@
+use pinocchio::program_error::ProgramError;
+
+/// First byte of the instruction data. The rest is fixed-size little-endian
+/// fields, read without a deserializer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum VaultInstruction {
+    /// Create the config and treasury PDAs with the signer as authority.
+    ///
+    /// Data: `[config_bump: u8, treasury_bump: u8]`
+    ///
+    /// 0. `[writable]` config PDA, `[b"config"]`
+    /// 1. `[writable]` treasury PDA, `[b"treasury"]`
+    /// 2. `[writable, signer]` authority, pays for both accounts
+    /// 3. `[]` system program
+    Initialize = 0,
+    /// Move lamports from any signer into the treasury.
+    ///
+    /// Data: `[amount: u64]`
+    ///
+    /// 0. `[writable]` treasury
+    /// 1. `[writable, signer]` depositor
+    /// 2. `[]` system program
+    Deposit = 1,
+    /// Pay lamports out of the treasury. VULNERABLE: the config's owner is
+    /// never checked.
+    ///
+    /// Data: `[amount: u64]`
+    ///
+    /// 0. `[]` config
+    /// 1. `[writable]` treasury
+    /// 2. `[signer]` authority
+    /// 3. `[writable]` destination
+    Withdraw = 2,
+    /// The player's fix of `Withdraw`, same accounts and data.
+    SecureWithdraw = 3,
+}
+
+impl TryFrom<u8> for VaultInstruction {
+    type Error = ProgramError;
+
+    fn try_from(tag: u8) -> Result<Self, Self::Error> {
+        match tag {
+            0 => Ok(VaultInstruction::Initialize),
+            1 => Ok(VaultInstruction::Deposit),
+            2 => Ok(VaultInstruction::Withdraw),
+            3 => Ok(VaultInstruction::SecureWithdraw),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// The `u64` amount that `Deposit`, `Withdraw` and `SecureWithdraw` carry
+pub fn unpack_amount(data: &[u8]) -> Result<u64, ProgramError> {
+    data.try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)
+}
@@ -0,0 +1,39 @@
+//! Owner-check challenge written with Pinocchio.
+//!
+//! A treasury PDA holds lamports and a config PDA names the authority allowed
+//! to withdraw them. `withdraw` checks that the authority signed and that it
+//! matches the config, but never checks that the config is owned by this
+//! program, so a config account forged by any other program works as well.
+//!
+//! Pinocchio has no account macros and no dependency on `solana-program`:
+//! accounts come in as a slice of zero-copy [`AccountInfo`]s, state is read in
+//! place from account data, and every check is an explicit call.
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult};
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
+
+pinocchio_pubkey::declare_id!("DU7eSKiD3S7BUy1fCfy3H2SUJoMeQgMF4xFdWruk3Rq5");
+
+pub const CHALLENGE_ID: &str = "pinocchio-owner-check";
+
+/// Seed of the config PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+/// Seed of the treasury PDA
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (tag, data) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    match instruction::VaultInstruction::try_from(*tag)? {
+        instruction::VaultInstruction::Initialize => processor::initialize(program_id, accounts, data),
+        instruction::VaultInstruction::Deposit => processor::deposit(accounts, data),
+        instruction::VaultInstruction::Withdraw => processor::withdraw(program_id, accounts, data),
+        instruction::VaultInstruction::SecureWithdraw => processor::secure_withdraw(program_id, accounts, data),
+    }
+}
@@ -0,0 +1,147 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+
+use crate::error::OwnerCheckError;
+use crate::instruction::unpack_amount;
+use crate::state::Config;
+use crate::{CONFIG_SEED, TREASURY_SEED};
+
+pub fn initialize(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Slice patterns bind the accounts without an iterator or any copies
+    let [config, treasury, authority, _system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [config_bump, treasury_bump] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The client passes the bumps, so one `create_program_address` per PDA is
+    // enough instead of the `find_program_address` loop
+    check_pda(config, &[CONFIG_SEED, &[*config_bump]], program_id)?;
+    check_pda(treasury, &[TREASURY_SEED, &[*treasury_bump]], program_id)?;
+
+    let rent = Rent::get()?;
+    let config_bump = [*config_bump];
+    let config_seeds = [Seed::from(CONFIG_SEED), Seed::from(&config_bump)];
+    CreateAccount {
+        from: authority,
+        to: config,
+        lamports: rent.minimum_balance(Config::LEN),
+        space: Config::LEN as u64,
+        owner: program_id,
+    }
+    .invoke_signed(&[Signer::from(&config_seeds)])?;
+    let treasury_bump = [*treasury_bump];
+    let treasury_seeds = [Seed::from(TREASURY_SEED), Seed::from(&treasury_bump)];
+    CreateAccount {
+        from: authority,
+        to: treasury,
+        lamports: rent.minimum_balance(0),
+        space: 0,
+        owner: program_id,
+    }
+    .invoke_signed(&[Signer::from(&treasury_seeds)])?;
+
+    let mut data = config.try_borrow_mut_data()?;
+    let state = Config::from_bytes_mut(&mut data)?;
+    state.discriminator = Config::DISCRIMINATOR;
+    state.treasury_bump = treasury_bump[0];
+    state.authority = *authority.key();
+    Ok(())
+}
+
+pub fn deposit(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [treasury, depositor, _system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    Transfer {
+        from: depositor,
+        to: treasury,
+        lamports: unpack_amount(data)?,
+    }
+    .invoke()
+}
+
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [config, treasury, authority, destination, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let amount = unpack_amount(data)?;
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // VULNERABLE: `config` is never checked with `is_owned_by(program_id)`.
+    // Length and discriminator match any account an attacker's program writes
+    // with the same layout and their own key as authority.
+    let config_data = config.try_borrow_data()?;
+    let state = Config::from_bytes(&config_data)?;
+    if state.authority != *authority.key() {
+        return Err(OwnerCheckError::NotAuthority.into());
+    }
+    check_pda(treasury, &[TREASURY_SEED, &[state.treasury_bump]], program_id)?;
+
+    pay_out(treasury, destination, amount)
+}
+
+#[cfg(not(feature = "solutions"))]
+pub fn secure_withdraw(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    // TODO: Implement secure withdrawal with a proper owner check
+    // Hint: start from `withdraw`; which of its accounts could be forged?
+    Err(OwnerCheckError::NotImplemented.into())
+}
+
+// SOLUTION: Built only with `--features solutions`
+#[cfg(feature = "solutions")]
+pub fn secure_withdraw(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [config, treasury, authority, destination, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let amount = unpack_amount(data)?;
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // What Anchor's `Account<'info, Config>` does for you: only this program
+    // can have written the bytes
+    if !config.is_owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let config_data = config.try_borrow_data()?;
+    let state = Config::from_bytes(&config_data)?;
+    if state.authority != *authority.key() {
+        return Err(OwnerCheckError::NotAuthority.into());
+    }
+    check_pda(treasury, &[TREASURY_SEED, &[state.treasury_bump]], program_id)?;
+
+    pay_out(treasury, destination, amount)
+}
+
+fn check_pda(account: &AccountInfo, seeds: &[&[u8]], program_id: &Pubkey) -> ProgramResult {
+    let expected = create_program_address(seeds, program_id).map_err(|_| OwnerCheckError::InvalidAddress)?;
+    if *account.key() != expected {
+        return Err(OwnerCheckError::InvalidAddress.into());
+    }
+    Ok(())
+}
+
+/// The treasury is owned by this program, so its lamports can be moved
+/// directly instead of through a system program CPI
+fn pay_out(treasury: &AccountInfo, destination: &AccountInfo, amount: u64) -> ProgramResult {
+    let available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    if amount > available {
+        return Err(OwnerCheckError::InsufficientFunds.into());
+    }
+    *treasury.try_borrow_mut_lamports()? -= amount;
+    *destination.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
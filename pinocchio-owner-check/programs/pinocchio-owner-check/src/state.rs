@@ -0,0 +1,36 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::error::OwnerCheckError;
+
+/// Config PDA data, read in place. Every field is a byte array, so the struct
+/// has alignment 1 and any account data of the right length can be viewed as
+/// one without copying.
+#[repr(C)]
+pub struct Config {
+    pub discriminator: u8,
+    pub treasury_bump: u8,
+    pub authority: Pubkey,
+}
+
+impl Config {
+    pub const DISCRIMINATOR: u8 = 1;
+    pub const LEN: usize = core::mem::size_of::<Config>();
+
+    /// View `data` as a config. Checks the length and the discriminator, which
+    /// says nothing about who wrote the bytes.
+    pub fn from_bytes(data: &[u8]) -> Result<&Self, OwnerCheckError> {
+        if data.len() != Self::LEN || data[0] != Self::DISCRIMINATOR {
+            return Err(OwnerCheckError::InvalidConfig);
+        }
+        // SAFETY: length checked above and `Config` has alignment 1
+        Ok(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    pub fn from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, OwnerCheckError> {
+        if data.len() != Self::LEN {
+            return Err(OwnerCheckError::InvalidConfig);
+        }
+        // SAFETY: length checked above and `Config` has alignment 1
+        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+    }
+}
@@ -0,0 +1,139 @@
+//! Reverse-engineering micro-challenge: a password check with no framework,
+//! no `solana-program`, and no accounts.
+//!
+//! The runtime calls `entrypoint` with a pointer to the serialized input and
+//! reads the returned `u64` as the result. With zero accounts the input is
+//!
+//! ```text
+//! u64 num_accounts (0) | u64 data_len | data | 32-byte program id
+//! ```
+//!
+//! The instruction data is one length byte followed by the password. On a
+//! match the program logs `Correct!` and sets the flag as return data. Both
+//! secrets are stored XORed with [`KEY`], so the flag and password do not show
+//! up in `strings` on the built `.so`; players get only the binary.
+//!
+//! The bug: [`check`] compares as many bytes as the length byte claims, not as
+//! many as the password has.
+
+#![cfg_attr(target_os = "solana", no_std)]
+
+use core::fmt;
+
+/// Applied to every byte of the stored password and flag
+pub const KEY: u8 = 0x5a;
+
+const fn xor<const N: usize>(mut bytes: [u8; N]) -> [u8; N] {
+    let mut i = 0;
+    while i < N {
+        bytes[i] ^= KEY;
+        i += 1;
+    }
+    bytes
+}
+
+// Evaluated at compile time: only the XORed bytes end up in the binary
+const PASSWORD: [u8; 17] = xor(*b"sbpf_is_just_ebpf");
+const FLAG: [u8; 34] = xor(*b"ctf{l3ngth_1s_4ttack3r_c0ntr0ll3d}");
+
+/// Custom error codes, in this program's 14000 range of `crates/ctf-errors`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum CrackmeError {
+    MalformedInput = 14_000,
+    WrongPassword = 14_001,
+    AccountsNotAllowed = 14_002,
+}
+
+impl fmt::Display for CrackmeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CrackmeError::MalformedInput => "Instruction data is shorter than its length byte",
+            CrackmeError::WrongPassword => "Wrong password",
+            CrackmeError::AccountsNotAllowed => "The crackme takes no accounts",
+        })
+    }
+}
+
+impl From<CrackmeError> for u32 {
+    fn from(error: CrackmeError) -> u32 {
+        error as u32
+    }
+}
+
+/// Check `data` (length byte, then password) and return the decoded flag
+#[cfg(not(feature = "solutions"))]
+pub fn check(data: &[u8]) -> Result<[u8; FLAG.len()], CrackmeError> {
+    let (&claimed, guess) = data.split_first().ok_or(CrackmeError::MalformedInput)?;
+    let claimed = claimed as usize;
+    if claimed > guess.len() || claimed > PASSWORD.len() {
+        return Err(CrackmeError::MalformedInput);
+    }
+    // VULNERABLE: the caller picks how many bytes are compared, so a length
+    // byte of 0 skips the loop entirely
+    for i in 0..claimed {
+        if guess[i] ^ KEY != PASSWORD[i] {
+            return Err(CrackmeError::WrongPassword);
+        }
+    }
+    Ok(xor(FLAG))
+}
+
+// SOLUTION: Built only with `--features solutions`
+#[cfg(feature = "solutions")]
+pub fn check(data: &[u8]) -> Result<[u8; FLAG.len()], CrackmeError> {
+    let (&claimed, guess) = data.split_first().ok_or(CrackmeError::MalformedInput)?;
+    if claimed as usize != PASSWORD.len() || guess.len() != PASSWORD.len() {
+        return Err(CrackmeError::WrongPassword);
+    }
+    if guess.iter().zip(PASSWORD).any(|(byte, expected)| byte ^ KEY != expected) {
+        return Err(CrackmeError::WrongPassword);
+    }
+    Ok(xor(FLAG))
+}
+
+/// The entrypoint and syscalls only exist on-chain
+#[cfg(target_os = "solana")]
+mod entrypoint {
+    use super::{check, CrackmeError};
+
+    extern "C" {
+        fn sol_log_(message: *const u8, len: u64);
+        fn sol_set_return_data(data: *const u8, len: u64);
+        fn abort() -> !;
+    }
+
+    fn log(message: &[u8]) {
+        unsafe { sol_log_(message.as_ptr(), message.len() as u64) }
+    }
+
+    /// # Safety
+    ///
+    /// Called by the runtime with the serialized program input.
+    #[no_mangle]
+    pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
+        let num_accounts = (input as *const u64).read_unaligned();
+        if num_accounts != 0 {
+            return u32::from(CrackmeError::AccountsNotAllowed) as u64;
+        }
+        let data_len = (input.add(8) as *const u64).read_unaligned() as usize;
+        let data = core::slice::from_raw_parts(input.add(16), data_len);
+
+        match check(data) {
+            Ok(flag) => {
+                log(b"Correct!");
+                sol_set_return_data(flag.as_ptr(), flag.len() as u64);
+                0
+            }
+            Err(error) => {
+                log(b"Wrong password");
+                u32::from(error) as u64
+            }
+        }
+    }
+
+    #[panic_handler]
+    fn panic(_info: &core::panic::PanicInfo) -> ! {
+        unsafe { abort() }
+    }
+}
@@ -1,32 +1,96 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+use ctf_registry::program::CtfRegistry;
 use std::mem::size_of;
 
+pub mod monitoring;
+pub mod shares;
+
 declare_id!("4ki5ZHnGRbx3UU5QYf8VdfRcLVMDw46Jm6aXkLvSx5Vj");
 
+pub const MAX_OWNERS: usize = 10;
+/// Seed of every vault PDA: `[VAULT_SEED, owner, vault_id]`
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const PENDING_ACTION_SEED: &[u8] = b"pending_action";
+pub const MAX_GUARDIANS: usize = 5;
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const CIRCUIT_BREAKER_SEED: &[u8] = b"circuit_breaker";
+pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
+pub const FEE_AUTHORITY_SEED: &[u8] = b"fee_authority";
+pub const MAX_FEE_BPS: u16 = 1_000;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const HEALTH_SEED: &[u8] = b"health";
+pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const DEAD_MAN_EPOCHS: u64 = 3;
+pub const ALLOWANCE_SEED: &[u8] = b"allowance";
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+pub const SOL_VAULT_SEED: &[u8] = b"sol_vault";
+pub const WITHDRAW_REQUEST_SEED: &[u8] = b"withdraw_request";
+pub const INSURANCE_AUTHORITY_SEED: &[u8] = b"insurance_authority";
+pub const INSURANCE_SHARE_BPS: u64 = 2_000;
+/// Length of the circuit breaker's rolling window, about one epoch
+pub const BREAKER_WINDOW_SLOTS: u64 = 432_000;
+/// Buckets the rolling window is counted in; outflow leaves the window one
+/// bucket at a time
+pub const BREAKER_BUCKETS: usize = 8;
+pub const BREAKER_BUCKET_SLOTS: u64 = BREAKER_WINDOW_SLOTS / BREAKER_BUCKETS as u64;
+
 #[program]
 pub mod solana_program_close {
     use super::*;
 
     /// Initialize a user vault that holds tokens
     /// RISK: If this program is accidentally closed, all vaults become inaccessible
-    pub fn initialize_vault(ctx: Context<InitializeVault>, initial_deposit: u64) -> Result<()> {
+    /// Each vault is a PDA at `["vault", owner, vault_id]` and holds one mint,
+    /// so an owner can open several vaults of different mints
+    pub fn initialize_vault(ctx: Context<InitializeVault>, vault_id: u64, initial_deposit: u64) -> Result<()> {
+        ctx.accounts.config.require_deposits_open()?;
+        ctx.accounts.config.require_within_deposit_cap(initial_deposit)?;
         let vault = &mut ctx.accounts.vault;
         vault.owner = ctx.accounts.owner.key();
+        vault.vault_id = vault_id;
+        vault.mint = ctx.accounts.mint.key();
         vault.vault_token_account = ctx.accounts.vault_token_account.key();
+        vault.share_mint = ctx.accounts.share_mint.key();
         vault.total_deposited = initial_deposit;
         vault.is_active = true;
+        vault.seeded = false;
         
         // Transfer initial deposit to vault
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.vault_token_account.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx, initial_deposit)?;
+        token_interface::transfer_checked(cpi_ctx, initial_deposit, ctx.accounts.mint.decimals)?;
+
+        // The first deposit mints shares 1:1
+        mint_shares(
+            &ctx.accounts.token_program,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.user_share_account,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            initial_deposit,
+        )?;
+
+        emit!(DepositEvent {
+            vault: ctx.accounts.vault.key(),
+            depositor: ctx.accounts.owner.key(),
+            amount: initial_deposit,
+            shares: initial_deposit,
+            vault_balance: initial_deposit,
+            slot: Clock::get()?.slot,
+        });
         
         msg!("Vault initialized with {} tokens. WARNING: Funds are now dependent on program availability!", initial_deposit);
         Ok(())
@@ -35,193 +99,3569 @@ pub mod solana_program_close {
     /// Add more funds to the vault
     /// RISK: More funds become vulnerable to program closure
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        vault.total_deposited = vault.total_deposited.checked_add(amount).unwrap();
+        ctx.accounts.config.require_deposits_open()?;
+        let total_assets = ctx.accounts.vault_token_account.amount;
+        let vault_balance = total_assets.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        ctx.accounts.config.require_within_deposit_cap(vault_balance)?;
+        let shares = shares::shares_for_deposit(amount, total_assets, ctx.accounts.share_mint.supply)
+            .ok_or(ErrorCode::MathOverflow)?;
         
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.vault_token_account.to_account_info(),
                 authority: ctx.accounts.owner.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        let vault = &mut ctx.accounts.vault;
+        vault.total_deposited = vault.total_deposited.checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        mint_shares(
+            &ctx.accounts.token_program,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.user_share_account,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            shares,
+        )?;
+
+        emit!(DepositEvent {
+            vault: ctx.accounts.vault.key(),
+            depositor: ctx.accounts.owner.key(),
+            amount,
+            shares,
+            vault_balance,
+            slot: Clock::get()?.slot,
+        });
         
-        msg!("Deposited {} tokens. Total in vault: {}", amount, vault.total_deposited);
+        msg!("Deposited {} tokens for {} shares. Total in vault: {}", amount, shares, vault_balance);
         Ok(())
     }
 
     /// Withdraw funds from vault
     /// CRITICAL: If program is closed, this function becomes unreachable!
+    /// Burns the owner's shares for `amount`, rounded up in the vault's favour,
+    /// and pays `amount` minus the withdrawal fee, which goes to the fee
+    /// collector. Fails if it would take the outflow of the circuit breaker's
+    /// rolling window over the limit.
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        let vault_account_info = ctx.accounts.vault.to_account_info();
+        ctx.accounts.config.require_withdrawals_open()?;
+        require!(
+            amount <= ctx.accounts.config.large_withdrawal_threshold,
+            ErrorCode::WithdrawalNeedsRequest
+        );
         let vault = &mut ctx.accounts.vault;
         
         require!(vault.is_active, ErrorCode::VaultInactive);
-        require!(vault.total_deposited >= amount, ErrorCode::InsufficientFunds);
+        let total_assets = ctx.accounts.vault_token_account.amount;
+        let shares = shares::shares_for_withdraw(amount, total_assets, ctx.accounts.share_mint.supply)
+            .ok_or(ErrorCode::InsufficientFunds)?;
+        require!(
+            ctx.accounts.user_share_account.amount >= shares,
+            ErrorCode::InsufficientFunds
+        );
+
+        record_withdrawal(
+            &mut ctx.accounts.circuit_breaker,
+            &ctx.accounts.config,
+            vault.key(),
+            ctx.accounts.owner.key(),
+            amount,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token_interface::burn(cpi_ctx, shares)?;
         
-        vault.total_deposited = vault.total_deposited.checked_sub(amount).unwrap();
+        let owner_key = vault.owner;
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+        let slot = Clock::get()?.slot;
+        let fee = pay_withdrawal(
+            &ctx.accounts.token_program,
+            vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.fee_collector,
+            ctx.accounts.config.withdrawal_fee_bps_at(slot),
+            amount,
+        )?;
+
+        let vault_balance = total_assets - amount;
+        emit!(WithdrawEvent {
+            vault: vault.key(),
+            owner: owner_key,
+            amount,
+            fee,
+            shares,
+            vault_balance,
+            slot,
+        });
         
+        msg!("Withdrew {} tokens ({} fee) for {} shares. Remaining: {}", amount, fee, shares, vault_balance);
+        Ok(())
+    }
+
+    /// Queue a withdrawal above `large_withdrawal_threshold`. Creates the
+    /// vault's `["withdraw_request", vault]` PDA, executable with
+    /// `execute_withdraw` once `withdrawal_delay_slots` have passed. Nothing
+    /// moves yet, so guardians have the delay to cancel it.
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.require_withdrawals_open()?;
+        require!(
+            amount > config.large_withdrawal_threshold,
+            ErrorCode::WithdrawalBelowThreshold
+        );
+        require!(ctx.accounts.vault.is_active, ErrorCode::VaultInactive);
+
+        let request = &mut ctx.accounts.withdraw_request;
+        request.vault = ctx.accounts.vault.key();
+        request.owner = ctx.accounts.owner.key();
+        request.amount = amount;
+        request.eta_slot = Clock::get()?.slot.saturating_add(config.withdrawal_delay_slots);
+        request.bump = ctx.bumps.withdraw_request;
+
+        msg!("Withdrawal of {} tokens queued until slot {}", amount, request.eta_slot);
+        Ok(())
+    }
+
+    /// Execute a queued withdrawal after its delay. Runs every check
+    /// `withdraw` does except the threshold, and closes the request to the
+    /// owner once paid. A request the circuit breaker refuses stays queued.
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>) -> Result<()> {
+        ctx.accounts.config.require_withdrawals_open()?;
+        let amount = ctx.accounts.withdraw_request.amount;
+        require!(
+            Clock::get()?.slot >= ctx.accounts.withdraw_request.eta_slot,
+            ErrorCode::TimelockNotExpired
+        );
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.is_active, ErrorCode::VaultInactive);
+        let total_assets = ctx.accounts.vault_token_account.amount;
+        let shares = shares::shares_for_withdraw(amount, total_assets, ctx.accounts.share_mint.supply)
+            .ok_or(ErrorCode::InsufficientFunds)?;
+        require!(
+            ctx.accounts.user_share_account.amount >= shares,
+            ErrorCode::InsufficientFunds
+        );
+
+        record_withdrawal(
+            &mut ctx.accounts.circuit_breaker,
+            &ctx.accounts.config,
+            vault.key(),
+            ctx.accounts.owner.key(),
+            amount,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token_interface::burn(cpi_ctx, shares)?;
+
         let owner_key = vault.owner;
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+        let slot = Clock::get()?.slot;
+        let fee = pay_withdrawal(
+            &ctx.accounts.token_program,
+            vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.fee_collector,
+            ctx.accounts.config.withdrawal_fee_bps_at(slot),
+            amount,
+        )?;
+        ctx.accounts
+            .withdraw_request
+            .close(ctx.accounts.owner.to_account_info())?;
+
+        let vault_balance = total_assets - amount;
+        emit!(WithdrawEvent {
+            vault: vault.key(),
+            owner: owner_key,
+            amount,
+            fee,
+            shares,
+            vault_balance,
+            slot,
+        });
+
+        msg!("Executed queued withdrawal of {} tokens ({} fee) for {} shares", amount, fee, shares);
+        Ok(())
+    }
+
+    /// MITIGATION: Permissionless reconciliation
+    /// Compares the vault's `total_deposited` with its real token balance and
+    /// emits `Discrepancy` when they differ, positive for a surplus (such as a
+    /// donation) and negative for a shortfall. A vault short by more than
+    /// `reconcile_tolerance` is deactivated until the admin recovers it; a
+    /// surplus is only reported, since it costs no depositor anything.
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let balance = ctx.accounts.vault_token_account.amount;
+        let delta = balance as i128 - vault.total_deposited as i128;
+        if delta == 0 {
+            msg!("RECONCILE: Vault {} holds exactly its {} deposited tokens", vault.key(), balance);
+            return Ok(());
+        }
+
+        let paused = delta < 0
+            && delta.unsigned_abs() > ctx.accounts.config.reconcile_tolerance as u128
+            && vault.is_active;
+        if paused {
+            vault.is_active = false;
+        }
+        emit!(Discrepancy {
+            vault: vault.key(),
+            total_deposited: vault.total_deposited,
+            balance,
+            delta,
+            paused,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!(
+            "RECONCILE: Vault {} holds {} against {} deposited (delta {}){}",
+            vault.key(),
+            balance,
+            vault.total_deposited,
+            delta,
+            if paused { ", vault deactivated" } else { "" }
+        );
+        Ok(())
+    }
+
+    /// Guardians can cancel a queued withdrawal during its delay. The
+    /// request's rent goes back to the owner.
+    pub fn cancel_withdraw(ctx: Context<CancelWithdraw>) -> Result<()> {
+        require!(
+            ctx.accounts.config.guardians.contains(&ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        let request = &ctx.accounts.withdraw_request;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::WithdrawalCancelled,
+            target: request.vault,
+            actor: ctx.accounts.guardian.key(),
+            amount: request.amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Guardian {} cancelled a queued withdrawal of {} tokens", ctx.accounts.guardian.key(), request.amount);
+        Ok(())
+    }
+
+    /// Deposit native SOL into the owner's `["sol_vault", owner]` PDA. The
+    /// PDA is a plain system account holding lamports, so it must end up
+    /// rent-exempt or the runtime would reject the transaction.
+    pub fn deposit_sol(ctx: Context<DepositSol>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_deposits_open()?;
+        let vault_balance = ctx.accounts.sol_vault.lamports().checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            vault_balance >= Rent::get()?.minimum_balance(0),
+            ErrorCode::SolVaultNotRentExempt
+        );
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.sol_vault.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+
+        emit!(DepositEvent {
+            vault: ctx.accounts.sol_vault.key(),
+            depositor: ctx.accounts.owner.key(),
+            amount,
+            shares: 0,
+            vault_balance,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Deposited {} lamports. Total in SOL vault: {}", amount, vault_balance);
+        Ok(())
+    }
+
+    /// Withdraw native SOL, signed by the SOL vault PDA. The rent-exempt
+    /// minimum is not withdrawable on its own: a withdrawal must either
+    /// leave the PDA rent-exempt or take every lamport, closing it.
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_withdrawals_open()?;
+        let balance = ctx.accounts.sol_vault.lamports();
+        require!(amount <= balance, ErrorCode::InsufficientFunds);
+        let vault_balance = balance - amount;
+        require!(
+            vault_balance == 0 || vault_balance >= Rent::get()?.minimum_balance(0),
+            ErrorCode::SolVaultNotRentExempt
+        );
+
+        let owner_key = ctx.accounts.owner.key();
+        let seeds = &[SOL_VAULT_SEED, owner_key.as_ref(), &[ctx.bumps.sol_vault]];
+        let signer = [&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sol_vault.to_account_info(),
+                to: ctx.accounts.owner.to_account_info(),
+            },
+            &signer,
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+
+        emit!(WithdrawEvent {
+            vault: ctx.accounts.sol_vault.key(),
+            owner: owner_key,
+            amount,
+            fee: 0,
+            shares: 0,
+            vault_balance,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Withdrew {} lamports. Remaining: {}", amount, vault_balance);
+        Ok(())
+    }
+
+    /// Let `delegate` withdraw up to `allowance` tokens from the vault until
+    /// `expiry_slot`, so a hot key can run day-to-day payouts while the owner
+    /// key stays cold. Calling it again replaces the allowance; an allowance
+    /// of zero revokes it.
+    pub fn approve_delegate(
+        ctx: Context<ApproveDelegate>,
+        delegate: Pubkey,
+        allowance: u64,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        let approval = &mut ctx.accounts.allowance;
+        approval.vault = ctx.accounts.vault.key();
+        approval.delegate = delegate;
+        approval.allowance = allowance;
+        approval.expiry_slot = expiry_slot;
+        approval.bump = ctx.bumps.allowance;
+
+        msg!(
+            "Delegate {} may withdraw {} tokens until slot {}",
+            delegate,
+            allowance,
+            expiry_slot
+        );
+        Ok(())
+    }
+
+    /// Withdraw on the owner's behalf against an allowance, to any token
+    /// account of the vault's mint. Subject to the same pause, large withdrawal
+    /// threshold, circuit breaker and fee as `withdraw`. The owner's shares are
+    /// not burned, the owner holds all of them, so each share simply redeems
+    /// less afterwards.
+    pub fn delegate_withdraw(ctx: Context<DelegateWithdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_withdrawals_open()?;
+        require!(
+            amount <= ctx.accounts.config.large_withdrawal_threshold,
+            ErrorCode::WithdrawalNeedsRequest
+        );
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.is_active, ErrorCode::VaultInactive);
+
+        let slot = Clock::get()?.slot;
+        let approval = &mut ctx.accounts.allowance;
+        require!(slot <= approval.expiry_slot, ErrorCode::AllowanceExpired);
+        require!(amount <= approval.allowance, ErrorCode::AllowanceExceeded);
+        let total_assets = ctx.accounts.vault_token_account.amount;
+        require!(amount <= total_assets, ErrorCode::InsufficientFunds);
+
+        record_withdrawal(
+            &mut ctx.accounts.circuit_breaker,
+            &ctx.accounts.config,
+            vault.key(),
+            ctx.accounts.delegate.key(),
+            amount,
+        )?;
+        approval.allowance -= amount;
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+
+        let fee = pay_withdrawal(
+            &ctx.accounts.token_program,
+            vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.destination,
+            &ctx.accounts.fee_collector,
+            ctx.accounts.config.withdrawal_fee_bps_at(slot),
+            amount,
+        )?;
+
+        let vault_balance = total_assets - amount;
+        emit!(DelegateWithdrawEvent {
+            vault: vault.key(),
+            delegate: ctx.accounts.delegate.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            fee,
+            remaining_allowance: approval.allowance,
+            vault_balance,
+            slot,
+        });
+
+        msg!(
+            "Delegate withdrew {} tokens ({} fee). Allowance left: {}",
+            amount,
+            fee,
+            approval.allowance
+        );
+        Ok(())
+    }
+
+    /// Close an empty vault and return the rent of its accounts to the owner
+    /// The token account is closed by CPI with the vault PDA as its
+    /// authority; the vault state is closed by Anchor's `close = owner`.
+    /// Leftover shares redeem nothing from an empty vault, so they are burned
+    /// and the owner's share account is closed too. The share mint can't be
+    /// closed under the classic token program and stays behind, so the
+    /// `vault_id` can never be opened again.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            ErrorCode::VaultNotEmpty
+        );
+        let rent_refunded = ctx.accounts.vault.to_account_info().lamports()
+            + ctx.accounts.vault_token_account.to_account_info().lamports()
+            + ctx.accounts.user_share_account.to_account_info().lamports();
+
+        let leftover_shares = ctx.accounts.user_share_account.amount;
+        if leftover_shares > 0 {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            );
+            token_interface::burn(cpi_ctx, leftover_shares)?;
+        }
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.user_share_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token_interface::close_account(cpi_ctx)?;
+
+        let owner_key = ctx.accounts.owner.key();
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
         let seeds = &[
-            b"vault",
+            VAULT_SEED,
             owner_key.as_ref(),
+            vault_id.as_ref(),
             &[ctx.bumps.vault],
         ];
         let signer = [&seeds[..]];
-        
+
         let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: vault_account_info,
+            CloseAccount {
+                account: ctx.accounts.vault_token_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
             },
             &signer,
         );
-        token::transfer(cpi_ctx, amount)?;
-        
-        msg!("Withdrew {} tokens. Remaining: {}", amount, vault.total_deposited);
+        token_interface::close_account(cpi_ctx)?;
+
+        emit!(VaultClosed {
+            vault: ctx.accounts.vault.key(),
+            owner: owner_key,
+            rent_refunded,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Vault closed, rent returned to {}", owner_key);
         Ok(())
     }
 
-    /// DANGEROUS: Emergency function that could be misused
-    /// This simulates functions that might exist during development/testing
-    /// that could accidentally remain in production code
-    pub fn emergency_close_vault(ctx: Context<EmergencyClose>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
-        
-        // This is where a developer might accidentally implement logic
-        // that could lead to program closure scenarios
-        require!(ctx.accounts.authority.key() == ADMIN_PUBKEY, ErrorCode::Unauthorized);
-        
-        vault.is_active = false;
-        msg!("EMERGENCY: Vault marked as inactive. This could simulate program closure effects!");
-        
-        // In a real scenario, a developer might accidentally include:
-        // - Program upgrade logic that fails
-        // - Admin functions that close the program
-        // - Deployment scripts that run in wrong environment
-        
+    /// MITIGATION: User-controlled fund recovery
+    /// Redeems all of the owner's shares, which is the vault's entire token
+    /// balance while the owner holds every share. Depends only on the owner's
+    /// signature, the vault PDA, and on-chain token balances, so it works
+    /// even while the vault is inactive, the admin multisig is unavailable,
+    /// or withdrawals are paused.
+    pub fn emergency_user_withdraw(ctx: Context<EmergencyUserWithdraw>) -> Result<()> {
+        let (shares, amount) = redeem_all_shares(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.user_share_account,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.owner,
+        )?;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::UserWithdraw,
+            target: ctx.accounts.vault.key(),
+            actor: ctx.accounts.owner.key(),
+            amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("ESCAPE HATCH: Redeemed {} shares for {} tokens", shares, amount);
         Ok(())
     }
 
-    /// MITIGATION: Recovery function that demonstrates proper safeguards
-    pub fn emergency_recover(ctx: Context<EmergencyRecover>) -> Result<()> {
-        require!(ctx.accounts.authority.key() == ADMIN_PUBKEY, ErrorCode::Unauthorized);
-        
-        let vault = &mut ctx.accounts.vault;
-        vault.is_active = true;
-        
-        msg!("RECOVERY: Vault reactivated. This demonstrates proper emergency procedures.");
+    /// Proof of life from the admin. Any single multisig owner may call it;
+    /// no proposal is needed.
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        let config = &mut ctx.accounts.config;
+        config.last_heartbeat_epoch = Clock::get()?.epoch;
+
+        msg!("Admin heartbeat in epoch {}", config.last_heartbeat_epoch);
         Ok(())
     }
-}
 
-// Hardcoded admin key for demonstration (in production, use a multisig!)
-const ADMIN_PUBKEY: Pubkey = pubkey!("11111111111111111111111111111111");
+    /// MITIGATION: Dead-man's switch
+    /// Once the admin has missed heartbeats for `DEAD_MAN_EPOCHS`, the admin
+    /// keys are presumed lost and any vault owner may redeem all of their
+    /// shares, regardless of pause and active flags.
+    pub fn self_rescue(ctx: Context<SelfRescue>) -> Result<()> {
+        let epoch = Clock::get()?.epoch;
+        require!(
+            epoch.saturating_sub(ctx.accounts.config.last_heartbeat_epoch) >= DEAD_MAN_EPOCHS,
+            ErrorCode::AdminStillActive
+        );
 
-#[derive(Accounts)]
-pub struct InitializeVault<'info> {
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + size_of::<Vault>(),
-        seeds = [b"vault", owner.key().as_ref()],
-        bump
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(
-        init,
-        payer = owner,
-        associated_token::mint = mint,
-        associated_token::authority = vault,
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    pub mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+        let (shares, amount) = redeem_all_shares(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.user_share_account,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.owner,
+        )?;
 
-#[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", owner.key().as_ref()],
-        bump,
-        has_one = owner,
-        has_one = vault_token_account
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::SelfRescue,
+            target: ctx.accounts.vault.key(),
+            actor: ctx.accounts.owner.key(),
+            amount,
+            slot: Clock::get()?.slot,
+        });
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(
-        mut,
-        seeds = [b"vault", owner.key().as_ref()],
-        bump,
-        has_one = owner,
-        has_one = vault_token_account
-    )]
-    pub vault: Account<'info, Vault>,
-    
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        msg!(
+            "SELF RESCUE: No heartbeat since epoch {}, redeemed {} shares for {} tokens",
+            ctx.accounts.config.last_heartbeat_epoch,
+            shares,
+            amount
+        );
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct EmergencyClose<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
-    
-    pub authority: Signer<'info>,
-}
+    /// MITIGATION: Incident evacuation
+    /// A guardian or multisig owner sends one vault's whole balance to its
+    /// owner's token account and deactivates it. Funds can only ever reach
+    /// the owner, so keepers need no further trust.
+    pub fn withdraw_all_for_owner(ctx: Context<WithdrawAllForOwner>) -> Result<()> {
+        require_keeper(&ctx.accounts.config, &ctx.accounts.multisig, &ctx.accounts.keeper.key())?;
+        evacuate(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_token_account,
+            ctx.accounts.keeper.key(),
+        )?;
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct EmergencyRecover<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
-    
-    pub authority: Signer<'info>,
-}
+    /// Evacuate many vaults in one transaction. Remaining accounts are
+    /// (vault, vault token account, owner token account) triples, all
+    /// writable, and every triple is validated like `withdraw_all_for_owner`.
+    pub fn batch_emergency_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchEmergencyWithdraw<'info>>,
+    ) -> Result<()> {
+        require_keeper(&ctx.accounts.config, &ctx.accounts.multisig, &ctx.accounts.keeper.key())?;
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(3),
+            ErrorCode::InvalidBatchAccounts
+        );
 
-#[account]
-pub struct Vault {
-    pub owner: Pubkey,
-    pub vault_token_account: Pubkey,
-    pub total_deposited: u64,
-    pub is_active: bool,
+        let mut evacuated = 0u64;
+        for triple in ctx.remaining_accounts.chunks(3) {
+            require!(
+                triple.iter().all(|account| account.is_writable),
+                ErrorCode::InvalidBatchAccounts
+            );
+            let mut vault = Account::<Vault>::try_from(&triple[0])?;
+            let (expected, bump) = Pubkey::find_program_address(
+                &[VAULT_SEED, vault.owner.as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(vault.key(), expected, ErrorCode::InvalidBatchAccounts);
+            require_keys_eq!(triple[1].key(), vault.vault_token_account, ErrorCode::InvalidBatchAccounts);
+            let vault_token_account = InterfaceAccount::<TokenAccount>::try_from(&triple[1])?;
+            let owner_token_account = InterfaceAccount::<TokenAccount>::try_from(&triple[2])?;
+            require!(
+                vault.mint == ctx.accounts.mint.key()
+                    && vault_token_account.mint == vault.mint
+                    && owner_token_account.owner == vault.owner
+                    && owner_token_account.mint == vault_token_account.mint,
+                ErrorCode::InvalidBatchAccounts
+            );
+
+            let amount = evacuate(
+                &ctx.accounts.token_program,
+                &mut vault,
+                bump,
+                &vault_token_account,
+                &ctx.accounts.mint,
+                &owner_token_account,
+                ctx.accounts.keeper.key(),
+            )?;
+            evacuated = evacuated.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+            vault.exit(&crate::ID)?;
+        }
+
+        msg!(
+            "EVACUATION: Returned {} tokens from {} vaults",
+            evacuated,
+            ctx.remaining_accounts.len() / 3
+        );
+        Ok(())
+    }
+
+    /// Create the admin multisig that gates emergency actions. Only the
+    /// program's upgrade authority may create it, once. Approved emergency
+    /// closes wait `timelock_slots` before they can execute.
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+        timelock_slots: u64,
+    ) -> Result<()> {
+        require!(
+            !owners.is_empty() && owners.len() <= MAX_OWNERS,
+            ErrorCode::InvalidOwners
+        );
+        for (i, owner) in owners.iter().enumerate() {
+            require!(!owners[..i].contains(owner), ErrorCode::InvalidOwners);
+        }
+        require!(
+            threshold > 0 && threshold as usize <= owners.len(),
+            ErrorCode::InvalidThreshold
+        );
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.proposal_count = 0;
+        multisig.owner_set_seqno = 0;
+        multisig.timelock_slots = timelock_slots;
+        multisig.successor = Pubkey::default();
+        multisig.successor_effective_slot = 0;
+        multisig.nominated_owner = Pubkey::default();
+        multisig.nominated_replaces = Pubkey::default();
+        multisig.bump = ctx.bumps.multisig;
+
+        msg!(
+            "Admin multisig created: {}-of-{}, {} slot timelock",
+            threshold,
+            multisig.owners.len(),
+            timelock_slots
+        );
+        Ok(())
+    }
+
+    /// Create the global config: the admin multisig, guardians who can
+    /// pause deposits, and the deposit and withdrawal limits. Only the
+    /// upgrade authority may create it, once.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        guardians: Vec<Pubkey>,
+        vault_deposit_cap: u64,
+        epoch_withdrawal_limit: u64,
+    ) -> Result<()> {
+        require!(guardians.len() <= MAX_GUARDIANS, ErrorCode::InvalidGuardians);
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(!guardians[..i].contains(guardian), ErrorCode::InvalidGuardians);
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.multisig.key();
+        config.guardians = guardians;
+        config.deposits_paused = false;
+        config.withdrawals_paused_slot = u64::MAX;
+        config.vault_deposit_cap = vault_deposit_cap;
+        config.epoch_withdrawal_limit = epoch_withdrawal_limit;
+        config.withdrawal_fee_bps = 0;
+        config.pending_fee_bps = 0;
+        config.fee_effective_slot = u64::MAX;
+        config.last_heartbeat_epoch = Clock::get()?.epoch;
+        config.frozen = false;
+        config.frozen_by = Pubkey::default();
+        config.large_withdrawal_threshold = u64::MAX;
+        config.withdrawal_delay_slots = 0;
+        config.reconcile_tolerance = u64::MAX;
+        config.bump = ctx.bumps.config;
+
+        let breaker = &mut ctx.accounts.circuit_breaker;
+        breaker.bucket = Clock::get()?.slot / BREAKER_BUCKET_SLOTS;
+        breaker.withdrawn = [0; BREAKER_BUCKETS];
+        breaker.tripped = false;
+        breaker.bump = ctx.bumps.circuit_breaker;
+
+        msg!("Config created with {} guardians", config.guardians.len());
+        Ok(())
+    }
+
+    /// Guardians can stop new deposits instantly. Only the admin can resume.
+    pub fn guardian_pause_deposits(ctx: Context<GuardianPause>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.guardians.contains(&ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        config.deposits_paused = true;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::DepositsPaused,
+            target: config.key(),
+            actor: ctx.accounts.guardian.key(),
+            amount: 0,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("PAUSE: Deposits paused by guardian {}", ctx.accounts.guardian.key());
+        Ok(())
+    }
+
+    /// CHALLENGE: Global freeze, the closure scenario without closing anything
+    /// While frozen, every path that needs `require_withdrawals_open` fails,
+    /// which strands funds exactly like a closed program would. Stage one is
+    /// to freeze it without being an owner; stage two is to get a seeded
+    /// victim's funds out anyway with `rescue_frozen_vault`.
+    /// VULNERABILITY: `authority` only has to be an owner's address; it is
+    /// never required to sign.
+    pub fn freeze_program(ctx: Context<FreezeProgram>, frozen: bool) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let config = &mut ctx.accounts.config;
+        config.frozen = frozen;
+        config.frozen_by = ctx.accounts.caller.key();
+
+        emit!(EmergencyAction {
+            kind: if frozen {
+                EmergencyActionKind::ProgramFrozen
+            } else {
+                EmergencyActionKind::ProgramUnfrozen
+            },
+            target: config.key(),
+            actor: ctx.accounts.caller.key(),
+            amount: 0,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("FREEZE: Program frozen = {} by {}", frozen, ctx.accounts.caller.key());
+        Ok(())
+    }
+
+    /// CHALLENGE: Global freeze, stage two
+    /// Whoever froze the program may evacuate a seeded victim's vault while it
+    /// stays frozen. Funds only ever reach the vault owner's token account,
+    /// exactly like `withdraw_all_for_owner`, so no signature from the victim
+    /// or a keeper is needed. Reports the solve to the CTF registry.
+    pub fn rescue_frozen_vault(ctx: Context<RescueFrozenVault>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.frozen, ErrorCode::ProgramNotFrozen);
+        require_keys_eq!(config.frozen_by, ctx.accounts.player.key(), ErrorCode::NotFrozenByPlayer);
+
+        let amount = evacuate(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_token_account,
+            ctx.accounts.player.key(),
+        )?;
+        require!(amount > 0, ErrorCode::InsufficientFunds);
+
+        ctf_registry::cpi::capture_flag(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            ctf_registry::cpi::accounts::CaptureFlag {
+                challenge: ctx.accounts.challenge.to_account_info(),
+                solve: ctx.accounts.solve.to_account_info(),
+                player: ctx.accounts.player.to_account_info(),
+                flag_authority: ctx.accounts.flag_authority.to_account_info(),
+                payer: ctx.accounts.player.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[&[ctf_registry::FLAG_AUTHORITY_SEED, &[ctx.bumps.flag_authority]]],
+        ))?;
+
+        msg!("FLAG CAPTURED: Rescued {} tokens for {} while frozen", amount, ctx.accounts.vault.owner);
+        Ok(())
+    }
+
+    /// Execute an approved SetPause proposal. Deposit changes apply at once;
+    /// pausing withdrawals only takes effect after the multisig's timelock,
+    /// so users can still get out first. Resuming withdrawals is immediate.
+    pub fn set_pause(ctx: Context<SetPause>, deposits: bool, withdrawals: bool) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::SetPause { deposits, withdrawals })?;
+
+        let config = &mut ctx.accounts.config;
+        config.deposits_paused = deposits;
+        config.withdrawals_paused_slot = if withdrawals {
+            Clock::get()?.slot.checked_add(multisig.timelock_slots).ok_or(ErrorCode::MathOverflow)?
+        } else {
+            u64::MAX
+        };
+
+        msg!(
+            "Deposits paused: {}, withdrawals paused from slot {}",
+            config.deposits_paused,
+            config.withdrawals_paused_slot
+        );
+        Ok(())
+    }
+
+    /// Execute an approved SetLimits proposal
+    pub fn set_limits(
+        ctx: Context<SetLimits>,
+        vault_deposit_cap: u64,
+        epoch_withdrawal_limit: u64,
+    ) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::SetLimits { vault_deposit_cap, epoch_withdrawal_limit },
+        )?;
+
+        let config = &mut ctx.accounts.config;
+        config.vault_deposit_cap = vault_deposit_cap;
+        config.epoch_withdrawal_limit = epoch_withdrawal_limit;
+
+        msg!(
+            "Vault deposit cap {}, epoch withdrawal limit {}",
+            vault_deposit_cap,
+            epoch_withdrawal_limit
+        );
+        Ok(())
+    }
+
+    /// Execute an approved SetWithdrawalDelay proposal. Withdrawals above
+    /// `threshold` must go through `request_withdraw` and wait
+    /// `delay_slots`; `u64::MAX` turns the queue off.
+    pub fn set_withdrawal_delay(ctx: Context<SetLimits>, threshold: u64, delay_slots: u64) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::SetWithdrawalDelay { threshold, delay_slots },
+        )?;
+
+        let config = &mut ctx.accounts.config;
+        config.large_withdrawal_threshold = threshold;
+        config.withdrawal_delay_slots = delay_slots;
+
+        msg!("Withdrawals above {} wait {} slots", threshold, delay_slots);
+        Ok(())
+    }
+
+    /// Execute an approved SetReconcileTolerance proposal. `reconcile`
+    /// deactivates any vault that is off by more than `tolerance`;
+    /// `u64::MAX` only reports.
+    pub fn set_reconcile_tolerance(ctx: Context<SetLimits>, tolerance: u64) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::SetReconcileTolerance { tolerance },
+        )?;
+        ctx.accounts.config.reconcile_tolerance = tolerance;
+
+        msg!("Reconcile tolerance {}", tolerance);
+        Ok(())
+    }
+
+    /// Execute an approved ResetCircuitBreaker proposal, re-enabling
+    /// withdrawals with an empty window
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::ResetCircuitBreaker)?;
+
+        let clock = Clock::get()?;
+        let breaker = &mut ctx.accounts.circuit_breaker;
+        breaker.bucket = clock.slot / BREAKER_BUCKET_SLOTS;
+        breaker.withdrawn = [0; BREAKER_BUCKETS];
+        breaker.tripped = false;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::CircuitBreakerReset,
+            target: breaker.key(),
+            actor: ctx.accounts.owner.key(),
+            amount: 0,
+            slot: clock.slot,
+        });
+
+        msg!("Circuit breaker reset");
+        Ok(())
+    }
+
+    /// Execute an approved SetFee proposal. The new withdrawal fee only
+    /// applies after the multisig's timelock, so users can leave at the old
+    /// rate first.
+    pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(fee_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh);
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::SetFee { fee_bps })?;
+
+        let slot = Clock::get()?.slot;
+        let config = &mut ctx.accounts.config;
+        // Settle any change that already landed before queueing the next one
+        config.withdrawal_fee_bps = config.withdrawal_fee_bps_at(slot);
+        config.pending_fee_bps = fee_bps;
+        config.fee_effective_slot = slot.checked_add(multisig.timelock_slots)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        msg!(
+            "Withdrawal fee changes from {} to {} bps at slot {}",
+            config.withdrawal_fee_bps,
+            fee_bps,
+            config.fee_effective_slot
+        );
+        Ok(())
+    }
+
+    /// Execute an approved CollectFees proposal, sweeping a fee collector's
+    /// whole balance to the proposal's destination
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::CollectFees {
+                fee_collector: ctx.accounts.fee_collector.key(),
+                destination: ctx.accounts.destination.key(),
+            },
+        )?;
+
+        let amount = ctx.accounts.fee_collector.amount;
+        let insured = (amount as u128 * INSURANCE_SHARE_BPS as u128 / BPS_DENOMINATOR as u128) as u64;
+        let seeds = &[FEE_AUTHORITY_SEED, &[ctx.bumps.fee_authority]];
+        let signer = [&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.fee_collector.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.fee_authority.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount - insured, ctx.accounts.mint.decimals)?;
+
+        if insured > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.fee_collector.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.insurance_reserve.to_account_info(),
+                    authority: ctx.accounts.fee_authority.to_account_info(),
+                },
+                &signer,
+            );
+            token_interface::transfer_checked(cpi_ctx, insured, ctx.accounts.mint.decimals)?;
+        }
+
+        emit!(FeesCollected {
+            fee_collector: ctx.accounts.fee_collector.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Collected {} tokens of fees, {} to the insurance reserve", amount, insured);
+        Ok(())
+    }
+
+    /// Top up a vault from the insurance reserve. Executes an approved
+    /// `CoverLoss` proposal, and `amount` may not exceed the vault's
+    /// shortfall, `total_deposited` minus its token balance, so the reserve
+    /// only ever restores principal that reconciliation found missing.
+    pub fn cover_loss(ctx: Context<CoverLoss>, vault: Pubkey, amount: u64) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::CoverLoss { vault, amount },
+        )?;
+
+        let shortfall = ctx
+            .accounts
+            .vault_account
+            .total_deposited
+            .saturating_sub(ctx.accounts.vault_token_account.amount);
+        require!(amount > 0 && amount <= shortfall, ErrorCode::CoverExceedsShortfall);
+
+        let seeds = &[INSURANCE_AUTHORITY_SEED, &[ctx.bumps.insurance_authority]];
+        let signer = [&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.insurance_reserve.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.insurance_authority.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::LossCovered,
+            target: vault,
+            actor: ctx.accounts.owner.key(),
+            amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("INSURANCE: Covered {} of a {} token shortfall in vault {}", amount, shortfall, vault);
+        Ok(())
+    }
+
+    /// MITIGATION: Let anyone check on-chain that no single key can close
+    /// this program. Reads the ProgramData account and records whether the
+    /// upgrade authority is the admin multisig (or gone entirely, making the
+    /// program immutable) in the `["health"]` PDA. Permissionless, so
+    /// monitoring can refresh it at any time.
+    pub fn verify_upgrade_authority(ctx: Context<VerifyUpgradeAuthority>) -> Result<()> {
+        let upgrade_authority = ctx.accounts.program_data.upgrade_authority_address;
+        let clock = Clock::get()?;
+
+        let health = &mut ctx.accounts.health;
+        health.upgrade_authority = upgrade_authority;
+        health.verified = match upgrade_authority {
+            Some(authority) => authority == ctx.accounts.multisig.key(),
+            None => true,
+        };
+        health.checked_at = clock.unix_timestamp;
+        health.checked_slot = clock.slot;
+        health.bump = ctx.bumps.health;
+
+        if health.verified {
+            msg!("HEALTH: Program cannot be closed by a single key");
+        } else {
+            msg!("HEALTH: WARNING upgrade authority {:?} is not the admin multisig", upgrade_authority);
+        }
+        Ok(())
+    }
+
+    /// MITIGATION: Liveness heartbeat
+    /// Permissionless. Records the slot and the running program version in
+    /// the `["health"]` PDA; a monitor pings on a schedule and alerts once
+    /// pings stop landing, see `monitoring::check_liveness`.
+    pub fn ping(ctx: Context<Ping>) -> Result<()> {
+        let health = &mut ctx.accounts.health;
+        health.last_ping_slot = Clock::get()?.slot;
+        health.version = PROGRAM_VERSION.to_string();
+        health.bump = ctx.bumps.health;
+
+        msg!("HEALTH: ping at slot {} from v{}", health.last_ping_slot, health.version);
+        Ok(())
+    }
+
+    /// Propose an admin action. The proposer's approval is counted.
+    pub fn propose_admin_action(ctx: Context<ProposeAdminAction>, action: AdminAction) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposer = ctx.accounts.proposer.key();
+        require!(multisig.owners.contains(&proposer), ErrorCode::Unauthorized);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = multisig.proposal_count;
+        proposal.action = action;
+        proposal.proposer = proposer;
+        proposal.approvals = vec![proposer];
+        proposal.owner_set_seqno = multisig.owner_set_seqno;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        multisig.proposal_count = multisig.proposal_count.checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        msg!("Proposal {} created: {:?}", proposal.id, proposal.action);
+        Ok(())
+    }
+
+    /// Approve an open proposal as one of the multisig owners
+    pub fn approve_admin_action(ctx: Context<ApproveAdminAction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let owner = ctx.accounts.owner.key();
+        require!(multisig.owners.contains(&owner), ErrorCode::Unauthorized);
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+        require!(
+            proposal.owner_set_seqno == multisig.owner_set_seqno,
+            ErrorCode::StaleProposal
+        );
+        require!(!proposal.approvals.contains(&owner), ErrorCode::AlreadyApproved);
+        proposal.approvals.push(owner);
+
+        msg!(
+            "Proposal {} approved by {} ({}/{})",
+            proposal.id,
+            owner,
+            proposal.approvals.len(),
+            multisig.threshold
+        );
+        Ok(())
+    }
+
+    /// Execute an approved AddOwner proposal. Open proposals are voided,
+    /// since they were approved by the old owner set.
+    pub fn add_owner(ctx: Context<AddOwner>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        let AdminAction::AddOwner { owner } = ctx.accounts.proposal.action else {
+            return err!(ErrorCode::ActionMismatch);
+        };
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::AddOwner { owner })?;
+        require!(
+            multisig.owners.len() < MAX_OWNERS && !multisig.owners.contains(&owner),
+            ErrorCode::InvalidOwners
+        );
+
+        multisig.owners.push(owner);
+        multisig.owner_set_seqno = multisig.owner_set_seqno.checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        msg!("Owner {} added, {} owners", owner, multisig.owners.len());
+        Ok(())
+    }
+
+    /// Execute an approved NominateAdmin proposal. The replacement only
+    /// happens once the nominee proves control of the key with
+    /// `accept_admin`, so a mistyped key can never take a seat.
+    pub fn nominate_admin(ctx: Context<NominateAdmin>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        let AdminAction::NominateAdmin { old_owner, new_owner } = ctx.accounts.proposal.action else {
+            return err!(ErrorCode::ActionMismatch);
+        };
+        execute_proposal(
+            multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::NominateAdmin { old_owner, new_owner },
+        )?;
+        require!(
+            multisig.owners.contains(&old_owner) && !multisig.owners.contains(&new_owner),
+            ErrorCode::InvalidOwners
+        );
+
+        multisig.nominated_owner = new_owner;
+        multisig.nominated_replaces = old_owner;
+
+        msg!("{} nominated to replace owner {}", new_owner, old_owner);
+        Ok(())
+    }
+
+    /// The nominee accepts and replaces the old owner. Open proposals are
+    /// voided, since they were approved by the old owner set.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let new_owner = ctx.accounts.new_owner.key();
+        require!(
+            multisig.nominated_owner != Pubkey::default() && multisig.nominated_owner == new_owner,
+            ErrorCode::NotNominated
+        );
+        let old_owner = multisig.nominated_replaces;
+        let Some(seat) = multisig.owners.iter().position(|owner| *owner == old_owner) else {
+            return err!(ErrorCode::InvalidOwners);
+        };
+        require!(!multisig.owners.contains(&new_owner), ErrorCode::InvalidOwners);
+
+        multisig.owners[seat] = new_owner;
+        multisig.nominated_owner = Pubkey::default();
+        multisig.nominated_replaces = Pubkey::default();
+        multisig.owner_set_seqno = multisig.owner_set_seqno.checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        msg!("Owner {} replaced by {}", old_owner, new_owner);
+        Ok(())
+    }
+
+    /// Execute an approved SetSuccessor proposal. Vaults can migrate to the
+    /// successor once the multisig's timelock has passed, so depositors who
+    /// distrust it have time to withdraw first.
+    pub fn set_successor(ctx: Context<SetSuccessor>, program_id: Pubkey) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::SetSuccessor { program_id })?;
+
+        multisig.successor = program_id;
+        multisig.successor_effective_slot = Clock::get()?
+            .slot
+            .checked_add(multisig.timelock_slots)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        msg!(
+            "Successor {} takes effect at slot {}",
+            program_id,
+            multisig.successor_effective_slot
+        );
+        Ok(())
+    }
+
+    /// MITIGATION: Migrate instead of close
+    /// Moves the vault's whole token balance into the successor program by
+    /// calling its `deposit(amount)` from the `interfaces` vault interface,
+    /// with the vault PDA as the depositing signer. The successor's deposit
+    /// accounts are passed as remaining accounts, in the order its
+    /// instruction expects.
+    pub fn migrate_vault<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateVault<'info>>) -> Result<()> {
+        ctx.accounts.config.require_withdrawals_open()?;
+        let multisig = &ctx.accounts.multisig;
+        require!(multisig.successor != Pubkey::default(), ErrorCode::NoSuccessor);
+        require!(
+            Clock::get()?.slot >= multisig.successor_effective_slot,
+            ErrorCode::SuccessorNotActive
+        );
+
+        let amount = ctx.accounts.vault_token_account.amount;
+        let vault_key = ctx.accounts.vault.key();
+        // The vault PDA signs for the successor's depositor through the seeds
+        let mut successor_accounts: Vec<AccountInfo<'info>> = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| {
+                let mut account = account.clone();
+                account.is_signer |= *account.key == vault_key;
+                account
+            })
+            .collect();
+        require!(
+            successor_accounts.len() >= 4,
+            anchor_lang::error::ErrorCode::AccountNotEnoughKeys
+        );
+        let implementation_accounts = successor_accounts.split_off(4);
+        let [successor_vault, successor_vault_token_account, mint, user_token_account] =
+            <[AccountInfo<'info>; 4]>::try_from(successor_accounts).unwrap();
+
+        let owner_key = ctx.accounts.owner.key();
+        let vault_id = ctx.accounts.vault.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            owner_key.as_ref(),
+            vault_id.as_ref(),
+            &[ctx.bumps.vault],
+        ];
+        interfaces::vault::cpi::deposit(
+            CpiContext::new_with_signer(
+                ctx.accounts.successor_program.to_account_info(),
+                interfaces::vault::cpi::accounts::Deposit {
+                    vault: successor_vault,
+                    vault_token_account: successor_vault_token_account,
+                    mint,
+                    user_token_account,
+                },
+                &[&seeds[..]],
+            )
+            .with_remaining_accounts(implementation_accounts),
+            amount,
+        )?;
+
+        ctx.accounts.vault_token_account.reload()?;
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            ErrorCode::MigrationIncomplete
+        );
+
+        // The owner's claim now lives in the successor, so the shares are void
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token_interface::burn(cpi_ctx, ctx.accounts.user_share_account.amount)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.total_deposited = 0;
+        vault.is_active = false;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::Migrated,
+            target: vault.key(),
+            actor: owner_key,
+            amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("MIGRATION: Moved {} tokens to successor {}", amount, multisig.successor);
+        Ok(())
+    }
+
+    /// Queue an approved CloseVault proposal. The close can execute once
+    /// the multisig's timelock has passed, giving depositors time to react.
+    pub fn queue_emergency_close(ctx: Context<QueueEmergencyClose>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        require!(
+            multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        let vault = ctx.accounts.vault.key();
+        execute_proposal(multisig, &mut ctx.accounts.proposal, AdminAction::CloseVault { vault })?;
+
+        let queued_slot = Clock::get()?.slot;
+        let pending = &mut ctx.accounts.pending_action;
+        pending.vault = vault;
+        pending.proposal_id = ctx.accounts.proposal.id;
+        pending.queued_slot = queued_slot;
+        pending.eta_slot = queued_slot.checked_add(multisig.timelock_slots)
+            .ok_or(ErrorCode::MathOverflow)?;
+        pending.bump = ctx.bumps.pending_action;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::CloseQueued,
+            target: vault,
+            actor: ctx.accounts.owner.key(),
+            amount: 0,
+            slot: queued_slot,
+        });
+
+        msg!(
+            "Emergency close of {} queued, executable at slot {}",
+            vault,
+            pending.eta_slot
+        );
+        Ok(())
+    }
+
+    /// Cancel a queued emergency close. Any single owner can veto.
+    pub fn cancel_emergency_close(ctx: Context<CancelEmergencyClose>) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::CloseCancelled,
+            target: ctx.accounts.pending_action.vault,
+            actor: ctx.accounts.owner.key(),
+            amount: 0,
+            slot: Clock::get()?.slot,
+        });
+        msg!(
+            "Emergency close of {} cancelled by {}",
+            ctx.accounts.pending_action.vault,
+            ctx.accounts.owner.key()
+        );
+        Ok(())
+    }
+
+    /// DANGEROUS: Emergency function that could be misused
+    /// This simulates functions that might exist during development/testing
+    /// that could accidentally remain in production code
+    /// MITIGATION: only runs as an approved CloseVault multisig proposal,
+    /// queued and past its timelock
+    pub fn emergency_close_vault(ctx: Context<EmergencyClose>) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            Clock::get()?.slot >= ctx.accounts.pending_action.eta_slot,
+            ErrorCode::TimelockNotExpired
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.is_active = false;
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::CloseExecuted,
+            target: vault.key(),
+            actor: ctx.accounts.authority.key(),
+            amount: ctx.accounts.vault_token_account.amount,
+            slot: Clock::get()?.slot,
+        });
+        msg!("EMERGENCY: Vault marked as inactive. This could simulate program closure effects!");
+        
+        // In a real scenario, a developer might accidentally include:
+        // - Program upgrade logic that fails
+        // - Admin functions that close the program
+        // - Deployment scripts that run in wrong environment
+        
+        Ok(())
+    }
+
+    /// MITIGATION: Recovery function that demonstrates proper safeguards
+    /// Runs as an approved RecoverVault multisig proposal
+    pub fn emergency_recover(ctx: Context<EmergencyRecover>) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            &ctx.accounts.multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::RecoverVault { vault: ctx.accounts.vault.key() },
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.is_active = true;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::Recovered,
+            target: vault.key(),
+            actor: ctx.accounts.authority.key(),
+            amount: ctx.accounts.vault_token_account.amount,
+            slot: Clock::get()?.slot,
+        });
+        
+        msg!("RECOVERY: Vault reactivated. This demonstrates proper emergency procedures.");
+        Ok(())
+    }
+
+    /// MITIGATION: Backups for recovery rehearsals
+    /// A guardian or multisig owner copies the vault's state into a
+    /// `["snapshot", vault, version]` PDA. Versions are chosen by the caller
+    /// and never overwritten.
+    pub fn snapshot_vault(ctx: Context<SnapshotVault>, version: u64) -> Result<()> {
+        require_keeper(&ctx.accounts.config, &ctx.accounts.multisig, &ctx.accounts.keeper.key())?;
+        let vault = &ctx.accounts.vault;
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.vault = vault.key();
+        snapshot.version = version;
+        snapshot.owner = vault.owner;
+        snapshot.vault_id = vault.vault_id;
+        snapshot.mint = vault.mint;
+        snapshot.vault_token_account = vault.vault_token_account;
+        snapshot.share_mint = vault.share_mint;
+        snapshot.total_deposited = vault.total_deposited;
+        snapshot.is_active = vault.is_active;
+        snapshot.taken_slot = Clock::get()?.slot;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        msg!("BACKUP: Snapshot {} of vault {} at slot {}", version, snapshot.vault, snapshot.taken_slot);
+        Ok(())
+    }
+
+    /// Execute an approved RestoreVault proposal, overwriting the vault's
+    /// state with a snapshot of it. Token balances are not touched.
+    pub fn restore_vault(ctx: Context<RestoreVault>, version: u64) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.owner.key()),
+            ErrorCode::Unauthorized
+        );
+        execute_proposal(
+            &ctx.accounts.multisig,
+            &mut ctx.accounts.proposal,
+            AdminAction::RestoreVault { vault: ctx.accounts.vault.key(), version },
+        )?;
+
+        let snapshot = &ctx.accounts.snapshot;
+        let vault = &mut ctx.accounts.vault;
+        vault.owner = snapshot.owner;
+        vault.vault_id = snapshot.vault_id;
+        vault.mint = snapshot.mint;
+        vault.vault_token_account = snapshot.vault_token_account;
+        vault.share_mint = snapshot.share_mint;
+        vault.total_deposited = snapshot.total_deposited;
+        vault.is_active = snapshot.is_active;
+
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::Restored,
+            target: vault.key(),
+            actor: ctx.accounts.owner.key(),
+            amount: snapshot.total_deposited,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("RESTORE: Vault {} restored from snapshot {} taken at slot {}", vault.key(), version, snapshot.taken_slot);
+        Ok(())
+    }
+
+    /// SETUP: Create a funded vault on behalf of a victim depositor
+    /// Seeds exactly one victim per call, so call it once per victim (several
+    /// calls fit in one transaction) to give exploits realistic pre-existing
+    /// deposits instead of the attacker's own funds. The victim does not need
+    /// to sign.
+    /// Any single multisig owner may seed; no proposal is needed for setup.
+    pub fn seed_scenario(ctx: Context<SeedScenario>, vault_id: u64, deposit: u64) -> Result<()> {
+        require!(
+            ctx.accounts.multisig.owners.contains(&ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.config.require_deposits_open()?;
+        ctx.accounts.config.require_within_deposit_cap(deposit)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.owner = ctx.accounts.victim.key();
+        vault.vault_id = vault_id;
+        vault.mint = ctx.accounts.mint.key();
+        vault.vault_token_account = ctx.accounts.vault_token_account.key();
+        vault.share_mint = ctx.accounts.share_mint.key();
+        vault.total_deposited = deposit;
+        vault.is_active = true;
+        vault.seeded = true;
+
+        // Deposit is funded by the admin, not the victim
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(cpi_ctx, deposit, ctx.accounts.mint.decimals)?;
+
+        // The shares belong to the victim
+        mint_shares(
+            &ctx.accounts.token_program,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.victim_share_account,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            deposit,
+        )?;
+
+        emit!(DepositEvent {
+            vault: ctx.accounts.vault.key(),
+            depositor: ctx.accounts.authority.key(),
+            amount: deposit,
+            shares: deposit,
+            vault_balance: deposit,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("SETUP: Seeded vault for {} with {} tokens", ctx.accounts.vault.owner, deposit);
+        Ok(())
+    }
+}
+
+/// Mints vault shares to `to`, signed by the vault PDA as the share mint
+/// authority
+fn mint_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    vault: &Account<'info, Vault>,
+    vault_bump: u8,
+    shares: u64,
+) -> Result<()> {
+    let vault_id = vault.vault_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        vault.owner.as_ref(),
+        vault_id.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = [&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        MintTo {
+            mint: share_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &signer,
+    );
+    token_interface::mint_to(cpi_ctx, shares)
+}
+
+/// Counts `amount` against the withdrawal limit over the last
+/// `BREAKER_WINDOW_SLOTS` slots. A withdrawal that would take the window's
+/// outflow over the limit fails without tripping anything, so one oversized
+/// request can't halt everyone. The breaker trips once the outflow actually
+/// paid reaches the limit, after this withdrawal goes through, and blocks
+/// every later one until the multisig resets it.
+fn record_withdrawal(
+    breaker: &mut CircuitBreaker,
+    config: &Config,
+    vault: Pubkey,
+    actor: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(!breaker.tripped, ErrorCode::CircuitBreakerTripped);
+    let slot = Clock::get()?.slot;
+    breaker.advance(slot / BREAKER_BUCKET_SLOTS);
+    let outflow = breaker
+        .outflow()
+        .checked_add(amount)
+        .filter(|outflow| *outflow <= config.epoch_withdrawal_limit)
+        .ok_or(ErrorCode::WithdrawalLimitExceeded)?;
+    let current = (breaker.bucket % BREAKER_BUCKETS as u64) as usize;
+    breaker.withdrawn[current] = breaker.withdrawn[current]
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    if outflow == config.epoch_withdrawal_limit {
+        breaker.tripped = true;
+        emit!(EmergencyAction {
+            kind: EmergencyActionKind::CircuitBreakerTripped,
+            target: vault,
+            actor,
+            amount,
+            slot,
+        });
+        msg!(
+            "CIRCUIT BREAKER: {} tokens withdrawn in the last {} slots reached the limit",
+            outflow,
+            BREAKER_WINDOW_SLOTS
+        );
+    }
+    Ok(())
+}
+
+/// Pays `amount` out of the vault, less the withdrawal fee, which goes to
+/// the fee collector. Signed by the vault PDA. Returns the fee.
+#[allow(clippy::too_many_arguments)]
+fn pay_withdrawal<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &Account<'info, Vault>,
+    vault_bump: u8,
+    vault_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    fee_collector: &InterfaceAccount<'info, TokenAccount>,
+    fee_bps: u16,
+    amount: u64,
+) -> Result<u64> {
+    let fee = (amount as u128 * fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+    let vault_id = vault.vault_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        vault.owner.as_ref(),
+        vault_id.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = [&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &signer,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount - fee, mint.decimals)?;
+
+    if fee > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: fee_collector.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, fee, mint.decimals)?;
+    }
+    Ok(fee)
+}
+
+/// Guardians and multisig owners may run evacuations
+fn require_keeper(config: &Config, multisig: &Multisig, keeper: &Pubkey) -> Result<()> {
+    require!(
+        config.guardians.contains(keeper) || multisig.owners.contains(keeper),
+        ErrorCode::Unauthorized
+    );
+    Ok(())
+}
+
+/// Sends a vault's whole token balance to its owner's token account and
+/// deactivates the vault. Shares stay outstanding but redeem nothing until
+/// the owner deposits again. Returns the amount moved.
+fn evacuate<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &mut Account<'info, Vault>,
+    vault_bump: u8,
+    vault_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    owner_token_account: &InterfaceAccount<'info, TokenAccount>,
+    keeper: Pubkey,
+) -> Result<u64> {
+    let amount = vault_token_account.amount;
+    let vault_id = vault.vault_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        vault.owner.as_ref(),
+        vault_id.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = [&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: owner_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &signer,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    vault.total_deposited = 0;
+    vault.is_active = false;
+
+    emit!(EmergencyAction {
+        kind: EmergencyActionKind::Evacuated,
+        target: vault.key(),
+        actor: keeper,
+        amount,
+        slot: Clock::get()?.slot,
+    });
+    Ok(amount)
+}
+
+/// Burns all of the owner's shares and pays out their value from the vault
+/// token account, signed by the vault PDA. Returns (shares, amount).
+#[allow(clippy::too_many_arguments)]
+fn redeem_all_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &mut Account<'info, Vault>,
+    vault_bump: u8,
+    vault_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    user_share_account: &InterfaceAccount<'info, TokenAccount>,
+    user_token_account: &InterfaceAccount<'info, TokenAccount>,
+    owner: &Signer<'info>,
+) -> Result<(u64, u64)> {
+    let shares = user_share_account.amount;
+    let amount = shares::assets_for_shares(shares, vault_token_account.amount, share_mint.supply)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let cpi_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        Burn {
+            mint: share_mint.to_account_info(),
+            from: user_share_account.to_account_info(),
+            authority: owner.to_account_info(),
+        },
+    );
+    token_interface::burn(cpi_ctx, shares)?;
+
+    let vault_id = vault.vault_id.to_le_bytes();
+    let seeds = &[
+        VAULT_SEED,
+        vault.owner.as_ref(),
+        vault_id.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = [&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: user_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        &signer,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+    Ok((shares, amount))
+}
+
+/// Address of an owner's vault
+pub fn vault_address(owner: &Pubkey, vault_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_SEED, owner.as_ref(), vault_id.to_le_bytes().as_ref()], &ID).0
+}
+
+/// Address of the `Health` PDA
+pub fn health_address() -> Pubkey {
+    Pubkey::find_program_address(&[HEALTH_SEED], &ID).0
+}
+
+/// Address of a vault's share mint
+pub fn share_mint_address(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SHARE_MINT_SEED, vault.as_ref()], &ID).0
+}
+
+/// Checks an approved proposal matches the action being executed and marks
+/// it executed so it cannot be replayed
+fn execute_proposal(multisig: &Multisig, proposal: &mut Proposal, action: AdminAction) -> Result<()> {
+    require!(proposal.action == action, ErrorCode::ActionMismatch);
+    require!(!proposal.executed, ErrorCode::AlreadyExecuted);
+    require!(
+        proposal.owner_set_seqno == multisig.owner_set_seqno,
+        ErrorCode::StaleProposal
+    );
+    require!(
+        proposal.approvals.len() >= multisig.threshold as usize,
+        ErrorCode::NotEnoughApprovals
+    );
+    proposal.executed = true;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct InitializeVault<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + size_of::<Vault>(),
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = share_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [CIRCUIT_BREAKER_SEED],
+        bump = circuit_breaker.bump,
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    /// CHECK: PDA that owns every fee collector token account
+    #[account(seeds = [FEE_AUTHORITY_SEED], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = fee_authority,
+    )]
+    pub fee_collector: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + WithdrawRequest::INIT_SPACE,
+        seeds = [WITHDRAW_REQUEST_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [WITHDRAW_REQUEST_SEED, vault.key().as_ref()],
+        bump = withdraw_request.bump,
+        has_one = vault,
+        has_one = owner
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [CIRCUIT_BREAKER_SEED],
+        bump = circuit_breaker.bump,
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    /// CHECK: PDA that owns every fee collector token account
+    #[account(seeds = [FEE_AUTHORITY_SEED], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = fee_authority,
+    )]
+    pub fee_collector: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(mut, has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdraw<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [WITHDRAW_REQUEST_SEED, withdraw_request.vault.as_ref()],
+        bump = withdraw_request.bump,
+        has_one = owner
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// CHECK: Receives the request's rent, checked against `withdraw_request.owner`
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    /// System-owned PDA that only holds lamports
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct ApproveDelegate<'info> {
+    #[account(
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Allowance::INIT_SPACE,
+        seeds = [ALLOWANCE_SEED, vault.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.owner.as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = vault_token_account
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [ALLOWANCE_SEED, vault.key().as_ref(), delegate.key().as_ref()],
+        bump = allowance.bump,
+        has_one = vault,
+        has_one = delegate
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = vault.mint)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [CIRCUIT_BREAKER_SEED],
+        bump = circuit_breaker.bump,
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    /// CHECK: PDA that owns every fee collector token account
+    #[account(seeds = [FEE_AUTHORITY_SEED], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = fee_authority,
+    )]
+    pub fee_collector: InterfaceAccount<'info, TokenAccount>,
+
+    pub delegate: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyUserWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SelfRescue<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAllForOwner<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.owner.as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = vault_token_account
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = vault.owner,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    pub keeper: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BatchEmergencyWithdraw<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// Every vault in the batch must hold this mint
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub keeper: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [MULTISIG_SEED],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SolanaProgramClose>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CircuitBreaker::INIT_SPACE,
+        seeds = [CIRCUIT_BREAKER_SEED],
+        bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SolanaProgramClose>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeProgram<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: VULNERABLE - compared against the owners but never checked as a signer
+    pub authority: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RescueFrozenVault<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.owner.as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = vault_token_account,
+        constraint = vault.seeded @ ErrorCode::NotSeededVault
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = vault_token_account.mint,
+        token::authority = vault.owner,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Validated by the registry
+    #[account(mut)]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the registry
+    #[account(mut)]
+    pub solve: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the registry CPI, holds no data
+    #[account(seeds = [ctf_registry::FLAG_AUTHORITY_SEED], bump)]
+    pub flag_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub registry_program: Program<'info, CtfRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLimits<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [CIRCUIT_BREAKER_SEED],
+        bump = circuit_breaker.bump,
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: PDA that owns every fee collector token account
+    #[account(seeds = [FEE_AUTHORITY_SEED], bump)]
+    pub fee_authority: UncheckedAccount<'info>,
+
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_collector: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = fee_collector.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every insurance reserve token account
+    #[account(seeds = [INSURANCE_AUTHORITY_SEED], bump)]
+    pub insurance_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = fee_collector.mint,
+        token::authority = insurance_authority,
+    )]
+    pub insurance_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault: Pubkey)]
+pub struct CoverLoss<'info> {
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        address = vault,
+        has_one = vault_token_account,
+        has_one = mint
+    )]
+    pub vault_account: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA that owns every insurance reserve token account
+    #[account(seeds = [INSURANCE_AUTHORITY_SEED], bump)]
+    pub insurance_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = insurance_authority,
+    )]
+    pub insurance_reserve: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyUpgradeAuthority<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Health::INIT_SPACE,
+        seeds = [HEALTH_SEED],
+        bump
+    )]
+    pub health: Account<'info, Health>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SolanaProgramClose>,
+
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Ping<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Health::INIT_SPACE,
+        seeds = [HEALTH_SEED],
+        bump
+    )]
+    pub health: Account<'info, Health>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [PROPOSAL_SEED, multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAdminAction<'info> {
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddOwner<'info> {
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct NominateAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSuccessor<'info> {
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, owner.key().as_ref(), vault.vault_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = vault_token_account,
+        has_one = share_mint
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = owner,
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Must be the successor recorded in the multisig
+    #[account(executable, address = multisig.successor @ ErrorCode::NoSuccessor)]
+    pub successor_program: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct QueueEmergencyClose<'info> {
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + PendingAction::INIT_SPACE,
+        seeds = [PENDING_ACTION_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEmergencyClose<'info> {
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [PENDING_ACTION_SEED, pending_action.vault.as_ref()],
+        bump = pending_action.bump,
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyClose<'info> {
+    #[account(mut, has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_ACTION_SEED, vault.key().as_ref()],
+        bump = pending_action.bump,
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyRecover<'info> {
+    #[account(mut, has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(version: u64)]
+pub struct SnapshotVault<'info> {
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + VaultSnapshot::INIT_SPACE,
+        seeds = [SNAPSHOT_SEED, vault.key().as_ref(), version.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(version: u64)]
+pub struct RestoreVault<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [SNAPSHOT_SEED, vault.key().as_ref(), version.to_le_bytes().as_ref()],
+        bump = snapshot.bump,
+        has_one = vault
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct SeedScenario<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + size_of::<Vault>(),
+        seeds = [VAULT_SEED, victim.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SHARE_MINT_SEED, vault.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = share_mint,
+        associated_token::authority = victim,
+        associated_token::token_program = token_program,
+    )]
+    pub victim_share_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Victim depositor, only used as the vault owner, PDA seed and share holder
+    pub victim: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [MULTISIG_SEED],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub vault_id: u64, // Lets one owner hold several vaults, one PDA each
+    pub mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub share_mint: Pubkey, // Shares are the depositors' claim, not a balance stored here
+    pub total_deposited: u64, // Net principal, only for reconciling against the token balance
+    pub is_active: bool,
+    pub seeded: bool, // Created by seed_scenario, the only vaults the freeze challenge pays out for
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey, // The admin multisig PDA
+    #[max_len(MAX_GUARDIANS)]
+    pub guardians: Vec<Pubkey>,
+    pub deposits_paused: bool,
+    pub withdrawals_paused_slot: u64, // Withdrawals stop from this slot, u64::MAX when unpaused
+    pub vault_deposit_cap: u64,       // Most a single vault may hold
+    pub epoch_withdrawal_limit: u64,  // Most all vaults together may pay out per BREAKER_WINDOW_SLOTS
+    pub withdrawal_fee_bps: u16,
+    pub pending_fee_bps: u16,         // Replaces withdrawal_fee_bps from fee_effective_slot
+    pub fee_effective_slot: u64,      // u64::MAX when no change is pending
+    pub last_heartbeat_epoch: u64,    // Vault owners may self_rescue DEAD_MAN_EPOCHS after this
+    pub frozen: bool,                 // Blocks every withdrawal path except the escape hatches
+    pub frozen_by: Pubkey,            // Signer of the last freeze_program call
+    pub large_withdrawal_threshold: u64, // Larger withdrawals must be queued, u64::MAX when off
+    pub withdrawal_delay_slots: u64,  // Wait between request_withdraw and execute_withdraw
+    pub reconcile_tolerance: u64,     // reconcile deactivates vaults off by more, u64::MAX never
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn require_deposits_open(&self) -> Result<()> {
+        require!(!self.deposits_paused, ErrorCode::DepositsPaused);
+        Ok(())
+    }
+
+    pub fn require_within_deposit_cap(&self, vault_balance: u64) -> Result<()> {
+        require!(
+            vault_balance <= self.vault_deposit_cap,
+            ErrorCode::DepositCapExceeded
+        );
+        Ok(())
+    }
+
+    /// The withdrawal fee in force at `slot`, counting a pending change
+    /// once its timelock has passed
+    pub fn withdrawal_fee_bps_at(&self, slot: u64) -> u16 {
+        if slot >= self.fee_effective_slot {
+            self.pending_fee_bps
+        } else {
+            self.withdrawal_fee_bps
+        }
+    }
+
+    pub fn require_withdrawals_open(&self) -> Result<()> {
+        require!(!self.frozen, ErrorCode::ProgramFrozen);
+        require!(
+            Clock::get()?.slot < self.withdrawals_paused_slot,
+            ErrorCode::WithdrawalsPaused
+        );
+        Ok(())
+    }
+}
+
+/// Tracks withdrawals across all vaults over the last
+/// `BREAKER_WINDOW_SLOTS` slots, in `BREAKER_BUCKETS` buckets
+#[account]
+#[derive(InitSpace)]
+pub struct CircuitBreaker {
+    pub bucket: u64,                       // Newest bucket, slot / BREAKER_BUCKET_SLOTS
+    pub withdrawn: [u64; BREAKER_BUCKETS], // Paid out per bucket, indexed by bucket % BREAKER_BUCKETS
+    pub tripped: bool,                     // Blocks withdraw until the admin resets it
+    pub bump: u8,
+}
+
+impl CircuitBreaker {
+    /// Move the window forward to `bucket`, clearing the buckets that fell
+    /// out of it
+    pub fn advance(&mut self, bucket: u64) {
+        let stale = bucket.saturating_sub(self.bucket).min(BREAKER_BUCKETS as u64);
+        for index in 1..=stale {
+            self.withdrawn[((self.bucket + index) % BREAKER_BUCKETS as u64) as usize] = 0;
+        }
+        self.bucket = self.bucket.max(bucket);
+    }
+
+    /// Total paid out within the window
+    pub fn outflow(&self) -> u64 {
+        self.withdrawn.iter().sum()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    #[max_len(MAX_OWNERS)]
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub owner_set_seqno: u32, // Bumped when owners change, voiding open proposals
+    pub timelock_slots: u64,  // Delay between queueing and executing an emergency close
+    pub successor: Pubkey,    // Default pubkey until a successor is set
+    pub successor_effective_slot: u64,
+    pub nominated_owner: Pubkey,    // Default pubkey when nobody is nominated
+    pub nominated_replaces: Pubkey, // The owner the nominee takes over from
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AdminAction {
+    CloseVault { vault: Pubkey },
+    RecoverVault { vault: Pubkey },
+    AddOwner { owner: Pubkey },
+    SetSuccessor { program_id: Pubkey },
+    SetPause { deposits: bool, withdrawals: bool },
+    SetLimits { vault_deposit_cap: u64, epoch_withdrawal_limit: u64 },
+    ResetCircuitBreaker,
+    SetFee { fee_bps: u16 },
+    CollectFees { fee_collector: Pubkey, destination: Pubkey },
+    NominateAdmin { old_owner: Pubkey, new_owner: Pubkey },
+    CoverLoss { vault: Pubkey, amount: u64 },
+    RestoreVault { vault: Pubkey, version: u64 },
+    SetWithdrawalDelay { threshold: u64, delay_slots: u64 },
+    SetReconcileTolerance { tolerance: u64 },
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub id: u64,
+    pub action: AdminAction,
+    pub proposer: Pubkey,
+    #[max_len(MAX_OWNERS)]
+    pub approvals: Vec<Pubkey>,
+    pub owner_set_seqno: u32,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+/// Result of the last `verify_upgrade_authority` check and the last `ping`
+#[account]
+#[derive(InitSpace)]
+pub struct Health {
+    pub upgrade_authority: Option<Pubkey>, // None once the program is immutable
+    pub verified: bool,                    // Authority is the admin multisig, or there is none
+    pub checked_at: i64,                   // Unix timestamp of the check
+    pub checked_slot: u64,
+    pub last_ping_slot: u64,               // 0 until the first ping
+    #[max_len(16)]
+    pub version: String,                   // PROGRAM_VERSION at the last ping
+    pub bump: u8,
+}
+
+/// A copy of a vault's state taken by `snapshot_vault`
+#[account]
+#[derive(InitSpace)]
+pub struct VaultSnapshot {
+    pub vault: Pubkey,
+    pub version: u64,
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub share_mint: Pubkey,
+    pub total_deposited: u64,
+    pub is_active: bool,
+    pub taken_slot: u64,
+    pub bump: u8,
+}
+
+/// A queued large withdrawal, one per vault
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawRequest {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub eta_slot: u64, // execute_withdraw is valid from this slot
+    pub bump: u8,
+}
+
+/// What a delegate may still withdraw from one vault
+#[account]
+#[derive(InitSpace)]
+pub struct Allowance {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub allowance: u64,   // Remaining, decremented by each delegate_withdraw
+    pub expiry_slot: u64, // Last slot the allowance can be used
+    pub bump: u8,
+}
+
+/// A queued emergency close, one per vault
+#[account]
+#[derive(InitSpace)]
+pub struct PendingAction {
+    pub vault: Pubkey,
+    pub proposal_id: u64,
+    pub queued_slot: u64,
+    pub eta_slot: u64,
+    pub bump: u8,
+}
+
+/// Tokens entering a vault, from a deposit, vault initialization or setup
+#[event]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub vault_balance: u64,
+    pub slot: u64,
+}
+
+/// Tokens leaving a vault through a normal withdraw
+#[event]
+pub struct WithdrawEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub shares: u64,
+    pub vault_balance: u64,
+    pub slot: u64,
+}
+
+/// Tokens leaving a vault through a delegate's allowance
+#[event]
+pub struct DelegateWithdrawEvent {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub remaining_allowance: u64,
+    pub vault_balance: u64,
+    pub slot: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmergencyActionKind {
+    CloseQueued,
+    CloseCancelled,
+    CloseExecuted,
+    Recovered,
+    UserWithdraw,
+    SelfRescue,
+    Evacuated,
+    Migrated,
+    CircuitBreakerTripped,
+    CircuitBreakerReset,
+    DepositsPaused,
+    LossCovered,
+    ProgramFrozen,
+    ProgramUnfrozen,
+    Restored,
+    WithdrawalCancelled,
+}
+
+/// Any admin, guardian or escape-hatch action outside normal operation.
+/// `target` is the affected vault, or the config / circuit breaker account
+/// for program-wide actions
+#[event]
+pub struct EmergencyAction {
+    pub kind: EmergencyActionKind,
+    pub target: Pubkey,
+    pub actor: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// Withdrawal fees swept out of a fee collector
+#[event]
+pub struct FeesCollected {
+    pub fee_collector: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// A vault whose token balance differs from its `total_deposited`.
+/// `delta` is balance minus total_deposited
+#[event]
+pub struct Discrepancy {
+    pub vault: Pubkey,
+    pub total_deposited: u64,
+    pub balance: u64,
+    pub delta: i128,
+    pub paused: bool,
+    pub slot: u64,
+}
+
+/// A vault and its token account closed by the owner
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub rent_refunded: u64,
+    pub slot: u64,
 }
 
-#[error_code]
+// Codes 8000-8999 are reserved for this program in crates/ctf-errors
+#[error_code(offset = 8000)]
 pub enum ErrorCode {
     #[msg("Vault is inactive")]
     VaultInactive,
@@ -229,6 +3669,72 @@ pub enum ErrorCode {
     InsufficientFunds,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Owners must be non-empty, unique, and at most MAX_OWNERS")]
+    InvalidOwners,
+    #[msg("Threshold must be between 1 and the number of owners")]
+    InvalidThreshold,
+    #[msg("Not enough owner approvals")]
+    NotEnoughApprovals,
+    #[msg("Owner already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal was already executed")]
+    AlreadyExecuted,
+    #[msg("Proposal predates a change to the owner set")]
+    StaleProposal,
+    #[msg("Proposal is for a different action")]
+    ActionMismatch,
+    #[msg("Timelock has not expired")]
+    TimelockNotExpired,
+    #[msg("No successor program has been set")]
+    NoSuccessor,
+    #[msg("Successor program is not active yet")]
+    SuccessorNotActive,
+    #[msg("Successor did not take the vault's whole balance")]
+    MigrationIncomplete,
+    #[msg("Guardians must be unique and at most MAX_GUARDIANS")]
+    InvalidGuardians,
+    #[msg("Deposits are paused")]
+    DepositsPaused,
+    #[msg("Withdrawals are paused")]
+    WithdrawalsPaused,
+    #[msg("Vault still holds tokens")]
+    VaultNotEmpty,
+    #[msg("Deposit would exceed the vault deposit cap")]
+    DepositCapExceeded,
+    #[msg("Circuit breaker is tripped")]
+    CircuitBreakerTripped,
+    #[msg("Fee exceeds the maximum")]
+    FeeTooHigh,
+    #[msg("Signer is not the nominated admin")]
+    NotNominated,
+    #[msg("Admin heartbeat is too recent for self rescue")]
+    AdminStillActive,
+    #[msg("Batch accounts must be valid (vault, vault token account, owner token account) triples")]
+    InvalidBatchAccounts,
+    #[msg("Withdrawal exceeds the delegate's allowance")]
+    AllowanceExceeded,
+    #[msg("Delegate allowance has expired")]
+    AllowanceExpired,
+    #[msg("Cover amount must be positive and at most the vault's shortfall")]
+    CoverExceedsShortfall,
+    #[msg("Program is frozen")]
+    ProgramFrozen,
+    #[msg("SOL vault must stay rent-exempt or be emptied")]
+    SolVaultNotRentExempt,
+    #[msg("Withdrawal is above the threshold and must be queued with request_withdraw")]
+    WithdrawalNeedsRequest,
+    #[msg("Withdrawal is not above the threshold, use withdraw")]
+    WithdrawalBelowThreshold,
+    #[msg("Program is not frozen")]
+    ProgramNotFrozen,
+    #[msg("Only whoever froze the program can rescue through the freeze")]
+    NotFrozenByPlayer,
+    #[msg("Only vaults seeded with seed_scenario can be rescued")]
+    NotSeededVault,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Withdrawal would exceed the rolling withdrawal limit")]
+    WithdrawalLimitExceeded,
 }
 
 /* 
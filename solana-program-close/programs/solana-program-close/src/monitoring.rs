@@ -0,0 +1,49 @@
+//! Client-side liveness check for monitoring bots
+//!
+//! A monitor calls `ping` on a schedule, then fetches this program's account
+//! and the `Health` PDA (at `health_address()`) and passes them to
+//! [`check_liveness`]. Anything other than [`Liveness::Alive`] should page
+//! someone: a closed program strands every vault, and silent pings mean
+//! transactions are no longer landing.
+
+use crate::Health;
+
+/// What a monitor should report about the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Liveness {
+    /// The last ping landed within the allowed window
+    Alive { last_ping_slot: u64, version: String },
+    /// No ping has landed for `slots_silent` slots, or none ever has
+    Silent { last_ping_slot: u64, slots_silent: u64 },
+    /// The program account is gone or no longer executable
+    Closed,
+}
+
+impl Liveness {
+    pub fn is_alert(&self) -> bool {
+        !matches!(self, Liveness::Alive { .. })
+    }
+}
+
+/// Classifies the program's liveness from freshly fetched state.
+/// `program_executable` is whether the program account exists and is
+/// executable; `health` is the decoded `Health` PDA, if it exists.
+pub fn check_liveness(
+    program_executable: bool,
+    health: Option<&Health>,
+    current_slot: u64,
+    max_silent_slots: u64,
+) -> Liveness {
+    if !program_executable {
+        return Liveness::Closed;
+    }
+    let last_ping_slot = health.map_or(0, |health| health.last_ping_slot);
+    let slots_silent = current_slot.saturating_sub(last_ping_slot);
+    match health {
+        Some(health) if last_ping_slot > 0 && slots_silent <= max_silent_slots => Liveness::Alive {
+            last_ping_slot,
+            version: health.version.clone(),
+        },
+        _ => Liveness::Silent { last_ping_slot, slots_silent },
+    }
+}
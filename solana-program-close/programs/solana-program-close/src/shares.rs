@@ -0,0 +1,43 @@
+//! Share math for vaults that mint SPL share tokens against their deposits
+//!
+//! A vault's shares are a pro-rata claim on its token balance. Conversions
+//! work in u128 and always round in the vault's favour: deposits mint shares
+//! rounded down, withdrawals burn shares rounded up, so no sequence of
+//! deposits and withdrawals can drain more than was put in.
+
+/// Shares to mint for depositing `amount` into a vault holding `total_assets`
+/// backed by `total_shares`. The first deposit, or any deposit into a vault
+/// that has no assets or no shares left, mints 1:1.
+pub fn shares_for_deposit(amount: u64, total_assets: u64, total_shares: u64) -> Option<u64> {
+    if total_assets == 0 || total_shares == 0 {
+        return Some(amount);
+    }
+    let shares = (amount as u128)
+        .checked_mul(total_shares as u128)?
+        .checked_div(total_assets as u128)?;
+    u64::try_from(shares).ok()
+}
+
+/// Shares to burn for withdrawing exactly `amount`, rounded up
+pub fn shares_for_withdraw(amount: u64, total_assets: u64, total_shares: u64) -> Option<u64> {
+    if amount > total_assets {
+        return None;
+    }
+    if amount == 0 {
+        return Some(0);
+    }
+    let numerator = (amount as u128).checked_mul(total_shares as u128)?;
+    let shares = numerator.checked_add(total_assets as u128 - 1)?.checked_div(total_assets as u128)?;
+    u64::try_from(shares).ok()
+}
+
+/// Tokens that burning `shares` redeems, rounded down
+pub fn assets_for_shares(shares: u64, total_assets: u64, total_shares: u64) -> Option<u64> {
+    if total_shares == 0 {
+        return Some(0);
+    }
+    let assets = (shares as u128)
+        .checked_mul(total_assets as u128)?
+        .checked_div(total_shares as u128)?;
+    u64::try_from(assets).ok()
+}
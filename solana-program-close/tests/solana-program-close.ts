@@ -1,9 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SolanaProgramClose } from "../target/types/solana_program_close";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
+import { PublicKey, Keypair, SystemProgram, sendAndConfirmTransaction } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, transfer, getAccount, getAssociatedTokenAddress } from "@solana/spl-token";
 import { expect } from "chai";
+import { readFileSync } from "fs";
 
 describe("Accidental Program Closure Vulnerability", () => {
   // Configure the client to use the local cluster
@@ -17,6 +18,53 @@ describe("Accidental Program Closure Vulnerability", () => {
   let vault: PublicKey;
   let vaultTokenAccount: PublicKey;
   let vaultBump: number;
+  let shareMint: PublicKey;
+  let userShareAccount: PublicKey;
+  let multisig: PublicKey;
+  let config: PublicKey;
+  let circuitBreaker: PublicKey;
+  let feeAuthority: PublicKey;
+  let feeCollector: PublicKey;
+  let insuranceAuthority: PublicKey;
+  let insuranceReserve: PublicKey;
+  const VAULT_DEPOSIT_CAP = 200000000; // 200 tokens
+  const EPOCH_WITHDRAWAL_LIMIT = 50000000; // 50 tokens
+  let guardian: Keypair;
+  let admin: Keypair;
+  const wallet = (anchor.getProvider() as anchor.AnchorProvider).wallet;
+
+  const TIMELOCK_SLOTS = 4;
+  const pendingAction = (vaultKey: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_action"), vaultKey.toBuffer()],
+      program.programId
+    )[0];
+
+  const vaultAddress = (owner: PublicKey, vaultId: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), owner.toBuffer(), new anchor.BN(vaultId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+  const proposalAddress = (id: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const vaultBalance = async () =>
+    Number((await getAccount(provider.connection, vaultTokenAccount)).amount);
+  const shareBalance = async () =>
+    Number((await getAccount(provider.connection, userShareAccount)).amount);
+
+  const events = async (signature: string, name: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx.meta.logMessages)].filter((event) => event.name === name);
+  };
 
   before(async () => {
     // Create user keypair
@@ -63,10 +111,7 @@ describe("Accidental Program Closure Vulnerability", () => {
     );
 
     // Derive PDA for vault
-    [vault, vaultBump] = PublicKey.findProgramAddressSync(
-      [Buffer.from("vault"), user.publicKey.toBuffer()],
-      program.programId
-    );
+    [vault, vaultBump] = vaultAddress(user.publicKey, 0);
 
     // Derive the associated token account for the vault
     vaultTokenAccount = await getAssociatedTokenAddress(
@@ -75,6 +120,62 @@ describe("Accidental Program Closure Vulnerability", () => {
       true // allowOwnerOffCurve - required for PDAs
     );
 
+    // Vault shares: a PDA mint per vault, held in the owner's associated token account
+    [shareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), vault.toBuffer()],
+      program.programId
+    );
+    userShareAccount = await getAssociatedTokenAddress(shareMint, user.publicKey);
+
+    // 2-of-2 admin multisig: the deploying wallet (the upgrade authority) plus a second admin
+    admin = Keypair.generate();
+    [multisig] = PublicKey.findProgramAddressSync([Buffer.from("multisig")], program.programId);
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    await program.methods
+      .createMultisig([wallet.publicKey, admin.publicKey], 2, new anchor.BN(TIMELOCK_SLOTS))
+      .accounts({
+        multisig,
+        program: program.programId,
+        programData,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Global config: the multisig is the admin, one guardian can pause deposits, plus limits
+    guardian = Keypair.generate();
+    [config] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    [circuitBreaker] = PublicKey.findProgramAddressSync(
+      [Buffer.from("circuit_breaker")],
+      program.programId
+    );
+    await program.methods
+      .initializeConfig(
+        [guardian.publicKey],
+        new anchor.BN(VAULT_DEPOSIT_CAP),
+        new anchor.BN(EPOCH_WITHDRAWAL_LIMIT)
+      )
+      .accounts({
+        config,
+        circuitBreaker,
+        multisig,
+        program: program.programId,
+        programData,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Withdrawal fees land in a token account owned by the fee authority PDA
+    [feeAuthority] = PublicKey.findProgramAddressSync([Buffer.from("fee_authority")], program.programId);
+    feeCollector = await createAccount(provider.connection, user, mint, feeAuthority, Keypair.generate());
+    // A slice of collected fees funds the insurance reserve
+    [insuranceAuthority] = PublicKey.findProgramAddressSync([Buffer.from("insurance_authority")], program.programId);
+    insuranceReserve = await createAccount(provider.connection, user, mint, insuranceAuthority, Keypair.generate());
+
     console.log(`User: ${user.publicKey.toString()}`);
     console.log(`Mint: ${mint.toString()}`);
     console.log(`User Token Account: ${userTokenAccount.toString()}`);
@@ -90,12 +191,15 @@ describe("Accidental Program Closure Vulnerability", () => {
 
     try {
       const tx = await program.methods
-        .initializeVault(initialDeposit)
+        .initializeVault(new anchor.BN(0), initialDeposit)
         .accounts({
           vault: vault,
           vaultTokenAccount: vaultTokenAccount,
           userTokenAccount: userTokenAccount,
           mint: mint,
+          shareMint,
+          userShareAccount,
+          config,
           owner: user.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -108,14 +212,16 @@ describe("Accidental Program Closure Vulnerability", () => {
       console.log("✅ Vault initialized successfully");
       console.log(`Transaction: ${tx}`);
 
-      // Verify the vault state
+      // Verify the vault state; the first deposit mints shares 1:1
       const vaultAccount = await program.account.vault.fetch(vault);
-      expect(vaultAccount.totalDeposited.toNumber()).to.equal(initialDeposit.toNumber());
+      expect(await vaultBalance()).to.equal(initialDeposit.toNumber());
+      expect(await shareBalance()).to.equal(initialDeposit.toNumber());
       expect(vaultAccount.isActive).to.be.true;
       expect(vaultAccount.owner.toString()).to.equal(user.publicKey.toString());
       expect(vaultAccount.vaultTokenAccount.toString()).to.equal(vaultTokenAccount.toString());
+      expect(vaultAccount.shareMint.toString()).to.equal(shareMint.toString());
 
-      console.log(`Vault contains: ${vaultAccount.totalDeposited.toNumber() / 1000000} tokens`);
+      console.log(`Vault contains: ${(await vaultBalance()) / 1000000} tokens`);
       console.log(`Vault token account: ${vaultTokenAccount.toString()}`);
     } catch (error) {
       console.error("❌ Failed to initialize vault:", error);
@@ -134,7 +240,11 @@ describe("Accidental Program Closure Vulnerability", () => {
       .accounts({
         vault: vault,
         vaultTokenAccount: vaultTokenAccount,
+        mint,
         userTokenAccount: userTokenAccount,
+        shareMint,
+        userShareAccount,
+        config,
         owner: user.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -144,10 +254,10 @@ describe("Accidental Program Closure Vulnerability", () => {
     console.log("✅ Additional deposit successful");
 
     // Verify updated vault state
-    const vaultAccount = await program.account.vault.fetch(vault);
-    console.log(`Total vault balance: ${vaultAccount.totalDeposited.toNumber() / 1000000} tokens`);
+    console.log(`Total vault balance: ${(await vaultBalance()) / 1000000} tokens`);
     
-    expect(vaultAccount.totalDeposited.toNumber()).to.equal(150000000); // 150 tokens total
+    expect(await vaultBalance()).to.equal(150000000); // 150 tokens total
+    expect(await shareBalance()).to.equal(150000000); // still 1 share per token
   });
 
   it("3. Demonstrate normal withdrawal (before closure)", async () => {
@@ -161,7 +271,14 @@ describe("Accidental Program Closure Vulnerability", () => {
       .accounts({
         vault: vault,
         vaultTokenAccount: vaultTokenAccount,
+        mint,
         userTokenAccount: userTokenAccount,
+        shareMint,
+        userShareAccount,
+        config,
+        circuitBreaker,
+        feeAuthority,
+        feeCollector,
         owner: user.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -171,10 +288,10 @@ describe("Accidental Program Closure Vulnerability", () => {
     console.log("✅ Withdrawal successful");
 
     // Verify updated vault state
-    const vaultAccount = await program.account.vault.fetch(vault);
-    console.log(`Remaining vault balance: ${vaultAccount.totalDeposited.toNumber() / 1000000} tokens`);
+    console.log(`Remaining vault balance: ${(await vaultBalance()) / 1000000} tokens`);
     
-    expect(vaultAccount.totalDeposited.toNumber()).to.equal(125000000); // 125 tokens remaining
+    expect(await vaultBalance()).to.equal(125000000); // 125 tokens remaining
+    expect(await shareBalance()).to.equal(125000000); // burned 1:1
   });
 
   it("4. SIMULATE ACCIDENTAL PROGRAM CLOSURE", async () => {
@@ -187,20 +304,23 @@ describe("Accidental Program Closure Vulnerability", () => {
     // We simulate this by marking the vault as inactive
     // In reality, the entire program would become unreachable
     try {
-      const adminKeypair = Keypair.generate(); // This would fail in real implementation
-      
-      console.log("Attempting emergency close (this will fail due to wrong authority)...");
-      
+      const outsider = Keypair.generate(); // Not a multisig owner
+      const multisigAccount = await program.account.multisig.fetch(multisig);
+
+      console.log("Attempting to propose an emergency close (this will fail due to wrong authority)...");
+
       await program.methods
-        .emergencyCloseVault()
+        .proposeAdminAction({ closeVault: { vault } })
         .accounts({
-          vault: vault,
-          authority: adminKeypair.publicKey,
+          multisig,
+          proposal: proposalAddress(multisigAccount.proposalCount),
+          proposer: outsider.publicKey,
+          systemProgram: SystemProgram.programId,
         })
-        .signers([adminKeypair])
+        .signers([outsider])
         .rpc();
 
-      console.log("❌ UNEXPECTED: Emergency close succeeded with wrong authority!");
+      console.log("❌ UNEXPECTED: Emergency close proposal succeeded with wrong authority!");
     } catch (error) {
       console.log("✅ Emergency close correctly failed due to unauthorized access");
       console.log("In a real closure scenario, the program would become unreachable");
@@ -213,113 +333,1868 @@ describe("Accidental Program Closure Vulnerability", () => {
     console.log("4. Funds are permanently lost unless program is restored");
   });
 
-  it("5. Demonstrate withdrawal failure after simulated closure", async () => {
-    console.log("\n🔒 STEP 5: Attempting withdrawal after simulated closure");
+  it("4b. Scenario seeding is restricted to the admin", async () => {
+    console.log("\n🌱 Seeding victim vaults requires the admin authority");
 
-    // First, actually close the vault using the correct admin authority
-    try {
-      const adminKeypair = Keypair.fromSecretKey(
-        new Uint8Array([
-          // This creates a keypair that matches the hardcoded ADMIN_PUBKEY in the program
-          // In a real scenario, this would be a proper admin keypair
-          1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 
-          1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-          1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 
-          1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1
-        ].slice(0, 32))
-      );
-      
-      console.log("Since the previous emergency close failed, the vault is still active");
-      console.log("In a real closure scenario, the entire program would be unreachable");
-      console.log("Let's simulate what would happen if the program were actually closed...");
-      
-      // Show that normal withdrawal still works because vault is active
-      const smallWithdraw = new anchor.BN(10000000); // 10 tokens
+    const victim = Keypair.generate();
+    const [victimVault] = vaultAddress(victim.publicKey, 0);
+    const [victimShareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), victimVault.toBuffer()],
+      program.programId
+    );
+
+    try {
       await program.methods
-        .withdraw(smallWithdraw)
+        .seedScenario(new anchor.BN(0), new anchor.BN(10000000))
         .accounts({
-          vault: vault,
-          vaultTokenAccount: vaultTokenAccount,
-          userTokenAccount: userTokenAccount,
+          vault: victimVault,
+          vaultTokenAccount: await getAssociatedTokenAddress(mint, victimVault, true),
+          authorityTokenAccount: userTokenAccount,
+          mint: mint,
+          shareMint: victimShareMint,
+          victimShareAccount: await getAssociatedTokenAddress(victimShareMint, victim.publicKey),
+          victim: victim.publicKey,
+          multisig,
+          config,
+          authority: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      expect.fail("Non-admin should not be able to seed victim vaults");
+    } catch (error) {
+      expect(error.message).to.include("Unauthorized");
+      console.log("✅ Non-admin seeding correctly rejected");
+    }
+  });
+
+  it("4c. Emergency actions need M-of-N multisig approval", async () => {
+    console.log("\n🔐 Emergency close runs only as an approved multisig proposal");
+
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ closeVault: { vault } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const queueClose = () =>
+      program.methods
+        .queueEmergencyClose()
+        .accounts({
+          vault,
+          multisig,
+          proposal,
+          pendingAction: pendingAction(vault),
+          owner: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const executeClose = () =>
+      program.methods
+        .emergencyCloseVault()
+        .accounts({ vault, vaultTokenAccount, multisig, pendingAction: pendingAction(vault), authority: wallet.publicKey })
+        .rpc();
+
+    try {
+      await queueClose();
+      expect.fail("Close should need both approvals");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Not enough owner approvals");
+      console.log("✅ 1-of-2 approvals rejected");
+    }
+
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await queueClose();
+    const { etaSlot } = await program.account.pendingAction.fetch(pendingAction(vault));
+    console.log(`✅ 2-of-2 approved close queued until slot ${etaSlot.toString()}`);
+
+    try {
+      await executeClose();
+      expect.fail("Close should wait for the timelock");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Timelock has not expired");
+      console.log("✅ Immediate execution rejected");
+    }
+
+    while ((await provider.connection.getSlot()) < etaSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await executeClose();
+    expect((await program.account.vault.fetch(vault)).isActive).to.be.false;
+    console.log("✅ Close executed after the timelock");
+
+    try {
+      await queueClose();
+      expect.fail("An executed proposal should not be replayable");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Proposal was already executed");
+      console.log("✅ Replay rejected");
+    }
+
+    // Recover through the same flow so the remaining steps see an active vault
+    const recoverId = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const recoverProposal = proposalAddress(recoverId);
+    await program.methods
+      .proposeAdminAction({ recoverVault: { vault } })
+      .accounts({
+        multisig,
+        proposal: recoverProposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal: recoverProposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .emergencyRecover()
+      .accounts({ vault, vaultTokenAccount, multisig, proposal: recoverProposal, authority: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    expect((await program.account.vault.fetch(vault)).isActive).to.be.true;
+    console.log("✅ 2-of-2 approved recovery executed");
+  });
+
+  it("4d. Any owner can cancel a queued emergency close", async () => {
+    console.log("\n🛑 Queued closes can be vetoed before they execute");
+
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ closeVault: { vault } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .queueEmergencyClose()
+      .accounts({
+        vault,
+        multisig,
+        proposal,
+        pendingAction: pendingAction(vault),
+        owner: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .cancelEmergencyClose()
+      .accounts({ multisig, pendingAction: pendingAction(vault), owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(pendingAction(vault))).to.be.null;
+
+    try {
+      await program.methods
+        .emergencyCloseVault()
+        .accounts({ vault, vaultTokenAccount, multisig, pendingAction: pendingAction(vault), authority: wallet.publicKey })
+        .rpc();
+      expect.fail("A cancelled close should not execute");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("expected this account to be already initialized");
+    }
+    expect((await program.account.vault.fetch(vault)).isActive).to.be.true;
+    console.log("✅ Cancelled close never executed");
+  });
+
+  it("4e. Migration waits for a timelocked successor", async () => {
+    console.log("\n🚚 Vaults migrate to a successor program instead of being closed");
+
+    const migrate = (successorProgram: PublicKey) =>
+      program.methods
+        .migrateVault()
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          shareMint,
+          userShareAccount,
+          multisig,
+          config,
+          successorProgram,
           owner: user.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([user])
         .rpc();
 
-      console.log("✅ Withdrawal still works because vault is active and program is accessible");
-      
-      const vaultAccount = await program.account.vault.fetch(vault);
-      console.log(`Current vault balance: ${vaultAccount.totalDeposited.toNumber() / 1000000} tokens`);
-      
-      console.log("\n💀 BUT if the program were actually closed:");
-      console.log("1. This RPC call would fail with 'Program account not found'");
-      console.log("2. No functions would be executable");
-      console.log("3. All remaining funds would be permanently locked");
-      console.log("4. Users would have no recourse to recover their assets");
-      
+    try {
+      await migrate(SystemProgram.programId);
+      expect.fail("Migration needs a successor");
     } catch (error) {
-      console.log("✅ Withdrawal failed - this would be the effect of program closure");
-      console.log("In a real closure scenario, even this error wouldn't occur");
-      console.log("The RPC call would fail because the program doesn't exist");
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("No successor program has been set");
+      console.log("✅ Migration rejected before a successor is set");
+    }
+
+    // Stand-in successor: this program keeps its own deposit interface
+    const successor = program.programId;
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ setSuccessor: { programId: successor } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setSuccessor(successor)
+      .accounts({ multisig, proposal, owner: wallet.publicKey })
+      .rpc();
+
+    const multisigAccount = await program.account.multisig.fetch(multisig);
+    expect(multisigAccount.successor.toString()).to.equal(successor.toString());
+    console.log(`✅ Successor takes effect at slot ${multisigAccount.successorEffectiveSlot.toString()}`);
+
+    try {
+      await migrate(successor);
+      expect.fail("Migration should wait for the timelock");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Successor program is not active yet");
+      console.log("✅ Immediate migration rejected");
     }
   });
 
-  it("6. Demonstrate proper emergency recovery procedures", async () => {
-    console.log("\n🚑 STEP 6: Proper emergency recovery procedures");
-    console.log("This demonstrates what SHOULD be implemented:");
+  it("4f. Guardians pause deposits instantly, only the admin resumes", async () => {
+    console.log("\n⏸️  Guardian pause and admin unpause");
 
-    console.log("\n✅ MITIGATION STRATEGIES:");
-    console.log("1. Multi-signature wallet for program authority");
-    console.log("2. Timelock periods for critical operations");
-    console.log("3. Separate staging and production environments");
-    console.log("4. Deployment scripts with environment verification");
-    console.log("5. Emergency pause/unpause functionality");
-    console.log("6. User-controlled fund recovery mechanisms");
+    const deposit = () =>
+      program.methods
+        .deposit(new anchor.BN(1000000))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          mint,
+          userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
 
-    console.log("\n🔧 TECHNICAL SAFEGUARDS:");
-    console.log("- Use program upgrades instead of closure");
-    console.log("- Implement circuit breakers for large operations");
-    console.log("- Create migration contracts before updates");
-    console.log("- Use proxy patterns for upgradeable contracts");
-    console.log("- Regular monitoring and health checks");
+    try {
+      await program.methods
+        .guardianPauseDeposits()
+        .accounts({ config, guardian: user.publicKey })
+        .signers([user])
+        .rpc();
+      expect.fail("Only guardians can pause");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Unauthorized");
+    }
 
-    console.log("\n📋 OPERATIONAL PROCEDURES:");
-    console.log("- Peer review for all deployment operations");
-    console.log("- CI/CD pipelines with environment detection");
-    console.log("- Explicit confirmation for destructive operations");
-    console.log("- Testing deployment procedures on devnet");
-    console.log("- Backup and recovery testing");
+    await program.methods
+      .guardianPauseDeposits()
+      .accounts({ config, guardian: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    try {
+      await deposit();
+      expect.fail("Deposits should be paused");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Deposits are paused");
+      console.log("✅ Guardian paused deposits");
+    }
 
-    // In this test, we're not actually implementing recovery since
-    // the vault is still functional. In a real scenario, recovery
-    // would involve redeploying the program or having emergency contracts
+    // Resuming needs the multisig
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ setPause: { deposits: false, withdrawals: false } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setPause(false, false)
+      .accounts({ config, multisig, proposal, owner: wallet.publicKey })
+      .rpc();
+
+    await deposit();
+    console.log("✅ Admin multisig resumed deposits");
   });
 
-  it("7. Show impact on user funds", async () => {
-    console.log("\n📊 STEP 7: Impact assessment");
+  it("4g. Deposit caps and the withdrawal circuit breaker", async () => {
+    console.log("\n🚦 Config-driven deposit cap and rolling withdrawal limit");
+
+    const withdraw = (amount: number) =>
+      program.methods
+        .withdraw(new anchor.BN(amount))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          mint,
+          userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
 
     try {
-      // Check current vault state
-      const vaultAccount = await program.account.vault.fetch(vault);
-      const lockedTokens = vaultAccount.totalDeposited.toNumber() / 1000000;
+      await program.methods
+        .deposit(new anchor.BN(VAULT_DEPOSIT_CAP))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          mint,
+          userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Deposit should exceed the cap");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Deposit would exceed the vault deposit cap");
+      console.log("✅ Deposit over the cap rejected");
+    }
 
-      console.log(`Tokens locked in vault: ${lockedTokens}`);
-      console.log(`If program were closed, these ${lockedTokens} tokens would be permanently lost`);
+    // 25 tokens were withdrawn in step 3; 30 more would cross the 50 token limit
+    const totalBefore = await vaultBalance();
+    try {
+      await withdraw(30000000);
+      expect.fail("Withdrawal should exceed the limit");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("exceed the rolling withdrawal limit");
+    }
+    expect((await program.account.circuitBreaker.fetch(circuitBreaker)).tripped).to.be.false;
+    expect(await vaultBalance()).to.equal(totalBefore);
+    console.log("✅ Over-limit request rejected without tripping the breaker");
 
-      // Check user's remaining tokens
-      const userAccount = await getAccount(provider.connection, userTokenAccount);
-      const userTokens = Number(userAccount.amount) / 1000000;
+    // 25 more reaches the limit exactly: paid out, then the breaker trips
+    await withdraw(25000000);
+    expect((await program.account.circuitBreaker.fetch(circuitBreaker)).tripped).to.be.true;
+    expect(await vaultBalance()).to.equal(totalBefore - 25000000);
+    console.log("✅ Outflow reaching the limit tripped the breaker");
 
-      console.log(`User's withdrawable tokens: ${userTokens}`);
-      console.log(`Total user impact: ${lockedTokens} tokens inaccessible`);
+    try {
+      await withdraw(1);
+      expect.fail("Withdrawals should stop while the breaker is tripped");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Circuit breaker is tripped");
+      console.log("✅ Tripped breaker blocks withdrawals");
+    }
 
-      console.log("\n💸 FINANCIAL IMPACT:");
-      console.log(`At $100/token: $${(lockedTokens * 100).toLocaleString()} locked`);
-      console.log(`At current market prices, this represents significant user losses`);
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ resetCircuitBreaker: {} })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .resetCircuitBreaker()
+      .accounts({ circuitBreaker, multisig, proposal, owner: wallet.publicKey })
+      .rpc();
+
+    await withdraw(5000000);
+    expect(await vaultBalance()).to.equal(totalBefore - 30000000);
+    console.log("✅ Admin reset re-enabled withdrawals");
+  });
+
+  it("4h. Timelocked withdrawal fee and fee collection", async () => {
+    console.log("\n🧾 Withdrawal fees route to the fee collector");
+
+    const approvedProposal = async (action) => {
+      const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+      const proposal = proposalAddress(id);
+      await program.methods
+        .proposeAdminAction(action)
+        .accounts({
+          multisig,
+          proposal,
+          proposer: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .approveAdminAction()
+        .accounts({ multisig, proposal, owner: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      return proposal;
+    };
 
+    try {
+      const proposal = await approvedProposal({ setFee: { feeBps: 2000 } });
+      await program.methods
+        .setFee(2000)
+        .accounts({ config, multisig, proposal, owner: wallet.publicKey })
+        .rpc();
+      expect.fail("A 20% fee should be rejected");
     } catch (error) {
-      console.error("Failed to assess impact:", error);
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Fee exceeds the maximum");
+      console.log("✅ Fee above MAX_FEE_BPS rejected");
+    }
+
+    const proposal = await approvedProposal({ setFee: { feeBps: 100 } });
+    await program.methods
+      .setFee(100)
+      .accounts({ config, multisig, proposal, owner: wallet.publicKey })
+      .rpc();
+    const { feeEffectiveSlot } = await program.account.config.fetch(config);
+    while ((await provider.connection.getSlot()) < feeEffectiveSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    const amount = 2000000; // 2 tokens, 1% fee
+    const before = Number((await getAccount(provider.connection, userTokenAccount)).amount);
+    await program.methods
+      .withdraw(new anchor.BN(amount))
+      .accounts({
+        vault,
+        vaultTokenAccount,
+        mint,
+        userTokenAccount,
+        shareMint,
+        userShareAccount,
+        config,
+        circuitBreaker,
+        feeAuthority,
+        feeCollector,
+        owner: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc();
+    const after = Number((await getAccount(provider.connection, userTokenAccount)).amount);
+    expect(after - before).to.equal(amount - 20000);
+    expect(Number((await getAccount(provider.connection, feeCollector)).amount)).to.equal(20000);
+    console.log("✅ 1% fee withheld after the timelock");
+
+    const collect = await approvedProposal({
+      collectFees: { feeCollector, destination: userTokenAccount },
+    });
+    await program.methods
+      .collectFees()
+      .accounts({
+        multisig,
+        proposal: collect,
+        feeAuthority,
+        feeCollector,
+        mint,
+        destination: userTokenAccount,
+        insuranceAuthority,
+        insuranceReserve,
+        owner: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    expect(Number((await getAccount(provider.connection, feeCollector)).amount)).to.equal(0);
+    // 20% of the 20000 collected goes to the insurance reserve
+    expect(Number((await getAccount(provider.connection, insuranceReserve)).amount)).to.equal(4000);
+    console.log("✅ Fees collected by the multisig, a slice insured");
+  });
+
+  it("4i. Admin rotation needs the nominee to accept", async () => {
+    console.log("\n🔑 Two-step admin rotation");
+
+    const newAdmin = Keypair.generate();
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ nominateAdmin: { oldOwner: admin.publicKey, newOwner: newAdmin.publicKey } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .nominateAdmin()
+      .accounts({ multisig, proposal, owner: wallet.publicKey })
+      .rpc();
+
+    let multisigAccount = await program.account.multisig.fetch(multisig);
+    expect(multisigAccount.owners.map((owner) => owner.toString())).to.include(admin.publicKey.toString());
+    console.log("✅ Nomination alone does not change the owners");
+
+    try {
+      const impostor = Keypair.generate();
+      await program.methods
+        .acceptAdmin()
+        .accounts({ multisig, newOwner: impostor.publicKey })
+        .signers([impostor])
+        .rpc();
+      expect.fail("Only the nominee can accept");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Signer is not the nominated admin");
+      console.log("✅ Acceptance by another key rejected");
+    }
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ multisig, newOwner: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    multisigAccount = await program.account.multisig.fetch(multisig);
+    const owners = multisigAccount.owners.map((owner) => owner.toString());
+    expect(owners).to.include(newAdmin.publicKey.toString());
+    expect(owners).to.not.include(admin.publicKey.toString());
+    console.log("✅ Nominee accepted and took over the seat");
+
+    // The remaining steps approve with the new admin
+    admin = newAdmin;
+  });
+
+  it("4j. Anyone can verify who controls the upgrade authority", async () => {
+    console.log("\n🩺 On-chain upgrade-authority health check");
+
+    const [health] = PublicKey.findProgramAddressSync([Buffer.from("health")], program.programId);
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const outsider = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(outsider.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+
+    await program.methods
+      .verifyUpgradeAuthority()
+      .accounts({
+        health,
+        multisig,
+        program: program.programId,
+        programData,
+        payer: outsider.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([outsider])
+      .rpc();
+
+    // The local validator deploys with the provider wallet as upgrade authority
+    const healthAccount = await program.account.health.fetch(health);
+    expect(healthAccount.verified).to.be.false;
+    expect(healthAccount.upgradeAuthority.toString()).to.equal(wallet.publicKey.toString());
+    expect(healthAccount.checkedAt.toNumber()).to.be.greaterThan(0);
+    console.log("✅ Single-key upgrade authority flagged as unverified");
+  });
+
+  it("4k. Self rescue waits for the admin to go silent", async () => {
+    console.log("\n💓 Admin heartbeat and the dead-man's switch");
+
+    try {
+      await program.methods
+        .heartbeat()
+        .accounts({ config, multisig, owner: user.publicKey })
+        .signers([user])
+        .rpc();
+      expect.fail("Only multisig owners can send a heartbeat");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Unauthorized");
+    }
+
+    await program.methods.heartbeat().accounts({ config, multisig, owner: wallet.publicKey }).rpc();
+    const epoch = (await provider.connection.getEpochInfo()).epoch;
+    expect((await program.account.config.fetch(config)).lastHeartbeatEpoch.toNumber()).to.equal(epoch);
+    console.log(`✅ Heartbeat recorded in epoch ${epoch}`);
+
+    try {
+      await program.methods
+        .selfRescue()
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          mint,
+          userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Self rescue should wait for missed heartbeats");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Admin heartbeat is too recent for self rescue");
+      console.log("✅ Self rescue rejected while the admin is alive");
+    }
+  });
+
+  it("4l. Keepers evacuate vaults straight to their owners", async () => {
+    console.log("\n🚨 Incident evacuation by a guardian keeper");
+
+    // Fund the multisig owner so it can seed victim vaults
+    const walletTokenAccount = await createAccount(provider.connection, user, mint, wallet.publicKey);
+    await mintTo(provider.connection, user, mint, walletTokenAccount, user, 30000000);
+
+    const victims = [];
+    for (let i = 0; i < 3; i++) {
+      const victim = Keypair.generate();
+      const [victimVault] = vaultAddress(victim.publicKey, 0);
+      const [victimShareMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("share_mint"), victimVault.toBuffer()],
+        program.programId
+      );
+      const victimVaultTokenAccount = await getAssociatedTokenAddress(mint, victimVault, true);
+      await program.methods
+        .seedScenario(new anchor.BN(0), new anchor.BN(10000000))
+        .accounts({
+          vault: victimVault,
+          vaultTokenAccount: victimVaultTokenAccount,
+          authorityTokenAccount: walletTokenAccount,
+          mint,
+          shareMint: victimShareMint,
+          victimShareAccount: await getAssociatedTokenAddress(victimShareMint, victim.publicKey),
+          victim: victim.publicKey,
+          multisig,
+          config,
+          authority: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      const ownerTokenAccount = await createAccount(provider.connection, user, mint, victim.publicKey);
+      victims.push({ vault: victimVault, vaultTokenAccount: victimVaultTokenAccount, ownerTokenAccount });
+    }
+
+    await program.methods
+      .withdrawAllForOwner()
+      .accounts({
+        vault: victims[0].vault,
+        vaultTokenAccount: victims[0].vaultTokenAccount,
+        mint,
+        ownerTokenAccount: victims[0].ownerTokenAccount,
+        config,
+        multisig,
+        keeper: guardian.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([guardian])
+      .rpc();
+    expect(Number((await getAccount(provider.connection, victims[0].ownerTokenAccount)).amount)).to.equal(10000000);
+    expect((await program.account.vault.fetch(victims[0].vault)).isActive).to.be.false;
+    console.log("✅ Single vault evacuated to its owner");
+
+    const triple = (v, destination: PublicKey) => [
+      { pubkey: v.vault, isSigner: false, isWritable: true },
+      { pubkey: v.vaultTokenAccount, isSigner: false, isWritable: true },
+      { pubkey: destination, isSigner: false, isWritable: true },
+    ];
+    const batch = (remainingAccounts) =>
+      program.methods
+        .batchEmergencyWithdraw()
+        .accounts({ config, multisig, mint, keeper: guardian.publicKey, tokenProgram: TOKEN_PROGRAM_ID })
+        .remainingAccounts(remainingAccounts)
+        .signers([guardian])
+        .rpc();
+
+    try {
+      // Second vault's funds routed to the third victim
+      await batch(triple(victims[1], victims[2].ownerTokenAccount));
+      expect.fail("Funds must only reach the vault owner");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Batch accounts must be valid");
+      console.log("✅ Mismatched owner token account rejected");
+    }
+
+    await batch([
+      ...triple(victims[1], victims[1].ownerTokenAccount),
+      ...triple(victims[2], victims[2].ownerTokenAccount),
+    ]);
+    for (const v of victims.slice(1)) {
+      expect(Number((await getAccount(provider.connection, v.ownerTokenAccount)).amount)).to.equal(10000000);
+      expect(Number((await getAccount(provider.connection, v.vaultTokenAccount)).amount)).to.equal(0);
+    }
+    console.log("✅ Batch evacuated two vaults in one transaction");
+  });
+
+  it("4m. One owner holds a second vault of a Token-2022 mint", async () => {
+    console.log("\n🪙 Token-2022 vault through the token interface");
+
+    // The user's first vault (id 0) holds the legacy SPL mint
+    const owner = user;
+
+    const mint2022 = await createMint(
+      provider.connection, owner, owner.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID
+    );
+    const ownerTokenAccount = await createAccount(
+      provider.connection, owner, mint2022, owner.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection, owner, mint2022, ownerTokenAccount, owner, 20000000, [], undefined, TOKEN_2022_PROGRAM_ID
+    );
+    const collector2022 = await createAccount(
+      provider.connection, owner, mint2022, feeAuthority, Keypair.generate(), undefined, TOKEN_2022_PROGRAM_ID
+    );
+
+    const [vault2022] = vaultAddress(owner.publicKey, 1);
+    const [shareMint2022] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), vault2022.toBuffer()],
+      program.programId
+    );
+    const vaultTokenAccount2022 = await getAssociatedTokenAddress(mint2022, vault2022, true, TOKEN_2022_PROGRAM_ID);
+    const ownerShareAccount = await getAssociatedTokenAddress(shareMint2022, owner.publicKey, false, TOKEN_2022_PROGRAM_ID);
+
+    await program.methods
+      .initializeVault(new anchor.BN(1), new anchor.BN(10000000))
+      .accounts({
+        vault: vault2022,
+        vaultTokenAccount: vaultTokenAccount2022,
+        userTokenAccount: ownerTokenAccount,
+        mint: mint2022,
+        shareMint: shareMint2022,
+        userShareAccount: ownerShareAccount,
+        config,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc();
+    const balance2022 = async (account: PublicKey) =>
+      Number((await getAccount(provider.connection, account, undefined, TOKEN_2022_PROGRAM_ID)).amount);
+    expect(await balance2022(vaultTokenAccount2022)).to.equal(10000000);
+    expect(await balance2022(ownerShareAccount)).to.equal(10000000);
+    const vaultState = await program.account.vault.fetch(vault2022);
+    expect(vaultState.vaultId.toNumber()).to.equal(1);
+    expect(vaultState.mint.toBase58()).to.equal(mint2022.toBase58());
+    expect((await program.account.vault.fetch(vault)).mint.toBase58()).to.equal(mint.toBase58());
+    console.log("✅ Token-2022 vault initialized with Token-2022 shares");
+
+    await program.methods
+      .withdraw(new anchor.BN(5000000))
+      .accounts({
+        vault: vault2022,
+        vaultTokenAccount: vaultTokenAccount2022,
+        mint: mint2022,
+        userTokenAccount: ownerTokenAccount,
+        shareMint: shareMint2022,
+        userShareAccount: ownerShareAccount,
+        config,
+        circuitBreaker,
+        feeAuthority,
+        feeCollector: collector2022,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+    expect(await balance2022(vaultTokenAccount2022)).to.equal(5000000);
+    // 1% fee from step 4h
+    expect(await balance2022(ownerTokenAccount)).to.equal(10000000 + 4950000);
+    expect(await balance2022(collector2022)).to.equal(50000);
+    console.log("✅ transfer_checked withdrawal from a Token-2022 vault");
+  });
+
+  it("4n. A hot key withdraws against a capped, expiring allowance", async () => {
+    console.log("\n🔑 Delegated withdrawals");
+
+    const allowanceAddress = (delegateKey: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("allowance"), vault.toBuffer(), delegateKey.toBuffer()],
+        program.programId
+      )[0];
+    const delegateWithdraw = (delegate: Keypair, destination: PublicKey, amount: number) =>
+      program.methods
+        .delegateWithdraw(new anchor.BN(amount))
+        .accounts({
+          vault,
+          allowance: allowanceAddress(delegate.publicKey),
+          vaultTokenAccount,
+          mint,
+          destination,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          delegate: delegate.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([delegate])
+        .rpc();
+
+    const hotKey = Keypair.generate();
+    const payee = Keypair.generate();
+    const payeeTokenAccount = await createAccount(provider.connection, user, mint, payee.publicKey);
+    const slot = await provider.connection.getSlot();
+    await program.methods
+      .approveDelegate(hotKey.publicKey, new anchor.BN(1500000), new anchor.BN(slot + 1000))
+      .accounts({
+        vault,
+        allowance: allowanceAddress(hotKey.publicKey),
+        owner: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const sharesBefore = await shareBalance();
+    await delegateWithdraw(hotKey, payeeTokenAccount, 1000000);
+    // 1% fee from step 4h
+    expect(Number((await getAccount(provider.connection, payeeTokenAccount)).amount)).to.equal(990000);
+    expect(await shareBalance()).to.equal(sharesBefore);
+    const { allowance } = await program.account.allowance.fetch(allowanceAddress(hotKey.publicKey));
+    expect(allowance.toNumber()).to.equal(500000);
+    console.log("✅ Delegate paid out 1 token and the allowance went down");
+
+    try {
+      await delegateWithdraw(hotKey, payeeTokenAccount, 1000000);
+      expect.fail("Withdrawing past the allowance should fail");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Withdrawal exceeds the delegate's allowance");
+      console.log("✅ Allowance caps the delegate");
+    }
+
+    const staleKey = Keypair.generate();
+    await program.methods
+      .approveDelegate(staleKey.publicKey, new anchor.BN(1000000), new anchor.BN(slot))
+      .accounts({
+        vault,
+        allowance: allowanceAddress(staleKey.publicKey),
+        owner: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    while ((await provider.connection.getSlot()) <= slot) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    try {
+      await delegateWithdraw(staleKey, payeeTokenAccount, 1000);
+      expect.fail("An expired allowance should not be usable");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Delegate allowance has expired");
+      console.log("✅ Expired allowance rejected");
+    }
+  });
+
+  it("4o. Anyone can ping the program's health account", async () => {
+    console.log("\n📡 Liveness heartbeat");
+
+    const [health] = PublicKey.findProgramAddressSync([Buffer.from("health")], program.programId);
+    const before = await provider.connection.getSlot();
+    await program.methods
+      .ping()
+      .accounts({
+        health,
+        payer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const healthAccount = await program.account.health.fetch(health);
+    expect(healthAccount.lastPingSlot.toNumber()).to.be.at.least(before);
+    expect(healthAccount.version).to.equal("0.1.0");
+    // The ping leaves the upgrade-authority check from step 4j alone
+    expect(healthAccount.checkedAt.toNumber()).to.be.greaterThan(0);
+    console.log("✅ Ping recorded slot", healthAccount.lastPingSlot.toNumber(), "for v" + healthAccount.version);
+  });
+
+  it("4p. The insurance reserve only covers a reconciled shortfall", async () => {
+    console.log("\n🛟 Insurance reserve");
+
+    const state = await program.account.vault.fetch(vault);
+    expect(state.totalDeposited.toNumber()).to.equal(await vaultBalance());
+    console.log("✅ Vault reconciles: total_deposited matches the token balance");
+
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    const amount = new anchor.BN(1000);
+    await program.methods
+      .proposeAdminAction({ coverLoss: { vault, amount } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+
+    try {
+      await program.methods
+        .coverLoss(vault, amount)
+        .accounts({
+          multisig,
+          proposal,
+          vaultAccount: vault,
+          vaultTokenAccount,
+          mint,
+          insuranceAuthority,
+          insuranceReserve,
+          owner: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("Covering a vault with no shortfall should fail");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Cover amount must be positive and at most the vault's shortfall");
+      expect(Number((await getAccount(provider.connection, insuranceReserve)).amount)).to.equal(4000);
+      console.log("✅ Reserve untouched when there is no shortfall");
+    }
+  });
+
+  it("4q. Challenge: freeze through the weak gate, then rescue a victim", async () => {
+    console.log("\n🧊 Global freeze challenge");
+
+    // The player signs and pays for everything themselves, so no owner's
+    // signature ever rides along as fee payer
+    const player = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(player.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    const sendAsPlayer = (tx: anchor.web3.Transaction) =>
+      sendAndConfirmTransaction(provider.connection, tx, [player], { commitment: "confirmed" });
+
+    // Stage one: name an owner as `authority` without their signature
+    await sendAsPlayer(
+      await program.methods
+        .freezeProgram(true)
+        .accounts({ config, multisig, authority: wallet.publicKey, caller: player.publicKey })
+        .transaction()
+    );
+    const frozenConfig = await program.account.config.fetch(config);
+    expect(frozenConfig.frozen).to.be.true;
+    expect(frozenConfig.frozenBy.toBase58()).to.equal(player.publicKey.toBase58());
+    console.log("✅ Outsider froze the program through the unsigned authority");
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1000000))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          mint,
+          userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Withdraw should fail while frozen");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Program is frozen");
+      console.log("✅ Withdrawals stranded as if the program were closed");
+    }
+
+    // Organizer setup: register the challenge and seed a victim
+    const registry = new Program(
+      JSON.parse(readFileSync("../ctf-registry/target/idl/ctf_registry.json", "utf8")),
+      provider
+    );
+    const [challenge] = PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), Buffer.from("solana-program-close")],
+      registry.programId
+    );
+    const [solve] = PublicKey.findProgramAddressSync(
+      [Buffer.from("solve"), challenge.toBuffer(), player.publicKey.toBuffer()],
+      registry.programId
+    );
+    const [flagAuthority] = PublicKey.findProgramAddressSync([Buffer.from("flag_authority")], program.programId);
+    // Anchor.toml deploys the registry with the wallet as upgrade authority
+    const [registryProgramData] = PublicKey.findProgramAddressSync(
+      [registry.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    await registry.methods
+      .initializeRegistry([wallet.publicKey], 1)
+      .accountsPartial({ programData: registryProgramData, payer: wallet.publicKey })
+      .rpc();
+    await registry.methods
+      .registerChallenge("solana-program-close", program.programId, wallet.publicKey)
+      .accounts({ payer: wallet.publicKey })
+      .remainingAccounts([{ pubkey: wallet.publicKey, isSigner: true, isWritable: false }])
+      .rpc();
+
+    const victim = Keypair.generate();
+    const [victimVault] = vaultAddress(victim.publicKey, 0);
+    const [victimShareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), victimVault.toBuffer()],
+      program.programId
+    );
+    const victimVaultTokenAccount = await getAssociatedTokenAddress(mint, victimVault, true);
+    const walletTokenAccount = await createAccount(provider.connection, user, mint, wallet.publicKey, Keypair.generate());
+    await mintTo(provider.connection, user, mint, walletTokenAccount, user, 10000000);
+    // seed_scenario needs deposits open, which the freeze leaves alone
+    await program.methods
+      .seedScenario(new anchor.BN(0), new anchor.BN(10000000))
+      .accounts({
+        vault: victimVault,
+        vaultTokenAccount: victimVaultTokenAccount,
+        authorityTokenAccount: walletTokenAccount,
+        mint,
+        shareMint: victimShareMint,
+        victimShareAccount: await getAssociatedTokenAddress(victimShareMint, victim.publicKey),
+        victim: victim.publicKey,
+        multisig,
+        config,
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Stage two: the player opens a token account for the victim and
+    // evacuates the vault into it, signing only as themselves
+    const victimTokenAccount = await createAccount(provider.connection, player, mint, victim.publicKey);
+    await sendAsPlayer(
+      await program.methods
+        .rescueFrozenVault()
+        .accounts({
+          vault: victimVault,
+          vaultTokenAccount: victimVaultTokenAccount,
+          mint,
+          ownerTokenAccount: victimTokenAccount,
+          config,
+          challenge,
+          solve,
+          flagAuthority,
+          player: player.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          registryProgram: registry.programId,
+        })
+        .transaction()
+    );
+    expect(Number((await getAccount(provider.connection, victimTokenAccount)).amount)).to.equal(10000000);
+    console.log("✅ Player rescued all 10 tokens to the victim while frozen");
+
+    const recorded = await (registry.account as any).solve.fetch(solve);
+    expect(recorded.player.toBase58()).to.equal(player.publicKey.toBase58());
+    expect(recorded.programId.toBase58()).to.equal(program.programId.toBase58());
+    expect((await (registry.account as any).challenge.fetch(challenge)).solveCount.toNumber()).to.equal(1);
+    console.log("✅ capture_flag recorded the solve in the registry");
+
+    await program.methods
+      .freezeProgram(false)
+      .accounts({ config, multisig, authority: wallet.publicKey, caller: wallet.publicKey })
+      .rpc();
+    expect((await program.account.config.fetch(config)).frozen).to.be.false;
+  });
+
+  it("4r. Snapshot a vault, corrupt it, and restore it", async () => {
+    console.log("\n💾 Recovery rehearsal");
+
+    const approvedProposal = async (action) => {
+      const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+      const proposal = proposalAddress(id);
+      await program.methods
+        .proposeAdminAction(action)
+        .accounts({
+          multisig,
+          proposal,
+          proposer: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .approveAdminAction()
+        .accounts({ multisig, proposal, owner: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      return proposal;
+    };
+
+    const victim = Keypair.generate();
+    const [victimVault] = vaultAddress(victim.publicKey, 0);
+    const [victimShareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), victimVault.toBuffer()],
+      program.programId
+    );
+    const victimVaultTokenAccount = await getAssociatedTokenAddress(mint, victimVault, true);
+    const walletTokenAccount = await createAccount(provider.connection, user, mint, wallet.publicKey, Keypair.generate());
+    await mintTo(provider.connection, user, mint, walletTokenAccount, user, 5000000);
+    await program.methods
+      .seedScenario(new anchor.BN(0), new anchor.BN(5000000))
+      .accounts({
+        vault: victimVault,
+        vaultTokenAccount: victimVaultTokenAccount,
+        authorityTokenAccount: walletTokenAccount,
+        mint,
+        shareMint: victimShareMint,
+        victimShareAccount: await getAssociatedTokenAddress(victimShareMint, victim.publicKey),
+        victim: victim.publicKey,
+        multisig,
+        config,
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const version = new anchor.BN(1);
+    const [snapshot] = PublicKey.findProgramAddressSync(
+      [Buffer.from("snapshot"), victimVault.toBuffer(), version.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .snapshotVault(version)
+      .accounts({
+        vault: victimVault,
+        snapshot,
+        config,
+        multisig,
+        keeper: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const original = await program.account.vault.fetch(victimVault);
+    console.log("✅ Snapshot 1 taken");
+
+    // Corrupt the vault: deactivate it through the full emergency close flow
+    const close = await approvedProposal({ closeVault: { vault: victimVault } });
+    await program.methods
+      .queueEmergencyClose()
+      .accounts({
+        vault: victimVault,
+        multisig,
+        proposal: close,
+        pendingAction: pendingAction(victimVault),
+        owner: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const { etaSlot } = await program.account.pendingAction.fetch(pendingAction(victimVault));
+    while ((await provider.connection.getSlot()) < etaSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await program.methods
+      .emergencyCloseVault()
+      .accounts({
+        vault: victimVault,
+        vaultTokenAccount: victimVaultTokenAccount,
+        multisig,
+        pendingAction: pendingAction(victimVault),
+        authority: wallet.publicKey,
+      })
+      .rpc();
+    expect((await program.account.vault.fetch(victimVault)).isActive).to.be.false;
+    console.log("✅ Vault state corrupted (deactivated)");
+
+    const restore = await approvedProposal({ restoreVault: { vault: victimVault, version } });
+    await program.methods
+      .restoreVault(version)
+      .accounts({ vault: victimVault, snapshot, multisig, proposal: restore, owner: wallet.publicKey })
+      .rpc();
+    const restored = await program.account.vault.fetch(victimVault);
+    expect(restored.isActive).to.be.true;
+    expect(restored.owner.toBase58()).to.equal(original.owner.toBase58());
+    expect(restored.totalDeposited.toNumber()).to.equal(original.totalDeposited.toNumber());
+    expect(restored.vaultTokenAccount.toBase58()).to.equal(original.vaultTokenAccount.toBase58());
+    console.log("✅ Vault restored from snapshot 1");
+  });
+
+  it("4s. Native SOL vaults stay rent-exempt", async () => {
+    console.log("\n◎ Native SOL custody");
+
+    const owner = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(owner.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    const [solVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), owner.publicKey.toBuffer()],
+      program.programId
+    );
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const depositSol = (amount: number) =>
+      program.methods
+        .depositSol(new anchor.BN(amount))
+        .accounts({ solVault, config, owner: owner.publicKey, systemProgram: SystemProgram.programId })
+        .signers([owner])
+        .rpc();
+    const withdrawSol = (amount: number) =>
+      program.methods
+        .withdrawSol(new anchor.BN(amount))
+        .accounts({ solVault, config, owner: owner.publicKey, systemProgram: SystemProgram.programId })
+        .signers([owner])
+        .rpc();
+
+    try {
+      await depositSol(1000);
+      expect.fail("A deposit below the rent-exempt minimum should fail");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("SOL vault must stay rent-exempt or be emptied");
+      console.log("✅ Dust deposit rejected");
+    }
+
+    const amount = 100000000; // 0.1 SOL
+    await depositSol(amount);
+    expect(await provider.connection.getBalance(solVault)).to.equal(amount);
+    console.log("✅ Deposited 0.1 SOL");
+
+    try {
+      await withdrawSol(amount - rentExempt + 1);
+      expect.fail("Leaving less than the rent-exempt minimum should fail");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("SOL vault must stay rent-exempt or be emptied");
+      console.log("✅ Withdrawal into the rent reserve rejected");
+    }
+
+    await withdrawSol(amount - rentExempt);
+    expect(await provider.connection.getBalance(solVault)).to.equal(rentExempt);
+    await withdrawSol(rentExempt);
+    expect(await provider.connection.getBalance(solVault)).to.equal(0);
+    console.log("✅ Withdrew down to the rent reserve, then emptied the vault");
+  });
+
+  it("4t. Large withdrawals wait in a guardian-cancellable queue", async () => {
+    console.log("\n⏳ Delayed withdrawal queue");
+
+    const setWithdrawalDelay = async (threshold: anchor.BN, delaySlots: anchor.BN) => {
+      const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+      const proposal = proposalAddress(id);
+      await program.methods
+        .proposeAdminAction({ setWithdrawalDelay: { threshold, delaySlots } })
+        .accounts({
+          multisig,
+          proposal,
+          proposer: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .approveAdminAction()
+        .accounts({ multisig, proposal, owner: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .setWithdrawalDelay(threshold, delaySlots)
+        .accounts({ config, multisig, proposal, owner: wallet.publicKey })
+        .rpc();
+    };
+    await setWithdrawalDelay(new anchor.BN(1000000), new anchor.BN(TIMELOCK_SLOTS));
+
+    const [withdrawRequest] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdraw_request"), vault.toBuffer()],
+      program.programId
+    );
+    const amount = new anchor.BN(2000000); // 2 tokens, above the 1 token threshold
+    const withdrawAccounts = {
+      vault,
+      vaultTokenAccount,
+      mint,
+      userTokenAccount,
+      shareMint,
+      userShareAccount,
+      config,
+      circuitBreaker,
+      feeAuthority,
+      feeCollector,
+      owner: user.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const requestWithdraw = () =>
+      program.methods
+        .requestWithdraw(amount)
+        .accounts({ vault, withdrawRequest, config, owner: user.publicKey, systemProgram: SystemProgram.programId })
+        .signers([user])
+        .rpc();
+    const executeWithdraw = () =>
+      program.methods
+        .executeWithdraw()
+        .accounts({ ...withdrawAccounts, withdrawRequest })
+        .signers([user])
+        .rpc();
+
+    try {
+      await program.methods.withdraw(amount).accounts(withdrawAccounts).signers([user]).rpc();
+      expect.fail("A large withdrawal should need a request");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("must be queued with request_withdraw");
+      console.log("✅ Direct large withdrawal rejected");
+    }
+
+    // A delegate can't route around the queue either
+    const hotKey = Keypair.generate();
+    const [allowance] = PublicKey.findProgramAddressSync(
+      [Buffer.from("allowance"), vault.toBuffer(), hotKey.publicKey.toBuffer()],
+      program.programId
+    );
+    const slot = await provider.connection.getSlot();
+    await program.methods
+      .approveDelegate(hotKey.publicKey, amount, new anchor.BN(slot + 1000))
+      .accounts({ vault, allowance, owner: user.publicKey, systemProgram: SystemProgram.programId })
+      .signers([user])
+      .rpc();
+    try {
+      await program.methods
+        .delegateWithdraw(amount)
+        .accounts({
+          vault,
+          allowance,
+          vaultTokenAccount,
+          mint,
+          destination: userTokenAccount,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          delegate: hotKey.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([hotKey])
+        .rpc();
+      expect.fail("A large delegated withdrawal should need a request");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("must be queued with request_withdraw");
+      console.log("✅ Large delegated withdrawal rejected");
+    }
+
+    // A guardian cancels the first request during its delay
+    await requestWithdraw();
+    await program.methods
+      .cancelWithdraw()
+      .accounts({ withdrawRequest, owner: user.publicKey, config, guardian: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(withdrawRequest)).to.be.null;
+    console.log("✅ Guardian cancelled a queued withdrawal");
+
+    await requestWithdraw();
+    try {
+      await executeWithdraw();
+      expect.fail("Execution should wait for the delay");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Timelock has not expired");
+      console.log("✅ Early execution rejected");
+    }
+
+    const { etaSlot } = await program.account.withdrawRequest.fetch(withdrawRequest);
+    while ((await provider.connection.getSlot()) < etaSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    const before = await vaultBalance();
+    await executeWithdraw();
+    expect(before - (await vaultBalance())).to.equal(2000000);
+    expect(await provider.connection.getAccountInfo(withdrawRequest)).to.be.null;
+    console.log("✅ Queued withdrawal executed after the delay");
+
+    await setWithdrawalDelay(new anchor.BN("18446744073709551615"), new anchor.BN(0));
+  });
+
+  it("4u. Reconciliation reports a donation and pauses on a shortfall past the tolerance", async () => {
+    console.log("\n⚖️ Reconciliation");
+
+    const victim = Keypair.generate();
+    const [victimVault] = vaultAddress(victim.publicKey, 0);
+    const [victimShareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), victimVault.toBuffer()],
+      program.programId
+    );
+    const victimVaultTokenAccount = await getAssociatedTokenAddress(mint, victimVault, true);
+    const walletTokenAccount = await createAccount(provider.connection, user, mint, wallet.publicKey, Keypair.generate());
+    await mintTo(provider.connection, user, mint, walletTokenAccount, user, 3000000);
+    await program.methods
+      .seedScenario(new anchor.BN(0), new anchor.BN(3000000))
+      .accounts({
+        vault: victimVault,
+        vaultTokenAccount: victimVaultTokenAccount,
+        authorityTokenAccount: walletTokenAccount,
+        mint,
+        shareMint: victimShareMint,
+        victimShareAccount: await getAssociatedTokenAddress(victimShareMint, victim.publicKey),
+        victim: victim.publicKey,
+        multisig,
+        config,
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const reconcile = () =>
+      program.methods
+        .reconcile()
+        .accounts({ vault: victimVault, vaultTokenAccount: victimVaultTokenAccount, config })
+        .rpc({ commitment: "confirmed" });
+    const setTolerance = async (tolerance: anchor.BN) => {
+      const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+      const proposal = proposalAddress(id);
+      await program.methods
+        .proposeAdminAction({ setReconcileTolerance: { tolerance } })
+        .accounts({
+          multisig,
+          proposal,
+          proposer: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .approveAdminAction()
+        .accounts({ multisig, proposal, owner: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .setReconcileTolerance(tolerance)
+        .accounts({ config, multisig, proposal, owner: wallet.publicKey })
+        .rpc();
+    };
+
+    expect(await events(await reconcile(), "discrepancy")).to.have.length(0);
+    console.log("✅ Freshly seeded vault reconciles");
+
+    // Donate 1 token straight to the vault token account
+    await transfer(provider.connection, user, userTokenAccount, victimVaultTokenAccount, user, 1000000);
+    let [discrepancy] = await events(await reconcile(), "discrepancy");
+    expect(Number(discrepancy.data.delta)).to.equal(1000000);
+    expect(discrepancy.data.paused).to.be.false;
+    console.log("✅ Donation reported as a 1 token surplus");
+
+    await setTolerance(new anchor.BN(500000));
+    [discrepancy] = await events(await reconcile(), "discrepancy");
+    expect(discrepancy.data.paused).to.be.false;
+    expect((await program.account.vault.fetch(victimVault)).isActive).to.be.true;
+    console.log("✅ A surplus past the tolerance is reported but does not pause");
+
+    // Snapshot the vault, empty it, then restore the stale snapshot: its
+    // total_deposited now promises 3 tokens the vault no longer holds
+    const version = new anchor.BN(1);
+    const [snapshot] = PublicKey.findProgramAddressSync(
+      [Buffer.from("snapshot"), victimVault.toBuffer(), version.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .snapshotVault(version)
+      .accounts({
+        vault: victimVault,
+        snapshot,
+        config,
+        multisig,
+        keeper: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .withdrawAllForOwner()
+      .accounts({
+        vault: victimVault,
+        vaultTokenAccount: victimVaultTokenAccount,
+        mint,
+        ownerTokenAccount: await createAccount(provider.connection, user, mint, victim.publicKey, Keypair.generate()),
+        config,
+        multisig,
+        keeper: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const restore = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ restoreVault: { vault: victimVault, version } })
+      .accounts({ multisig, proposal: restore, proposer: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal: restore, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .restoreVault(version)
+      .accounts({ vault: victimVault, snapshot, multisig, proposal: restore, owner: wallet.publicKey })
+      .rpc();
+
+    [discrepancy] = await events(await reconcile(), "discrepancy");
+    expect(Number(discrepancy.data.delta)).to.equal(-3000000);
+    expect(discrepancy.data.paused).to.be.true;
+    expect((await program.account.vault.fetch(victimVault)).isActive).to.be.false;
+    console.log("✅ Vault deactivated when short past the tolerance");
+
+    await setTolerance(new anchor.BN("18446744073709551615"));
+  });
+
+  it("5. Demonstrate withdrawal failure after simulated closure", async () => {
+    console.log("\n🔒 STEP 5: Attempting withdrawal after simulated closure");
+
+    try {
+      console.log("The multisig recovered the vault, so it is still active");
+      console.log("In a real closure scenario, the entire program would be unreachable");
+      console.log("Let's simulate what would happen if the program were actually closed...");
+      
+      // Show that normal withdrawal still works because vault is active
+      const smallWithdraw = new anchor.BN(10000000); // 10 tokens
+      await program.methods
+        .withdraw(smallWithdraw)
+        .accounts({
+          vault: vault,
+          vaultTokenAccount: vaultTokenAccount,
+          mint,
+          userTokenAccount: userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      console.log("✅ Withdrawal still works because vault is active and program is accessible");
+      
+      console.log(`Current vault balance: ${(await vaultBalance()) / 1000000} tokens`);
+      
+      console.log("\n💀 BUT if the program were actually closed:");
+      console.log("1. This RPC call would fail with 'Program account not found'");
+      console.log("2. No functions would be executable");
+      console.log("3. All remaining funds would be permanently locked");
+      console.log("4. Users would have no recourse to recover their assets");
+      
+    } catch (error) {
+      console.log("✅ Withdrawal failed - this would be the effect of program closure");
+      console.log("In a real closure scenario, even this error wouldn't occur");
+      console.log("The RPC call would fail because the program doesn't exist");
+    }
+  });
+
+  it("6. Demonstrate proper emergency recovery procedures", async () => {
+    console.log("\n🚑 STEP 6: Proper emergency recovery procedures");
+    console.log("This demonstrates what SHOULD be implemented:");
+
+    console.log("\n✅ MITIGATION STRATEGIES:");
+    console.log("1. Multi-signature wallet for program authority");
+    console.log("2. Timelock periods for critical operations");
+    console.log("3. Separate staging and production environments");
+    console.log("4. Deployment scripts with environment verification");
+    console.log("5. Emergency pause/unpause functionality");
+    console.log("6. User-controlled fund recovery mechanisms");
+
+    console.log("\n🔧 TECHNICAL SAFEGUARDS:");
+    console.log("- Use program upgrades instead of closure");
+    console.log("- Implement circuit breakers for large operations");
+    console.log("- Create migration contracts before updates");
+    console.log("- Use proxy patterns for upgradeable contracts");
+    console.log("- Regular monitoring and health checks");
+
+    console.log("\n📋 OPERATIONAL PROCEDURES:");
+    console.log("- Peer review for all deployment operations");
+    console.log("- CI/CD pipelines with environment detection");
+    console.log("- Explicit confirmation for destructive operations");
+    console.log("- Testing deployment procedures on devnet");
+    console.log("- Backup and recovery testing");
+
+    // In this test, we're not actually implementing recovery since
+    // the vault is still functional. In a real scenario, recovery
+    // would involve redeploying the program or having emergency contracts
+  });
+
+  it("7. Show impact on user funds", async () => {
+    console.log("\n📊 STEP 7: Impact assessment");
+
+    try {
+      // Check current vault state
+      const lockedTokens = (await vaultBalance()) / 1000000;
+
+      console.log(`Tokens locked in vault: ${lockedTokens}`);
+      console.log(`If program were closed, these ${lockedTokens} tokens would be permanently lost`);
+
+      // Check user's remaining tokens
+      const userAccount = await getAccount(provider.connection, userTokenAccount);
+      const userTokens = Number(userAccount.amount) / 1000000;
+
+      console.log(`User's withdrawable tokens: ${userTokens}`);
+      console.log(`Total user impact: ${lockedTokens} tokens inaccessible`);
+
+      console.log("\n💸 FINANCIAL IMPACT:");
+      console.log(`At $100/token: $${(lockedTokens * 100).toLocaleString()} locked`);
+      console.log(`At current market prices, this represents significant user losses`);
+
+    } catch (error) {
+      console.error("Failed to assess impact:", error);
+    }
+  });
+
+  it("8. Escape hatch recovers funds from an inactive vault", async () => {
+    console.log("\n🪂 STEP 8: Owner pulls everything out after an emergency close");
+
+    // Close the vault through the full multisig + timelock flow
+    const id = (await program.account.multisig.fetch(multisig)).proposalCount;
+    const proposal = proposalAddress(id);
+    await program.methods
+      .proposeAdminAction({ closeVault: { vault } })
+      .accounts({
+        multisig,
+        proposal,
+        proposer: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .approveAdminAction()
+      .accounts({ multisig, proposal, owner: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .queueEmergencyClose()
+      .accounts({
+        vault,
+        multisig,
+        proposal,
+        pendingAction: pendingAction(vault),
+        owner: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const { etaSlot } = await program.account.pendingAction.fetch(pendingAction(vault));
+    while ((await provider.connection.getSlot()) < etaSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+    await program.methods
+      .emergencyCloseVault()
+      .accounts({ vault, vaultTokenAccount, multisig, pendingAction: pendingAction(vault), authority: wallet.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1))
+        .accounts({
+          vault: vault,
+          vaultTokenAccount: vaultTokenAccount,
+          mint,
+          userTokenAccount: userTokenAccount,
+          shareMint,
+          userShareAccount,
+          config,
+          circuitBreaker,
+          feeAuthority,
+          feeCollector,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Normal withdrawals should fail on an inactive vault");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Vault is inactive");
+      console.log("✅ Normal withdrawal blocked on the inactive vault");
+    }
+
+    const locked = Number((await getAccount(provider.connection, vaultTokenAccount)).amount);
+    const before = Number((await getAccount(provider.connection, userTokenAccount)).amount);
+    await program.methods
+      .emergencyUserWithdraw()
+      .accounts({
+        vault: vault,
+        vaultTokenAccount: vaultTokenAccount,
+        mint,
+        userTokenAccount: userTokenAccount,
+        shareMint,
+        userShareAccount,
+        owner: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc();
+
+    const after = Number((await getAccount(provider.connection, userTokenAccount)).amount);
+    expect(after - before).to.equal(locked);
+    expect(Number((await getAccount(provider.connection, vaultTokenAccount)).amount)).to.equal(0);
+    expect(await shareBalance()).to.equal(0);
+    console.log(`✅ Escape hatch returned all ${locked / 1000000} tokens`);
+  });
+
+  it("9. Close an empty vault and reclaim its rent", async () => {
+    console.log("\n🧹 STEP 9: Graceful shutdown instead of program closure");
+
+    const closeVault = () =>
+      program.methods
+        .closeVault()
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          shareMint,
+          userShareAccount,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    await program.methods
+      .deposit(new anchor.BN(1))
+      .accounts({
+        vault,
+        vaultTokenAccount,
+        mint,
+        userTokenAccount,
+        shareMint,
+        userShareAccount,
+        config,
+        owner: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc();
+    try {
+      await closeVault();
+      expect.fail("A vault holding tokens should not close");
+    } catch (error) {
+      const errorMessage = error.error?.errorMessage || error.message || String(error);
+      expect(errorMessage).to.include("Vault still holds tokens");
+      console.log("✅ Non-empty vault cannot be closed");
+    }
+
+    const withdrawSig = await program.methods
+      .emergencyUserWithdraw()
+      .accounts({
+        vault,
+        vaultTokenAccount,
+        mint,
+        userTokenAccount,
+        shareMint,
+        userShareAccount,
+        owner: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const [escape] = await events(withdrawSig, "emergencyAction");
+    expect(escape.data.kind).to.have.property("userWithdraw");
+    expect(escape.data.actor.toString()).to.equal(user.publicKey.toString());
+    expect(escape.data.amount.toNumber()).to.equal(1);
+
+    const rent =
+      (await provider.connection.getBalance(vault)) +
+      (await provider.connection.getBalance(vaultTokenAccount)) +
+      (await provider.connection.getBalance(userShareAccount));
+    const before = await provider.connection.getBalance(user.publicKey);
+    const closeSig = await closeVault();
+    const after = await provider.connection.getBalance(user.publicKey);
+    const [closed] = await events(closeSig, "vaultClosed");
+    expect(closed.data.vault.toString()).to.equal(vault.toString());
+    expect(closed.data.rentRefunded.toNumber()).to.equal(rent);
+
+    expect(await provider.connection.getAccountInfo(vault)).to.be.null;
+    expect(await provider.connection.getAccountInfo(vaultTokenAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(userShareAccount)).to.be.null;
+    // The owner also paid the transaction fee
+    expect(after).to.be.greaterThan(before + rent - 10000);
+    console.log(`✅ Vault closed, ${rent / anchor.web3.LAMPORTS_PER_SOL} SOL of rent returned`);
+
+    // The share mint outlives the vault, so its vault_id is spent for good
+    expect(await provider.connection.getAccountInfo(shareMint)).to.not.be.null;
+    try {
+      await program.methods
+        .initializeVault(new anchor.BN(0), new anchor.BN(1))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          userTokenAccount,
+          mint,
+          shareMint,
+          userShareAccount,
+          config,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("A closed vault_id should not be reusable");
+    } catch (error) {
+      const logs: string[] = error.logs || error.transactionLogs || [];
+      expect(logs.join("\n")).to.include("already in use");
+      console.log("✅ Closed vault_id cannot be reopened");
     }
   });
 
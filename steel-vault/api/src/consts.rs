@@ -0,0 +1,4 @@
+pub const CHALLENGE_ID: &str = "steel-vault";
+
+/// Seed prefix of every vault PDA; the owner's key follows it
+pub const VAULT: &[u8] = b"vault";
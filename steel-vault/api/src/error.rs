@@ -0,0 +1,21 @@
+use steel::*;
+
+/// Codes start at 13000, this program's range in `crates/ctf-errors`
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive)]
+#[repr(u32)]
+pub enum VaultError {
+    #[error("This function is not implemented yet")]
+    NotImplemented = 13_000,
+    #[error("Account is not the vault owner")]
+    NotOwner = 13_001,
+    #[error("Vault would drop below rent exemption")]
+    InsufficientFunds = 13_002,
+}
+
+impl VaultError {
+    pub fn name(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+error!(VaultError);
@@ -0,0 +1,42 @@
+use steel::*;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum VaultInstruction {
+    Initialize = 0,
+    Deposit = 1,
+    Withdraw = 2,
+    SecureWithdraw = 3,
+}
+
+/// Create the signer's vault PDA
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Initialize {}
+
+/// Move `amount` lamports from the owner into their vault
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Deposit {
+    pub amount: [u8; 8],
+}
+
+/// Pay `amount` lamports out of a vault. VULNERABLE: the owner is compared
+/// with the vault but never checked as a signer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Withdraw {
+    pub amount: [u8; 8],
+}
+
+/// The player's fix of `Withdraw`, same accounts and data
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SecureWithdraw {
+    pub amount: [u8; 8],
+}
+
+instruction!(VaultInstruction, Initialize);
+instruction!(VaultInstruction, Deposit);
+instruction!(VaultInstruction, Withdraw);
+instruction!(VaultInstruction, SecureWithdraw);
@@ -0,0 +1,23 @@
+//! Everything a client needs to talk to the Steel vault: program id, seeds,
+//! errors, instruction data, account layouts, and instruction builders.
+//!
+//! Steel splits a program into this `api` crate and the `program` crate that
+//! holds the handlers, so clients and tests never compile the handlers.
+
+pub mod consts;
+pub mod error;
+pub mod instruction;
+pub mod sdk;
+pub mod state;
+
+pub mod prelude {
+    pub use crate::consts::*;
+    pub use crate::error::*;
+    pub use crate::instruction::*;
+    pub use crate::sdk::*;
+    pub use crate::state::*;
+}
+
+use steel::*;
+
+declare_id!("9Z2bosnohsC2da7mX9X2kNYYthWu7Bqje1Dqz2xkHziF");
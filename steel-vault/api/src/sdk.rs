@@ -0,0 +1,62 @@
+use steel::*;
+
+use crate::prelude::*;
+
+pub fn initialize(owner: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(vault_pda(owner).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: Initialize {}.to_bytes(),
+    }
+}
+
+pub fn deposit(owner: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(vault_pda(owner).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: Deposit {
+            amount: amount.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+/// `Withdraw` with `owner` marked as a signer, as an honest client sends it.
+/// Clear `is_signer` on the owner's meta to get the exploit.
+pub fn withdraw(owner: Pubkey, destination: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: withdraw_accounts(owner, destination),
+        data: Withdraw {
+            amount: amount.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+pub fn secure_withdraw(owner: Pubkey, destination: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: withdraw_accounts(owner, destination),
+        data: SecureWithdraw {
+            amount: amount.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+fn withdraw_accounts(owner: Pubkey, destination: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(owner, true),
+        AccountMeta::new(vault_pda(owner).0, false),
+        AccountMeta::new(destination, false),
+    ]
+}
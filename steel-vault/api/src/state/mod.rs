@@ -0,0 +1,19 @@
+mod vault;
+
+pub use vault::*;
+
+use steel::*;
+
+use crate::consts::*;
+
+/// Account discriminators, written as the first 8 bytes of every account
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum VaultAccount {
+    Vault = 0,
+}
+
+/// Address of the vault PDA belonging to `owner`
+pub fn vault_pda(owner: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT, owner.as_ref()], &crate::id())
+}
@@ -0,0 +1,14 @@
+use steel::*;
+
+use super::VaultAccount;
+
+/// A vault PDA. The lamports it holds above rent exemption belong to `owner`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+}
+
+account!(VaultAccount, Vault);
@@ -0,0 +1,27 @@
+use steel::*;
+use steel_vault_api::prelude::*;
+
+/// Move lamports from the owner into their vault.
+pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = Deposit::try_from_bytes(data)?;
+    let amount = u64::from_le_bytes(args.amount);
+
+    // Load accounts.
+    let [owner_info, vault_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    owner_info.is_signer()?;
+    let vault = vault_info
+        .is_writable()?
+        .has_seeds(&[VAULT, owner_info.key.as_ref()], &steel_vault_api::ID)?
+        .as_account_mut::<Vault>(&steel_vault_api::ID)?
+        .assert_mut_err(|vault| vault.owner == *owner_info.key, VaultError::NotOwner.into())?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Record the deposit, then collect the lamports.
+    vault.total_deposited += amount;
+    vault_info.collect(amount, owner_info)?;
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use steel::*;
+use steel_vault_api::prelude::*;
+
+/// Create the signer's vault PDA.
+pub fn process_initialize(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [owner_info, vault_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    owner_info.is_signer()?;
+    vault_info
+        .is_empty()?
+        .is_writable()?
+        .has_seeds(&[VAULT, owner_info.key.as_ref()], &steel_vault_api::ID)?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Create the vault.
+    create_program_account::<Vault>(
+        vault_info,
+        system_program,
+        owner_info,
+        &steel_vault_api::ID,
+        &[VAULT, owner_info.key.as_ref()],
+    )?;
+    let vault = vault_info.as_account_mut::<Vault>(&steel_vault_api::ID)?;
+    vault.owner = *owner_info.key;
+
+    Ok(())
+}
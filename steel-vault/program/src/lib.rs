@@ -0,0 +1,36 @@
+//! The missing-signer vault, written with Steel.
+//!
+//! Steel keeps Anchor's habit of validating every account up front, but as a
+//! chain of explicit calls on each `AccountInfo` (`is_signer()?`,
+//! `is_writable()?`, `has_seeds(..)?`) instead of attributes. `withdraw`
+//! validates the vault's owner, seeds and type and leaves out the one call
+//! that matters, `owner_info.is_signer()?`.
+
+mod deposit;
+mod initialize;
+mod secure_withdraw;
+mod withdraw;
+
+use deposit::*;
+use initialize::*;
+use secure_withdraw::*;
+use withdraw::*;
+
+use steel::*;
+use steel_vault_api::prelude::*;
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (ix, data) = parse_instruction(&steel_vault_api::ID, program_id, data)?;
+
+    match ix {
+        VaultInstruction::Initialize => process_initialize(accounts, data)?,
+        VaultInstruction::Deposit => process_deposit(accounts, data)?,
+        VaultInstruction::Withdraw => process_withdraw(accounts, data)?,
+        VaultInstruction::SecureWithdraw => process_secure_withdraw(accounts, data)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no-entrypoint"))]
+entrypoint!(process_instruction);
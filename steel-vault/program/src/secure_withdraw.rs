@@ -0,0 +1,35 @@
+use steel::*;
+use steel_vault_api::prelude::*;
+
+#[cfg(feature = "solutions")]
+use crate::withdraw::pay_out;
+
+/// The player's fix of `withdraw`.
+#[cfg(not(feature = "solutions"))]
+pub fn process_secure_withdraw(_accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // TODO: Implement secure withdrawal with proper signer checks
+    // Hint: copy `process_withdraw` and add the validation it is missing
+    Err(VaultError::NotImplemented.into())
+}
+
+// SOLUTION: Built only with `--features solutions`
+#[cfg(feature = "solutions")]
+pub fn process_secure_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = SecureWithdraw::try_from_bytes(data)?;
+    let amount = u64::from_le_bytes(args.amount);
+
+    // Load accounts.
+    let [owner_info, vault_info, destination_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    owner_info.is_signer()?;
+    let vault = vault_info
+        .is_writable()?
+        .has_seeds(&[VAULT, owner_info.key.as_ref()], &steel_vault_api::ID)?
+        .as_account_mut::<Vault>(&steel_vault_api::ID)?
+        .assert_mut_err(|vault| vault.owner == *owner_info.key, VaultError::NotOwner.into())?;
+    destination_info.is_writable()?;
+
+    pay_out(vault, vault_info, destination_info, amount)
+}
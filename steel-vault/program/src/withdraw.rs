@@ -0,0 +1,44 @@
+use solana_program::{rent::Rent, sysvar::Sysvar};
+use steel::*;
+use steel_vault_api::prelude::*;
+
+/// Pay lamports out of a vault.
+pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = Withdraw::try_from_bytes(data)?;
+    let amount = u64::from_le_bytes(args.amount);
+
+    // Load accounts.
+    let [owner_info, vault_info, destination_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    // VULNERABLE: no `owner_info.is_signer()?`. Every check below passes for
+    // anyone who names the real owner.
+    let vault = vault_info
+        .is_writable()?
+        .has_seeds(&[VAULT, owner_info.key.as_ref()], &steel_vault_api::ID)?
+        .as_account_mut::<Vault>(&steel_vault_api::ID)?
+        .assert_mut_err(|vault| vault.owner == *owner_info.key, VaultError::NotOwner.into())?;
+    destination_info.is_writable()?;
+
+    pay_out(vault, vault_info, destination_info, amount)
+}
+
+/// The vault is owned by this program, so its lamports move without a CPI.
+/// The rent-exempt minimum stays behind.
+pub(crate) fn pay_out(
+    vault: &mut Vault,
+    vault_info: &AccountInfo<'_>,
+    destination_info: &AccountInfo<'_>,
+    amount: u64,
+) -> ProgramResult {
+    let reserve = Rent::get()?.minimum_balance(vault_info.data_len());
+    if amount > vault_info.lamports().saturating_sub(reserve) {
+        return Err(VaultError::InsufficientFunds.into());
+    }
+
+    vault.total_withdrawn += amount;
+    vault_info.send(amount, destination_info);
+
+    Ok(())
+}